@@ -1,6 +1,8 @@
 use std::io;
 use std::io::Read;
 
+const MAX_PREALLOCATED: usize = 8192;
+
 /** Reading a chunk from a reader, until b'\n' is encountered or until chunk_size is met
  *
  * Basically, the same as split() with a limit.
@@ -23,11 +25,8 @@ impl<B: Read> Iterator for Chunked<B> {
         if self.done {
             return None;
         }
-        let capacity = if self.chunk_size > 0 {
-            self.chunk_size
-        } else {
-            0
-        };
+        // A big chunk_size is only a limit, most of the chunks are going to be way shorter
+        let capacity = self.chunk_size.min(MAX_PREALLOCATED);
         let mut retval: Vec<u8> = Vec::with_capacity(capacity);
 
         loop {
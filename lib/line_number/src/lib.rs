@@ -0,0 +1,38 @@
+/** The NUMBER right aligned in WIDTH columns, the way cat -n and pr -n write it.
+ *
+ * With TRUNCATE, only the low-order digits of a wider number are kept, like pr
+ * does, they tell more than the high-order ones. Otherwise it's written whole.
+ */
+pub fn line_number(number: usize, width: usize, truncate: bool) -> String {
+    let number = format!("{:>width$}", number, width = width);
+    match truncate {
+        true => number[number.len() - width..].to_string(),
+        false => number,
+    }
+}
+
+/// The LINE after its number and the SEPARATOR
+pub fn prepend_line_number(line: &[u8], number: usize, width: usize, separator: &[u8]) -> Vec<u8> {
+    let mut numbered = line_number(number, width, false).into_bytes();
+    numbered.extend_from_slice(separator);
+    numbered.extend_from_slice(line);
+    numbered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aligns_the_number() {
+        assert_eq!(line_number(7, 6, false), "     7");
+        assert_eq!(line_number(1234567, 6, false), "1234567");
+        assert_eq!(line_number(1234567, 5, true), "34567");
+        assert_eq!(line_number(12, 5, true), "   12");
+    }
+
+    #[test]
+    fn prepends_the_number() {
+        assert_eq!(prepend_line_number(b"line\n", 3, 3, b"\t"), b"  3\tline\n");
+    }
+}
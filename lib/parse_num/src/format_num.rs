@@ -0,0 +1,301 @@
+// Formatting numbers with the multiplier suffixes, the inverse of parse_num:
+// 1500 becomes "1.5K" in SI scale, 1536 becomes "1.5K" in IEC one or "1.5Ki" in IEC-I.
+// Values are exact fractions, so nothing is lost on the way from the input to the output,
+// and the rounding is done only once, the way the user asked for.
+
+use ibig::UBig;
+
+use super::suffix::suffix_char;
+
+/// Scales a number can be represented in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    /// Powers of 1000: K, M, G...
+    Si,
+    /// Powers of 1024: K, M, G...
+    Iec,
+    /// Powers of 1024: Ki, Mi, Gi...
+    IecI,
+}
+
+impl Scale {
+    pub fn base(&self) -> u32 {
+        match self {
+            Scale::Si => 1000,
+            Scale::Iec | Scale::IecI => 1024,
+        }
+    }
+
+    /// Suffix for the power of the base, None if the power is beyond the table
+    pub fn suffix(&self, power: u32) -> Option<String> {
+        if power == 0 {
+            return Some(String::new());
+        }
+        let c = suffix_char(power)?;
+        Some(match self {
+            Scale::IecI => format!("{}i", c),
+            _ => c.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RoundMode {
+    /// Towards +infinity
+    Up,
+    /// Towards -infinity
+    Down,
+    #[default]
+    FromZero,
+    TowardsZero,
+    /// To the nearest integer, halves are rounded away from zero
+    Nearest,
+}
+
+impl RoundMode {
+    /// Rounds num/den to an integer, `negative` is the sign of the whole fraction
+    pub fn round(&self, negative: bool, num: &UBig, den: &UBig) -> UBig {
+        let quotient = num / den;
+        let remainder = num % den;
+        if remainder == UBig::from(0_u8) {
+            return quotient;
+        }
+        let away_from_zero = match self {
+            RoundMode::Up => !negative,
+            RoundMode::Down => negative,
+            RoundMode::FromZero => true,
+            RoundMode::TowardsZero => false,
+            RoundMode::Nearest => remainder * UBig::from(2_u8) >= *den,
+        };
+        if away_from_zero {
+            quotient + UBig::from(1_u8)
+        } else {
+            quotient
+        }
+    }
+}
+
+/// Exact value to be formatted: ±num/den
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fraction {
+    pub negative: bool,
+    pub num: UBig,
+    pub den: UBig,
+}
+
+impl Fraction {
+    pub fn new(negative: bool, num: UBig, den: UBig) -> Self {
+        Self { negative, num, den }
+    }
+
+    /// Approximate value, good enough for the diagnostics
+    pub fn to_f64(&self) -> f64 {
+        let value = self.num.to_f64() / self.den.to_f64();
+        if self.negative {
+            -value
+        } else {
+            value
+        }
+    }
+}
+
+impl From<u64> for Fraction {
+    fn from(value: u64) -> Self {
+        Self::new(false, UBig::from(value), UBig::from(1_u8))
+    }
+}
+
+/** Formats the value without any scaling, with exactly `precision` decimal digits */
+pub fn format_fixed(value: &Fraction, round: RoundMode, precision: usize) -> String {
+    let digits = round.round(value.negative, &(&value.num * pow10(precision)), &value.den);
+    with_decimals(value.negative, &digits, precision)
+}
+
+/** Formats the value in the given scale: "1.5K", "15K", "1.5Mi".
+ *
+ * Values under 10 get one decimal digit, unless `precision` is given. Then there are
+ * exactly that many decimal digits, but the value is rounded to no more of them, than
+ * the power has (3 per power of the base), the same way GNU numfmt does it.
+ * Returns None, if the value is too large for the suffixes table.
+ */
+pub fn format_num(
+    value: &Fraction,
+    scale: Scale,
+    round: RoundMode,
+    precision: Option<usize>,
+) -> Option<String> {
+    let base = UBig::from(scale.base());
+    let mut power = 0_u32;
+    let mut divisor = value.den.clone();
+    while value.num >= &divisor * &base {
+        divisor *= &base;
+        power += 1;
+    }
+
+    let round_digits = match precision {
+        Some(p) => p.min(power as usize * 3),
+        None if value.num < &divisor * UBig::from(10_u8) => 1,
+        None => 0,
+    };
+    let mut digits = round.round(
+        value.negative,
+        &(&value.num * pow10(round_digits)),
+        &divisor,
+    );
+    // Rounding may push the value over the base, like 999.99 to 1000, then it's 1.0 of the
+    // next power. The digits are kept and just shifted, so they aren't rounded twice.
+    let mut shift = UBig::from(1_u8);
+    if digits >= &base * pow10(round_digits) {
+        shift = base;
+        power += 1;
+    }
+    let suffix = scale.suffix(power)?;
+
+    let is_zero = digits == UBig::from(0_u8);
+    let shown_digits = match precision {
+        Some(p) => p,
+        None if !is_zero && power > 0 && digits < &shift * pow10(round_digits + 1) => 1,
+        None => 0,
+    };
+    if shown_digits != round_digits || shift != UBig::from(1_u8) {
+        digits = round_half_even(
+            &(digits * pow10(shown_digits)),
+            &(shift * pow10(round_digits)),
+        );
+    }
+    Some(with_decimals(value.negative, &digits, shown_digits) + &suffix)
+}
+
+fn pow10(n: usize) -> UBig {
+    UBig::from(10_u8).pow(n)
+}
+
+/// The way printf() rounds the digits, that don't fit into the precision
+fn round_half_even(num: &UBig, den: &UBig) -> UBig {
+    let quotient = num / den;
+    let twice_remainder = (num % den) * UBig::from(2_u8);
+    if twice_remainder > *den
+        || (twice_remainder == *den && &quotient % UBig::from(2_u8) == UBig::from(1_u8))
+    {
+        quotient + UBig::from(1_u8)
+    } else {
+        quotient
+    }
+}
+
+/// "1234" with 2 decimals is "12.34", with 5 decimals it's "0.01234"
+fn with_decimals(negative: bool, digits: &UBig, decimals: usize) -> String {
+    let mut s = format!("{:0>width$}", digits.to_string(), width = decimals + 1);
+    if decimals > 0 {
+        s.insert(s.len() - decimals, '.');
+    }
+    if negative && *digits != UBig::from(0_u8) {
+        s.insert(0, '-');
+    }
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn human(value: u64, scale: Scale) -> String {
+        format_num(&Fraction::from(value), scale, RoundMode::FromZero, None).unwrap()
+    }
+
+    #[test]
+    fn formats_in_si_scale() {
+        assert_eq!(human(0, Scale::Si), "0");
+        assert_eq!(human(999, Scale::Si), "999");
+        assert_eq!(human(1000, Scale::Si), "1.0K");
+        assert_eq!(human(1001, Scale::Si), "1.1K");
+        assert_eq!(human(1500, Scale::Si), "1.5K");
+        assert_eq!(human(9999, Scale::Si), "10K");
+        assert_eq!(human(99999, Scale::Si), "100K");
+        assert_eq!(human(999999, Scale::Si), "1.0M");
+        assert_eq!(human(123456789, Scale::Si), "124M");
+    }
+
+    #[test]
+    fn formats_in_iec_scales() {
+        assert_eq!(human(1024, Scale::Iec), "1.0K");
+        assert_eq!(human(1025, Scale::Iec), "1.1K");
+        assert_eq!(human(10240, Scale::Iec), "10K");
+        assert_eq!(human(1048575, Scale::Iec), "1.0M");
+        assert_eq!(human(1, Scale::IecI), "1");
+        assert_eq!(human(1536, Scale::IecI), "1.5Ki");
+    }
+
+    #[test]
+    fn handles_fractions_below_base() {
+        let fraction = |num: u64, den: u64| Fraction::new(false, UBig::from(num), UBig::from(den));
+        let format = |f: &Fraction| format_num(f, Scale::Si, RoundMode::FromZero, None).unwrap();
+        // printf() style rounding of the single decimal digit, when there is no suffix
+        assert_eq!(format(&fraction(5, 2)), "2");
+        assert_eq!(format(&fraction(3, 2)), "2");
+        assert_eq!(format(&fraction(1, 2)), "0");
+        assert_eq!(format(&fraction(996, 100)), "10");
+    }
+
+    #[test]
+    fn respects_round_modes() {
+        let round = |value: u64, negative: bool, mode: RoundMode| {
+            let value = Fraction::new(negative, UBig::from(value), UBig::from(1_u8));
+            format_num(&value, Scale::Si, mode, None).unwrap()
+        };
+        assert_eq!(round(1999, false, RoundMode::Down), "1.9K");
+        assert_eq!(round(1999, true, RoundMode::Down), "-2.0K");
+        assert_eq!(round(1001, false, RoundMode::Up), "1.1K");
+        assert_eq!(round(1001, true, RoundMode::Up), "-1.0K");
+        assert_eq!(round(1999, true, RoundMode::TowardsZero), "-1.9K");
+        assert_eq!(round(1999, true, RoundMode::FromZero), "-2.0K");
+        assert_eq!(round(1050, false, RoundMode::Nearest), "1.1K");
+        assert_eq!(round(1049, false, RoundMode::Nearest), "1.0K");
+    }
+
+    #[test]
+    fn respects_precision() {
+        let format = |value: u64, precision: usize| {
+            format_num(
+                &Fraction::from(value),
+                Scale::Si,
+                RoundMode::FromZero,
+                Some(precision),
+            )
+            .unwrap()
+        };
+        assert_eq!(format(1234567, 5), "1.23457M");
+        assert_eq!(format(1234567, 1), "1.3M");
+        assert_eq!(format(1500, 5), "1.50000K");
+        assert_eq!(format(999999, 5), "999.99900K");
+        assert_eq!(format(1, 1), "1.0");
+        assert_eq!(format(9999, 0), "10K");
+    }
+
+    #[test]
+    fn refuses_values_beyond_the_table() {
+        let huge = Fraction::new(false, UBig::from(1000_u32).pow(11), UBig::from(1_u8));
+        assert_eq!(
+            format_num(&huge, Scale::Si, RoundMode::FromZero, None),
+            None
+        );
+        let max = Fraction::new(false, UBig::from(1000_u32).pow(10), UBig::from(1_u8));
+        assert_eq!(
+            format_num(&max, Scale::Si, RoundMode::FromZero, None),
+            Some("1.0Q".to_string())
+        );
+    }
+
+    #[test]
+    fn formats_fixed_point() {
+        let value = Fraction::new(true, UBig::from(125_u8), UBig::from(100_u8));
+        assert_eq!(format_fixed(&value, RoundMode::FromZero, 2), "-1.25");
+        assert_eq!(format_fixed(&value, RoundMode::FromZero, 1), "-1.3");
+        assert_eq!(format_fixed(&value, RoundMode::TowardsZero, 0), "-1");
+        assert_eq!(format_fixed(&value, RoundMode::FromZero, 4), "-1.2500");
+        assert_eq!(
+            format_fixed(&Fraction::from(5), RoundMode::FromZero, 0),
+            "5"
+        );
+    }
+}
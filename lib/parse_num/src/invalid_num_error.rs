@@ -0,0 +1,121 @@
+use std::error::Error;
+use std::fmt;
+
+use super::ParseNumError;
+
+/** ParseNumError with the context needed for GNU-style diagnostics:
+ * the offending input and the quantity it was supposed to express.
+ *
+ * Displayed the same way GNU does it: "invalid number of lines: '5x'"
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidNumError {
+    pub input: String,
+    /// What the NUM is counting, in plural: "lines", "bytes", "seconds"
+    pub quantity: &'static str,
+    pub kind: ParseNumError,
+}
+
+impl InvalidNumError {
+    pub fn new(input: &str, quantity: &'static str, kind: ParseNumError) -> Self {
+        Self {
+            input: input.to_string(),
+            quantity,
+            kind,
+        }
+    }
+
+    /** Looks for InvalidNumError in the error itself or in its chain of sources.
+     *
+     * Argument parsers wrap the value parser errors into their own ones, this allows
+     * to fish the original error out to report it in GNU format.
+     */
+    pub fn find<'a>(err: &'a (dyn Error + 'static)) -> Option<&'a InvalidNumError> {
+        let mut current = Some(err);
+        while let Some(e) = current {
+            if let Some(invalid) = e.downcast_ref::<InvalidNumError>() {
+                return Some(invalid);
+            }
+            current = e.source();
+        }
+        None
+    }
+}
+
+impl fmt::Display for InvalidNumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid number of {}: '{}'", self.quantity, self.input)
+    }
+}
+
+impl Error for InvalidNumError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.kind)
+    }
+}
+
+/** Wraps one of the crate parsers, so its errors carry the input and the quantity.
+ *
+ * Suitable as a clap value_parser: `value_parser = num_parser("lines", parse_num)`
+ */
+pub fn num_parser<T: 'static>(
+    quantity: &'static str,
+    parse: fn(&str) -> Result<T, ParseNumError>,
+) -> impl Fn(&str) -> Result<T, InvalidNumError> + Clone + Send + Sync + 'static {
+    move |input: &str| parse(input).map_err(|kind| InvalidNumError::new(input, quantity, kind))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{parse_duration, parse_num};
+
+    #[test]
+    fn displays_gnu_message() {
+        let err = InvalidNumError::new("5x", "lines", ParseNumError::BadMultiplierValue(1));
+        assert_eq!(err.to_string(), "invalid number of lines: '5x'");
+        let err = InvalidNumError::new("", "bytes", ParseNumError::Empty);
+        assert_eq!(err.to_string(), "invalid number of bytes: ''");
+    }
+
+    #[test]
+    fn num_parser_wraps_errors() {
+        let parser = num_parser("bytes", parse_num);
+        assert_eq!(parser("10Q").unwrap().to_u64_saturating(), u64::MAX);
+        assert_eq!(
+            parser("10x"),
+            Err(InvalidNumError::new(
+                "10x",
+                "bytes",
+                ParseNumError::BadMultiplierValue(1)
+            ))
+        );
+        let parser = num_parser("seconds", parse_duration);
+        assert_eq!(
+            parser("x").unwrap_err().to_string(),
+            "invalid number of seconds: 'x'"
+        );
+    }
+
+    #[derive(Debug)]
+    struct Wrapper(InvalidNumError);
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "wrapped: {}", self.0)
+        }
+    }
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    #[test]
+    fn find_walks_the_source_chain() {
+        let err = InvalidNumError::new("5x", "lines", ParseNumError::BadMultiplierValue(1));
+        let wrapped = Wrapper(err.clone());
+        assert_eq!(InvalidNumError::find(&wrapped), Some(&err));
+        assert_eq!(InvalidNumError::find(&err), Some(&err));
+        assert_eq!(InvalidNumError::find(&ParseNumError::Empty), None);
+    }
+}
@@ -1,6 +1,18 @@
+mod format_num;
+mod invalid_num_error;
 mod num_value;
+mod parse_duration;
+mod parse_float_num;
 mod parse_num;
 mod parse_num_error;
+mod parse_size;
+mod suffix;
 pub use crate::parse_num::parse_num;
+pub use format_num::{format_fixed, format_num, Fraction, RoundMode, Scale};
+pub use invalid_num_error::{num_parser, InvalidNumError};
 pub use num_value::{Multiplier, NumValue};
+pub use parse_duration::parse_duration;
+pub use parse_float_num::parse_float_num;
 pub use parse_num_error::ParseNumError;
+pub use parse_size::parse_size;
+pub use suffix::power_of;
@@ -7,38 +7,40 @@ pub enum Multiplier {
     PowB10(u32),
 }
 
+/** Parsed NUM value.
+ *
+ * The value itself is arbitrary-precision, so something like "99999999999999999999999"
+ * or "1Q" can be represented. Utilities, that need a machine integer, should use the
+ * saturating conversions, treating anything that doesn't fit as "effectively infinite",
+ * the same way GNU does.
+ */
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct NumValue {
     pub prefix: Option<char>,
-    pub value: usize,
+    pub value: UBig,
     pub multiplier: Option<Multiplier>,
 }
 
 impl NumValue {
     pub fn to_ubig(&self) -> UBig {
         match self.multiplier {
-            None => UBig::from(self.value),
-            Some(Multiplier::Numeric(n)) => UBig::from(self.value) * n,
-            Some(Multiplier::Pow(n)) => {
-                UBig::from(self.value) * UBig::from(1024_usize).pow(n as usize)
-            }
-            Some(Multiplier::PowB10(n)) => {
-                UBig::from(self.value) * UBig::from(1000_usize).pow(n as usize)
-            }
+            None => self.value.clone(),
+            Some(Multiplier::Numeric(n)) => &self.value * UBig::from(n),
+            Some(Multiplier::Pow(n)) => &self.value * UBig::from(1024_usize).pow(n as usize),
+            Some(Multiplier::PowB10(n)) => &self.value * UBig::from(1000_usize).pow(n as usize),
         }
     }
 
     pub fn to_usize(&self) -> Option<usize> {
-        match self.multiplier {
-            None => Some(self.value),
-            Some(Multiplier::Numeric(n)) => self.value.checked_mul(n as usize),
-            Some(Multiplier::Pow(n)) => 1024_usize
-                .checked_pow(n)
-                .and_then(|m| self.value.checked_mul(m)),
-            Some(Multiplier::PowB10(n)) => 1000_usize
-                .checked_pow(n)
-                .and_then(|mul| self.value.checked_mul(mul)),
-        }
+        usize::try_from(&self.to_ubig()).ok()
+    }
+
+    pub fn to_usize_saturating(&self) -> usize {
+        self.to_usize().unwrap_or(usize::MAX)
+    }
+
+    pub fn to_u64_saturating(&self) -> u64 {
+        u64::try_from(&self.to_ubig()).unwrap_or(u64::MAX)
     }
 }
 
@@ -46,7 +48,7 @@ impl From<usize> for NumValue {
     fn from(value: usize) -> Self {
         Self {
             prefix: None,
-            value: value,
+            value: UBig::from(value),
             multiplier: None,
         }
     }
@@ -56,7 +58,7 @@ impl From<i32> for NumValue {
     fn from(value: i32) -> Self {
         Self {
             prefix: if value < 0 { Some('-') } else { None },
-            value: value.abs() as usize,
+            value: UBig::from(value.unsigned_abs()),
             multiplier: None,
         }
     }
@@ -66,127 +68,85 @@ impl From<i32> for NumValue {
 mod test {
     use super::*;
 
+    fn num(value: usize, multiplier: Option<Multiplier>) -> NumValue {
+        NumValue {
+            prefix: None,
+            value: UBig::from(value),
+            multiplier,
+        }
+    }
+
     #[test]
     fn numvalue_to_ubig() {
+        assert_eq!(num(3, None).to_ubig(), UBig::from(3_u32));
         assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: None
-            }
-            .to_ubig(),
-            UBig::from(3_u32)
-        );
-        assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: Some(Multiplier::Numeric(3))
-            }
-            .to_ubig(),
+            num(3, Some(Multiplier::Numeric(3))).to_ubig(),
             UBig::from(9_u32)
         );
         assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: Some(Multiplier::Pow(3))
-            }
-            .to_ubig(),
+            num(3, Some(Multiplier::Pow(3))).to_ubig(),
             UBig::from(3_u32 * 1024_u32.pow(3))
         );
         assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: Some(Multiplier::PowB10(3))
-            }
-            .to_ubig(),
+            num(3, Some(Multiplier::PowB10(3))).to_ubig(),
             UBig::from(3_u32 * 1000_u32.pow(3))
         );
     }
 
     #[test]
     fn numvalue_to_usize() {
+        assert_eq!(num(3, None).to_usize(), Some(3));
+        assert_eq!(num(3, Some(Multiplier::Numeric(3))).to_usize(), Some(9));
         assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: None
-            }
-            .to_usize(),
-            Some(3)
-        );
-        assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: Some(Multiplier::Numeric(3))
-            }
-            .to_usize(),
-            Some(9)
-        );
-        assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: Some(Multiplier::Pow(3))
-            }
-            .to_usize(),
+            num(3, Some(Multiplier::Pow(3))).to_usize(),
             Some(3 * 1024_usize.pow(3))
         );
         assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: Some(Multiplier::PowB10(3))
-            }
-            .to_usize(),
+            num(3, Some(Multiplier::PowB10(3))).to_usize(),
             Some(3 * 1000_usize.pow(3))
         );
     }
 
     #[test]
     fn numvalue_to_usize_overflow() {
+        assert_eq!(num(3, Some(Multiplier::Pow(15))).to_usize(), None);
+    }
+
+    #[test]
+    fn numvalue_saturating_conversions() {
+        assert_eq!(num(3, Some(Multiplier::Pow(1))).to_usize_saturating(), 3072);
+        assert_eq!(num(3, Some(Multiplier::Pow(1))).to_u64_saturating(), 3072);
         assert_eq!(
-            NumValue {
-                prefix: None,
-                value: 3,
-                multiplier: Some(Multiplier::Pow(15))
-            }
-            .to_usize(),
-            None
+            num(3, Some(Multiplier::Pow(15))).to_usize_saturating(),
+            usize::MAX
         );
+        assert_eq!(
+            num(3, Some(Multiplier::Pow(15))).to_u64_saturating(),
+            u64::MAX
+        );
+        let huge = NumValue {
+            prefix: None,
+            value: "99999999999999999999999".parse().unwrap(),
+            multiplier: None,
+        };
+        assert_eq!(huge.to_usize(), None);
+        assert_eq!(huge.to_usize_saturating(), usize::MAX);
+        assert_eq!(huge.to_u64_saturating(), u64::MAX);
     }
 
     #[test]
     fn from_usize() {
-        assert_eq!(
-            NumValue::from(123_usize),
-            NumValue {
-                prefix: None,
-                value: 123,
-                multiplier: None
-            }
-        );
+        assert_eq!(NumValue::from(123_usize), num(123, None));
     }
 
     #[test]
     fn from_i32() {
-        assert_eq!(
-            NumValue::from(123),
-            NumValue {
-                prefix: None,
-                value: 123,
-                multiplier: None
-            }
-        );
+        assert_eq!(NumValue::from(123), num(123, None));
         assert_eq!(
             NumValue::from(-123),
             NumValue {
                 prefix: Some('-'),
-                value: 123,
-                multiplier: None
+                ..num(123, None)
             }
         );
     }
@@ -0,0 +1,82 @@
+// Time interval parsing, the way sleep and timeout do it.
+// Floating point number of seconds, optionally followed by a unit:
+// s seconds (default), m minutes, h hours, d days. E.g. "1.5s", "2m", "1h", "0.5d".
+// "inf" and "infinity" mean an endless interval, which is saturated to Duration::MAX.
+
+use std::time::Duration;
+
+use super::parse_float_num::split_float;
+use super::suffix::duration_multiplier;
+use super::ParseNumError;
+
+pub fn parse_duration(duration: &str) -> Result<Duration, ParseNumError> {
+    let (value, unit) = split_float(duration)?;
+    if value < 0.0 {
+        return Err(ParseNumError::BadNumericValue);
+    }
+    let seconds = value * duration_multiplier(unit)?;
+    // Anything that doesn't fit is close enough to infinity anyway
+    Ok(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_to_seconds() {
+        assert_eq!(parse_duration("1"), Ok(Duration::from_secs(1)));
+        assert_eq!(parse_duration("1.5"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("0"), Ok(Duration::ZERO));
+        assert_eq!(parse_duration("+2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("-0"), Ok(Duration::ZERO));
+    }
+
+    #[test]
+    fn applies_units() {
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("0.5d"), Ok(Duration::from_secs(43200)));
+        assert_eq!(parse_duration("1e1s"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration(".25m"), Ok(Duration::from_secs(15)));
+    }
+
+    #[test]
+    fn rejects_bad_units() {
+        assert_eq!(
+            parse_duration("1x"),
+            Err(ParseNumError::BadMultiplierValue(1))
+        );
+        assert_eq!(
+            parse_duration("1ms"),
+            Err(ParseNumError::BadMultiplierValue(2))
+        );
+        // size multipliers aren't time units
+        assert_eq!(
+            parse_duration("1K"),
+            Err(ParseNumError::BadMultiplierValue(1))
+        );
+    }
+
+    #[test]
+    fn rejects_negative_values() {
+        assert_eq!(parse_duration("-1"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_duration("-inf"), Err(ParseNumError::BadNumericValue));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(parse_duration(""), Err(ParseNumError::Empty));
+        assert_eq!(parse_duration("s"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_duration("nan"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_duration("€€€"), Err(ParseNumError::BadNumericValue));
+    }
+
+    #[test]
+    fn saturates_huge_values() {
+        assert_eq!(parse_duration("inf"), Ok(Duration::MAX));
+        assert_eq!(parse_duration("infinity"), Ok(Duration::MAX));
+        assert_eq!(parse_duration("1e300d"), Ok(Duration::MAX));
+    }
+}
@@ -0,0 +1,167 @@
+// Floating point NUM parsing, aka xstrtod() followed by a multiplier suffix.
+// The number may have a sign, a fractional part and a decimal exponent: "-1.5e3".
+// Multiplier suffixes are the same as for parse_num, so "1.5K" is 1536.
+// "inf" and "infinity" (case insensitive) are accepted too, but not "nan".
+
+use super::suffix::{multiplier_to_f64, parse_multiplier};
+use super::ParseNumError;
+
+pub fn parse_float_num(num: &str) -> Result<f64, ParseNumError> {
+    let (value, rest) = split_float(num)?;
+    match parse_multiplier(rest)? {
+        Some(m) => Ok(value * multiplier_to_f64(&m)),
+        None => Ok(value),
+    }
+}
+
+/** Splits the input into the leading float value and the rest of the string (suffix) */
+pub(crate) fn split_float(num: &str) -> Result<(f64, &str), ParseNumError> {
+    if num.is_empty() {
+        return Err(ParseNumError::Empty);
+    }
+    let bytes = num.as_bytes();
+    let mut i = 0;
+    if matches!(bytes[0], b'-' | b'+') {
+        i += 1;
+    }
+
+    let unsigned = &num[i..];
+    for inf in ["infinity", "inf"] {
+        if unsigned
+            .get(..inf.len())
+            .is_some_and(|s| s.eq_ignore_ascii_case(inf))
+        {
+            let value = if bytes[0] == b'-' {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            };
+            return Ok((value, &unsigned[inf.len()..]));
+        }
+    }
+
+    let digits_start = i;
+    while i < bytes.len() && bytes[i].is_ascii_digit() {
+        i += 1;
+    }
+    let mut n_digits = i - digits_start;
+    if i < bytes.len() && bytes[i] == b'.' {
+        i += 1;
+        let fraction_start = i;
+        while i < bytes.len() && bytes[i].is_ascii_digit() {
+            i += 1;
+        }
+        n_digits += i - fraction_start;
+    }
+    if n_digits == 0 {
+        return Err(ParseNumError::BadNumericValue);
+    }
+    // 'E' is also an exa- suffix, so it's an exponent only if there are digits after it
+    if i < bytes.len() && matches!(bytes[i], b'e' | b'E') {
+        let mut j = i + 1;
+        if j < bytes.len() && matches!(bytes[j], b'-' | b'+') {
+            j += 1;
+        }
+        let exponent_start = j;
+        while j < bytes.len() && bytes[j].is_ascii_digit() {
+            j += 1;
+        }
+        if j > exponent_start {
+            i = j;
+        }
+    }
+
+    let value: f64 = num[..i]
+        .parse()
+        .map_err(|_| ParseNumError::BadNumericValue)?;
+    Ok((value, &num[i..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_numbers() {
+        assert_eq!(parse_float_num("0"), Ok(0.0));
+        assert_eq!(parse_float_num("42"), Ok(42.0));
+        assert_eq!(parse_float_num("1.5"), Ok(1.5));
+        assert_eq!(parse_float_num(".5"), Ok(0.5));
+        assert_eq!(parse_float_num("5."), Ok(5.0));
+    }
+
+    #[test]
+    fn parses_sign() {
+        assert_eq!(parse_float_num("-1.5"), Ok(-1.5));
+        assert_eq!(parse_float_num("+1.5"), Ok(1.5));
+        assert_eq!(parse_float_num("-"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_float_num("+"), Err(ParseNumError::BadNumericValue));
+    }
+
+    #[test]
+    fn rejects_non_ascii() {
+        assert_eq!(parse_float_num("€€€"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_float_num("-in€"), Err(ParseNumError::BadNumericValue));
+        assert!(split_float("infé").is_ok());
+    }
+
+    #[test]
+    fn parses_exponent() {
+        assert_eq!(parse_float_num("1e3"), Ok(1000.0));
+        assert_eq!(parse_float_num("1.5E+2"), Ok(150.0));
+        assert_eq!(parse_float_num("25e-1"), Ok(2.5));
+    }
+
+    #[test]
+    fn e_without_digits_is_a_suffix() {
+        assert_eq!(parse_float_num("1E"), Ok(1024_f64.powi(6)));
+        assert_eq!(parse_float_num("1EB"), Ok(1e18));
+        assert_eq!(
+            parse_float_num("1e+"),
+            Err(ParseNumError::BadMultiplierValue(2))
+        );
+    }
+
+    #[test]
+    fn applies_multiplier_suffix() {
+        assert_eq!(parse_float_num("1.5K"), Ok(1536.0));
+        assert_eq!(parse_float_num("0.5KiB"), Ok(512.0));
+        assert_eq!(parse_float_num("2kB"), Ok(2000.0));
+        assert_eq!(parse_float_num("2b"), Ok(1024.0));
+        assert_eq!(parse_float_num("1Q"), Ok(1024_f64.powi(10)));
+        assert_eq!(
+            parse_float_num("1.5x"),
+            Err(ParseNumError::BadMultiplierValue(1))
+        );
+        assert_eq!(
+            parse_float_num("1.5Kb"),
+            Err(ParseNumError::BadMultiplierValue(2))
+        );
+    }
+
+    #[test]
+    fn parses_infinity() {
+        assert_eq!(parse_float_num("inf"), Ok(f64::INFINITY));
+        assert_eq!(parse_float_num("INFINITY"), Ok(f64::INFINITY));
+        assert_eq!(parse_float_num("-Inf"), Ok(f64::NEG_INFINITY));
+        assert_eq!(parse_float_num("nan"), Err(ParseNumError::BadNumericValue));
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(parse_float_num(""), Err(ParseNumError::Empty));
+        assert_eq!(parse_float_num("."), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_float_num("a"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(
+            parse_float_num("1.2.3"),
+            Err(ParseNumError::BadMultiplierValue(1))
+        );
+    }
+
+    #[test]
+    fn split_float_returns_the_rest() {
+        assert_eq!(split_float("1.5s"), Ok((1.5, "s")));
+        assert_eq!(split_float("2e1m"), Ok((20.0, "m")));
+        assert_eq!(split_float("3"), Ok((3.0, "")));
+    }
+}
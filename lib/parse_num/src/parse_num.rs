@@ -1,9 +1,13 @@
 // Implementation of coreutils NUM parsing, aka xdectoint()
 // NUM may have a multiplier suffix: b 512, kB 1000, K 1024, MB 1000*1000, M 1024*1024,
-// GB 1000*1000*1000, G 1024*1024*1024, and so on for T, P, E, Z, Y.
+// GB 1000*1000*1000, G 1024*1024*1024, and so on for T, P, E, Z, Y, R, Q.
+// See suffix.rs for the full table.
 
+use ibig::UBig;
+
+use super::suffix::parse_multiplier;
+use super::NumValue;
 use super::ParseNumError;
-use super::{Multiplier, NumValue};
 
 pub fn parse_num(num: &str) -> Result<NumValue, ParseNumError> {
     let prefix = match num.chars().next() {
@@ -12,83 +16,29 @@ pub fn parse_num(num: &str) -> Result<NumValue, ParseNumError> {
         Some(_) => None,
         None => return Err(ParseNumError::Empty),
     };
-    let value_start_index = if let Some(_) = prefix { 1 } else { 0 };
+    let value_start_index = if prefix.is_some() { 1 } else { 0 };
     let value_end_index = num[value_start_index..]
         .chars()
         .enumerate()
-        .find(|(_, c)| !c.is_digit(10))
+        .find(|(_, c)| !c.is_ascii_digit())
         .map(|(i, _)| i + value_start_index)
         .unwrap_or(num.len());
-    let value: usize = num[value_start_index..value_end_index]
+    let value: UBig = num[value_start_index..value_end_index]
         .parse()
         .map_err(|_| ParseNumError::BadNumericValue)?;
     let multiplier = parse_multiplier(&num[value_end_index..])?;
 
     Ok(NumValue {
-        prefix: prefix,
-        value: value,
-        multiplier: multiplier,
+        prefix,
+        value,
+        multiplier,
     })
 }
 
-fn parse_multiplier(mult: &str) -> Result<Option<Multiplier>, ParseNumError> {
-    if mult.len() == 0 {
-        return Ok(None);
-    }
-    if mult.len() > 2 {
-        return Err(ParseNumError::BadMultiplierValue(3));
-    }
-
-    let mut chars = mult.chars();
-    match chars.next() {
-        Some('b') => Ok(Some(Multiplier::Numeric(512))),
-        Some(m) => {
-            let val = match m.to_ascii_uppercase() {
-                'K' => Ok(1),
-                'M' => Ok(2),
-                'G' => Ok(3),
-                'T' => Ok(4),
-                'P' => Ok(5),
-                'E' => Ok(6),
-                'Z' => Ok(7),
-                'Y' => Ok(8),
-                _ => Err(ParseNumError::BadMultiplierValue(1)),
-            }?;
-            match chars.next() {
-                // lowercase 'b' isn't considered a valid suffix for some reason in the GNU implementation
-                Some('B') => Ok(Some(Multiplier::PowB10(val))),
-                Some(_) => Err(ParseNumError::BadMultiplierValue(2)),
-                None => Ok(Some(Multiplier::Pow(val))),
-            }
-        }
-        None => Err(ParseNumError::BadMultiplierValue(0)),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn parse_multiplier_returns_correct_result() {
-        assert_eq!(
-            parse_multiplier("zxc"),
-            Err(ParseNumError::BadMultiplierValue(3))
-        );
-        assert_eq!(parse_multiplier("b"), Ok(Some(Multiplier::Numeric(512))));
-        // should just "B" be equal to 512 too?..
-        for (i, mult) in ["K", "M", "G", "T", "P", "E", "Z", "Y"].iter().enumerate() {
-            let str = mult.to_string();
-            assert_eq!(
-                parse_multiplier(&str),
-                Ok(Some(Multiplier::Pow((i + 1) as u32)))
-            );
-            assert_eq!(
-                parse_multiplier(&(str + "B")),
-                Ok(Some(Multiplier::PowB10((i + 1) as u32)))
-            );
-        }
-    }
+    use crate::Multiplier;
 
     #[test]
     fn parse_num_correctly_parses_prefix() {
@@ -100,13 +50,36 @@ mod tests {
 
     #[test]
     fn parse_num_correctly_parses_value() {
-        assert_eq!(parse_num("-123").unwrap().value, 123);
-        assert_eq!(parse_num("+321").unwrap().value, 321);
-        assert_eq!(parse_num("67").unwrap().value, 67);
+        assert_eq!(parse_num("-123").unwrap().value, UBig::from(123_u32));
+        assert_eq!(parse_num("+321").unwrap().value, UBig::from(321_u32));
+        assert_eq!(parse_num("67").unwrap().value, UBig::from(67_u32));
+    }
+
+    #[test]
+    fn parse_num_accepts_values_beyond_usize() {
+        let num = parse_num("99999999999999999999999").unwrap();
+        assert_eq!(num.value.to_string(), "99999999999999999999999");
+        assert_eq!(num.to_usize_saturating(), usize::MAX);
+        assert_eq!(parse_num("1Q").unwrap().to_u64_saturating(), u64::MAX);
+    }
+
+    #[test]
+    fn parse_num_rejects_sign_without_value() {
+        assert_eq!(parse_num("-"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_num("+K"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_num("--1"), Err(ParseNumError::BadNumericValue));
     }
 
     #[test]
     fn parse_num_correctly_parses_suffix() {
+        assert_eq!(
+            parse_num("1KiB").unwrap().multiplier,
+            Some(Multiplier::Pow(1))
+        );
+        assert_eq!(
+            parse_num("1Q").unwrap().multiplier,
+            Some(Multiplier::Pow(10))
+        );
         assert_eq!(
             parse_num("-123a"),
             Err(ParseNumError::BadMultiplierValue(1)),
@@ -0,0 +1,129 @@
+// SIZE parsing for the utilities working with byte offsets, like dd and od.
+// Same multiplier suffixes as parse_num, but the value can be written in any of
+// the C integer bases, aka xstrtoumax() with base 0:
+// "0x1F" is hexadecimal, "017" is octal, everything else is decimal.
+// Sizes can't be negative, so no sign prefix is accepted.
+
+use ibig::UBig;
+
+use super::suffix::parse_multiplier;
+use super::NumValue;
+use super::ParseNumError;
+
+pub fn parse_size(size: &str) -> Result<NumValue, ParseNumError> {
+    if size.is_empty() {
+        return Err(ParseNumError::Empty);
+    }
+    let hex = size
+        .as_bytes()
+        .get(..2)
+        .is_some_and(|p| p.eq_ignore_ascii_case(b"0x"));
+    let (radix, digits_start) = if size.len() > 2 && hex {
+        (16, 2)
+    } else if size.len() > 1 && size.starts_with('0') {
+        (8, 1)
+    } else {
+        (10, 0)
+    };
+    let digits_end = size[digits_start..]
+        .char_indices()
+        .find(|(_, c)| !c.is_digit(radix))
+        .map(|(i, _)| i + digits_start)
+        .unwrap_or(size.len());
+    // A lonely leading zero of an octal number is still a valid zero, e.g. "0K"
+    let digits = match &size[digits_start..digits_end] {
+        "" if radix == 8 => "0",
+        d => d,
+    };
+    let value = UBig::from_str_radix(digits, radix).map_err(|_| ParseNumError::BadNumericValue)?;
+    let multiplier = parse_multiplier(&size[digits_end..])?;
+
+    Ok(NumValue {
+        prefix: None,
+        value,
+        multiplier,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Multiplier;
+
+    #[test]
+    fn parses_decimal() {
+        assert_eq!(parse_size("0"), Ok(NumValue::from(0_usize)));
+        assert_eq!(parse_size("123"), Ok(NumValue::from(123_usize)));
+    }
+
+    #[test]
+    fn parses_hexadecimal() {
+        assert_eq!(parse_size("0x1F"), Ok(NumValue::from(31_usize)));
+        assert_eq!(parse_size("0Xff"), Ok(NumValue::from(255_usize)));
+        assert_eq!(parse_size("0x"), Err(ParseNumError::BadMultiplierValue(1)));
+    }
+
+    #[test]
+    fn parses_octal() {
+        assert_eq!(parse_size("017"), Ok(NumValue::from(15_usize)));
+        assert_eq!(parse_size("00"), Ok(NumValue::from(0_usize)));
+        assert_eq!(parse_size("08"), Err(ParseNumError::BadMultiplierValue(1)));
+    }
+
+    #[test]
+    fn rejects_non_ascii() {
+        assert_eq!(parse_size("€1"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_size("é"), Err(ParseNumError::BadNumericValue));
+    }
+
+    #[test]
+    fn applies_suffixes() {
+        assert_eq!(
+            parse_size("0x10K").unwrap(),
+            NumValue {
+                prefix: None,
+                value: UBig::from(16_u32),
+                multiplier: Some(Multiplier::Pow(1)),
+            }
+        );
+        assert_eq!(
+            parse_size("010kB").unwrap().multiplier,
+            Some(Multiplier::PowB10(1))
+        );
+        assert_eq!(
+            parse_size("0K").unwrap().multiplier,
+            Some(Multiplier::Pow(1))
+        );
+        assert_eq!(
+            parse_size("2MiB").unwrap().to_usize(),
+            Some(2 * 1024 * 1024)
+        );
+        assert_eq!(
+            parse_size("3b").unwrap().multiplier,
+            Some(Multiplier::Numeric(512))
+        );
+        assert_eq!(parse_size("3Kx"), Err(ParseNumError::BadMultiplierValue(2)));
+    }
+
+    #[test]
+    fn hex_digits_are_not_taken_for_suffixes() {
+        // 'B' and 'E' are valid hex digits, so they're the part of the value
+        assert_eq!(parse_size("0x1B"), Ok(NumValue::from(27_usize)));
+        assert_eq!(parse_size("0x1E"), Ok(NumValue::from(30_usize)));
+    }
+
+    #[test]
+    fn rejects_signs_and_garbage() {
+        assert_eq!(parse_size(""), Err(ParseNumError::Empty));
+        assert_eq!(parse_size("-1"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_size("+1"), Err(ParseNumError::BadNumericValue));
+        assert_eq!(parse_size("K"), Err(ParseNumError::BadNumericValue));
+    }
+
+    #[test]
+    fn accepts_values_beyond_usize() {
+        let size = parse_size("0xFFFFFFFFFFFFFFFFFFFFFFFF").unwrap();
+        assert_eq!(size.to_usize(), None);
+        assert_eq!(size.to_u64_saturating(), u64::MAX);
+    }
+}
@@ -0,0 +1,168 @@
+// Multiplier suffixes table, shared by all of the parsers in the crate.
+// b 512, kB 1000, K 1024, KiB 1024, MB 1000*1000, M 1024*1024, MiB 1024*1024,
+// and so on for G, T, P, E, Z, Y, R, Q.
+// Durations have their own suffixes: s seconds, m minutes, h hours, d days.
+
+use super::Multiplier;
+use super::ParseNumError;
+
+/// Power suffixes in the order of their power, i.e. K is 1024^1 (or 1000^1), M is 1024^2
+const POWERS: [char; 10] = ['K', 'M', 'G', 'T', 'P', 'E', 'Z', 'Y', 'R', 'Q'];
+
+/// Power (1-based) of the multiplier character, case insensitive
+pub fn power_of(c: char) -> Option<u32> {
+    let c = c.to_ascii_uppercase();
+    POWERS.iter().position(|p| *p == c).map(|i| (i + 1) as u32)
+}
+
+/// Multiplier character for the power (1-based), the inverse of power_of
+pub fn suffix_char(power: u32) -> Option<char> {
+    let index = power.checked_sub(1)? as usize;
+    POWERS.get(index).copied()
+}
+
+pub fn parse_multiplier(mult: &str) -> Result<Option<Multiplier>, ParseNumError> {
+    if mult.is_empty() {
+        return Ok(None);
+    }
+    if mult.len() > 3 || (mult.len() == 3 && !mult.ends_with("iB")) {
+        return Err(ParseNumError::BadMultiplierValue(3));
+    }
+
+    let mut chars = mult.chars();
+    match chars.next() {
+        Some('b') => match chars.next() {
+            None => Ok(Some(Multiplier::Numeric(512))),
+            Some(_) => Err(ParseNumError::BadMultiplierValue(2)),
+        },
+        Some(m) => {
+            let val = power_of(m).ok_or(ParseNumError::BadMultiplierValue(1))?;
+            match chars.as_str() {
+                // lowercase 'b' isn't considered a valid suffix for some reason in the GNU implementation
+                "B" => Ok(Some(Multiplier::PowB10(val))),
+                "iB" => Ok(Some(Multiplier::Pow(val))),
+                "" => Ok(Some(Multiplier::Pow(val))),
+                _ => Err(ParseNumError::BadMultiplierValue(2)),
+            }
+        }
+        None => Err(ParseNumError::BadMultiplierValue(0)),
+    }
+}
+
+/// Multiplier value as a float, for the parsers that don't need the exact integer result
+pub fn multiplier_to_f64(mult: &Multiplier) -> f64 {
+    match *mult {
+        Multiplier::Numeric(n) => n as f64,
+        Multiplier::Pow(n) => 1024_f64.powi(n as i32),
+        Multiplier::PowB10(n) => 1000_f64.powi(n as i32),
+    }
+}
+
+/// Number of seconds in a duration unit
+pub fn duration_multiplier(unit: &str) -> Result<f64, ParseNumError> {
+    match unit {
+        "" | "s" => Ok(1.0),
+        "m" => Ok(60.0),
+        "h" => Ok(60.0 * 60.0),
+        "d" => Ok(60.0 * 60.0 * 24.0),
+        _ if unit.len() > 1 => Err(ParseNumError::BadMultiplierValue(2)),
+        _ => Err(ParseNumError::BadMultiplierValue(1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_of_returns_position_in_table() {
+        assert_eq!(power_of('K'), Some(1));
+        assert_eq!(power_of('k'), Some(1));
+        assert_eq!(power_of('Y'), Some(8));
+        assert_eq!(power_of('R'), Some(9));
+        assert_eq!(power_of('Q'), Some(10));
+        assert_eq!(power_of('b'), None);
+        assert_eq!(power_of('X'), None);
+    }
+
+    #[test]
+    fn suffix_char_is_inverse_of_power_of() {
+        assert_eq!(suffix_char(0), None);
+        assert_eq!(suffix_char(1), Some('K'));
+        assert_eq!(suffix_char(10), Some('Q'));
+        assert_eq!(suffix_char(11), None);
+        for c in POWERS {
+            assert_eq!(suffix_char(power_of(c).unwrap()), Some(c));
+        }
+    }
+
+    #[test]
+    fn parse_multiplier_returns_correct_result() {
+        assert_eq!(parse_multiplier(""), Ok(None));
+        assert_eq!(
+            parse_multiplier("zxc"),
+            Err(ParseNumError::BadMultiplierValue(3))
+        );
+        assert_eq!(
+            parse_multiplier("KiBs"),
+            Err(ParseNumError::BadMultiplierValue(3))
+        );
+        assert_eq!(parse_multiplier("b"), Ok(Some(Multiplier::Numeric(512))));
+        assert_eq!(
+            parse_multiplier("bB"),
+            Err(ParseNumError::BadMultiplierValue(2))
+        );
+        assert_eq!(
+            parse_multiplier("x"),
+            Err(ParseNumError::BadMultiplierValue(1))
+        );
+        // should just "B" be equal to 512 too?..
+        assert_eq!(
+            parse_multiplier("B"),
+            Err(ParseNumError::BadMultiplierValue(1))
+        );
+        for (i, mult) in POWERS.iter().enumerate() {
+            let str = mult.to_string();
+            assert_eq!(
+                parse_multiplier(&str),
+                Ok(Some(Multiplier::Pow((i + 1) as u32)))
+            );
+            assert_eq!(
+                parse_multiplier(&(str.clone() + "B")),
+                Ok(Some(Multiplier::PowB10((i + 1) as u32)))
+            );
+            assert_eq!(
+                parse_multiplier(&(str.clone() + "iB")),
+                Ok(Some(Multiplier::Pow((i + 1) as u32)))
+            );
+            assert_eq!(
+                parse_multiplier(&(str + "b")),
+                Err(ParseNumError::BadMultiplierValue(2))
+            );
+        }
+    }
+
+    #[test]
+    fn multiplier_to_f64_matches_integer_value() {
+        assert_eq!(multiplier_to_f64(&Multiplier::Numeric(512)), 512.0);
+        assert_eq!(multiplier_to_f64(&Multiplier::Pow(2)), 1048576.0);
+        assert_eq!(multiplier_to_f64(&Multiplier::PowB10(3)), 1e9);
+    }
+
+    #[test]
+    fn duration_multiplier_handles_all_units() {
+        assert_eq!(duration_multiplier(""), Ok(1.0));
+        assert_eq!(duration_multiplier("s"), Ok(1.0));
+        assert_eq!(duration_multiplier("m"), Ok(60.0));
+        assert_eq!(duration_multiplier("h"), Ok(3600.0));
+        assert_eq!(duration_multiplier("d"), Ok(86400.0));
+        assert_eq!(
+            duration_multiplier("x"),
+            Err(ParseNumError::BadMultiplierValue(1))
+        );
+        assert_eq!(
+            duration_multiplier("ms"),
+            Err(ParseNumError::BadMultiplierValue(2))
+        );
+    }
+}
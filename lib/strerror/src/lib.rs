@@ -0,0 +1,30 @@
+use std::io;
+
+/** io::Error message without the " (os error N)" suffix, the way GNU prints it
+ *
+ * "No such file or directory (os error 2)" becomes "No such file or directory"
+ */
+pub fn strerror(err: &io::Error) -> String {
+    let message = err.to_string();
+    match message.rfind(" (os error ") {
+        Some(i) if err.raw_os_error().is_some() => message[..i].to_string(),
+        _ => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_os_error_code() {
+        let err = io::Error::from_raw_os_error(2); // ENOENT
+        assert_eq!(strerror(&err), "No such file or directory");
+    }
+
+    #[test]
+    fn keeps_custom_messages() {
+        let err = io::Error::other("something (os error 1)");
+        assert_eq!(strerror(&err), "something (os error 1)");
+    }
+}
@@ -0,0 +1,115 @@
+use std::str;
+
+use unicode_width::UnicodeWidthChar;
+
+use super::TabStops;
+
+/** The column of the line, and the tab stop to look for the next one from.
+ *
+ * The columns go from 0, the characters take as many of them as they're wide.
+ */
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Columns {
+    pub column: usize,
+    index: usize,
+}
+
+impl Columns {
+    pub fn advance(&mut self, width: usize) {
+        self.column = self.column.saturating_add(width);
+    }
+
+    /// One column back, the next tab stop is looked for over again
+    pub fn backspace(&mut self) {
+        self.column = self.column.saturating_sub(1);
+        self.index = self.index.saturating_sub(1);
+    }
+
+    /// The column of the next tab stop, None past the last one
+    pub fn next_tab(&mut self, tab_stops: &TabStops) -> Option<usize> {
+        tab_stops.next(self.column, &mut self.index)
+    }
+
+    /// The start of the next line
+    pub fn reset(&mut self) {
+        *self = Columns::default();
+    }
+}
+
+/// The columns of the character, the control ones take one, like the bytes
+pub fn width(c: char) -> usize {
+    c.width().unwrap_or(1)
+}
+
+/** The characters of UTF-8 text along with their bytes.
+ *
+ * The bytes, that aren't UTF-8, go one by one without a character.
+ */
+pub struct Chars<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Chars<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Chars { bytes }
+    }
+}
+
+impl<'a> Iterator for Chars<'a> {
+    type Item = (&'a [u8], Option<char>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = *self.bytes.first()?;
+        if first.is_ascii() {
+            let (bytes, rest) = self.bytes.split_at(1);
+            self.bytes = rest;
+            return Some((bytes, Some(first as char)));
+        }
+        let start = &self.bytes[..self.bytes.len().min(4)];
+        let valid = match str::from_utf8(start) {
+            Ok(valid) => valid,
+            Err(e) => str::from_utf8(&start[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        let c = valid.chars().next();
+        let (bytes, rest) = self.bytes.split_at(c.map_or(1, char::len_utf8));
+        self.bytes = rest;
+        Some((bytes, c))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_the_characters() {
+        let chars: Vec<_> = Chars::new(b"a\xc3\xa9\xff\xe2\x82").collect();
+        assert_eq!(
+            chars,
+            [
+                (&b"a"[..], Some('a')),
+                (&b"\xc3\xa9"[..], Some('é')),
+                (&b"\xff"[..], None),
+                (&b"\xe2"[..], None),
+                (&b"\x82"[..], None)
+            ]
+        );
+        let widths: Vec<_> = "a\u{0301}日\u{7}".chars().map(width).collect();
+        assert_eq!(widths, [1, 0, 2, 1]);
+    }
+
+    #[test]
+    fn tracks_the_tab_stops() {
+        let tab_stops = TabStops::parse(&["2,5"]).unwrap();
+        let mut columns = Columns::default();
+        columns.advance(3);
+        assert_eq!(columns.next_tab(&tab_stops), Some(5));
+        columns.column = 5;
+        assert_eq!(columns.next_tab(&tab_stops), None);
+        columns.backspace();
+        columns.backspace();
+        assert_eq!(columns.next_tab(&tab_stops), Some(5));
+        columns.reset();
+        assert_eq!(columns.next_tab(&tab_stops), Some(2));
+    }
+}
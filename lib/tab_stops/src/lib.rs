@@ -0,0 +1,6 @@
+mod columns;
+mod tab_stop_error;
+mod tab_stops;
+pub use columns::{width, Chars, Columns};
+pub use tab_stop_error::TabStopError;
+pub use tab_stops::TabStops;
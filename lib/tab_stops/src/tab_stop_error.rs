@@ -0,0 +1,51 @@
+use std::error::Error;
+use std::fmt;
+
+/// The errors of the tab lists, the same messages as GNU expand and unexpand
+#[derive(Debug, Clone, PartialEq)]
+pub enum TabStopError {
+    /// The rest of the list, starting from the invalid character
+    InvalidCharacters(String),
+    /// The '/' or '+' prefix after the digits
+    NotAtStart(char, String),
+    /// The prefixed value, which is followed by another one
+    NotLast(char),
+    MutuallyExclusive,
+    TooLarge(String),
+    Zero,
+    NotAscending,
+}
+
+impl fmt::Display for TabStopError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TabStopError::InvalidCharacters(rest) => {
+                write!(f, "tab size contains invalid character(s): {}", quote(rest))
+            }
+            TabStopError::NotAtStart(prefix, rest) => write!(
+                f,
+                "'{}' specifier not at start of number: {}",
+                prefix,
+                quote(rest)
+            ),
+            TabStopError::NotLast(prefix) => {
+                write!(f, "'{}' specifier only allowed with the last value", prefix)
+            }
+            TabStopError::MutuallyExclusive => {
+                write!(f, "'/' specifier is mutually exclusive with '+'")
+            }
+            TabStopError::TooLarge(stop) => write!(f, "tab stop is too large {}", quote(stop)),
+            TabStopError::Zero => write!(f, "tab size cannot be 0"),
+            TabStopError::NotAscending => write!(f, "tab sizes must be ascending"),
+        }
+    }
+}
+
+impl Error for TabStopError {}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
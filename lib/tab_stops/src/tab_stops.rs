@@ -0,0 +1,208 @@
+use super::TabStopError;
+
+const DEFAULT_TAB_SIZE: usize = 8;
+
+/** The tab stops of the -t lists, the way GNU expand and unexpand take them.
+ *
+ * The stops are the columns, counting from 0, separated by commas or blanks.
+ * A single number is the distance between the stops. The last number can be
+ * prefixed with '/', for the stops every N columns after the explicit ones,
+ * or with '+', for the stops every N columns counting from the last one.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TabStops {
+    stops: Vec<usize>,
+    extend: usize,
+    increment: usize,
+    /// The stops are the multiples of it, unless it's 0
+    size: usize,
+}
+
+impl Default for TabStops {
+    fn default() -> Self {
+        TabStops {
+            stops: Vec::new(),
+            extend: 0,
+            increment: 0,
+            size: DEFAULT_TAB_SIZE,
+        }
+    }
+}
+
+impl TabStops {
+    /// The stops of all the lists, the ones of every list go after the previous ones
+    pub fn parse<S: AsRef<str>>(lists: &[S]) -> Result<TabStops, TabStopError> {
+        let mut tab_stops = TabStops::default();
+        for list in lists {
+            tab_stops.parse_list(list.as_ref())?;
+        }
+        tab_stops.validate()?;
+        tab_stops.size = match (tab_stops.stops.len(), tab_stops.extend, tab_stops.increment) {
+            (0, 0, 0) => DEFAULT_TAB_SIZE,
+            (0, 0, increment) => increment,
+            (0, extend, _) => extend,
+            (1, 0, 0) => tab_stops.stops[0],
+            _ => 0,
+        };
+        Ok(tab_stops)
+    }
+
+    /** The column of the next tab stop after the COLUMN, None past the last one.
+     *
+     * The INDEX is the explicit stop to start looking from, it goes forward along
+     * the line, and is reset for the next one.
+     */
+    pub fn next(&self, column: usize, index: &mut usize) -> Option<usize> {
+        if self.size != 0 {
+            return Some(column + self.size - column % self.size);
+        }
+        while let Some(&stop) = self.stops.get(*index) {
+            if column < stop {
+                return Some(stop);
+            }
+            *index += 1;
+        }
+        if self.extend != 0 {
+            return Some(column + self.extend - column % self.extend);
+        }
+        if self.increment != 0 {
+            let last = self.stops.last().copied().unwrap_or(0);
+            return Some(column + self.increment - (column - last) % self.increment);
+        }
+        None
+    }
+
+    /// The prefixes go on to the following numbers of the list, until another prefix
+    fn parse_list(&mut self, list: &str) -> Result<(), TabStopError> {
+        let mut prefix = None;
+        let mut value: Option<usize> = None;
+        // Where the digits of the value start
+        let mut start = 0;
+        for (i, c) in list.char_indices() {
+            match c {
+                ',' | ' ' | '\t' => {
+                    if let Some(value) = value.take() {
+                        self.add(value, prefix)?;
+                    }
+                }
+                '/' | '+' => {
+                    if value.is_some() {
+                        return Err(TabStopError::NotAtStart(c, list[i..].to_string()));
+                    }
+                    prefix = Some(c);
+                }
+                '0'..='9' => {
+                    if value.is_none() {
+                        start = i;
+                    }
+                    let digit = c as usize - '0' as usize;
+                    value = value
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|v| v.checked_add(digit));
+                    if value.is_none() {
+                        let digits = list[start..].split(|c: char| !c.is_ascii_digit()).next();
+                        let digits = digits.unwrap_or_default().to_string();
+                        return Err(TabStopError::TooLarge(digits));
+                    }
+                }
+                _ => return Err(TabStopError::InvalidCharacters(list[i..].to_string())),
+            }
+        }
+        match value {
+            Some(value) => self.add(value, prefix),
+            None => Ok(()),
+        }
+    }
+
+    fn add(&mut self, value: usize, prefix: Option<char>) -> Result<(), TabStopError> {
+        match prefix {
+            Some('/') if self.extend != 0 => return Err(TabStopError::NotLast('/')),
+            Some('/') => self.extend = value,
+            Some(_) if self.increment != 0 => return Err(TabStopError::NotLast('+')),
+            Some(_) => self.increment = value,
+            None => self.stops.push(value),
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), TabStopError> {
+        let mut previous = None;
+        for &stop in &self.stops {
+            if stop == 0 {
+                return Err(TabStopError::Zero);
+            }
+            if previous.is_some_and(|previous| stop <= previous) {
+                return Err(TabStopError::NotAscending);
+            }
+            previous = Some(stop);
+        }
+        if self.extend != 0 && self.increment != 0 {
+            return Err(TabStopError::MutuallyExclusive);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn stops(list: &str, count: usize) -> Vec<usize> {
+        let tab_stops = TabStops::parse(&[list]).unwrap();
+        let (mut column, mut index) = (0, 0);
+        let mut stops = Vec::new();
+        while let Some(stop) = tab_stops.next(column, &mut index) {
+            if stops.len() == count {
+                break;
+            }
+            stops.push(stop);
+            column = stop;
+        }
+        stops
+    }
+
+    fn error(lists: &[&str]) -> String {
+        TabStops::parse(lists).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn finds_the_stops() {
+        assert_eq!(stops("", 3), [8, 16, 24]);
+        assert_eq!(stops("3", 3), [3, 6, 9]);
+        assert_eq!(stops("2 5,,7", 4), [2, 5, 7]);
+        assert_eq!(stops("2,/4", 4), [2, 4, 8, 12]);
+        assert_eq!(stops("3,+4", 4), [3, 7, 11, 15]);
+        assert_eq!(stops("+0", 2), [8, 16]);
+        let tab_stops = TabStops::parse(&["/3", "5"]).unwrap();
+        assert_eq!(tab_stops.next(5, &mut 0), Some(6));
+        assert_eq!(tab_stops.next(3, &mut 0), Some(5));
+    }
+
+    #[test]
+    fn reports_the_errors() {
+        assert_eq!(
+            error(&["4x"]),
+            "tab size contains invalid character(s): 'x'"
+        );
+        assert_eq!(error(&["4/"]), "'/' specifier not at start of number: '/'");
+        assert_eq!(
+            error(&["/2,5"]),
+            "'/' specifier only allowed with the last value"
+        );
+        assert_eq!(
+            error(&["/2,+3,4"]),
+            "'+' specifier only allowed with the last value"
+        );
+        assert_eq!(
+            error(&["2,+3,/4"]),
+            "'/' specifier is mutually exclusive with '+'"
+        );
+        assert_eq!(
+            error(&["1,99999999999999999999999"]),
+            "tab stop is too large '99999999999999999999999'"
+        );
+        assert_eq!(error(&["3,0"]), "tab size cannot be 0");
+        assert_eq!(error(&["4", "4"]), "tab sizes must be ascending");
+    }
+}
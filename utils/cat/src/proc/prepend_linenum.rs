@@ -1,8 +1,7 @@
+use line_number::prepend_line_number;
+
 pub fn prepend_linenum(v: &Vec<u8>, num: usize) -> Vec<u8> {
-    let mut retval = v.clone();
-    let preface = Vec::from(format!("{: >6} ", num).as_bytes());
-    retval.splice(..0, preface);
-    retval
+    prepend_line_number(v, num, 6, b" ")
 }
 
 #[cfg(test)]
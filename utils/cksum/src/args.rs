@@ -0,0 +1,381 @@
+use std::error::Error;
+
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+use crate::cksum::algorithm::Algorithm;
+use crate::cksum::cksum_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// How the digests are written
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Encoding {
+    #[default]
+    Hex,
+    Base64,
+    Raw,
+}
+
+/// Print or verify checksums.
+/// By default use the 32 bit CRC algorithm
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input
+    pub file: Vec<String>,
+
+    /// select the digest type to use
+    #[arg(short = 'a', long, value_enum, value_name = "TYPE")]
+    pub algorithm: Option<Algorithm>,
+
+    /// emit base64-encoded digests, not hexadecimal
+    #[arg(long, default_value_t = false)]
+    pub base64: bool,
+
+    /// read checksums from the FILEs and check them
+    #[arg(short = 'c', long, default_value_t = false)]
+    pub check: bool,
+
+    /// digest length in bits; must not exceed the max for the blake2 algorithm
+    /// and must be a multiple of 8
+    #[arg(short = 'l', long, value_name = "BITS", value_parser = parse_length)]
+    pub length: Option<usize>,
+
+    /// emit a raw binary digest, not hexadecimal
+    #[arg(long, default_value_t = false)]
+    pub raw: bool,
+
+    /// create a BSD-style checksum (the default)
+    #[arg(long, default_value_t = false, overrides_with = "untagged")]
+    pub tag: bool,
+
+    /// create a reversed style checksum, without digest type
+    #[arg(long, default_value_t = false, overrides_with = "tag")]
+    pub untagged: bool,
+
+    /// end each output line with NUL, not newline, and disable file name escaping
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero: bool,
+
+    /// don't fail or report status for missing files
+    #[arg(long, default_value_t = false)]
+    pub ignore_missing: bool,
+
+    /// don't print OK for each successfully verified file
+    #[arg(long, default_value_t = false)]
+    pub quiet: bool,
+
+    /// don't output anything, status code shows success
+    #[arg(long, default_value_t = false)]
+    pub status: bool,
+
+    /// exit non-zero for improperly formatted checksum lines
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+
+    /// warn about improperly formatted checksum lines
+    #[arg(short = 'w', long, default_value_t = false)]
+    pub warn: bool,
+}
+
+/// Print or check checksums, the options of the legacy md5sum, sha1sum and the like
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct SumArgs {
+    /// With no FILE, or when FILE is -, read standard input
+    pub file: Vec<String>,
+
+    /// read in binary mode
+    #[arg(short = 'b', long, default_value_t = false, overrides_with = "text")]
+    pub binary: bool,
+
+    /// read checksums from the FILEs and check them
+    #[arg(short = 'c', long, default_value_t = false)]
+    pub check: bool,
+
+    /// digest length in bits; must not exceed the max for the blake2 algorithm
+    /// and must be a multiple of 8
+    #[arg(short = 'l', long, value_name = "BITS", value_parser = parse_length)]
+    pub length: Option<usize>,
+
+    /// create a BSD-style checksum
+    #[arg(long, default_value_t = false)]
+    pub tag: bool,
+
+    /// read in text mode (default)
+    #[arg(short = 't', long, default_value_t = false, overrides_with = "binary")]
+    pub text: bool,
+
+    /// end each output line with NUL, not newline, and disable file name escaping
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero: bool,
+
+    /// don't fail or report status for missing files
+    #[arg(long, default_value_t = false)]
+    pub ignore_missing: bool,
+
+    /// don't print OK for each successfully verified file
+    #[arg(long, default_value_t = false)]
+    pub quiet: bool,
+
+    /// don't output anything, status code shows success
+    #[arg(long, default_value_t = false)]
+    pub status: bool,
+
+    /// exit non-zero for improperly formatted checksum lines
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+
+    /// warn about improperly formatted checksum lines
+    #[arg(short = 'w', long, default_value_t = false)]
+    pub warn: bool,
+}
+
+/// The options of both cksum and the legacy utilities
+#[derive(Debug, Default)]
+pub struct Options {
+    /// The name in the diagnostics
+    pub program: &'static str,
+    pub files: Vec<String>,
+    /// CRC without -a of cksum, the checksum lines tell the algorithm then
+    pub algorithm: Option<Algorithm>,
+    /// Bytes of a BLAKE2b digest
+    pub length: Option<usize>,
+    pub check: bool,
+    pub tag: bool,
+    /// "*" before the names of the untagged lines
+    pub binary: bool,
+    pub encoding: Encoding,
+    pub zero: bool,
+    pub ignore_missing: bool,
+    pub quiet: bool,
+    pub status: bool,
+    pub strict: bool,
+    pub warn: bool,
+}
+
+impl Options {
+    /** The options of cksum, or of the legacy utility of the algorithm.
+     *
+     * The errors are reported the GNU way, and the process exits.
+     */
+    pub fn parse(legacy: Option<Algorithm>) -> Options {
+        let options = match legacy {
+            None => parse_cksum(),
+            Some(algorithm) => parse_sum(algorithm),
+        };
+        options.validate();
+        options
+    }
+
+    fn validate(&self) {
+        let usage_error = |message: &str| usage_error(self.program, message);
+        if self.check && self.zero {
+            usage_error("the --zero option is not supported when verifying checksums");
+        }
+        let only_checking = [
+            ("ignore-missing", self.ignore_missing),
+            ("status", self.status),
+            ("warn", self.warn),
+            ("quiet", self.quiet),
+            ("strict", self.strict),
+        ];
+        for (option, given) in only_checking {
+            if given && !self.check {
+                usage_error(&format!(
+                    "the --{} option is meaningful only when verifying checksums",
+                    option
+                ));
+            }
+        }
+        if self.check && self.algorithm.is_some_and(|algorithm| algorithm.is_crc()) {
+            fatal(
+                self.program,
+                "--check is not supported with --algorithm={bsd,sysv,crc,crc32b}",
+            );
+        }
+        if self.encoding == Encoding::Raw && self.files.len() > 1 {
+            fatal(
+                self.program,
+                "the --raw option is not supported with multiple files",
+            );
+        }
+    }
+}
+
+fn parse_cksum() -> Options {
+    let program = "cksum";
+    let (args, _) = get_matches::<Args>(<Args as CommandFactory>::command(), program);
+    if let Some(length) = args.length {
+        if args.algorithm != Some(Algorithm::Blake2b) {
+            fatal(
+                program,
+                "--length is only supported with --algorithm=blake2b",
+            );
+        }
+        check_max_length(program, length);
+    }
+    Options {
+        program,
+        files: args.file,
+        algorithm: args.algorithm,
+        length: args
+            .length
+            .filter(|&length| length != 0)
+            .map(|bits| bits / 8),
+        check: args.check,
+        tag: !args.untagged,
+        binary: false,
+        encoding: match (args.raw, args.base64) {
+            (true, _) => Encoding::Raw,
+            (false, true) => Encoding::Base64,
+            (false, false) => Encoding::Hex,
+        },
+        zero: args.zero,
+        ignore_missing: args.ignore_missing,
+        quiet: args.quiet,
+        status: args.status,
+        strict: args.strict,
+        warn: args.warn,
+    }
+}
+
+fn parse_sum(algorithm: Algorithm) -> Options {
+    let program = algorithm.legacy_program();
+    let about = format!(
+        "Print or check {} ({}-bit) checksums",
+        algorithm.tag(),
+        algorithm.digest_size() * 8
+    );
+    let mut command = <SumArgs as CommandFactory>::command()
+        .about(about.clone())
+        .long_about(about);
+    if algorithm != Algorithm::Blake2b {
+        command = command.mut_arg("length", |arg| arg.hide(true));
+    }
+    let (args, matches) = get_matches::<SumArgs>(command, program);
+    if let Some(length) = args.length {
+        if algorithm != Algorithm::Blake2b {
+            usage_error(program, "invalid option -- 'l'");
+        }
+        check_max_length(program, length);
+    }
+    // --tag implies --binary, so it's -t after --tag, that doesn't go with it
+    let index = |id| matches.index_of(id).unwrap_or(0);
+    if args.tag && args.text && index("text") > index("tag") {
+        usage_error(program, "--tag does not support --text mode");
+    }
+    if args.check && args.tag {
+        usage_error(
+            program,
+            "the --tag option is meaningless when verifying checksums",
+        );
+    }
+    if args.check && (args.binary || args.text) {
+        usage_error(
+            program,
+            "the --binary and --text options are meaningless when verifying checksums",
+        );
+    }
+    Options {
+        program,
+        files: args.file,
+        algorithm: Some(algorithm),
+        length: args
+            .length
+            .filter(|&length| length != 0)
+            .map(|bits| bits / 8),
+        check: args.check,
+        tag: args.tag,
+        binary: args.binary,
+        encoding: Encoding::Hex,
+        zero: args.zero,
+        ignore_missing: args.ignore_missing,
+        quiet: args.quiet,
+        status: args.status,
+        strict: args.strict,
+        warn: args.warn,
+    }
+}
+
+/// The arguments under the name of the program, ArgError goes in GNU format
+fn get_matches<T: FromArgMatches>(
+    command: clap::Command,
+    program: &'static str,
+) -> (T, ArgMatches) {
+    let matches = command
+        .name(program)
+        .bin_name(program)
+        .try_get_matches()
+        .unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                for line in invalid.0.lines() {
+                    eprintln!("{}: {}", program, line);
+                }
+                std::process::exit(1);
+            }
+            err.exit()
+        });
+    let args = T::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    (args, matches)
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(program: &str, message: &str) -> ! {
+    eprintln!("{}: {}", program, message);
+    eprintln!("Try '{} --help' for more information.", program);
+    std::process::exit(1);
+}
+
+fn fatal(program: &str, message: &str) -> ! {
+    eprintln!("{}: {}", program, message);
+    std::process::exit(1);
+}
+
+/// Bits of the digest, a multiple of 8, 0 means the whole digest
+fn parse_length(length: &str) -> Result<usize, ArgError> {
+    let invalid = || format!("invalid length: '{}'", length);
+    let bits: usize = length
+        .trim_start()
+        .parse()
+        .map_err(|_| ArgError(invalid()))?;
+    if !bits.is_multiple_of(8) {
+        return Err(ArgError(format!(
+            "{}\n{}",
+            invalid(),
+            "length is not a multiple of 8"
+        )));
+    }
+    Ok(bits)
+}
+
+/// The second line of the message goes with the name of the program too
+fn check_max_length(program: &str, bits: usize) {
+    let max = Algorithm::Blake2b.digest_size() * 8;
+    if bits > max {
+        eprintln!("{}: invalid length: '{}'", program, bits);
+        fatal(
+            program,
+            &format!("maximum digest length for 'BLAKE2b' is {} bits", max),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_lengths() {
+        assert_eq!(parse_length(" 256"), Ok(256));
+        assert_eq!(parse_length("0"), Ok(0));
+        assert_eq!(
+            parse_length("12"),
+            Err(ArgError(
+                "invalid length: '12'\nlength is not a multiple of 8".to_string()
+            ))
+        );
+        assert!(parse_length("8k").is_err());
+        assert!(parse_length("-8").is_err());
+    }
+}
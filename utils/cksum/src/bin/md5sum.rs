@@ -0,0 +1,7 @@
+use std::process::ExitCode;
+
+use cksum::Algorithm;
+
+fn main() -> ExitCode {
+    cksum::run(Some(Algorithm::Md5))
+}
@@ -0,0 +1,377 @@
+use blake2::digest::{Digest, Update, VariableOutput};
+use blake2::{Blake2b512, Blake2bVar};
+use clap::ValueEnum;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Sha224, Sha256, Sha384, Sha512};
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Algorithm {
+    /// equivalent to the POSIX cksum
+    #[default]
+    Crc,
+    /// the CRC of gzip and zlib
+    Crc32b,
+    /// equivalent to md5sum
+    Md5,
+    /// equivalent to sha1sum
+    Sha1,
+    /// equivalent to sha224sum
+    Sha224,
+    /// equivalent to sha256sum
+    Sha256,
+    /// equivalent to sha384sum
+    Sha384,
+    /// equivalent to sha512sum
+    Sha512,
+    /// equivalent to b2sum
+    Blake2b,
+    /// only available through cksum
+    Sm3,
+}
+
+impl Algorithm {
+    /// All the algorithms, that can be told by their tags in the checksum lines
+    pub const TAGGED: [Algorithm; 8] = [
+        Algorithm::Md5,
+        Algorithm::Sha1,
+        Algorithm::Sha224,
+        Algorithm::Sha256,
+        Algorithm::Sha384,
+        Algorithm::Sha512,
+        Algorithm::Blake2b,
+        Algorithm::Sm3,
+    ];
+
+    /// The name in the BSD-style lines
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Algorithm::Crc => "CRC",
+            Algorithm::Crc32b => "CRC32B",
+            Algorithm::Md5 => "MD5",
+            Algorithm::Sha1 => "SHA1",
+            Algorithm::Sha224 => "SHA224",
+            Algorithm::Sha256 => "SHA256",
+            Algorithm::Sha384 => "SHA384",
+            Algorithm::Sha512 => "SHA512",
+            Algorithm::Blake2b => "BLAKE2b",
+            Algorithm::Sm3 => "SM3",
+        }
+    }
+
+    /// The utility, which computes only this algorithm
+    pub fn legacy_program(&self) -> &'static str {
+        match self {
+            Algorithm::Md5 => "md5sum",
+            Algorithm::Sha1 => "sha1sum",
+            Algorithm::Sha224 => "sha224sum",
+            Algorithm::Sha256 => "sha256sum",
+            Algorithm::Sha384 => "sha384sum",
+            Algorithm::Sha512 => "sha512sum",
+            Algorithm::Blake2b => "b2sum",
+            Algorithm::Crc | Algorithm::Crc32b | Algorithm::Sm3 => "cksum",
+        }
+    }
+
+    /// Bytes of the whole digest
+    pub fn digest_size(&self) -> usize {
+        match self {
+            Algorithm::Crc | Algorithm::Crc32b => 4,
+            Algorithm::Md5 => 16,
+            Algorithm::Sha1 => 20,
+            Algorithm::Sha224 => 28,
+            Algorithm::Sha256 | Algorithm::Sm3 => 32,
+            Algorithm::Sha384 => 48,
+            Algorithm::Sha512 | Algorithm::Blake2b => 64,
+        }
+    }
+
+    /// The CRCs are written as numbers with the size of the input, and can't be checked
+    pub fn is_crc(&self) -> bool {
+        matches!(self, Algorithm::Crc | Algorithm::Crc32b)
+    }
+
+    /// The hasher of the digest, BLAKE2b can be shorter than its whole size
+    pub fn hasher(&self, length: Option<usize>) -> Box<dyn Hasher> {
+        match self {
+            Algorithm::Crc => Box::new(Crc::default()),
+            Algorithm::Crc32b => Box::new(Crc32b::default()),
+            Algorithm::Md5 => Box::new(Fixed(Md5::new())),
+            Algorithm::Sha1 => Box::new(Fixed(Sha1::new())),
+            Algorithm::Sha224 => Box::new(Fixed(Sha224::new())),
+            Algorithm::Sha256 => Box::new(Fixed(Sha256::new())),
+            Algorithm::Sha384 => Box::new(Fixed(Sha384::new())),
+            Algorithm::Sha512 => Box::new(Fixed(Sha512::new())),
+            Algorithm::Blake2b => match length {
+                Some(length) if length != self.digest_size() => match Blake2bVar::new(length) {
+                    Ok(hasher) => Box::new(Variable(hasher)),
+                    Err(_) => Box::new(Fixed(Blake2b512::new())),
+                },
+                _ => Box::new(Fixed(Blake2b512::new())),
+            },
+            Algorithm::Sm3 => Box::new(Sm3::default()),
+        }
+    }
+}
+
+/// The digest of the bytes given one part after another
+pub trait Hasher {
+    fn update(&mut self, bytes: &[u8]);
+
+    /// The digest, the CRCs go big endian
+    fn finish(self: Box<Self>) -> Vec<u8>;
+}
+
+struct Fixed<D>(D);
+
+impl<D: Digest> Hasher for Fixed<D> {
+    fn update(&mut self, bytes: &[u8]) {
+        Digest::update(&mut self.0, bytes);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.0.finalize().to_vec()
+    }
+}
+
+/// BLAKE2b of -l
+struct Variable(Blake2bVar);
+
+impl Hasher for Variable {
+    fn update(&mut self, bytes: &[u8]) {
+        Update::update(&mut self.0, bytes);
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        let mut digest = vec![0; self.0.output_size()];
+        // The buffer is of the output size, that can't fail
+        let _ = self.0.finalize_variable(&mut digest);
+        digest
+    }
+}
+
+/// CRC-32 of the polynomial 0x04C11DB7, most significant bit first
+const CRC_TABLE: [u32; 256] = crc_table(false);
+
+/// The same polynomial reflected, least significant bit first
+const CRC32B_TABLE: [u32; 256] = crc_table(true);
+
+const fn crc_table(reflected: bool) -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = if reflected { byte } else { byte << 24 };
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match (reflected, crc & 1 != 0, crc & 0x8000_0000 != 0) {
+                (true, true, _) => crc >> 1 ^ 0xEDB8_8320,
+                (true, false, _) => crc >> 1,
+                (false, _, true) => crc << 1 ^ 0x04C1_1DB7,
+                (false, _, false) => crc << 1,
+            };
+            bit += 1;
+        }
+        table[byte as usize] = crc;
+        byte += 1;
+    }
+    table
+}
+
+/// The POSIX cksum CRC, the size of the input goes after it, least significant byte first
+#[derive(Default)]
+struct Crc {
+    crc: u32,
+    size: u64,
+}
+
+impl Crc {
+    fn add(&mut self, byte: u8) {
+        self.crc = self.crc << 8 ^ CRC_TABLE[((self.crc >> 24) as u8 ^ byte) as usize];
+    }
+}
+
+impl Hasher for Crc {
+    fn update(&mut self, bytes: &[u8]) {
+        bytes.iter().for_each(|&byte| self.add(byte));
+        self.size += bytes.len() as u64;
+    }
+
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let mut size = self.size;
+        while size != 0 {
+            self.add(size as u8);
+            size >>= 8;
+        }
+        (!self.crc).to_be_bytes().to_vec()
+    }
+}
+
+#[derive(Default)]
+struct Crc32b {
+    crc: u32,
+}
+
+impl Hasher for Crc32b {
+    fn update(&mut self, bytes: &[u8]) {
+        let mut crc = !self.crc;
+        for &byte in bytes {
+            crc = crc >> 8 ^ CRC32B_TABLE[(crc as u8 ^ byte) as usize];
+        }
+        self.crc = !crc;
+    }
+
+    fn finish(self: Box<Self>) -> Vec<u8> {
+        self.crc.to_be_bytes().to_vec()
+    }
+}
+
+/// The Chinese national standard GB/T 32905-2016
+struct Sm3 {
+    state: [u32; 8],
+    block: [u8; 64],
+    buffered: usize,
+    size: u64,
+}
+
+impl Default for Sm3 {
+    fn default() -> Self {
+        Sm3 {
+            state: [
+                0x7380_166f,
+                0x4914_b2b9,
+                0x1724_42d7,
+                0xda8a_0600,
+                0xa96f_30bc,
+                0x1631_38aa,
+                0xe38d_ee4d,
+                0xb0fb_0e4e,
+            ],
+            block: [0; 64],
+            buffered: 0,
+            size: 0,
+        }
+    }
+}
+
+impl Sm3 {
+    fn compress(&mut self) {
+        let p0 = |x: u32| x ^ x.rotate_left(9) ^ x.rotate_left(17);
+        let p1 = |x: u32| x ^ x.rotate_left(15) ^ x.rotate_left(23);
+        let mut w = [0u32; 68];
+        for (word, bytes) in w.iter_mut().zip(self.block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for j in 16..68 {
+            w[j] = p1(w[j - 16] ^ w[j - 9] ^ w[j - 3].rotate_left(15))
+                ^ w[j - 13].rotate_left(7)
+                ^ w[j - 6];
+        }
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = self.state;
+        for j in 0..64 {
+            let (t, ff, gg) = match j {
+                0..=15 => (0x79cc_4519u32, a ^ b ^ c, e ^ f ^ g),
+                _ => (0x7a87_9d8a, (a & b) | (a & c) | (b & c), (e & f) | (!e & g)),
+            };
+            let ss1 = a
+                .rotate_left(12)
+                .wrapping_add(e)
+                .wrapping_add(t.rotate_left(j as u32 % 32))
+                .rotate_left(7);
+            let ss2 = ss1 ^ a.rotate_left(12);
+            let tt1 = ff
+                .wrapping_add(d)
+                .wrapping_add(ss2)
+                .wrapping_add(w[j] ^ w[j + 4]);
+            let tt2 = gg.wrapping_add(h).wrapping_add(ss1).wrapping_add(w[j]);
+            d = c;
+            c = b.rotate_left(9);
+            b = a;
+            a = tt1;
+            h = g;
+            g = f.rotate_left(19);
+            f = e;
+            e = p0(tt2);
+        }
+        for (state, word) in self.state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *state ^= word;
+        }
+    }
+}
+
+impl Hasher for Sm3 {
+    fn update(&mut self, mut bytes: &[u8]) {
+        self.size += bytes.len() as u64;
+        while !bytes.is_empty() {
+            let taken = bytes.len().min(64 - self.buffered);
+            self.block[self.buffered..self.buffered + taken].copy_from_slice(&bytes[..taken]);
+            self.buffered += taken;
+            bytes = &bytes[taken..];
+            if self.buffered == 64 {
+                self.compress();
+                self.buffered = 0;
+            }
+        }
+    }
+
+    /// The padding is the bit 1, the zeros, and the size in bits, like in SHA-256
+    fn finish(mut self: Box<Self>) -> Vec<u8> {
+        let bits = self.size.wrapping_mul(8);
+        let zeros = (119 - self.buffered) % 64;
+        let mut padding = vec![0x80];
+        padding.resize(1 + zeros, 0);
+        padding.extend_from_slice(&bits.to_be_bytes());
+        self.update(&padding);
+        self.state
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn hex(algorithm: Algorithm, length: Option<usize>, bytes: &[u8]) -> String {
+        let mut hasher = algorithm.hasher(length);
+        let (head, tail) = bytes.split_at(bytes.len() / 2);
+        hasher.update(head);
+        hasher.update(tail);
+        hasher
+            .finish()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    #[test]
+    fn computes_the_crcs() {
+        assert_eq!(
+            hex(Algorithm::Crc, None, b"abc"),
+            format!("{:08x}", 1219131554)
+        );
+        assert_eq!(
+            hex(Algorithm::Crc, None, b""),
+            format!("{:08x}", 4294967295u32)
+        );
+        assert_eq!(hex(Algorithm::Crc32b, None, b"123456789"), "cbf43926");
+    }
+
+    #[test]
+    fn computes_sm3() {
+        assert_eq!(
+            hex(Algorithm::Sm3, None, b"abc"),
+            "66c7f0f462eeedd9d1f2d46bdc10e4e24167c4875cf2f7a2297da02b8f4ba8e0"
+        );
+        assert_eq!(
+            hex(Algorithm::Sm3, None, &b"abcd".repeat(16)),
+            "debe9ff92275b8a138604889c18e5a4d6fdb70e5387e5765293dcba39c0c5732"
+        );
+    }
+
+    #[test]
+    fn shortens_blake2b() {
+        assert_eq!(hex(Algorithm::Blake2b, Some(1), b"abc"), "6b");
+        assert_eq!(hex(Algorithm::Blake2b, Some(64), b"abc").len(), 128);
+    }
+}
@@ -0,0 +1,401 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+
+use base64::{engine::general_purpose, Engine};
+use chunk_reader::ChunkReader;
+
+use super::algorithm::Algorithm;
+use super::cksum_error::{quote, CksumError};
+use super::{digest_file, escape};
+use crate::args::Options;
+
+/// A line of a checksum FILE
+#[derive(Debug, PartialEq)]
+struct Entry {
+    algorithm: Algorithm,
+    /// Bytes of a BLAKE2b digest
+    length: Option<usize>,
+    name: String,
+    digest: Vec<u8>,
+}
+
+/// What happened to the lines of a checksum FILE, for the summary
+#[derive(Default)]
+struct Counts {
+    formatted: usize,
+    improper: usize,
+    unreadable: usize,
+    mismatched: usize,
+    matched: usize,
+}
+
+/** Checks the FILEs listed in the checksum FILEs, the standard input without them.
+ *
+ * Returns whether all of them matched, and the checksum FILEs were fine.
+ */
+pub fn check_files<W: Write>(options: &Options, output: &mut W) -> Result<bool, CksumError> {
+    let stdin = ["-".to_string()];
+    let files = match options.files.is_empty() {
+        true => &stdin[..],
+        false => &options.files[..],
+    };
+    let mut all_ok = true;
+    for name in files {
+        let input: Box<dyn Read> = match name.as_str() {
+            "-" => Box::new(io::stdin()),
+            _ => match File::open(name) {
+                Ok(file) => Box::new(file),
+                Err(e) => {
+                    report(options, output, &CksumError::Read(name.clone(), e))?;
+                    all_ok = false;
+                    continue;
+                }
+            },
+        };
+        match check_file(options, name, input, output) {
+            Ok(ok) => all_ok &= ok,
+            Err(e @ CksumError::Read(..)) => {
+                report(options, output, &e)?;
+                all_ok = false;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(all_ok)
+}
+
+/// Checks the lines of a checksum FILE one by one, then sums them up
+fn check_file<W: Write>(
+    options: &Options,
+    name: &str,
+    input: Box<dyn Read>,
+    output: &mut W,
+) -> Result<bool, CksumError> {
+    let shown = match name {
+        "-" => "standard input",
+        _ => name,
+    };
+    let mut counts = Counts::default();
+    // Whether the untagged lines have a single space after the digest, the first one tells
+    let mut reversed = None;
+    // The algorithm of the last line, which the warnings tell, like GNU does
+    let mut algorithm = options.algorithm.unwrap_or_default();
+    for (number, line) in BufReader::new(input).chunks(b'\n', 0).enumerate() {
+        let line = line.map_err(|e| CksumError::Read(name.to_string(), e))?;
+        let line = line.strip_suffix(b"\n").unwrap_or(&line);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() || line[0] == b'#' {
+            continue;
+        }
+        let entry = std::str::from_utf8(line)
+            .ok()
+            .and_then(|line| parse_line(options, line, &mut reversed));
+        let Some(entry) = entry else {
+            counts.improper += 1;
+            if options.warn {
+                let tag = algorithm.tag();
+                warn(
+                    options,
+                    output,
+                    &format!(
+                        "{}: {}: improperly formatted {} checksum line",
+                        quote(shown),
+                        number + 1,
+                        tag
+                    ),
+                )?;
+            }
+            continue;
+        };
+        counts.formatted += 1;
+        algorithm = entry.algorithm;
+        let (prefix, escaped) = escape(&entry.name);
+        let status = match digest_file(&entry.name, entry.algorithm, entry.length) {
+            Err(e) if options.ignore_missing && e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                report(options, output, &CksumError::Read(entry.name.clone(), e))?;
+                counts.unreadable += 1;
+                "FAILED open or read"
+            }
+            Ok((digest, _)) if digest == entry.digest => {
+                counts.matched += 1;
+                if options.quiet {
+                    continue;
+                }
+                "OK"
+            }
+            Ok(_) => {
+                counts.mismatched += 1;
+                "FAILED"
+            }
+        };
+        if !options.status {
+            writeln!(output, "{}{}: {}", prefix, escaped, status).map_err(CksumError::Write)?;
+        }
+    }
+    if counts.formatted == 0 {
+        let message = format!(
+            "{}: no properly formatted checksum lines found",
+            quote(shown)
+        );
+        warn(options, output, &message)?;
+        return Ok(false);
+    }
+    if !options.status {
+        let warnings = [
+            (
+                counts.improper,
+                ["line is", "lines are"],
+                "improperly formatted",
+            ),
+            (
+                counts.unreadable,
+                ["listed file", "listed files"],
+                "could not be read",
+            ),
+            (
+                counts.mismatched,
+                ["computed checksum", "computed checksums"],
+                "did NOT match",
+            ),
+        ];
+        for (count, nouns, what) in warnings.into_iter().filter(|(count, ..)| *count > 0) {
+            let message = format!(
+                "WARNING: {} {} {}",
+                count,
+                nouns[usize::from(count > 1)],
+                what
+            );
+            warn(options, output, &message)?;
+        }
+        if options.ignore_missing && counts.matched == 0 {
+            let message = format!("{}: no file was verified", quote(shown));
+            warn(options, output, &message)?;
+        }
+    }
+    Ok(counts.mismatched == 0
+        && counts.unreadable == 0
+        && (!options.strict || counts.improper == 0)
+        && (!options.ignore_missing || counts.matched > 0))
+}
+
+/// The messages go after whatever is written before them
+fn warn<W: Write>(options: &Options, output: &mut W, message: &str) -> Result<(), CksumError> {
+    output.flush().map_err(CksumError::Write)?;
+    eprintln!("{}: {}", options.program, message);
+    Ok(())
+}
+
+fn report<W: Write>(
+    options: &Options,
+    output: &mut W,
+    error: &CksumError,
+) -> Result<(), CksumError> {
+    warn(options, output, &error.to_string())
+}
+
+/** The FILE and its digest in a checksum line.
+ *
+ * The lines are BSD-style "ALGORITHM (FILE) = DIGEST", or "DIGEST  FILE",
+ * with "*" instead of the second space in binary mode, or even a single
+ * space, if so is the first of such lines. The names with backslashes and
+ * line breaks are escaped, with a backslash before the line. The digests
+ * are either hexadecimal or base64.
+ */
+fn parse_line(options: &Options, line: &str, reversed: &mut Option<bool>) -> Option<Entry> {
+    let line = line.trim_start_matches([' ', '\t']);
+    let (escaped, line) = match line.strip_prefix('\\') {
+        Some(line) => (true, line),
+        None => (false, line),
+    };
+    let (algorithm, length, name, digest) = match parse_tagged(options, line) {
+        Some(tagged) => tagged,
+        None => parse_untagged(options, line, reversed)?,
+    };
+    let name = match escaped {
+        true => unescape(name)?,
+        false => name.to_string(),
+    };
+    Some(Entry {
+        algorithm,
+        length,
+        name,
+        digest,
+    })
+}
+
+type Parsed<'a> = (Algorithm, Option<usize>, &'a str, Vec<u8>);
+
+/// "ALGORITHM (FILE) = DIGEST", the last ")" ends the name
+fn parse_tagged<'a>(options: &Options, line: &'a str) -> Option<Parsed<'a>> {
+    let blank = [' ', '\t'];
+    Algorithm::TAGGED
+        .into_iter()
+        .filter(|&algorithm| options.algorithm.is_none_or(|given| given == algorithm))
+        .find_map(|algorithm| {
+            let rest = line.strip_prefix(algorithm.tag())?;
+            let (length, rest) = match rest.strip_prefix('-') {
+                Some(rest) if algorithm == Algorithm::Blake2b => {
+                    let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+                    let bits: usize = rest[..digits].parse().ok()?;
+                    let size = algorithm.digest_size();
+                    if bits == 0 || !bits.is_multiple_of(8) || bits / 8 > size {
+                        return None;
+                    }
+                    (Some(bits / 8), &rest[digits..])
+                }
+                _ => (None, rest),
+            };
+            let rest = rest.strip_prefix(' ').unwrap_or(rest);
+            let rest = rest.strip_prefix('(')?;
+            let close = rest.rfind(')')?;
+            let digest = rest[close + 1..]
+                .trim_start_matches(blank)
+                .strip_prefix('=')?
+                .trim_start_matches(blank);
+            let size = length.unwrap_or(algorithm.digest_size());
+            let digest = decode_digest(digest, Some(size))?;
+            let length = Some(digest.len()).filter(|_| algorithm == Algorithm::Blake2b);
+            Some((algorithm, length, &rest[..close], digest))
+        })
+}
+
+/// "DIGEST  FILE", which doesn't tell the algorithm, so it must be given
+fn parse_untagged<'a>(
+    options: &Options,
+    line: &'a str,
+    reversed: &mut Option<bool>,
+) -> Option<Parsed<'a>> {
+    let algorithm = options.algorithm?;
+    let end = line.find([' ', '\t'])?;
+    // The length of BLAKE2b is the one of the digest, whatever -l is
+    let size = Some(algorithm.digest_size()).filter(|_| algorithm != Algorithm::Blake2b);
+    let digest = decode_digest(&line[..end], size)?;
+    let rest = &line[end + 1..];
+    let name = match rest.strip_prefix([' ', '*']) {
+        Some(name) if rest.len() > 1 && *reversed != Some(true) => {
+            *reversed = Some(false);
+            name
+        }
+        _ if *reversed == Some(false) => return None,
+        _ => {
+            *reversed = Some(true);
+            rest
+        }
+    };
+    let length = Some(digest.len()).filter(|_| algorithm == Algorithm::Blake2b);
+    Some((algorithm, length, name, digest))
+}
+
+/** The bytes of a hexadecimal or a base64 digest of the size.
+ *
+ * The size of BLAKE2b is up to the digest, when it's not told.
+ */
+fn decode_digest(digest: &str, size: Option<usize>) -> Option<Vec<u8>> {
+    let max = Algorithm::Blake2b.digest_size();
+    let hex = digest.len().is_multiple_of(2) && digest.bytes().all(|c| c.is_ascii_hexdigit());
+    let bytes = match size {
+        Some(size) if hex && digest.len() == size * 2 => decode_hex(digest),
+        None if hex => decode_hex(digest),
+        _ => general_purpose::STANDARD.decode(digest).ok()?,
+    };
+    match size {
+        Some(size) if bytes.len() != size => None,
+        None if bytes.is_empty() || bytes.len() > max => None,
+        _ => Some(bytes),
+    }
+}
+
+fn decode_hex(digest: &str) -> Vec<u8> {
+    (0..digest.len())
+        .step_by(2)
+        .filter_map(|i| u8::from_str_radix(&digest[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// The name of an escaped line, only the backslashes and the line breaks are escaped
+fn unescape(name: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                'n' => '\n',
+                'r' => '\r',
+                _ => return None,
+            },
+            _ => c,
+        });
+    }
+    Some(unescaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const MD5: &str = "900150983cd24fb0d6963f7d28e17f72";
+
+    fn parsed(algorithm: Option<Algorithm>, lines: &[&str]) -> Vec<Option<(Algorithm, String)>> {
+        let options = Options {
+            algorithm,
+            ..Default::default()
+        };
+        let mut reversed = None;
+        lines
+            .iter()
+            .map(|line| parse_line(&options, line, &mut reversed))
+            .map(|entry| entry.map(|entry| (entry.algorithm, entry.name)))
+            .collect()
+    }
+
+    #[test]
+    fn parses_tagged_lines() {
+        let lines = [
+            format!("MD5 (a (b)) = {}", MD5),
+            format!("\\MD5(a\\nb)= {}", MD5.to_uppercase()),
+            "BLAKE2b-8 (c) = 6b".to_string(),
+            "SM3 (d) = kAFQmDzST7DWlj99KOF/cg==".to_string(),
+            "SHA1 (e) = 6b".to_string(),
+        ];
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        assert_eq!(
+            parsed(None, &lines),
+            [
+                Some((Algorithm::Md5, "a (b)".to_string())),
+                Some((Algorithm::Md5, "a\nb".to_string())),
+                Some((Algorithm::Blake2b, "c".to_string())),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_untagged_lines() {
+        let standard = [format!("{}  a", MD5), format!("{} *b ", MD5)];
+        let standard: Vec<&str> = standard.iter().map(String::as_str).collect();
+        assert_eq!(parsed(None, &standard), [None, None]);
+        assert_eq!(
+            parsed(Some(Algorithm::Md5), &standard),
+            [
+                Some((Algorithm::Md5, "a".to_string())),
+                Some((Algorithm::Md5, "b ".to_string())),
+            ]
+        );
+        let reversed = [format!("{} a", MD5), format!("{}  b", MD5)];
+        let reversed: Vec<&str> = reversed.iter().map(String::as_str).collect();
+        assert_eq!(
+            parsed(Some(Algorithm::Md5), &reversed),
+            [
+                Some((Algorithm::Md5, "a".to_string())),
+                Some((Algorithm::Md5, " b".to_string())),
+            ]
+        );
+        assert_eq!(
+            parsed(Some(Algorithm::Blake2b), &["6b  c", "6  d"]),
+            [Some((Algorithm::Blake2b, "c".to_string())), None]
+        );
+    }
+}
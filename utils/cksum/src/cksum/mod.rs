@@ -0,0 +1,211 @@
+pub mod algorithm;
+pub mod check;
+pub mod cksum_error;
+
+use std::fs::File;
+use std::io::{self, Read, Write};
+
+use base64::{engine::general_purpose, Engine};
+
+use crate::args::{Encoding, Options};
+use algorithm::Algorithm;
+use cksum_error::CksumError;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// The digest of the FILE, or of the standard input for "-", and its size
+pub fn digest_file(
+    name: &str,
+    algorithm: Algorithm,
+    length: Option<usize>,
+) -> io::Result<(Vec<u8>, u64)> {
+    let mut input: Box<dyn Read> = match name {
+        "-" => Box::new(io::stdin()),
+        _ => Box::new(File::open(name)?),
+    };
+    let mut hasher = algorithm.hasher(length);
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut size = 0;
+    loop {
+        match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => {
+                hasher.update(&buffer[..n]);
+                size += n as u64;
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((hasher.finish(), size))
+}
+
+/** Writes the checksums of the FILEs, the standard input without them.
+ *
+ * The FILEs, that can't be read, are reported, and the rest go on.
+ * Returns whether all of them were read.
+ */
+pub fn digest_files<W: Write>(options: &Options, output: &mut W) -> Result<bool, CksumError> {
+    let algorithm = options.algorithm.unwrap_or_default();
+    let stdin = ["-".to_string()];
+    let files = match options.files.is_empty() {
+        true => &stdin[..],
+        false => &options.files[..],
+    };
+    let mut all_read = true;
+    for name in files {
+        match digest_file(name, algorithm, options.length) {
+            Ok((digest, size)) => {
+                // The CRCs of the standard input go without a name, unless it's given
+                let name =
+                    (!options.files.is_empty() || !algorithm.is_crc()).then_some(name.as_str());
+                let line = format_line(options, name, &digest, size);
+                output.write_all(&line).map_err(CksumError::Write)?;
+            }
+            Err(e) => {
+                output.flush().map_err(CksumError::Write)?;
+                eprintln!("{}: {}", options.program, CksumError::Read(name.clone(), e));
+                all_read = false;
+            }
+        }
+    }
+    Ok(all_read)
+}
+
+/** The checksum line of a FILE.
+ *
+ * The CRCs are the number and the size, like POSIX cksum. The digests are
+ * BSD-style with --tag, "ALGORITHM (FILE) = DIGEST", or "DIGEST  FILE".
+ * The raw digest is just the bytes.
+ */
+pub fn format_line(options: &Options, name: Option<&str>, digest: &[u8], size: u64) -> Vec<u8> {
+    let algorithm = options.algorithm.unwrap_or_default();
+    if options.encoding == Encoding::Raw {
+        return digest.to_vec();
+    }
+    let delimiter = if options.zero { '\0' } else { '\n' };
+    if algorithm.is_crc() {
+        let crc = u32::from_be_bytes(digest.try_into().unwrap_or_default());
+        let line = match name {
+            Some(name) => format!("{} {} {}{}", crc, size, name, delimiter),
+            None => format!("{} {}{}", crc, size, delimiter),
+        };
+        return line.into_bytes();
+    }
+    let digest = match options.encoding {
+        Encoding::Base64 => encode_base64(digest),
+        _ => encode_hex(digest),
+    };
+    let name = name.unwrap_or("-");
+    let (prefix, name) = match options.zero {
+        true => ("", name.to_string()),
+        false => escape(name),
+    };
+    let line = match options.tag {
+        true => format!(
+            "{}{} ({}) = {}{}",
+            prefix,
+            tag(algorithm, options.length),
+            name,
+            digest,
+            delimiter
+        ),
+        false => format!(
+            "{}{} {}{}{}",
+            prefix,
+            digest,
+            if options.binary { '*' } else { ' ' },
+            name,
+            delimiter
+        ),
+    };
+    line.into_bytes()
+}
+
+/// The tag of the BSD-style lines, BLAKE2b tells the length, unless it's the whole digest
+fn tag(algorithm: Algorithm, length: Option<usize>) -> String {
+    match length {
+        Some(length) if algorithm == Algorithm::Blake2b && length != algorithm.digest_size() => {
+            format!("{}-{}", algorithm.tag(), length * 8)
+        }
+        _ => algorithm.tag().to_string(),
+    }
+}
+
+fn encode_hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The same encoding as the base64 utility
+fn encode_base64(digest: &[u8]) -> String {
+    general_purpose::STANDARD.encode(digest)
+}
+
+/** The name with the backslashes and the line breaks escaped.
+ *
+ * The line starts with a backslash then, that's the prefix.
+ */
+pub fn escape(name: &str) -> (&'static str, String) {
+    if !name.contains(['\\', '\n', '\r']) {
+        return ("", name.to_string());
+    }
+    let escaped = name
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r");
+    ("\\", escaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn line(options: Options, name: Option<&str>) -> String {
+        let algorithm = options.algorithm.unwrap_or_default();
+        let (digest, size) = {
+            let mut hasher = algorithm.hasher(options.length);
+            hasher.update(b"abc");
+            (hasher.finish(), 3)
+        };
+        String::from_utf8(format_line(&options, name, &digest, size)).unwrap()
+    }
+
+    #[test]
+    fn formats_the_crcs() {
+        assert_eq!(line(Options::default(), None), "1219131554 3\n");
+        let options = Options {
+            tag: true,
+            zero: true,
+            ..Default::default()
+        };
+        assert_eq!(line(options, Some("a\nb")), "1219131554 3 a\nb\0");
+    }
+
+    #[test]
+    fn formats_the_digests() {
+        let options = Options {
+            algorithm: Some(Algorithm::Md5),
+            tag: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            line(options, Some("a\\b")),
+            "\\MD5 (a\\\\b) = 900150983cd24fb0d6963f7d28e17f72\n"
+        );
+        let options = Options {
+            algorithm: Some(Algorithm::Blake2b),
+            length: Some(1),
+            binary: true,
+            encoding: Encoding::Base64,
+            ..Default::default()
+        };
+        assert_eq!(line(options, None), "aw== *-\n");
+        let options = Options {
+            algorithm: Some(Algorithm::Blake2b),
+            length: Some(2),
+            tag: true,
+            ..Default::default()
+        };
+        assert_eq!(line(options, Some("ca")), "BLAKE2b-16 (ca) = ae1e\n");
+    }
+}
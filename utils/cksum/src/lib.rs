@@ -0,0 +1,35 @@
+mod args;
+mod cksum;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Options;
+use cksum::check::check_files;
+use cksum::digest_files;
+
+pub use cksum::algorithm::Algorithm;
+
+/** Runs cksum, or the legacy utility of the algorithm, like md5sum.
+ *
+ * The legacy ones write the untagged lines by default, and have no -a.
+ */
+pub fn run(legacy: Option<Algorithm>) -> ExitCode {
+    let options = Options::parse(legacy);
+    let mut output = BufWriter::new(io::stdout());
+    let result = match options.check {
+        true => check_files(&options, &mut output),
+        false => digest_files(&options, &mut output),
+    };
+    let flushed = output
+        .flush()
+        .map_err(cksum::cksum_error::CksumError::Write);
+    match result.and_then(|ok| flushed.map(|_| ok)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("{}: {}", options.program, e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use clap::Parser;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Whether the FILEs are checked to be sorted
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CheckOrder {
+    /// Only after a line, which isn't in both FILEs, and the disorder isn't fatal
+    #[default]
+    Default,
+    Enabled,
+    Disabled,
+}
+
+/// Compare sorted files FILE1 and FILE2 line by line
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// When FILE1 or FILE2 (not both) is -, read standard input
+    #[arg(value_name = "FILE")]
+    pub files: Vec<String>,
+
+    /// suppress column 1 (lines unique to FILE1)
+    #[arg(short = '1', default_value_t = false)]
+    pub suppress_1: bool,
+
+    /// suppress column 2 (lines unique to FILE2)
+    #[arg(short = '2', default_value_t = false)]
+    pub suppress_2: bool,
+
+    /// suppress column 3 (lines that appear in both files)
+    #[arg(short = '3', default_value_t = false)]
+    pub suppress_3: bool,
+
+    /// check that the input is correctly sorted, even if all input lines are pairable
+    #[arg(
+        long = "check-order",
+        overrides_with = "nocheck_order",
+        default_value_t = false
+    )]
+    pub check_order_arg: bool,
+
+    /// do not check that the input is correctly sorted
+    #[arg(long, overrides_with = "check_order_arg", default_value_t = false)]
+    pub nocheck_order: bool,
+
+    /// separate columns with STR
+    #[arg(long = "output-delimiter", value_name = "STR")]
+    pub output_delimiters: Vec<String>,
+
+    /// output a summary
+    #[arg(long, default_value_t = false)]
+    pub total: bool,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+
+    /// The last one of --check-order and --nocheck-order
+    #[arg(skip)]
+    pub check_order: CheckOrder,
+
+    /// A TAB without --output-delimiter, the empty one is NUL
+    #[arg(skip)]
+    pub output_delimiter: Vec<u8>,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::parse();
+        match args.files.len() {
+            0 => usage_error("missing operand"),
+            1 => usage_error(&format!("missing operand after '{}'", args.files[0])),
+            2 => {}
+            _ => usage_error(&format!("extra operand '{}'", args.files[2])),
+        }
+        args.check_order = match (args.check_order_arg, args.nocheck_order) {
+            (true, _) => CheckOrder::Enabled,
+            (_, true) => CheckOrder::Disabled,
+            _ => CheckOrder::Default,
+        };
+        let delimiter = args.output_delimiters.first().map_or("\t", String::as_str);
+        if args
+            .output_delimiters
+            .iter()
+            .any(|other| other != delimiter)
+        {
+            eprintln!("comm: multiple output delimiters specified");
+            std::process::exit(1);
+        }
+        args.output_delimiter = match delimiter {
+            "" => vec![b'\0'],
+            _ => delimiter.as_bytes().to_vec(),
+        };
+        args
+    }
+
+    pub fn terminator(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("comm: {}", message);
+    eprintln!("Try 'comm --help' for more information.");
+    std::process::exit(1);
+}
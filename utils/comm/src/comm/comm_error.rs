@@ -0,0 +1,45 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum CommError {
+    /// Opening or reading a FILE, GNU reports both the same way
+    Read(String, io::Error),
+    Write(io::Error),
+    /// The number of the FILE, which isn't sorted, with --check-order
+    Unsorted(usize),
+    /// Some of the FILEs weren't sorted, after the warnings
+    NotSorted,
+}
+
+impl fmt::Display for CommError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommError::Read(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            CommError::Write(err) => write!(f, "write error: {}", strerror(err)),
+            CommError::Unsorted(file) => write!(f, "file {} is not in sorted order", file),
+            CommError::NotSorted => write!(f, "input is not in sorted order"),
+        }
+    }
+}
+
+impl Error for CommError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CommError::Read(_, err) | CommError::Write(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
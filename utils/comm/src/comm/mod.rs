@@ -0,0 +1,239 @@
+pub mod comm_error;
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+
+use chunk_reader::{ChunkReader, Chunked};
+
+use crate::args::{Args, CheckOrder};
+use comm_error::CommError;
+
+/// The lines of a FILE without the terminators
+pub struct Lines {
+    name: String,
+    chunks: Chunked<BufReader<Box<dyn Read>>>,
+    terminator: u8,
+}
+
+impl Lines {
+    pub fn open(name: &str, terminator: u8) -> Result<Lines, CommError> {
+        let input: Box<dyn Read> = match name {
+            "-" => Box::new(io::stdin()),
+            _ => Box::new(File::open(name).map_err(|e| CommError::Read(name.to_string(), e))?),
+        };
+        Ok(Lines::new(name, input, terminator))
+    }
+
+    fn new(name: &str, input: Box<dyn Read>, terminator: u8) -> Lines {
+        Lines {
+            name: name.to_string(),
+            chunks: BufReader::new(input).chunks(terminator, 0),
+            terminator,
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Vec<u8>>, CommError> {
+        match self.chunks.next() {
+            Some(Ok(mut line)) => {
+                if line.last() == Some(&self.terminator) {
+                    line.pop();
+                }
+                Ok(Some(line))
+            }
+            Some(Err(e)) => Err(CommError::Read(self.name.clone(), e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The current line of a FILE and the two before it, for checking the order
+#[derive(Default)]
+struct History {
+    current: Option<Vec<u8>>,
+    previous: Option<Vec<u8>>,
+    before_previous: Option<Vec<u8>>,
+}
+
+/** Writes the lines of the FILEs in three columns, the way GNU comm does.
+ *
+ * The FILEs are read along, the lesser of the current lines goes first.
+ * Unless --nocheck-order, the order is checked after the first line, which
+ * isn't in both FILEs, or from the start with --check-order.
+ */
+pub struct Comm<'a, W: Write> {
+    args: &'a Args,
+    output: &'a mut W,
+    /// The lines of the columns, for --total
+    totals: [u64; 3],
+    seen_unpairable: bool,
+    disorder_warned: [bool; 2],
+}
+
+impl<'a, W: Write> Comm<'a, W> {
+    pub fn new(args: &'a Args, output: &'a mut W) -> Self {
+        Comm {
+            args,
+            output,
+            totals: [0; 3],
+            seen_unpairable: false,
+            disorder_warned: [false; 2],
+        }
+    }
+
+    pub fn compare(&mut self, mut inputs: [Lines; 2]) -> Result<(), CommError> {
+        let mut histories = [History::default(), History::default()];
+        for (history, input) in histories.iter_mut().zip(inputs.iter_mut()) {
+            history.current = input.next()?;
+        }
+        loop {
+            let order = match (&histories[0].current, &histories[1].current) {
+                (None, None) => break,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(line1), Some(line2)) => line1.cmp(line2),
+            };
+            let (line, column) = match order {
+                Ordering::Less => (&histories[0].current, 1),
+                Ordering::Greater => (&histories[1].current, 2),
+                Ordering::Equal => (&histories[1].current, 3),
+            };
+            self.seen_unpairable |= order != Ordering::Equal;
+            self.write_line(line.as_deref().unwrap_or_default(), column)?;
+            let steps = [order.is_le(), order.is_ge()];
+            for (i, history) in histories.iter_mut().enumerate() {
+                if !steps[i] {
+                    continue;
+                }
+                history.before_previous = history.previous.take();
+                history.previous = history.current.take();
+                history.current = inputs[i].next()?;
+                // At the end, the last two lines may be unpairable after all
+                let (previous, current) = match &history.current {
+                    Some(current) => (&history.previous, current),
+                    None => match &history.previous {
+                        Some(previous) => (&history.before_previous, previous),
+                        None => continue,
+                    },
+                };
+                if let Some(previous) = previous {
+                    self.check_order(previous, current, i + 1)?;
+                }
+            }
+        }
+        if self.args.total {
+            let mut total = Vec::new();
+            for count in self.totals {
+                total.extend_from_slice(count.to_string().as_bytes());
+                total.extend_from_slice(&self.args.output_delimiter);
+            }
+            total.extend_from_slice(b"total");
+            total.push(self.args.terminator());
+            self.output.write_all(&total).map_err(CommError::Write)?;
+        }
+        match self.disorder_warned.contains(&true) {
+            true => Err(CommError::NotSorted),
+            false => Ok(()),
+        }
+    }
+
+    /// The line in its COLUMN, after the separators of the columns before it, which are shown
+    fn write_line(&mut self, line: &[u8], column: usize) -> Result<(), CommError> {
+        self.totals[column - 1] += 1;
+        let shown = [
+            !self.args.suppress_1,
+            !self.args.suppress_2,
+            !self.args.suppress_3,
+        ];
+        if !shown[column - 1] {
+            return Ok(());
+        }
+        let separators = shown[..column - 1].iter().filter(|&&shown| shown).count();
+        let mut written = self.args.output_delimiter.repeat(separators);
+        written.extend_from_slice(line);
+        written.push(self.args.terminator());
+        self.output.write_all(&written).map_err(CommError::Write)
+    }
+
+    /// Only the first disorder of a FILE is reported, it's fatal with --check-order
+    fn check_order(
+        &mut self,
+        previous: &[u8],
+        current: &[u8],
+        file: usize,
+    ) -> Result<(), CommError> {
+        let check = match self.args.check_order {
+            CheckOrder::Enabled => true,
+            CheckOrder::Default => self.seen_unpairable,
+            CheckOrder::Disabled => false,
+        };
+        if !check || self.disorder_warned[file - 1] || previous <= current {
+            return Ok(());
+        }
+        if self.args.check_order == CheckOrder::Enabled {
+            return Err(CommError::Unsorted(file));
+        }
+        self.output.flush().map_err(CommError::Write)?;
+        eprintln!("comm: {}", CommError::Unsorted(file));
+        self.disorder_warned[file - 1] = true;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn comm(args: &Args, file1: &[u8], file2: &[u8]) -> (String, Option<String>) {
+        let lines = |input: &[u8]| {
+            let input = Box::new(io::Cursor::new(input.to_vec()));
+            Lines::new("", input, args.terminator())
+        };
+        let mut output = Vec::new();
+        let result = Comm::new(args, &mut output).compare([lines(file1), lines(file2)]);
+        (
+            String::from_utf8(output).unwrap(),
+            result.err().map(|e| e.to_string()),
+        )
+    }
+
+    fn args() -> Args {
+        Args {
+            output_delimiter: b"\t".to_vec(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn writes_the_columns() {
+        let (output, _) = comm(&args(), b"a\nb\nd\n", b"b\nc\nd\ne");
+        assert_eq!(output, "a\n\t\tb\n\tc\n\t\td\n\te\n");
+        let args = Args {
+            suppress_1: true,
+            output_delimiter: b"::".to_vec(),
+            total: true,
+            zero_terminated: true,
+            ..args()
+        };
+        let (output, _) = comm(&args, b"a\0b\0d\0", b"b\0c\0d\0e\0");
+        let lines = ["::b", "c", "::d", "e", "1::2::2::total", ""];
+        assert_eq!(output, lines.join("\0"));
+    }
+
+    #[test]
+    fn checks_the_order() {
+        let (output, error) = comm(&args(), b"b\na\nc\n", b"b\nc\nd\ne\n");
+        assert_eq!(output, "\t\tb\na\n\t\tc\n\td\n\te\n");
+        assert_eq!(error, None);
+        let (output, error) = comm(&args(), b"a\nc\nb\n", b"b\n");
+        assert_eq!(output, "a\n\tb\nc\nb\n");
+        assert_eq!(error.as_deref(), Some("input is not in sorted order"));
+        let args = Args {
+            check_order: CheckOrder::Enabled,
+            ..args()
+        };
+        let (output, error) = comm(&args, b"b\na\n", b"b\n");
+        assert_eq!(output, "\t\tb\n");
+        assert_eq!(error.as_deref(), Some("file 1 is not in sorted order"));
+    }
+}
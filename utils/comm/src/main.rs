@@ -0,0 +1,24 @@
+mod args;
+mod comm;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use comm::comm_error::CommError;
+use comm::{Comm, Lines};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let result = Lines::open(&args.files[0], args.terminator())
+        .and_then(|lines1| Ok([lines1, Lines::open(&args.files[1], args.terminator())?]))
+        .and_then(|inputs| Comm::new(&args, &mut output).compare(inputs));
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(CommError::Write);
+    if let Err(e) = result.and(flushed) {
+        eprintln!("comm: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,90 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::csplit::csplit_error::ArgError;
+use crate::csplit::suffix_format::SuffixFormat;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Output pieces of FILE separated by PATTERN(s) to files 'xx00', 'xx01', ...
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// with FILE of -, read standard input
+    pub file: Option<String>,
+
+    /// INTEGER, /REGEXP/[OFFSET], %REGEXP%[OFFSET], {INTEGER} or {*}
+    #[arg(allow_hyphen_values = true)]
+    pub pattern: Vec<String>,
+
+    /// use sprintf FORMAT instead of %02d
+    #[arg(short = 'b', long, value_name = "FORMAT", value_parser = SuffixFormat::parse)]
+    pub suffix_format: Option<SuffixFormat>,
+
+    /// use PREFIX instead of 'xx'
+    #[arg(short = 'f', long, value_name = "PREFIX", default_value = "xx")]
+    pub prefix: String,
+
+    /// do not remove output files on errors
+    #[arg(short = 'k', long, default_value_t = false)]
+    pub keep_files: bool,
+
+    /// suppress the lines matching PATTERN
+    #[arg(long, default_value_t = false)]
+    pub suppress_matched: bool,
+
+    /// use specified number of digits instead of 2
+    #[arg(short = 'n', long, value_name = "DIGITS", value_parser = parse_digits, default_value = "2")]
+    pub digits: usize,
+
+    /// do not print counts of output file sizes
+    #[arg(
+        short = 's',
+        long,
+        visible_alias = "silent",
+        short_alias = 'q',
+        default_value_t = false
+    )]
+    pub quiet: bool,
+
+    /// remove empty output files
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub elide_empty_files: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                eprintln!("csplit: {}", invalid);
+                std::process::exit(1);
+            }
+            err.exit()
+        });
+        match &args.file {
+            None => usage_error("missing operand"),
+            Some(file) if args.pattern.is_empty() => {
+                usage_error(&format!("missing operand after '{}'", file))
+            }
+            _ => {}
+        }
+        if args.suffix_format.is_none() {
+            args.suffix_format = Some(SuffixFormat::digits(args.digits));
+        }
+        args
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("csplit: {}", message);
+    eprintln!("Try 'csplit --help' for more information.");
+    std::process::exit(1);
+}
+
+fn parse_digits(digits: &str) -> Result<usize, ArgError> {
+    digits
+        .parse()
+        .map_err(|_| ArgError(format!("invalid number: '{}'", digits)))
+}
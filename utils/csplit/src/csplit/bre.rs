@@ -0,0 +1,168 @@
+// POSIX basic regular expressions, the ones GNU csplit takes, translated into the syntax
+// of the regex crate. In BRE ( ) { } | + ? are literals, unless escaped, and * ^ $ are
+// special only where they can be, e.g. * at the very start is a literal.
+// Back references have no counterpart in the regex crate, so they are rejected.
+
+use regex::bytes::Regex;
+
+pub fn compile(bre: &str) -> Result<Regex, String> {
+    let translated = translate(bre)?;
+    Regex::new(&translated).map_err(|e| {
+        // The regex crate explains the error in several lines, the last one is the gist
+        let message = e.to_string();
+        let gist = message.lines().last().unwrap_or_default();
+        gist.trim_start_matches("error: ").to_string()
+    })
+}
+
+fn translate(bre: &str) -> Result<String, String> {
+    let chars: Vec<char> = bre.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+    // Whether the position is at the start of the expression or of a group
+    let mut at_start = true;
+    while i < chars.len() {
+        let c = chars[i];
+        let was_at_start = at_start;
+        at_start = false;
+        match c {
+            '\\' => {
+                i += 1;
+                let Some(&escaped) = chars.get(i) else {
+                    return Err("Trailing backslash".into());
+                };
+                match escaped {
+                    '(' | '|' => {
+                        result.push(escaped);
+                        at_start = true;
+                    }
+                    ')' | '{' | '}' | '+' | '?' => result.push(escaped),
+                    '<' | '>' => result.push_str(r"\b"),
+                    'b' | 'B' | 'w' | 'W' | 's' | 'S' => {
+                        result.push('\\');
+                        result.push(escaped);
+                    }
+                    '`' => result.push_str(r"\A"),
+                    '\'' => result.push_str(r"\z"),
+                    '1'..='9' => return Err("Invalid back reference".into()),
+                    _ => result.push_str(&regex::escape(&escaped.to_string())),
+                }
+            }
+            '[' => {
+                let end = bracket_end(&chars, i).ok_or("Unmatched [, [^, [:, [., or [=")?;
+                result.push_str(&translate_bracket(&chars[i + 1..end]));
+                i = end;
+            }
+            '*' if was_at_start => result.push_str(r"\*"),
+            '^' if was_at_start => {
+                result.push('^');
+                at_start = true;
+            }
+            '$' if i + 1 == chars.len()
+                || chars[i + 1..].starts_with(&['\\', ')'])
+                || chars[i + 1..].starts_with(&['\\', '|']) =>
+            {
+                result.push('$')
+            }
+            '.' | '*' => result.push(c),
+            _ => result.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    Ok(result)
+}
+
+/// Index of the ']' closing the bracket expression, that starts at the given index
+fn bracket_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if chars.get(i) == Some(&'^') {
+        i += 1;
+    }
+    // The leading ']' is a literal
+    if chars.get(i) == Some(&']') {
+        i += 1;
+    }
+    while i < chars.len() {
+        match chars[i] {
+            ']' => return Some(i),
+            // [:alpha:], [.x.] and [=x=] may contain ']'
+            '[' if matches!(chars.get(i + 1), Some(':' | '.' | '=')) => {
+                let delimiter = chars[i + 1];
+                i += 2;
+                while i + 1 < chars.len() && !(chars[i] == delimiter && chars[i + 1] == ']') {
+                    i += 1;
+                }
+                i += 2;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// The content of a bracket expression, where the backslash isn't special in BRE
+fn translate_bracket(content: &[char]) -> String {
+    let mut result = String::from("[");
+    let mut i = 0;
+    if content.first() == Some(&'^') {
+        result.push('^');
+        i += 1;
+    }
+    while i < content.len() {
+        match content[i] {
+            '[' if content.get(i + 1) == Some(&':') => {
+                let end = (i + 2..content.len())
+                    .find(|j| content[*j] == ':' && content.get(j + 1) == Some(&']'))
+                    .unwrap_or(content.len() - 1);
+                result.extend(&content[i..(end + 2).min(content.len())]);
+                i = end + 2;
+                continue;
+            }
+            // Set operations of the regex crate, like && and ~~, mean nothing in BRE
+            c @ ('\\' | '[' | ']' | '&' | '~') => {
+                result.push('\\');
+                result.push(c);
+            }
+            c => result.push(c),
+        }
+        i += 1;
+    }
+    result.push(']');
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn matches(bre: &str, line: &str) -> bool {
+        compile(bre).unwrap().is_match(line.as_bytes())
+    }
+
+    #[test]
+    fn translates_basic_syntax() {
+        assert_eq!(translate(r"a\(b\)*c").unwrap(), "a(b)*c");
+        assert_eq!(translate("(a+b?)").unwrap(), r"\(a\+b\?\)");
+        assert_eq!(translate(r"a\{2,3\}").unwrap(), "a{2,3}");
+        assert_eq!(translate("*a^b$").unwrap(), r"\*a\^b$");
+    }
+
+    #[test]
+    fn matches_lines() {
+        assert!(matches("^Chapter [0-9]", "Chapter 1"));
+        assert!(!matches("^Chapter [0-9]", " Chapter 1"));
+        assert!(matches(r"[\]", r"a\b"));
+        assert!(matches("[]a]", "]"));
+        assert!(matches("[[:digit:]]x", "5x"));
+        assert!(matches("a.c", "abc"));
+        assert!(matches("a|b", "a|b"));
+        assert!(matches(r"a\|b", "b"));
+    }
+
+    #[test]
+    fn rejects_what_cant_be_done() {
+        assert!(compile(r"\(a\)\1").is_err());
+        assert!(compile("[a").is_err());
+        assert!(compile(r"\(a").is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum CsplitError {
+    Open(String, io::Error),
+    Read(String, io::Error),
+    Create(String, io::Error),
+    Write(String, io::Error),
+    /// Printing the sizes
+    Output(io::Error),
+    /// The pattern argument and the repetition
+    OutOfRange(String, u64),
+    NotFound(String, u64),
+}
+
+impl fmt::Display for CsplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsplitError::Open(name, err) => {
+                write!(f, "cannot open '{}' for reading: {}", name, strerror(err))
+            }
+            CsplitError::Read(name, err)
+            | CsplitError::Create(name, err)
+            | CsplitError::Write(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            CsplitError::Output(err) => write!(f, "write error: {}", strerror(err)),
+            CsplitError::OutOfRange(pattern, repetition) => write!(
+                f,
+                "'{}': line number out of range{}",
+                pattern,
+                on_repetition(*repetition)
+            ),
+            CsplitError::NotFound(pattern, repetition) => write!(
+                f,
+                "'{}': match not found{}",
+                pattern,
+                on_repetition(*repetition)
+            ),
+        }
+    }
+}
+
+impl Error for CsplitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CsplitError::Open(_, err)
+            | CsplitError::Read(_, err)
+            | CsplitError::Create(_, err)
+            | CsplitError::Write(_, err)
+            | CsplitError::Output(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+fn on_repetition(repetition: u64) -> String {
+    match repetition {
+        0 => String::new(),
+        n => format!(" on repetition {}", n),
+    }
+}
+
+/// Wrong arguments and patterns, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
@@ -0,0 +1,291 @@
+mod bre;
+pub mod csplit_error;
+pub mod pattern;
+pub mod suffix_format;
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use chunk_reader::{ChunkReader, Chunked};
+use regex::bytes::Regex;
+
+use crate::args::Args;
+use csplit_error::CsplitError;
+use pattern::{Pattern, PatternKind, Repeat};
+use suffix_format::SuffixFormat;
+
+/** Input lines, numbered from 1, that are kept around until they are taken.
+ *
+ * Searching for a pattern may need to look ahead, while the lines before the match
+ * still have to go to the current file, or even the lines before those, with a negative
+ * offset. So the lines are buffered from the first one, that isn't taken yet.
+ */
+struct Lines<R> {
+    records: Chunked<BufReader<R>>,
+    buffer: VecDeque<Vec<u8>>,
+    /// Number of the first buffered line
+    first: u64,
+}
+
+impl<R: Read> Lines<R> {
+    fn new(input: R) -> Self {
+        Lines {
+            records: BufReader::new(input).chunks(b'\n', 0),
+            buffer: VecDeque::new(),
+            first: 1,
+        }
+    }
+
+    /// The line with the given number, which must not be taken yet, if there's one
+    fn get(&mut self, number: u64) -> io::Result<Option<&[u8]>> {
+        let index = (number - self.first) as usize;
+        while self.buffer.len() <= index {
+            match self.records.next() {
+                Some(line) => self.buffer.push_back(line?),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(&self.buffer[index]))
+    }
+
+    fn take(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.buffer.is_empty() && self.get(self.first)?.is_none() {
+            return Ok(None);
+        }
+        self.first += 1;
+        Ok(self.buffer.pop_front())
+    }
+}
+
+struct OutputFile {
+    name: String,
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+pub struct Splitter<'a, R, W> {
+    args: &'a Args,
+    format: &'a SuffixFormat,
+    input_name: &'a str,
+    lines: Lines<R>,
+    stdout: W,
+    current: Option<OutputFile>,
+    created: Vec<String>,
+    /// The line, where the next regex search starts
+    search_from: u64,
+}
+
+impl<'a, R: Read, W: Write> Splitter<'a, R, W> {
+    pub fn new(
+        args: &'a Args,
+        format: &'a SuffixFormat,
+        input_name: &'a str,
+        input: R,
+        stdout: W,
+    ) -> Self {
+        Splitter {
+            args,
+            format,
+            input_name,
+            lines: Lines::new(input),
+            stdout,
+            current: None,
+            created: Vec::new(),
+            search_from: 1,
+        }
+    }
+
+    /** Splits the input by the patterns, whatever is left after them goes to the last file */
+    pub fn split(&mut self, patterns: &[Pattern]) -> Result<(), CsplitError> {
+        for pattern in patterns {
+            let mut repetition = 0;
+            while match pattern.repeat {
+                Repeat::Times(n) => repetition <= n,
+                Repeat::Forever => true,
+            } {
+                match &pattern.kind {
+                    PatternKind::Line(line) => {
+                        let target = line.saturating_mul(repetition + 1);
+                        self.split_at_line(pattern, target, repetition)?
+                    }
+                    PatternKind::Regex {
+                        regex,
+                        offset,
+                        skip,
+                    } => {
+                        let found =
+                            self.split_at_match(pattern, regex, *offset, *skip, repetition)?;
+                        // Running out of matches is how {*} ends, and the whole thing with it
+                        if !found {
+                            return Ok(());
+                        }
+                    }
+                }
+                repetition += 1;
+            }
+        }
+        self.create_file()?;
+        self.write_rest()?;
+        self.close_file()
+    }
+
+    /// Removes the files created so far, it's all or nothing without -k
+    pub fn remove_files(&mut self) {
+        self.current = None;
+        for name in self.created.drain(..) {
+            let _ = fs::remove_file(name);
+        }
+    }
+
+    fn split_at_line(
+        &mut self,
+        pattern: &Pattern,
+        target: u64,
+        repetition: u64,
+    ) -> Result<(), CsplitError> {
+        self.create_file()?;
+        if target >= self.lines.first && self.get(target)?.is_none() {
+            self.write_rest()?;
+            self.close_file()?;
+            return Err(CsplitError::OutOfRange(pattern.arg.clone(), repetition));
+        }
+        self.write_until(target)?;
+        self.close_file()?;
+        self.search_from = target;
+        self.start_section()
+    }
+
+    /// Returns false, if there's no match, and it's fine, as it's {*}
+    fn split_at_match(
+        &mut self,
+        pattern: &Pattern,
+        regex: &Regex,
+        offset: i64,
+        skip: bool,
+        repetition: u64,
+    ) -> Result<bool, CsplitError> {
+        if !skip {
+            self.create_file()?;
+        }
+        let mut number = self.search_from.max(self.lines.first);
+        let matched = loop {
+            match self.get(number)? {
+                Some(line) if regex.is_match(line.strip_suffix(b"\n").unwrap_or(line)) => {
+                    break Some(number)
+                }
+                Some(_) => number += 1,
+                None => break None,
+            }
+        };
+        let Some(matched) = matched else {
+            if !skip {
+                self.write_rest()?;
+                self.close_file()?;
+            }
+            return match pattern.repeat {
+                Repeat::Forever => Ok(false),
+                _ => Err(CsplitError::NotFound(pattern.arg.clone(), repetition)),
+            };
+        };
+        let out_of_range = || CsplitError::OutOfRange(pattern.arg.clone(), repetition);
+        let target = matched as i64 + offset;
+        if target < self.lines.first as i64 {
+            self.close_file()?;
+            return Err(out_of_range());
+        }
+        let target = target as u64;
+        if target > self.lines.first && self.get(target - 1)?.is_none() {
+            self.write_rest()?;
+            self.close_file()?;
+            return Err(out_of_range());
+        }
+        self.write_until(target)?;
+        self.close_file()?;
+        self.search_from = matched.max(target) + 1;
+        self.start_section()?;
+        Ok(true)
+    }
+
+    fn get(&mut self, number: u64) -> Result<Option<&[u8]>, CsplitError> {
+        self.lines
+            .get(number)
+            .map_err(|e| CsplitError::Read(self.input_name.to_string(), e))
+    }
+
+    fn take(&mut self) -> Result<Option<Vec<u8>>, CsplitError> {
+        self.lines
+            .take()
+            .map_err(|e| CsplitError::Read(self.input_name.to_string(), e))
+    }
+
+    /// The line, the new section starts with, is the one that matched
+    fn start_section(&mut self) -> Result<(), CsplitError> {
+        if self.args.suppress_matched {
+            self.take()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the lines before the target one to the current file, if any
+    fn write_until(&mut self, target: u64) -> Result<(), CsplitError> {
+        while self.lines.first < target {
+            match self.take()? {
+                Some(line) => self.write(&line)?,
+                None => break,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_rest(&mut self) -> Result<(), CsplitError> {
+        while let Some(line) = self.take()? {
+            self.write(&line)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, line: &[u8]) -> Result<(), CsplitError> {
+        if let Some(file) = &mut self.current {
+            file.writer
+                .write_all(line)
+                .map_err(|e| CsplitError::Write(file.name.clone(), e))?;
+            file.size += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    fn create_file(&mut self) -> Result<(), CsplitError> {
+        let number = self.created.len() as u64;
+        let name = format!("{}{}", self.args.prefix, self.format.format(number));
+        let file = File::create(&name).map_err(|e| CsplitError::Create(name.clone(), e))?;
+        self.created.push(name.clone());
+        self.current = Some(OutputFile {
+            name,
+            writer: BufWriter::new(file),
+            size: 0,
+        });
+        Ok(())
+    }
+
+    /// Closes the current file, printing its size, or removing it, if it's empty and it's asked
+    fn close_file(&mut self) -> Result<(), CsplitError> {
+        let Some(mut file) = self.current.take() else {
+            return Ok(());
+        };
+        file.writer
+            .flush()
+            .map_err(|e| CsplitError::Write(file.name.clone(), e))?;
+        if file.size == 0 && self.args.elide_empty_files {
+            // The number goes to the next file then
+            drop(file.writer);
+            self.created.pop();
+            let _ = fs::remove_file(&file.name);
+            return Ok(());
+        }
+        if !self.args.quiet {
+            writeln!(self.stdout, "{}", file.size).map_err(CsplitError::Output)?;
+        }
+        Ok(())
+    }
+}
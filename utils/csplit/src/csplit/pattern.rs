@@ -0,0 +1,217 @@
+use regex::bytes::Regex;
+
+use super::bre;
+use super::csplit_error::ArgError;
+
+#[derive(Debug)]
+pub enum PatternKind {
+    /// Copy up to, but not including, the line with this number
+    Line(u64),
+    /// Copy, or skip for %REGEXP%, up to the matching line with the offset
+    Regex {
+        regex: Regex,
+        offset: i64,
+        skip: bool,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Repeat {
+    Times(u64),
+    Forever,
+}
+
+#[derive(Debug)]
+pub struct Pattern {
+    /// As given, for the diagnostics
+    pub arg: String,
+    pub kind: PatternKind,
+    pub repeat: Repeat,
+}
+
+/** Parses the PATTERN arguments, repetitions included, in GNU way.
+ *
+ * Line numbers going in the wrong order is an error, the same line number twice
+ * is only worth a warning.
+ */
+pub fn parse_patterns(args: &[String]) -> Result<Vec<Pattern>, ArgError> {
+    let mut patterns: Vec<Pattern> = Vec::new();
+    let mut last_line = 0;
+    let mut repeat_allowed = false;
+    for arg in args {
+        if let Some(count) = arg.strip_prefix('{') {
+            let last = patterns.last_mut().filter(|_| repeat_allowed);
+            let last = last.ok_or_else(|| ArgError(format!("'{}': invalid pattern", arg)))?;
+            last.repeat = parse_repeat(arg, count)?;
+            repeat_allowed = false;
+            continue;
+        }
+        let kind = match arg.chars().next() {
+            Some(delimiter @ ('/' | '%')) => parse_regex(arg, delimiter)?,
+            _ => {
+                let line: u64 = arg
+                    .parse()
+                    .map_err(|_| ArgError(format!("'{}': invalid pattern", arg)))?;
+                if line == 0 {
+                    return Err(ArgError(format!(
+                        "{}: line number must be greater than zero",
+                        arg
+                    )));
+                }
+                if line < last_line {
+                    return Err(ArgError(format!(
+                        "line number '{}' is smaller than preceding line number, {}",
+                        arg, last_line
+                    )));
+                }
+                if line == last_line {
+                    eprintln!(
+                        "csplit: warning: line number '{}' is the same as preceding line number",
+                        arg
+                    );
+                }
+                last_line = line;
+                PatternKind::Line(line)
+            }
+        };
+        patterns.push(Pattern {
+            arg: arg.clone(),
+            kind,
+            repeat: Repeat::Times(0),
+        });
+        repeat_allowed = true;
+    }
+    Ok(patterns)
+}
+
+fn parse_repeat(arg: &str, count: &str) -> Result<Repeat, ArgError> {
+    let count = count
+        .strip_suffix('}')
+        .ok_or_else(|| ArgError(format!("'{}': '}}' is required in repeat count", arg)))?;
+    match count {
+        "*" => Ok(Repeat::Forever),
+        _ => count.parse().map(Repeat::Times).map_err(|_| {
+            // GNU quotes it without the closing brace
+            ArgError(format!(
+                "'{{{}'}}: integer required between '{{' and '}}'",
+                count
+            ))
+        }),
+    }
+}
+
+fn parse_regex(arg: &str, delimiter: char) -> Result<PatternKind, ArgError> {
+    let body = &arg[1..];
+    let end = closing_delimiter(body, delimiter).ok_or_else(|| {
+        ArgError(format!(
+            "{}: closing delimiter '{}' missing",
+            arg, delimiter
+        ))
+    })?;
+    let regex = bre::compile(&body[..end])
+        .map_err(|e| ArgError(format!("'{}': invalid regular expression: {}", arg, e)))?;
+    let offset = match &body[end + 1..] {
+        "" => 0,
+        offset => parse_offset(offset)
+            .ok_or_else(|| ArgError(format!("'{}': integer expected after delimiter", arg)))?,
+    };
+    Ok(PatternKind::Regex {
+        regex,
+        offset,
+        skip: delimiter == '%',
+    })
+}
+
+/// Position of the delimiter, that isn't escaped with a backslash
+fn closing_delimiter(body: &str, delimiter: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in body.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == delimiter => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_offset(offset: &str) -> Option<i64> {
+    let digits = offset.strip_prefix(['+', '-']).unwrap_or(offset);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    offset.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Vec<Pattern>, ArgError> {
+        parse_patterns(&args.iter().map(|a| a.to_string()).collect::<Vec<_>>())
+    }
+
+    fn message(args: &[&str]) -> String {
+        parse(args).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_patterns_with_repetitions() {
+        let patterns = parse(&["5", "{2}", "/a/+3", "%b%-1", "{*}"]).unwrap();
+        assert!(matches!(patterns[0].kind, PatternKind::Line(5)));
+        assert_eq!(patterns[0].repeat, Repeat::Times(2));
+        assert!(matches!(
+            patterns[1].kind,
+            PatternKind::Regex {
+                offset: 3,
+                skip: false,
+                ..
+            }
+        ));
+        assert_eq!(patterns[1].repeat, Repeat::Times(0));
+        assert!(matches!(
+            patterns[2].kind,
+            PatternKind::Regex {
+                offset: -1,
+                skip: true,
+                ..
+            }
+        ));
+        assert_eq!(patterns[2].repeat, Repeat::Forever);
+    }
+
+    #[test]
+    fn rejects_bad_patterns_the_gnu_way() {
+        assert_eq!(message(&["0"]), "0: line number must be greater than zero");
+        assert_eq!(message(&["x"]), "'x': invalid pattern");
+        assert_eq!(message(&["/5"]), "/5: closing delimiter '/' missing");
+        assert_eq!(
+            message(&["/5/x"]),
+            "'/5/x': integer expected after delimiter"
+        );
+        assert_eq!(message(&["{2}"]), "'{2}': invalid pattern");
+        assert_eq!(message(&["/a/", "{*}", "{2}"]), "'{2}': invalid pattern");
+        assert_eq!(
+            message(&["5", "{x}"]),
+            "'{x'}: integer required between '{' and '}'"
+        );
+        assert_eq!(
+            message(&["5", "{3"]),
+            "'{3': '}' is required in repeat count"
+        );
+        assert_eq!(
+            message(&["6", "3"]),
+            "line number '3' is smaller than preceding line number, 6"
+        );
+    }
+
+    #[test]
+    fn escaped_delimiter_is_not_the_end() {
+        let patterns = parse(&[r"/a\/b/"]).unwrap();
+        match &patterns[0].kind {
+            PatternKind::Regex { regex, .. } => assert!(regex.is_match(b"a/b")),
+            _ => panic!("regex expected"),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use super::csplit_error::ArgError;
+
+/** printf-like format of the output file suffixes, with a single integer conversion.
+ *
+ * Supports what makes sense for the file numbers: the flags, the width, the precision,
+ * and the d, i, u, o, x and X conversions.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SuffixFormat {
+    prefix: String,
+    suffix: String,
+    left_align: bool,
+    zero_padding: bool,
+    alternate: bool,
+    /// '+' or ' ', for the signed conversions
+    sign: Option<char>,
+    width: usize,
+    precision: Option<usize>,
+    conversion: char,
+}
+
+impl SuffixFormat {
+    /// The default one: %0Nd
+    pub fn digits(digits: usize) -> Self {
+        SuffixFormat {
+            zero_padding: true,
+            width: digits,
+            conversion: 'd',
+            ..Default::default()
+        }
+    }
+
+    pub fn parse(format: &str) -> Result<Self, ArgError> {
+        let mut result = SuffixFormat::default();
+        let mut chars = format.chars().peekable();
+        let mut literal = String::new();
+        let mut found = false;
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            if chars.peek() == Some(&'%') {
+                chars.next();
+                literal.push('%');
+                continue;
+            }
+            if found {
+                return Err(ArgError(
+                    "too many % conversion specifications in suffix".into(),
+                ));
+            }
+            found = true;
+            result.prefix = std::mem::take(&mut literal);
+            while let Some(flag) = chars.next_if(|c| "-+ #0'".contains(*c)) {
+                match flag {
+                    '-' => result.left_align = true,
+                    '0' => result.zero_padding = true,
+                    '#' => result.alternate = true,
+                    '+' => result.sign = Some('+'),
+                    ' ' if result.sign.is_none() => result.sign = Some(' '),
+                    // Grouping of thousands, there's none in C locale
+                    _ => {}
+                }
+            }
+            result.width = take_number(&mut chars);
+            if chars.next_if_eq(&'.').is_some() {
+                result.precision = Some(take_number(&mut chars));
+            }
+            match chars.next() {
+                Some(c) if "diuoxX".contains(c) => result.conversion = c,
+                Some(c) => {
+                    return Err(ArgError(format!(
+                        "invalid conversion specifier in suffix: {}",
+                        c
+                    )))
+                }
+                None => return Err(ArgError("missing conversion specifier in suffix".into())),
+            }
+        }
+        if !found {
+            return Err(ArgError(
+                "missing % conversion specification in suffix".into(),
+            ));
+        }
+        result.suffix = literal;
+        Ok(result)
+    }
+
+    pub fn format(&self, number: u64) -> String {
+        let mut digits = match self.conversion {
+            'o' => format!("{:o}", number),
+            'x' => format!("{:x}", number),
+            'X' => format!("{:X}", number),
+            _ => number.to_string(),
+        };
+        if let Some(precision) = self.precision {
+            digits = format!("{:0>width$}", digits, width = precision);
+        }
+        let prefix = match self.conversion {
+            'o' if self.alternate && !digits.starts_with('0') => "0".to_string(),
+            'x' if self.alternate && number != 0 => "0x".to_string(),
+            'X' if self.alternate && number != 0 => "0X".to_string(),
+            'd' | 'i' => self.sign.map(String::from).unwrap_or_default(),
+            _ => String::new(),
+        };
+        let length = prefix.len() + digits.len();
+        let padded = if length >= self.width {
+            prefix + &digits
+        } else if self.left_align {
+            format!("{}{}{}", prefix, digits, " ".repeat(self.width - length))
+        } else if self.zero_padding && self.precision.is_none() {
+            format!("{}{}{}", prefix, "0".repeat(self.width - length), digits)
+        } else {
+            format!("{}{}{}", " ".repeat(self.width - length), prefix, digits)
+        };
+        format!("{}{}{}", self.prefix, padded, self.suffix)
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> usize {
+    let mut number = 0_usize;
+    while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+        number = number
+            .saturating_mul(10)
+            .saturating_add(digit.to_digit(10).unwrap_or(0) as usize);
+    }
+    number
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn format(format: &str, number: u64) -> String {
+        SuffixFormat::parse(format).unwrap().format(number)
+    }
+
+    #[test]
+    fn formats_like_printf() {
+        assert_eq!(SuffixFormat::digits(2).format(7), "07");
+        assert_eq!(format("%03x.txt", 255), "0ff.txt");
+        assert_eq!(format("%-5d|", 0), "0    |");
+        assert_eq!(format("%#o", 0), "0");
+        assert_eq!(format("%#o", 8), "010");
+        assert_eq!(format("%#6X", 255), "  0XFF");
+        assert_eq!(format("%%%.3d", 1), "%001");
+        assert_eq!(format("%+d", 1), "+1");
+        assert_eq!(format("%05.3u", 1), "  001");
+    }
+
+    #[test]
+    fn rejects_bad_formats_the_gnu_way() {
+        let message = |format| SuffixFormat::parse(format).unwrap_err().to_string();
+        assert_eq!(
+            message("a%db%d"),
+            "too many % conversion specifications in suffix"
+        );
+        assert_eq!(message("a%s"), "invalid conversion specifier in suffix: s");
+        assert_eq!(
+            message("abc"),
+            "missing % conversion specification in suffix"
+        );
+        assert_eq!(message("a%"), "missing conversion specifier in suffix");
+    }
+}
@@ -0,0 +1,50 @@
+mod args;
+mod csplit;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use csplit::csplit_error::CsplitError;
+use csplit::pattern::parse_patterns;
+use csplit::Splitter;
+use strerror::strerror;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let patterns = parse_patterns(&args.pattern).unwrap_or_else(|e| {
+        eprintln!("csplit: {}", e);
+        std::process::exit(1);
+    });
+    let name = args.file.as_deref().unwrap_or("-");
+    let input: Box<dyn Read> = match name {
+        "-" => Box::new(io::stdin()),
+        _ => match File::open(name) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("csplit: {}", CsplitError::Open(name.to_string(), e));
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    let format = args.suffix_format.clone().unwrap_or_default();
+    let mut output = BufWriter::new(io::stdout());
+    let mut splitter = Splitter::new(&args, &format, name, input, &mut output);
+    let result = splitter.split(&patterns);
+    if let Err(e) = &result {
+        if !args.keep_files {
+            splitter.remove_files();
+        }
+        drop(splitter);
+        let _ = output.flush();
+        eprintln!("csplit: {}", e);
+        return ExitCode::FAILURE;
+    }
+    drop(splitter);
+    if let Err(e) = output.flush() {
+        eprintln!("csplit: write error: {}", strerror(&e));
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
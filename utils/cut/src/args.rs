@@ -0,0 +1,169 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::cut::cut_error::ArgError;
+use crate::cut::list::{List, Unit};
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Mode {
+    #[default]
+    Bytes,
+    Characters,
+    Fields,
+}
+
+/// Print selected parts of lines from each FILE to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input
+    #[arg(default_value = "-")]
+    pub file: Vec<String>,
+
+    /// select only these bytes
+    #[arg(
+        short = 'b',
+        long,
+        value_name = "LIST",
+        value_parser = parse_positions,
+        allow_hyphen_values = true
+    )]
+    pub bytes: Option<List>,
+
+    /// select only these characters
+    #[arg(
+        short = 'c',
+        long,
+        value_name = "LIST",
+        value_parser = parse_positions,
+        allow_hyphen_values = true
+    )]
+    pub characters: Option<List>,
+
+    /// use DELIM instead of TAB for field delimiter
+    #[arg(short = 'd', long, value_name = "DELIM", value_parser = parse_delimiter)]
+    pub delimiter: Option<char>,
+
+    /// select only these fields; also print any line that contains no delimiter
+    /// character, unless the -s option is specified
+    #[arg(
+        short = 'f',
+        long,
+        value_name = "LIST",
+        value_parser = parse_fields,
+        allow_hyphen_values = true
+    )]
+    pub fields: Option<List>,
+
+    /// (ignored)
+    #[arg(short = 'n', default_value_t = false)]
+    pub no_split: bool,
+
+    /// complement the set of selected bytes, characters or fields
+    #[arg(long, default_value_t = false)]
+    pub complement: bool,
+
+    /// do not print lines not containing delimiters
+    #[arg(short = 's', long, default_value_t = false)]
+    pub only_delimited: bool,
+
+    /// use STRING as the output delimiter, the default is to use the input delimiter
+    #[arg(long, value_name = "STRING")]
+    pub output_delimiter: Option<String>,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+
+    /// Which one of the lists is given
+    #[arg(skip)]
+    pub mode: Mode,
+
+    /// The given list, complemented if asked
+    #[arg(skip)]
+    pub list: List,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                usage_error(&invalid.0);
+            }
+            err.exit()
+        });
+        let lists = [
+            (Mode::Bytes, args.bytes.take()),
+            (Mode::Characters, args.characters.take()),
+            (Mode::Fields, args.fields.take()),
+        ];
+        let mut given = lists.into_iter().filter(|(_, list)| list.is_some());
+        let Some((mode, Some(list))) = given.next() else {
+            usage_error("you must specify a list of bytes, characters, or fields");
+        };
+        if given.next().is_some() {
+            usage_error("only one list may be specified");
+        }
+        if mode != Mode::Fields {
+            if args.delimiter.is_some() {
+                usage_error("an input delimiter may be specified only when operating on fields");
+            }
+            if args.only_delimited {
+                usage_error(
+                    "suppressing non-delimited lines makes sense\n\tonly when operating on fields",
+                );
+            }
+        }
+        args.mode = mode;
+        args.list = match args.complement {
+            true => list.complement(),
+            false => list,
+        };
+        args
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("cut: {}", message);
+    eprintln!("Try 'cut --help' for more information.");
+    std::process::exit(1);
+}
+
+fn parse_positions(list: &str) -> Result<List, ArgError> {
+    List::parse(list, Unit::Positions)
+}
+
+fn parse_fields(list: &str) -> Result<List, ArgError> {
+    List::parse(list, Unit::Fields)
+}
+
+/// A single character, the empty one stands for NUL
+fn parse_delimiter(delimiter: &str) -> Result<char, ArgError> {
+    let mut chars = delimiter.chars();
+    match (chars.next(), chars.next()) {
+        (None, _) => Ok('\0'),
+        (Some(c), None) => Ok(c),
+        _ => Err(ArgError("the delimiter must be a single character".into())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_lists_starting_with_a_hyphen() {
+        let args = <Args as Parser>::try_parse_from(["cut", "-f", "-2"]).unwrap();
+        assert_eq!(args.fields, Some(List::parse("1-2", Unit::Fields).unwrap()));
+        let args = <Args as Parser>::try_parse_from(["cut", "-b", "-2", "file"]).unwrap();
+        assert_eq!(
+            args.bytes,
+            Some(List::parse("1-2", Unit::Positions).unwrap())
+        );
+        assert_eq!(args.file, ["file"]);
+    }
+}
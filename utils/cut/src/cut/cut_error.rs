@@ -0,0 +1,50 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum CutError {
+    /// Opening or reading a FILE, GNU reports both the same way
+    Read(String, io::Error),
+    Write(io::Error),
+}
+
+impl fmt::Display for CutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CutError::Read(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            CutError::Write(err) => write!(f, "write error: {}", strerror(err)),
+        }
+    }
+}
+
+impl Error for CutError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CutError::Read(_, err) | CutError::Write(err) => Some(err),
+        }
+    }
+}
+
+/// Wrong arguments, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
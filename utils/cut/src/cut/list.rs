@@ -0,0 +1,169 @@
+use super::cut_error::ArgError;
+
+/// What the list numbers, GNU words the errors differently for them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    Positions,
+    Fields,
+}
+
+/** Selected bytes, characters or fields: "1", "1,3-5", "-2" or "7-".
+ *
+ * Ranges are inclusive, 1-based and sorted, with an open end being usize::MAX.
+ * Overlapping ranges are merged, but adjacent ones are not, as --output-delimiter
+ * goes between them.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct List(Vec<(usize, usize)>);
+
+impl List {
+    pub fn parse(list: &str, unit: Unit) -> Result<List, ArgError> {
+        let mut ranges = Vec::new();
+        let mut offset = 0;
+        for item in list.split([',', ' ', '\t']) {
+            // GNU reports the rest of the list from the first wrong character
+            if let Some(i) = item.find(|c: char| !c.is_ascii_digit() && c != '-') {
+                return Err(unit.invalid_value(&list[offset + i..]));
+            }
+            offset += item.len() + 1;
+            let (start, end) = match item.split_once('-') {
+                None => {
+                    let n = unit.start(item)?;
+                    (n, n)
+                }
+                Some((_, end)) if end.contains('-') => return Err(unit.invalid_range()),
+                Some(("", "")) => return Err(ArgError("invalid range with no endpoint: -".into())),
+                Some(("", end)) => (1, unit.number(end)?),
+                Some((start, "")) => (unit.start(start)?, usize::MAX),
+                Some((start, end)) => (unit.start(start)?, unit.number(end)?),
+            };
+            if end < start {
+                return Err(ArgError("invalid decreasing range".into()));
+            }
+            ranges.push((start, end));
+        }
+        ranges.sort_unstable();
+        let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        Ok(List(merged))
+    }
+
+    /// Everything, that isn't selected, for --complement
+    pub fn complement(&self) -> List {
+        let mut ranges = Vec::new();
+        let mut next = 1;
+        for &(start, end) in &self.0 {
+            if start > next {
+                ranges.push((next, start - 1));
+            }
+            if end == usize::MAX {
+                return List(ranges);
+            }
+            next = end + 1;
+        }
+        ranges.push((next, usize::MAX));
+        List(ranges)
+    }
+
+    pub fn ranges(&self) -> &[(usize, usize)] {
+        &self.0
+    }
+}
+
+impl Unit {
+    /// The start of a range, or a single number, which can't be zero
+    fn start(self, number: &str) -> Result<usize, ArgError> {
+        match self.number(number)? {
+            0 => Err(self.numbered_from_1()),
+            n => Ok(n),
+        }
+    }
+
+    fn number(self, number: &str) -> Result<usize, ArgError> {
+        if number.is_empty() {
+            return Err(self.numbered_from_1());
+        }
+        number.parse().map_err(|_| {
+            ArgError(match self {
+                Unit::Positions => format!("byte/character offset '{}' is too large", number),
+                Unit::Fields => format!("field number '{}' is too large", number),
+            })
+        })
+    }
+
+    fn numbered_from_1(self) -> ArgError {
+        ArgError(match self {
+            Unit::Positions => "byte/character positions are numbered from 1".into(),
+            Unit::Fields => "fields are numbered from 1".into(),
+        })
+    }
+
+    fn invalid_value(self, value: &str) -> ArgError {
+        ArgError(match self {
+            Unit::Positions => format!("invalid byte/character position '{}'", value),
+            Unit::Fields => format!("invalid field value '{}'", value),
+        })
+    }
+
+    fn invalid_range(self) -> ArgError {
+        ArgError(match self {
+            Unit::Positions => "invalid byte or character range".into(),
+            Unit::Fields => "invalid field range".into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ranges(list: &str) -> Vec<(usize, usize)> {
+        List::parse(list, Unit::Fields).unwrap().0
+    }
+
+    #[test]
+    fn sorts_and_merges_overlapping_ranges() {
+        assert_eq!(ranges("5,1,3"), vec![(1, 1), (3, 3), (5, 5)]);
+        assert_eq!(ranges("1-3,2-4"), vec![(1, 4)]);
+        assert_eq!(ranges("1-2,3-4"), vec![(1, 2), (3, 4)]);
+        assert_eq!(ranges("3-,-4"), vec![(1, usize::MAX)]);
+        assert_eq!(ranges("1 3"), vec![(1, 1), (3, 3)]);
+    }
+
+    #[test]
+    fn complements_ranges() {
+        let complement = |list| List::parse(list, Unit::Fields).unwrap().complement().0;
+        assert_eq!(complement("2,4"), vec![(1, 1), (3, 3), (5, usize::MAX)]);
+        assert_eq!(complement("1-3"), vec![(4, usize::MAX)]);
+        assert_eq!(complement("3-"), vec![(1, 2)]);
+        assert_eq!(complement("1-"), vec![]);
+    }
+
+    #[test]
+    fn rejects_invalid_lists_the_gnu_way() {
+        let fields = |list| List::parse(list, Unit::Fields).unwrap_err().0;
+        let positions = |list| List::parse(list, Unit::Positions).unwrap_err().0;
+        assert_eq!(fields("0"), "fields are numbered from 1");
+        assert_eq!(fields("1,,2"), "fields are numbered from 1");
+        assert_eq!(fields("0-"), "fields are numbered from 1");
+        assert_eq!(fields("1-0"), "invalid decreasing range");
+        assert_eq!(fields("-"), "invalid range with no endpoint: -");
+        assert_eq!(fields("1-2-3"), "invalid field range");
+        assert_eq!(fields("2,x-1"), "invalid field value 'x-1'");
+        assert_eq!(
+            positions(""),
+            "byte/character positions are numbered from 1"
+        );
+        assert_eq!(positions("a,0"), "invalid byte/character position 'a,0'");
+        assert_eq!(positions("1-2-3"), "invalid byte or character range");
+        assert_eq!(
+            positions("99999999999999999999"),
+            "byte/character offset '99999999999999999999' is too large"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+pub mod cut_error;
+pub mod list;
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+
+use chunk_reader::ChunkReader;
+use memchr::memmem;
+
+use crate::args::{Args, Mode};
+use cut_error::CutError;
+
+/** Prints the selected parts of the lines of a single file.
+ *
+ * The last line gets its terminator, even if the input has none.
+ */
+pub fn cut<W: Write>(args: &Args, name: &str, output: &mut W) -> Result<(), CutError> {
+    let read_error = |e| CutError::Read(name.to_string(), e);
+    let input: Box<dyn Read> = match name {
+        "-" => Box::new(io::stdin()),
+        _ => Box::new(File::open(name).map_err(read_error)?),
+    };
+    let terminator = if args.zero_terminated { b'\0' } else { b'\n' };
+    let mut delimiter = [0; 4];
+    let delimiter = args
+        .delimiter
+        .unwrap_or('\t')
+        .encode_utf8(&mut delimiter)
+        .as_bytes();
+    let output_delimiter = match &args.output_delimiter {
+        Some(output_delimiter) => output_delimiter.as_bytes(),
+        None if args.mode == Mode::Fields => delimiter,
+        None => b"",
+    };
+    let cutter = Cutter {
+        ranges: args.list.ranges(),
+        output_delimiter,
+    };
+    for line in BufReader::new(input).chunks(terminator, 0) {
+        let line = line.map_err(read_error)?;
+        let line = line.strip_suffix(&[terminator]).unwrap_or(&line);
+        let written = match args.mode {
+            Mode::Bytes => cutter.bytes(line, output),
+            Mode::Characters => cutter.characters(line, output),
+            Mode::Fields => cutter.fields(
+                line,
+                &memmem::Finder::new(delimiter),
+                args.only_delimited,
+                output,
+            ),
+        };
+        match written {
+            Ok(true) => output.write_all(&[terminator]).map_err(CutError::Write)?,
+            Ok(false) => {}
+            Err(e) => return Err(CutError::Write(e)),
+        }
+    }
+    Ok(())
+}
+
+struct Cutter<'a> {
+    ranges: &'a [(usize, usize)],
+    output_delimiter: &'a [u8],
+}
+
+impl Cutter<'_> {
+    /// Returns whether the line is printed, so it needs a terminator
+    fn bytes<W: Write>(&self, line: &[u8], output: &mut W) -> io::Result<bool> {
+        self.write_ranges(line, line.len(), |i| i, output)?;
+        Ok(true)
+    }
+
+    /** Same as bytes, but counting UTF-8 characters.
+     *
+     * Every byte of an invalid sequence counts as a character of its own.
+     */
+    fn characters<W: Write>(&self, line: &[u8], output: &mut W) -> io::Result<bool> {
+        let mut starts = Vec::with_capacity(line.len() + 1);
+        let mut offset = 0;
+        for chunk in line.utf8_chunks() {
+            starts.extend(chunk.valid().char_indices().map(|(i, _)| offset + i));
+            offset += chunk.valid().len();
+            starts.extend(offset..offset + chunk.invalid().len());
+            offset += chunk.invalid().len();
+        }
+        let count = starts.len();
+        starts.push(line.len());
+        self.write_ranges(line, count, |i| starts[i], output)?;
+        Ok(true)
+    }
+
+    /// Writes the selected items out of the given number of them, with the byte offset of each
+    fn write_ranges<W: Write>(
+        &self,
+        line: &[u8],
+        count: usize,
+        offset: impl Fn(usize) -> usize,
+        output: &mut W,
+    ) -> io::Result<()> {
+        for (i, &(start, end)) in self.ranges.iter().enumerate() {
+            if start > count {
+                break;
+            }
+            if i > 0 {
+                output.write_all(self.output_delimiter)?;
+            }
+            output.write_all(&line[offset(start - 1)..offset(end.min(count))])?;
+        }
+        Ok(())
+    }
+
+    /// Lines without a delimiter are printed as they are, unless only_delimited
+    fn fields<W: Write>(
+        &self,
+        line: &[u8],
+        delimiter: &memmem::Finder,
+        only_delimited: bool,
+        output: &mut W,
+    ) -> io::Result<bool> {
+        let mut found = delimiter.find_iter(line).peekable();
+        if found.peek().is_none() {
+            if !only_delimited {
+                output.write_all(line)?;
+            }
+            return Ok(!only_delimited);
+        }
+        let last = self.ranges.last().map_or(0, |range| range.1);
+        let mut ranges = self.ranges.iter().peekable();
+        let mut start = 0;
+        let mut first = true;
+        for number in 1..=last {
+            let end = found.next().unwrap_or(line.len());
+            while ranges.next_if(|range| range.1 < number).is_some() {}
+            if ranges.peek().is_some_and(|range| range.0 <= number) {
+                if !first {
+                    output.write_all(self.output_delimiter)?;
+                }
+                output.write_all(&line[start..end])?;
+                first = false;
+            }
+            if end == line.len() {
+                break;
+            }
+            start = end + delimiter.needle().len();
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::list::{List, Unit};
+    use super::*;
+
+    fn cut_line(mode: Mode, list: &str, line: &str) -> String {
+        let list = List::parse(list, Unit::Fields).unwrap();
+        let cutter = Cutter {
+            ranges: list.ranges(),
+            output_delimiter: b":",
+        };
+        let mut output = Vec::new();
+        match mode {
+            Mode::Bytes => cutter.bytes(line.as_bytes(), &mut output),
+            Mode::Characters => cutter.characters(line.as_bytes(), &mut output),
+            Mode::Fields => {
+                let delimiter = memmem::Finder::new(b",");
+                cutter.fields(line.as_bytes(), &delimiter, false, &mut output)
+            }
+        }
+        .unwrap();
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    #[test]
+    fn delimits_separate_ranges() {
+        assert_eq!(cut_line(Mode::Bytes, "1-2,3-4", "abcdef"), "ab:cd");
+        assert_eq!(cut_line(Mode::Bytes, "5-,-2", "abcdef"), "ab:ef");
+        assert_eq!(cut_line(Mode::Bytes, "2,9", "abc"), "b");
+    }
+
+    #[test]
+    fn counts_utf8_characters() {
+        assert_eq!(cut_line(Mode::Characters, "2-3", "aé€b"), "é€");
+        assert_eq!(cut_line(Mode::Bytes, "2-3", "aé€b"), "é");
+        let mut output = Vec::new();
+        let list = List::parse("2", Unit::Positions).unwrap();
+        let cutter = Cutter {
+            ranges: list.ranges(),
+            output_delimiter: b"",
+        };
+        cutter.characters(b"\xff\xfe", &mut output).unwrap();
+        assert_eq!(output, b"\xfe");
+    }
+
+    #[test]
+    fn selects_fields() {
+        assert_eq!(cut_line(Mode::Fields, "2,4", "a,b,c,d,e"), "b:d");
+        assert_eq!(cut_line(Mode::Fields, "3-", "a,b,c,d"), "c:d");
+        assert_eq!(cut_line(Mode::Fields, "3", "a,b"), "");
+        assert_eq!(cut_line(Mode::Fields, "2", "no delimiter"), "no delimiter");
+        assert_eq!(cut_line(Mode::Fields, "1,2", "a,"), "a:");
+    }
+}
@@ -0,0 +1,35 @@
+mod args;
+mod cut;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use cut::cut;
+use cut::cut_error::CutError;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let mut status = ExitCode::SUCCESS;
+    for file in &args.file {
+        match cut(&args, file, &mut output) {
+            Ok(()) => {}
+            Err(e @ CutError::Write(_)) => {
+                eprintln!("cut: {}", e);
+                return ExitCode::FAILURE;
+            }
+            Err(e) => {
+                // Flushing whatever was written before the error, so it goes before the message
+                let _ = output.flush();
+                eprintln!("cut: {}", e);
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Err(e) = output.flush() {
+        eprintln!("cut: {}", CutError::Write(e));
+        return ExitCode::FAILURE;
+    }
+    status
+}
@@ -0,0 +1,347 @@
+use clap::Parser;
+use ibig::UBig;
+use parse_num::parse_num;
+
+use crate::dd::conversions::*;
+use crate::dd::stats::Status;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+const DEFAULT_BLOCKSIZE: usize = 512;
+/// The limit of bs=, ibs= and obs=, the buffers are indexed with isize
+const MAX_BLOCKSIZE: u64 = i64::MAX as u64 - 1;
+
+/// Copy a file, converting and formatting according to the operands
+#[derive(Parser, Debug, Default)]
+#[command(name = "dd", author, version, about, long_about)]
+pub struct Args {
+    /// bs=BYTES, cbs=BYTES, conv=CONVS, count=N, ibs=BYTES, if=FILE,
+    /// iflag=FLAGS, obs=BYTES, of=FILE, oflag=FLAGS, seek=N (or oseek=N),
+    /// skip=N (or iseek=N), status=none|noxfer|progress.
+    ///
+    /// N and BYTES may be followed by the multiplicative suffixes: c=1, w=2,
+    /// b=512, kB=1000, K=1024, MB=1000*1000, M=1024*1024, xM=M, and so on
+    /// for G, T, P, E, Z, Y, R, Q. If N ends in 'B', it counts bytes not
+    /// blocks.
+    ///
+    /// CONVS are ascii, ebcdic, ibm, block, unblock, lcase, ucase, sparse,
+    /// swab, sync, excl, nocreat, notrunc, noerror, fdatasync and fsync.
+    /// FLAGS are append, fullblock (iflag only), count_bytes, skip_bytes
+    /// and seek_bytes.
+    #[arg(value_name = "OPERAND")]
+    pub operands: Vec<String>,
+
+    /// Standard input, if not given
+    #[arg(skip)]
+    pub input_file: Option<String>,
+
+    /// Standard output, if not given
+    #[arg(skip)]
+    pub output_file: Option<String>,
+
+    #[arg(skip)]
+    pub input_blocksize: usize,
+
+    #[arg(skip)]
+    pub output_blocksize: usize,
+
+    /// 0 without cbs=, then there is no block or unblock
+    #[arg(skip)]
+    pub conversion_blocksize: usize,
+
+    /// skip= in ibs-sized blocks and the bytes past them
+    #[arg(skip)]
+    pub skip_records: u64,
+
+    #[arg(skip)]
+    pub skip_bytes: u64,
+
+    /// seek= in obs-sized blocks and the bytes past them
+    #[arg(skip)]
+    pub seek_records: u64,
+
+    #[arg(skip)]
+    pub seek_bytes: u64,
+
+    /// count= in ibs-sized blocks and the bytes past them, i64::MAX blocks without it
+    #[arg(skip)]
+    pub max_records: u64,
+
+    #[arg(skip)]
+    pub max_bytes: u64,
+
+    /// The bits of conv=
+    #[arg(skip)]
+    pub conversions: u32,
+
+    /// The bits of iflag=
+    #[arg(skip)]
+    pub input_flags: u32,
+
+    /// The bits of oflag=
+    #[arg(skip)]
+    pub output_flags: u32,
+
+    #[arg(skip)]
+    pub status: Status,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum NumError {
+    Invalid,
+    Overflow,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::parse();
+        let mut blocksize = 0;
+        let (mut skip, mut seek, mut count) = (0, 0, None);
+        for operand in &args.operands {
+            let Some((name, value)) = operand.split_once('=') else {
+                usage_error(&format!("unrecognized operand {}", quote(operand)));
+            };
+            match name {
+                "if" => args.input_file = Some(value.to_string()),
+                "of" => args.output_file = Some(value.to_string()),
+                "conv" => args.conversions |= symbols(value, &CONVERSIONS, "invalid conversion"),
+                "iflag" => args.input_flags |= symbols(value, &FLAGS, "invalid input flag"),
+                "oflag" => args.output_flags |= symbols(value, &FLAGS, "invalid output flag"),
+                "status" => args.status = parse_status(value),
+                _ => {
+                    let n = parse_integer(value);
+                    let (min, max) = match name {
+                        "bs" | "ibs" | "obs" => (1, MAX_BLOCKSIZE),
+                        "cbs" => (1, i64::MAX as u64),
+                        "skip" | "iseek" | "seek" | "oseek" | "count" => (0, i64::MAX as u64),
+                        _ => usage_error(&format!("unrecognized operand {}", quote(operand))),
+                    };
+                    let n = match n {
+                        Ok(n) if n < min => Err(NumError::Invalid),
+                        Ok(n) if n > max => Err(NumError::Overflow),
+                        n => n,
+                    };
+                    let n = n.unwrap_or_else(|err| match err {
+                        NumError::Invalid => fatal(&format!("invalid number: {}", quote(value))),
+                        NumError::Overflow => fatal(&format!(
+                            "invalid number: {}: Value too large for defined data type",
+                            quote(value)
+                        )),
+                    });
+                    let bytes = value.contains('B');
+                    match name {
+                        "bs" => blocksize = n as usize,
+                        "ibs" => args.input_blocksize = n as usize,
+                        "obs" => args.output_blocksize = n as usize,
+                        "cbs" => args.conversion_blocksize = n as usize,
+                        "skip" | "iseek" => {
+                            skip = n;
+                            args.input_flags |= if bytes { SKIP_BYTES } else { 0 };
+                        }
+                        "seek" | "oseek" => {
+                            seek = n;
+                            args.output_flags |= if bytes { SEEK_BYTES } else { 0 };
+                        }
+                        _ => {
+                            count = Some(n);
+                            args.input_flags |= if bytes { COUNT_BYTES } else { 0 };
+                        }
+                    }
+                }
+            }
+        }
+
+        // Without bs=, the partial reads are gathered into full obs-sized blocks
+        if blocksize != 0 {
+            args.input_blocksize = blocksize;
+            args.output_blocksize = blocksize;
+        } else {
+            args.conversions |= TWOBUFS;
+        }
+        if args.input_blocksize == 0 {
+            args.input_blocksize = DEFAULT_BLOCKSIZE;
+        }
+        if args.output_blocksize == 0 {
+            args.output_blocksize = DEFAULT_BLOCKSIZE;
+        }
+        if args.conversion_blocksize == 0 {
+            args.conversions &= !(BLOCK | UNBLOCK);
+        }
+        if args.output_flags & FULLBLOCK != 0 {
+            usage_error("invalid output flag: 'fullblock'");
+        }
+
+        let ibs = args.input_blocksize as u64;
+        let obs = args.output_blocksize as u64;
+        (args.skip_records, args.skip_bytes) = match args.input_flags & SKIP_BYTES != 0 {
+            true => (skip / ibs, skip % ibs),
+            false => (skip, 0),
+        };
+        (args.max_records, args.max_bytes) = match count {
+            Some(count) if args.input_flags & COUNT_BYTES != 0 => (count / ibs, count % ibs),
+            Some(count) => (count, 0),
+            None => (i64::MAX as u64, 0),
+        };
+        (args.seek_records, args.seek_bytes) = match args.output_flags & SEEK_BYTES != 0 {
+            true => (seek / obs, seek % obs),
+            false => (seek, 0),
+        };
+
+        let exclusive = [
+            (ASCII | EBCDIC | IBM, "any two of {ascii,ebcdic,ibm}"),
+            (BLOCK | UNBLOCK, "block and unblock"),
+            (LCASE | UCASE, "lcase and ucase"),
+            (EXCL | NOCREAT, "excl and nocreat"),
+        ];
+        for (mask, what) in exclusive {
+            if (args.conversions & mask).count_ones() > 1 {
+                fatal(&format!("cannot combine {}", what));
+            }
+        }
+        if args.output_flags & APPEND != 0 && args.conversions & NOTRUNC == 0 {
+            eprintln!("dd: you probably want conv=notrunc with oflag=append");
+        }
+        args
+    }
+}
+
+/// The OR of the symbols of the list, or the usage error
+fn symbols(list: &str, symbols: &[(&str, u32)], error: &str) -> u32 {
+    parse_symbols(list, symbols)
+        .unwrap_or_else(|symbol| usage_error(&format!("{}: {}", error, quote(symbol))))
+}
+
+/// The last one of the levels is the one
+fn parse_status(list: &str) -> Status {
+    let levels = [
+        ("none", Status::None),
+        ("noxfer", Status::NoXfer),
+        ("progress", Status::Progress),
+    ];
+    list.split(',').fold(Status::Default, |_, level| {
+        match levels.iter().find(|(name, _)| *name == level) {
+            Some((_, status)) => *status,
+            None => usage_error(&format!("invalid status level: {}", quote(level))),
+        }
+    })
+}
+
+/** N or BYTES, maybe multiplied by another one: 2x512, 1Mx4.
+ *
+ * N is read by parse_num(), with the suffixes of dd on top of it: c 1, w 2 and B
+ * right after the digits, which is just a mark of the bytes. The suffix alone is
+ * the same as 1 with it. Anything above i64::MAX overflows, unless it's multiplied
+ * by 0.
+ */
+fn parse_integer(num: &str) -> Result<u64, NumError> {
+    let (first, second) = match num.split_once('x') {
+        Some((first, second)) => (first, Some(second)),
+        None => (num, None),
+    };
+    let first = match first.strip_suffix('B') {
+        Some(digits) if digits.ends_with(|c: char| c.is_ascii_digit()) => digits,
+        _ => first,
+    };
+    let mut value = parse_factor(first)?;
+    if let Some(second) = second {
+        value *= parse_factor(second)?;
+        if value == UBig::from(0_u8) && num.starts_with("0x") {
+            eprintln!("dd: warning: '0x' is a zero multiplier; use '00x' if that is intended");
+        }
+    }
+    match u64::try_from(&value) {
+        Ok(value) if value <= i64::MAX as u64 => Ok(value),
+        _ => Err(NumError::Overflow),
+    }
+}
+
+fn parse_factor(factor: &str) -> Result<UBig, NumError> {
+    let factor = factor.trim_start();
+    let unsigned = factor.strip_prefix('+').unwrap_or(factor);
+    let digits_len = unsigned
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(unsigned.len());
+    let (digits, suffix) = unsigned.split_at(digits_len);
+    let digits = match digits.is_empty() {
+        true if suffix.is_empty() || unsigned.len() != factor.len() => {
+            return Err(NumError::Invalid)
+        }
+        true => "1",
+        false => digits,
+    };
+    let unit_len = suffix.chars().next().map_or(0, char::len_utf8);
+    if !matches!(&suffix[unit_len..], "" | "B" | "iB") {
+        return Err(NumError::Invalid);
+    }
+    let multiplier: u16 = match &suffix[..unit_len] {
+        "" | "c" => 1,
+        "w" => 2,
+        "b" => 512,
+        "E" | "G" | "k" | "K" | "M" | "P" | "Q" | "R" | "T" | "Y" | "Z" => {
+            let num = parse_num(&format!("{}{}", digits, suffix));
+            return num.map(|num| num.to_ubig()).map_err(|_| NumError::Invalid);
+        }
+        _ => return Err(NumError::Invalid),
+    };
+    let value: UBig = digits.parse().map_err(|_| NumError::Invalid)?;
+    Ok(value * UBig::from(multiplier))
+}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
+
+fn fatal(message: &str) -> ! {
+    eprintln!("dd: {}", message);
+    std::process::exit(1);
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("dd: {}", message);
+    eprintln!("Try 'dd --help' for more information.");
+    std::process::exit(1);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_the_sizes() {
+        assert_eq!(parse_integer("12"), Ok(12));
+        assert_eq!(parse_integer("+2w"), Ok(4));
+        assert_eq!(parse_integer("3B"), Ok(3));
+        assert_eq!(parse_integer("1bB"), Ok(512));
+        assert_eq!(parse_integer("1kB"), Ok(1000));
+        assert_eq!(parse_integer("1KiB"), Ok(1024));
+        assert_eq!(parse_integer("M"), Ok(1 << 20));
+        assert_eq!(parse_integer("2Bx3"), Ok(6));
+        assert_eq!(parse_integer("2x1M"), Ok(2 << 20));
+        assert_eq!(parse_integer("9223372036854775807"), Ok(i64::MAX as u64));
+    }
+
+    #[test]
+    fn rejects_the_bad_sizes() {
+        for num in [
+            "", "-1", "B", "1BB", "1m", "1e", "1x", "x2", "2x3B", "2x3x4", "+K",
+        ] {
+            assert_eq!(parse_integer(num), Err(NumError::Invalid), "{}", num);
+        }
+        assert_eq!(parse_integer("8EiB"), Err(NumError::Overflow));
+        assert_eq!(
+            parse_integer("2x9223372036854775807"),
+            Err(NumError::Overflow)
+        );
+        assert_eq!(parse_integer("00x99999999999999999999"), Ok(0));
+    }
+
+    #[test]
+    fn takes_the_last_status_level() {
+        assert_eq!(parse_status("none,progress,noxfer"), Status::NoXfer);
+        assert_eq!(parse_status("progress"), Status::Progress);
+    }
+}
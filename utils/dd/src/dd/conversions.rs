@@ -0,0 +1,222 @@
+// The symbols of conv=, iflag= and oflag=, and the tables of the character conversions.
+// The tables are the ones of GNU dd, which come from POSIX and 4.3BSD.
+
+/// conv=ascii: from EBCDIC to ASCII
+pub const ASCII: u32 = 1;
+/// conv=ebcdic: from ASCII to EBCDIC
+pub const EBCDIC: u32 = 1 << 1;
+/// conv=ibm: from ASCII to the alternate EBCDIC
+pub const IBM: u32 = 1 << 2;
+/// conv=block: pad the newline-terminated records with spaces to cbs
+pub const BLOCK: u32 = 1 << 3;
+/// conv=unblock: replace the trailing spaces of cbs-sized records with a newline
+pub const UNBLOCK: u32 = 1 << 4;
+pub const LCASE: u32 = 1 << 5;
+pub const UCASE: u32 = 1 << 6;
+pub const SWAB: u32 = 1 << 7;
+pub const NOERROR: u32 = 1 << 8;
+pub const NOTRUNC: u32 = 1 << 9;
+pub const SYNC: u32 = 1 << 10;
+/// The output goes through a buffer of its own, obs-sized
+pub const TWOBUFS: u32 = 1 << 11;
+pub const NOCREAT: u32 = 1 << 12;
+pub const EXCL: u32 = 1 << 13;
+pub const FDATASYNC: u32 = 1 << 14;
+pub const FSYNC: u32 = 1 << 15;
+pub const SPARSE: u32 = 1 << 16;
+
+/// The symbols of conv=, the conversions imply the ones they need
+pub const CONVERSIONS: [(&str, u32); 16] = [
+    ("ascii", ASCII | UNBLOCK | TWOBUFS),
+    ("ebcdic", EBCDIC | BLOCK | TWOBUFS),
+    ("ibm", IBM | BLOCK | TWOBUFS),
+    ("block", BLOCK | TWOBUFS),
+    ("unblock", UNBLOCK | TWOBUFS),
+    ("lcase", LCASE | TWOBUFS),
+    ("ucase", UCASE | TWOBUFS),
+    ("sparse", SPARSE),
+    ("swab", SWAB | TWOBUFS),
+    ("noerror", NOERROR),
+    ("nocreat", NOCREAT),
+    ("excl", EXCL),
+    ("notrunc", NOTRUNC),
+    ("sync", SYNC),
+    ("fdatasync", FDATASYNC),
+    ("fsync", FSYNC),
+];
+
+/// iflag=/oflag=append: open the output in append mode
+pub const APPEND: u32 = 1;
+/// iflag=fullblock: read until the input block is full or the input ends
+pub const FULLBLOCK: u32 = 1 << 1;
+/// iflag=count_bytes: count= is in bytes, not in blocks
+pub const COUNT_BYTES: u32 = 1 << 2;
+/// iflag=skip_bytes: skip= is in bytes, not in blocks
+pub const SKIP_BYTES: u32 = 1 << 3;
+/// oflag=seek_bytes: seek= is in bytes, not in blocks
+pub const SEEK_BYTES: u32 = 1 << 4;
+
+/// The symbols of iflag= and oflag=
+pub const FLAGS: [(&str, u32); 5] = [
+    ("append", APPEND),
+    ("fullblock", FULLBLOCK),
+    ("count_bytes", COUNT_BYTES),
+    ("skip_bytes", SKIP_BYTES),
+    ("seek_bytes", SEEK_BYTES),
+];
+
+/// The OR of the symbols of the comma separated list, or the first unknown symbol
+pub fn parse_symbols<'a>(list: &'a str, symbols: &[(&str, u32)]) -> Result<u32, &'a str> {
+    list.split(',').try_fold(0, |mask, symbol| {
+        match symbols.iter().find(|(name, _)| *name == symbol) {
+            Some((_, value)) => Ok(mask | value),
+            None => Err(symbol),
+        }
+    })
+}
+
+/** The byte translation of ascii, ebcdic, ibm, lcase and ucase.
+ *
+ * The newline and the space are what block and unblock look for, they're translated too.
+ */
+#[derive(Debug, Clone)]
+pub struct Translation {
+    pub table: [u8; 256],
+    pub newline: u8,
+    pub space: u8,
+    /// Whether the table changes anything at all
+    pub needed: bool,
+}
+
+impl Translation {
+    pub fn new(conversions: u32) -> Self {
+        let mut translation = Translation {
+            table: std::array::from_fn(|i| i as u8),
+            newline: b'\n',
+            space: b' ',
+            needed: false,
+        };
+        if conversions & ASCII != 0 {
+            translation.apply(&EBCDIC_TO_ASCII);
+        }
+        if conversions & UCASE != 0 {
+            translation.apply(&std::array::from_fn(|i| (i as u8).to_ascii_uppercase()));
+        } else if conversions & LCASE != 0 {
+            translation.apply(&std::array::from_fn(|i| (i as u8).to_ascii_lowercase()));
+        }
+        let ebcdic = match conversions {
+            c if c & EBCDIC != 0 => Some(&ASCII_TO_EBCDIC),
+            c if c & IBM != 0 => Some(&ASCII_TO_IBM),
+            _ => None,
+        };
+        if let Some(ebcdic) = ebcdic {
+            translation.apply(ebcdic);
+            translation.newline = translation.table[b'\n' as usize];
+            translation.space = translation.table[b' ' as usize];
+        }
+        translation
+    }
+
+    fn apply(&mut self, table: &[u8; 256]) {
+        for byte in self.table.iter_mut() {
+            *byte = table[*byte as usize];
+        }
+        self.needed = true;
+    }
+
+    pub fn translate(&self, buffer: &mut [u8]) {
+        for byte in buffer {
+            *byte = self.table[*byte as usize];
+        }
+    }
+}
+
+const EBCDIC_TO_ASCII: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x9c, 0x09, 0x86, 0x7f, 0x97, 0x8d, 0x8e, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x9d, 0x85, 0x08, 0x87, 0x18, 0x19, 0x92, 0x8f, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x80, 0x81, 0x82, 0x83, 0x84, 0x0a, 0x17, 0x1b, 0x88, 0x89, 0x8a, 0x8b, 0x8c, 0x05, 0x06, 0x07,
+    0x90, 0x91, 0x16, 0x93, 0x94, 0x95, 0x96, 0x04, 0x98, 0x99, 0x9a, 0x9b, 0x14, 0x15, 0x9e, 0x1a,
+    0x20, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xd5, 0x2e, 0x3c, 0x28, 0x2b, 0x7c,
+    0x26, 0xa9, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf, 0xb0, 0xb1, 0x21, 0x24, 0x2a, 0x29, 0x3b, 0x7e,
+    0x2d, 0x2f, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xcb, 0x2c, 0x25, 0x5f, 0x3e, 0x3f,
+    0xba, 0xbb, 0xbc, 0xbd, 0xbe, 0xbf, 0xc0, 0xc1, 0xc2, 0x60, 0x3a, 0x23, 0x40, 0x27, 0x3d, 0x22,
+    0xc3, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9,
+    0xca, 0x6a, 0x6b, 0x6c, 0x6d, 0x6e, 0x6f, 0x70, 0x71, 0x72, 0x5e, 0xcc, 0xcd, 0xce, 0xcf, 0xd0,
+    0xd1, 0xe5, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7a, 0xd2, 0xd3, 0xd4, 0x5b, 0xd6, 0xd7,
+    0xd8, 0xd9, 0xda, 0xdb, 0xdc, 0xdd, 0xde, 0xdf, 0xe0, 0xe1, 0xe2, 0xe3, 0xe4, 0x5d, 0xe6, 0xe7,
+    0x7b, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0xe8, 0xe9, 0xea, 0xeb, 0xec, 0xed,
+    0x7d, 0x4a, 0x4b, 0x4c, 0x4d, 0x4e, 0x4f, 0x50, 0x51, 0x52, 0xee, 0xef, 0xf0, 0xf1, 0xf2, 0xf3,
+    0x5c, 0x9f, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9,
+    0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+];
+
+const ASCII_TO_EBCDIC: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x37, 0x2d, 0x2e, 0x2f, 0x16, 0x05, 0x25, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x3c, 0x3d, 0x32, 0x26, 0x18, 0x19, 0x3f, 0x27, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x40, 0x5a, 0x7f, 0x7b, 0x5b, 0x6c, 0x50, 0x7d, 0x4d, 0x5d, 0x5c, 0x4e, 0x6b, 0x60, 0x4b, 0x61,
+    0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0x7a, 0x5e, 0x4c, 0x7e, 0x6e, 0x6f,
+    0x7c, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xd1, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6,
+    0xd7, 0xd8, 0xd9, 0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xad, 0xe0, 0xbd, 0x9a, 0x6d,
+    0x79, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96,
+    0x97, 0x98, 0x99, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xc0, 0x4f, 0xd0, 0x5f, 0x07,
+    0x20, 0x21, 0x22, 0x23, 0x24, 0x15, 0x06, 0x17, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x09, 0x0a, 0x1b,
+    0x30, 0x31, 0x1a, 0x33, 0x34, 0x35, 0x36, 0x08, 0x38, 0x39, 0x3a, 0x3b, 0x04, 0x14, 0x3e, 0xe1,
+    0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57,
+    0x58, 0x59, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x70, 0x71, 0x72, 0x73, 0x74, 0x75,
+    0x76, 0x77, 0x78, 0x80, 0x8a, 0x8b, 0x8c, 0x8d, 0x8e, 0x8f, 0x90, 0x6a, 0x9b, 0x9c, 0x9d, 0x9e,
+    0x9f, 0xa0, 0xaa, 0xab, 0xac, 0x4a, 0xae, 0xaf, 0xb0, 0xb1, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7,
+    0xb8, 0xb9, 0xba, 0xbb, 0xbc, 0xa1, 0xbe, 0xbf, 0xca, 0xcb, 0xcc, 0xcd, 0xce, 0xcf, 0xda, 0xdb,
+    0xdc, 0xdd, 0xde, 0xdf, 0xea, 0xeb, 0xec, 0xed, 0xee, 0xef, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+];
+
+const ASCII_TO_IBM: [u8; 256] = [
+    0x00, 0x01, 0x02, 0x03, 0x37, 0x2d, 0x2e, 0x2f, 0x16, 0x05, 0x25, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+    0x10, 0x11, 0x12, 0x13, 0x3c, 0x3d, 0x32, 0x26, 0x18, 0x19, 0x3f, 0x27, 0x1c, 0x1d, 0x1e, 0x1f,
+    0x40, 0x5a, 0x7f, 0x7b, 0x5b, 0x6c, 0x50, 0x7d, 0x4d, 0x5d, 0x5c, 0x4e, 0x6b, 0x60, 0x4b, 0x61,
+    0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0x7a, 0x5e, 0x4c, 0x7e, 0x6e, 0x6f,
+    0x7c, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xd1, 0xd2, 0xd3, 0xd4, 0xd5, 0xd6,
+    0xd7, 0xd8, 0xd9, 0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xad, 0xe0, 0xbd, 0x5f, 0x6d,
+    0x79, 0x81, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89, 0x91, 0x92, 0x93, 0x94, 0x95, 0x96,
+    0x97, 0x98, 0x99, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0xa8, 0xa9, 0xc0, 0x4f, 0xd0, 0xa1, 0x07,
+    0x20, 0x21, 0x22, 0x23, 0x24, 0x15, 0x06, 0x17, 0x28, 0x29, 0x2a, 0x2b, 0x2c, 0x09, 0x0a, 0x1b,
+    0x30, 0x31, 0x1a, 0x33, 0x34, 0x35, 0x36, 0x08, 0x38, 0x39, 0x3a, 0x3b, 0x04, 0x14, 0x3e, 0xe1,
+    0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x51, 0x52, 0x53, 0x54, 0x55, 0x56, 0x57,
+    0x58, 0x59, 0x62, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69, 0x70, 0x71, 0x72, 0x73, 0x74, 0x75,
+    0x76, 0x77, 0x78, 0x80, 0x8a, 0x8b, 0x8c, 0x8d, 0x8e, 0x8f, 0x90, 0x9a, 0x9b, 0x9c, 0x9d, 0x9e,
+    0x9f, 0xa0, 0xaa, 0xab, 0xac, 0xad, 0xae, 0xaf, 0xb0, 0xb1, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6, 0xb7,
+    0xb8, 0xb9, 0xba, 0xbb, 0xbc, 0xbd, 0xbe, 0xbf, 0xca, 0xcb, 0xcc, 0xcd, 0xce, 0xcf, 0xda, 0xdb,
+    0xdc, 0xdd, 0xde, 0xdf, 0xea, 0xeb, 0xec, 0xed, 0xee, 0xef, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xff,
+];
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_the_symbol_lists() {
+        assert_eq!(
+            parse_symbols("sync,noerror", &CONVERSIONS),
+            Ok(SYNC | NOERROR)
+        );
+        assert_eq!(
+            parse_symbols("ascii", &CONVERSIONS),
+            Ok(ASCII | UNBLOCK | TWOBUFS)
+        );
+        assert_eq!(parse_symbols("sync,,noerror", &CONVERSIONS), Err(""));
+        assert_eq!(parse_symbols("append,direct", &FLAGS), Err("direct"));
+    }
+
+    #[test]
+    fn translates_the_bytes() {
+        let mut text = *b"Hello, World\n";
+        Translation::new(LCASE).translate(&mut text);
+        assert_eq!(&text, b"hello, world\n");
+
+        let ebcdic = Translation::new(EBCDIC | UCASE);
+        ebcdic.translate(&mut text);
+        assert_eq!(text[..5], [0xc8, 0xc5, 0xd3, 0xd3, 0xd6]);
+        assert_eq!((ebcdic.newline, ebcdic.space), (0x25, 0x40));
+        Translation::new(ASCII).translate(&mut text);
+        assert_eq!(&text, b"HELLO, WORLD\n");
+        assert!(!Translation::new(SWAB).needed);
+    }
+}
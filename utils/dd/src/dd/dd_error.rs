@@ -0,0 +1,142 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+use super::stats::human_readable;
+
+#[derive(Debug)]
+pub enum DdError {
+    Open(String, io::Error),
+    /// seek= and obs=, that are too much for a file size
+    OffsetTooLarge(u64, usize),
+    /// The size to truncate to and the output file
+    Truncate(u64, String, io::Error),
+    Stat(String, io::Error),
+    /// The buffer ("input" or "output") and its size
+    Memory(&'static str, usize),
+    Read(String, io::Error),
+    Write(String, io::Error),
+    /// Writing an obs-sized block
+    WriteBlock(String, io::Error),
+    CannotSkip(String, io::Error),
+    CannotSeek(String, io::Error),
+    SkipOffset(String),
+    /// The size of the last partial read
+    PartialRead(usize),
+    Fdatasync(String, io::Error),
+    Fsync(String, io::Error),
+}
+
+impl fmt::Display for DdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DdError::Open(name, err) => {
+                write!(
+                    f,
+                    "failed to open {}: {}",
+                    quote_always(name),
+                    strerror(err)
+                )
+            }
+            DdError::OffsetTooLarge(seek, obs) => write!(
+                f,
+                "offset too large: cannot truncate to a length of seek={} ({}-byte) blocks",
+                seek, obs
+            ),
+            DdError::Truncate(size, name, err) => write!(
+                f,
+                "failed to truncate to {} bytes in output file {}: {}",
+                size,
+                quote_always(name),
+                strerror(err)
+            ),
+            DdError::Stat(name, err) => {
+                write!(f, "cannot fstat {}: {}", quote_always(name), strerror(err))
+            }
+            DdError::Memory(buffer, size) => write!(
+                f,
+                "memory exhausted by {} buffer of size {} bytes ({})",
+                buffer,
+                size,
+                human_readable(*size as u64, 1024, 1, 1)
+            ),
+            DdError::Read(name, err) => {
+                write!(f, "error reading {}: {}", quote_always(name), strerror(err))
+            }
+            DdError::Write(name, err) => {
+                write!(f, "error writing {}: {}", quote_always(name), strerror(err))
+            }
+            DdError::WriteBlock(name, err) => {
+                write!(f, "writing to {}: {}", quote_always(name), strerror(err))
+            }
+            DdError::CannotSkip(name, err) => {
+                write!(f, "{}: cannot skip: {}", quote(name), strerror(err))
+            }
+            DdError::CannotSeek(name, err) => {
+                write!(f, "{}: cannot seek: {}", quote(name), strerror(err))
+            }
+            DdError::SkipOffset(name) => {
+                write!(f, "{}: cannot skip to specified offset", quote(name))
+            }
+            DdError::PartialRead(size) => write!(
+                f,
+                "warning: partial read ({} byte{}); suggest iflag=fullblock",
+                size,
+                if *size == 1 { "" } else { "s" }
+            ),
+            DdError::Fdatasync(name, err) => {
+                write!(
+                    f,
+                    "fdatasync failed for {}: {}",
+                    quote_always(name),
+                    strerror(err)
+                )
+            }
+            DdError::Fsync(name, err) => {
+                write!(
+                    f,
+                    "fsync failed for {}: {}",
+                    quote_always(name),
+                    strerror(err)
+                )
+            }
+        }
+    }
+}
+
+impl Error for DdError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DdError::Open(_, err)
+            | DdError::Truncate(_, _, err)
+            | DdError::Stat(_, err)
+            | DdError::Read(_, err)
+            | DdError::Write(_, err)
+            | DdError::WriteBlock(_, err)
+            | DdError::CannotSkip(_, err)
+            | DdError::CannotSeek(_, err)
+            | DdError::Fdatasync(_, err)
+            | DdError::Fsync(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        quote_always(name)
+    }
+}
+
+/// The names of the files in the errors of the system calls are always quoted
+fn quote_always(name: &str) -> String {
+    match name.contains('\'') {
+        true => format!("\"{}\"", name),
+        false => format!("'{}'", name),
+    }
+}
@@ -0,0 +1,773 @@
+pub mod conversions;
+pub mod dd_error;
+pub mod signals;
+pub mod stats;
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::args::Args;
+use conversions::*;
+use dd_error::DdError;
+use signals::Pending;
+use stats::{Stats, Status};
+
+struct Input {
+    file: File,
+    name: String,
+    /// Where the input is, from its start
+    offset: u64,
+    seekable: bool,
+    /// Why the input isn't seekable
+    seek_errno: Option<i32>,
+    fullblock: bool,
+    /// With bs= and count= or skip=, the partial reads miscount the blocks
+    warn_partial_read: bool,
+    prev_nread: usize,
+}
+
+impl Input {
+    /// Reads a block, or reads until the block is full with iflag=fullblock
+    fn read(&mut self, buf: &mut [u8], stats: &mut Stats) -> io::Result<usize> {
+        if !self.fullblock {
+            return self.iread(buf, stats);
+        }
+        let mut nread = 0;
+        while nread < buf.len() {
+            match self.iread(&mut buf[nread..], stats)? {
+                0 => break,
+                n => nread += n,
+            }
+        }
+        Ok(nread)
+    }
+
+    fn iread(&mut self, buf: &mut [u8], stats: &mut Stats) -> io::Result<usize> {
+        let nread = loop {
+            process_signals(stats);
+            match self.file.read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                nread => break nread,
+            }
+        };
+        let n = *nread.as_ref().unwrap_or(&0);
+        // A short read may be due to a signal
+        if 0 < n && n < buf.len() {
+            process_signals(stats);
+        }
+        if 0 < n && self.warn_partial_read && 0 < self.prev_nread && self.prev_nread < buf.len() {
+            if stats.status != Status::None {
+                stats.diagnose(&DdError::PartialRead(self.prev_nread));
+            }
+            self.warn_partial_read = false;
+        }
+        self.prev_nread = n;
+        nread
+    }
+}
+
+struct Output {
+    file: File,
+    name: String,
+    /// conv=sparse, it's dropped when the output can't seek
+    sparse: bool,
+    /// The sparse output may need to grow to where the last seek went
+    final_op_was_seek: bool,
+}
+
+impl Output {
+    /// All of the bytes, or the bytes written and the error
+    fn write(&mut self, buf: &[u8], stats: &mut Stats) -> Result<usize, (usize, io::Error)> {
+        let mut total = 0;
+        while total < buf.len() {
+            process_signals(stats);
+            self.final_op_was_seek = false;
+            if self.sparse && buf.iter().all(|&b| b == 0) {
+                match self.file.seek(SeekFrom::Current(buf.len() as i64)) {
+                    Ok(_) => {
+                        self.final_op_was_seek = true;
+                        total = buf.len();
+                        continue;
+                    }
+                    Err(_) => self.sparse = false,
+                }
+            }
+            match self.file.write(&buf[total..]) {
+                Ok(0) => return Err((total, io::Error::from_raw_os_error(errno::ENOSPC))),
+                Ok(n) => total += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err((total, e)),
+            }
+        }
+        Ok(total)
+    }
+}
+
+/** The copying of the input to the output, block by block, with the conversions on the way.
+ *
+ * Without bs= (or with any conversion) the output has its own buffer, that goes out
+ * when it has obs bytes. With bs= the blocks go out as they're read.
+ */
+pub struct Dd<'a> {
+    args: &'a Args,
+    input: Input,
+    output: Output,
+    pub stats: Stats,
+    conversions: u32,
+    translation: Translation,
+    obuf: Vec<u8>,
+    /// The bytes in obuf
+    oc: usize,
+    /// The column of block and unblock
+    col: usize,
+    /// The spaces of unblock, that go out only if there's anything after them
+    pending_spaces: usize,
+}
+
+impl<'a> Dd<'a> {
+    pub fn open(args: &'a Args) -> Result<Self, DdError> {
+        let (input_name, mut input) = match &args.input_file {
+            Some(name) => {
+                let file = File::open(name).map_err(|e| DdError::Open(name.clone(), e))?;
+                (name.clone(), file)
+            }
+            None => {
+                let name = "standard input".to_string();
+                let file = standard_file(0).map_err(|e| DdError::Open(name.clone(), e))?;
+                (name, file)
+            }
+        };
+        let (offset, seek_errno) = match input.stream_position() {
+            Ok(offset) => (offset, None),
+            Err(e) => (0, Some(e.raw_os_error().unwrap_or(errno::ESPIPE))),
+        };
+        let fullblock = args.input_flags & FULLBLOCK != 0;
+        let skips = args.skip_records != 0;
+        let counts = 0 < args.max_records && args.max_records < i64::MAX as u64;
+        let input = Input {
+            file: input,
+            name: input_name,
+            offset,
+            seekable: seek_errno.is_none(),
+            seek_errno,
+            fullblock,
+            warn_partial_read: args.conversions & TWOBUFS == 0 && !fullblock && (skips || counts),
+            prev_nread: 0,
+        };
+
+        let (output_name, output) = match &args.output_file {
+            Some(name) => (name.clone(), open_output(name, args)?),
+            None => {
+                if args.output_flags & APPEND != 0 {
+                    set_append(1);
+                }
+                let name = "standard output".to_string();
+                let file = standard_file(1).map_err(|e| DdError::Open(name.clone(), e))?;
+                (name, file)
+            }
+        };
+        let output = Output {
+            file: output,
+            name: output_name,
+            sparse: args.conversions & SPARSE != 0,
+            final_op_was_seek: false,
+        };
+
+        Ok(Dd {
+            args,
+            input,
+            output,
+            stats: Stats::new(args.status),
+            conversions: args.conversions,
+            translation: Translation::new(args.conversions),
+            obuf: Vec::new(),
+            oc: 0,
+            col: 0,
+            pending_spaces: 0,
+        })
+    }
+
+    /** Copies the input, Ok(false) if some errors were reported on the way.
+     *
+     * The errors make dd quit with the statistics.
+     */
+    pub fn copy(&mut self) -> Result<bool, DdError> {
+        let args = self.args;
+        if args.skip_records != 0 || args.skip_bytes != 0 {
+            self.skip_input()?;
+        }
+        if args.seek_records != 0 || args.seek_bytes != 0 {
+            self.seek_output()?;
+        }
+        if args.max_records == 0 && args.max_bytes == 0 {
+            return Ok(true);
+        }
+
+        let ibs = args.input_blocksize;
+        let mut ibuf = alloc(ibs, "input")?;
+        if self.conversions & TWOBUFS != 0 {
+            self.obuf = alloc(args.output_blocksize, "output")?;
+        }
+        let mut success = true;
+        let mut partread = 0;
+        let mut saved_byte = None;
+        let mut swabbed = Vec::new();
+        let pad = match self.conversions & (BLOCK | UNBLOCK) {
+            0 => 0,
+            _ => b' ',
+        };
+        let records_limit = args
+            .max_records
+            .saturating_add((args.max_bytes != 0) as u64);
+        loop {
+            self.stats.progress();
+            let records = self.stats.r_partial + self.stats.r_full;
+            if records >= records_limit {
+                break;
+            }
+            // What can't be read is zeros (or spaces) then
+            if self.conversions & SYNC != 0 && self.conversions & NOERROR != 0 {
+                ibuf.fill(pad);
+            }
+            let size = match records >= args.max_records {
+                true => args.max_bytes as usize,
+                false => ibs,
+            };
+            let mut n = match self.input.read(&mut ibuf[..size], &mut self.stats) {
+                Ok(0) => break,
+                Ok(n) => {
+                    self.input.offset += n as u64;
+                    n
+                }
+                Err(e) => {
+                    let noerror = self.conversions & NOERROR != 0;
+                    if !noerror || self.stats.status != Status::None {
+                        let err = DdError::Read(self.input.name.clone(), e);
+                        self.stats.diagnose(&err);
+                    }
+                    if !noerror {
+                        success = false;
+                        break;
+                    }
+                    self.stats.print_stats();
+                    if !self.advance_input_after_read_error(ibs - partread) {
+                        success = false;
+                        // Not to complain about it over and over again
+                        self.input.seekable = false;
+                        self.input.seek_errno = Some(errno::ESPIPE);
+                    }
+                    if self.conversions & SYNC == 0 || partread != 0 {
+                        continue;
+                    }
+                    0
+                }
+            };
+
+            if n < ibs {
+                self.stats.r_partial += 1;
+                partread = n;
+                if self.conversions & SYNC != 0 {
+                    if self.conversions & NOERROR == 0 {
+                        ibuf[n..].fill(pad);
+                    }
+                    n = ibs;
+                }
+            } else {
+                self.stats.r_full += 1;
+                partread = 0;
+            }
+
+            if self.conversions & TWOBUFS == 0 {
+                match self.output.write(&ibuf[..n], &mut self.stats) {
+                    Ok(written) => {
+                        self.stats.w_bytes += written as u64;
+                        match n == ibs {
+                            true => self.stats.w_full += 1,
+                            false => self.stats.w_partial += 1,
+                        }
+                    }
+                    Err((written, e)) => {
+                        self.stats.w_bytes += written as u64;
+                        let err = DdError::Write(self.output.name.clone(), e);
+                        self.stats.diagnose(&err);
+                        return Ok(false);
+                    }
+                }
+                continue;
+            }
+
+            if self.translation.needed {
+                self.translation.translate(&mut ibuf[..n]);
+            }
+            if self.conversions & SWAB != 0 {
+                swab(&ibuf[..n], &mut saved_byte, &mut swabbed);
+                self.convert(&swabbed)?;
+            } else {
+                self.convert(&ibuf[..n])?;
+            }
+        }
+
+        if let Some(byte) = saved_byte {
+            self.convert(&[byte])?;
+        }
+        let cbs = args.conversion_blocksize;
+        if self.conversions & BLOCK != 0 && self.col > 0 {
+            // The last line without a newline is padded too
+            for _ in self.col..cbs {
+                self.output_char(self.translation.space)?;
+            }
+        }
+        if self.conversions & UNBLOCK != 0 && self.col > 0 {
+            self.output_char(self.translation.newline)?;
+        }
+        if self.oc != 0 {
+            let (written, result) = match self.output.write(&self.obuf[..self.oc], &mut self.stats)
+            {
+                Ok(written) => (written, Ok(())),
+                Err((written, e)) => (written, Err(e)),
+            };
+            self.stats.w_bytes += written as u64;
+            if written != 0 {
+                self.stats.w_partial += 1;
+            }
+            if let Err(e) = result {
+                let err = DdError::Write(self.output.name.clone(), e);
+                self.stats.diagnose(&err);
+                return Ok(false);
+            }
+        }
+
+        // The sparse output ends with a seek, it's extended to where it went
+        if self.output.final_op_was_seek {
+            if let Err(err) = self.extend_output() {
+                self.stats.diagnose(&err);
+                return Ok(false);
+            }
+        }
+        if self.conversions & (FDATASYNC | FSYNC) != 0 {
+            self.stats.progress_before_sync();
+        }
+        Ok(success)
+    }
+
+    /// conv=fdatasync and conv=fsync, false if they fail
+    pub fn synchronize_output(&mut self) -> bool {
+        let mut success = true;
+        let mut fsync = self.conversions & FSYNC != 0;
+        if self.conversions & FDATASYNC != 0 {
+            if let Err(e) = self.output.file.sync_data() {
+                if !matches!(e.raw_os_error(), Some(errno::ENOSYS | errno::EINVAL)) {
+                    let err = DdError::Fdatasync(self.output.name.clone(), e);
+                    self.stats.diagnose(&err);
+                    success = false;
+                }
+                fsync = true;
+            }
+        }
+        if fsync {
+            if let Err(e) = self.output.file.sync_all() {
+                let err = DdError::Fsync(self.output.name.clone(), e);
+                self.stats.diagnose(&err);
+                return false;
+            }
+        }
+        success
+    }
+
+    /// skip=, with a seek if the input can, by reading it otherwise
+    fn skip_input(&mut self) -> Result<(), DdError> {
+        let ibs = self.args.input_blocksize as u64;
+        let (mut records, mut bytes) = (self.args.skip_records, self.args.skip_bytes);
+        let wanted = records.checked_mul(ibs).and_then(|r| r.checked_add(bytes));
+        let offset0 = self.input.offset;
+
+        let offset = wanted.filter(|offset| *offset <= i64::MAX as u64);
+        let seek = offset.map(|offset| self.input.file.seek(SeekFrom::Current(offset as i64)));
+        if let (Some(mut offset), Some(Ok(_))) = (offset, &seek) {
+            let name = &self.input.name;
+            let metadata = self.input.file.metadata();
+            let metadata = metadata.map_err(|e| DdError::Stat(name.clone(), e))?;
+            let size = metadata.len();
+            // Past the end the whole blocks, that aren't there, count as not skipped
+            records = 0;
+            if metadata.is_file() && 0 < size && size.saturating_sub(self.input.offset) < offset {
+                records = offset.saturating_sub(size) / ibs;
+                offset = size.saturating_sub(self.input.offset);
+            }
+            self.input.offset += offset;
+        } else {
+            let seek_error = match seek {
+                Some(Err(e)) => e,
+                _ => io::Error::from_raw_os_error(errno::EOVERFLOW),
+            };
+            if self.input.file.seek(SeekFrom::End(0)).is_ok() {
+                return Err(DdError::CannotSkip(self.input.name.clone(), seek_error));
+            }
+            let mut ibuf = alloc(ibs as usize, "input")?;
+            while records != 0 || bytes != 0 {
+                let size = if records != 0 { ibs } else { bytes } as usize;
+                match self.input.read(&mut ibuf[..size], &mut self.stats) {
+                    Ok(0) => break,
+                    Ok(n) => self.input.offset += n as u64,
+                    Err(e) => return Err(DdError::Read(self.input.name.clone(), e)),
+                }
+                match records {
+                    0 => bytes = 0,
+                    _ => records -= 1,
+                }
+            }
+        }
+
+        let skipped = Some(self.input.offset - offset0);
+        if (records != 0 || skipped != wanted) && self.stats.status != Status::None {
+            self.stats
+                .diagnose(&DdError::SkipOffset(self.input.name.clone()));
+        }
+        Ok(())
+    }
+
+    /// seek=, with a seek if the output can, by reading it, and then writing zeros otherwise
+    fn seek_output(&mut self) -> Result<(), DdError> {
+        let obs = self.args.output_blocksize as u64;
+        let (mut records, mut bytes) = (self.args.seek_records, self.args.seek_bytes);
+        let offset = records.checked_mul(obs).and_then(|r| r.checked_add(bytes));
+        let offset = offset.filter(|offset| *offset <= i64::MAX as u64);
+        let seek = offset.map(|offset| self.output.file.seek(SeekFrom::Current(offset as i64)));
+        if let Some(Ok(_)) = seek {
+            return Ok(());
+        }
+        let seek_error = match seek {
+            Some(Err(e)) => e,
+            _ => io::Error::from_raw_os_error(errno::EOVERFLOW),
+        };
+        if self.output.file.seek(SeekFrom::End(0)).is_ok() {
+            return Err(DdError::CannotSeek(self.output.name.clone(), seek_error));
+        }
+
+        let mut obuf = alloc(obs as usize, "output")?;
+        while records != 0 || bytes != 0 {
+            let size = if records != 0 { obs } else { bytes } as usize;
+            let nread = loop {
+                process_signals(&mut self.stats);
+                match self.output.file.read(&mut obuf[..size]) {
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    nread => break nread,
+                }
+            };
+            match nread {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(_) => return Err(DdError::CannotSeek(self.output.name.clone(), seek_error)),
+            }
+            match records {
+                0 => bytes = 0,
+                _ => records -= 1,
+            }
+        }
+
+        // What isn't there to skip is written as zeros
+        obuf.fill(0);
+        while records != 0 || bytes != 0 {
+            let size = if records != 0 { obs } else { bytes } as usize;
+            if let Err((_, e)) = self.output.write(&obuf[..size], &mut self.stats) {
+                return Err(DdError::WriteBlock(self.output.name.clone(), e));
+            }
+            match records {
+                0 => bytes = 0,
+                _ => records -= 1,
+            }
+        }
+        Ok(())
+    }
+
+    /// conv=noerror goes on past the bad block, false if the input can't seek there
+    fn advance_input_after_read_error(&mut self, bad_portion: usize) -> bool {
+        let err = match (self.input.seekable, self.input.seek_errno) {
+            (false, Some(errno::ESPIPE)) => return true,
+            (false, errno) => io::Error::from_raw_os_error(errno.unwrap_or(errno::ESPIPE)),
+            (true, _) => {
+                self.input.offset += bad_portion as u64;
+                match self.input.file.seek(SeekFrom::Start(self.input.offset)) {
+                    Ok(_) => return true,
+                    Err(e) => e,
+                }
+            }
+        };
+        let err = DdError::CannotSeek(self.input.name.clone(), err);
+        self.stats.diagnose(&err);
+        false
+    }
+
+    /// The regular file ends where the last seek of conv=sparse went
+    fn extend_output(&mut self) -> Result<(), DdError> {
+        let name = &self.output.name;
+        let metadata = self.output.file.metadata();
+        let metadata = metadata.map_err(|e| DdError::Stat(name.clone(), e))?;
+        if !metadata.is_file() {
+            return Ok(());
+        }
+        match self.output.file.stream_position() {
+            Ok(offset) if metadata.len() < offset => self
+                .output
+                .file
+                .set_len(offset)
+                .map_err(|e| DdError::Truncate(offset, name.clone(), e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// block, unblock or just a copy to the output buffer
+    fn convert(&mut self, bytes: &[u8]) -> Result<(), DdError> {
+        if self.conversions & BLOCK != 0 {
+            self.copy_with_block(bytes)
+        } else if self.conversions & UNBLOCK != 0 {
+            self.copy_with_unblock(bytes)
+        } else {
+            self.copy_simple(bytes)
+        }
+    }
+
+    fn copy_simple(&mut self, mut bytes: &[u8]) -> Result<(), DdError> {
+        while !bytes.is_empty() {
+            let n = bytes.len().min(self.obuf.len() - self.oc);
+            self.obuf[self.oc..self.oc + n].copy_from_slice(&bytes[..n]);
+            self.oc += n;
+            bytes = &bytes[n..];
+            if self.oc >= self.obuf.len() {
+                self.write_output()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The lines are padded with spaces to cbs, or cut to it
+    fn copy_with_block(&mut self, bytes: &[u8]) -> Result<(), DdError> {
+        let cbs = self.args.conversion_blocksize;
+        for &c in bytes {
+            if c == self.translation.newline {
+                for _ in self.col..cbs {
+                    self.output_char(self.translation.space)?;
+                }
+                self.col = 0;
+            } else {
+                if self.col == cbs {
+                    self.stats.r_truncate += 1;
+                } else if self.col < cbs {
+                    self.output_char(c)?;
+                }
+                self.col += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// The cbs-sized records become lines, without the trailing spaces
+    fn copy_with_unblock(&mut self, bytes: &[u8]) -> Result<(), DdError> {
+        let cbs = self.args.conversion_blocksize;
+        let mut i = 0;
+        while i < bytes.len() {
+            let c = bytes[i];
+            self.col += 1;
+            if self.col > cbs {
+                // The byte goes to the next record
+                self.col = 0;
+                self.pending_spaces = 0;
+                self.output_char(self.translation.newline)?;
+                continue;
+            }
+            if c == self.translation.space {
+                self.pending_spaces += 1;
+            } else {
+                while self.pending_spaces > 0 {
+                    self.output_char(self.translation.space)?;
+                    self.pending_spaces -= 1;
+                }
+                self.output_char(c)?;
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn output_char(&mut self, c: u8) -> Result<(), DdError> {
+        self.obuf[self.oc] = c;
+        self.oc += 1;
+        if self.oc >= self.obuf.len() {
+            self.write_output()?;
+        }
+        Ok(())
+    }
+
+    /// Writes out the full output buffer
+    fn write_output(&mut self) -> Result<(), DdError> {
+        match self.output.write(&self.obuf, &mut self.stats) {
+            Ok(written) => {
+                self.stats.w_bytes += written as u64;
+                self.stats.w_full += 1;
+            }
+            Err((written, e)) => {
+                self.stats.w_bytes += written as u64;
+                if written != 0 {
+                    self.stats.w_partial += 1;
+                }
+                return Err(DdError::WriteBlock(self.output.name.clone(), e));
+            }
+        }
+        self.oc = 0;
+        Ok(())
+    }
+}
+
+/// SIGUSR1 prints the statistics, SIGINT prints them and kills dd
+fn process_signals(stats: &mut Stats) {
+    while let Some(pending) = signals::take() {
+        stats.print_stats();
+        if let Pending::Interrupt(signal) = pending {
+            signals::reraise(signal);
+        }
+    }
+}
+
+/// Swaps the pairs of bytes, the odd one waits for its pair in the next block
+fn swab(bytes: &[u8], saved_byte: &mut Option<u8>, swabbed: &mut Vec<u8>) {
+    swabbed.clear();
+    swabbed.extend(saved_byte.take());
+    swabbed.extend_from_slice(bytes);
+    if swabbed.len() % 2 == 1 {
+        *saved_byte = swabbed.pop();
+    }
+    for pair in swabbed.chunks_exact_mut(2) {
+        pair.swap(0, 1);
+    }
+}
+
+fn alloc(size: usize, buffer: &'static str) -> Result<Vec<u8>, DdError> {
+    let mut buf = Vec::new();
+    buf.try_reserve_exact(size)
+        .map_err(|_| DdError::Memory(buffer, size))?;
+    buf.resize(size, 0);
+    Ok(buf)
+}
+
+/// of=FILE is created, unless conv=nocreat, and truncated to seek=, unless conv=notrunc
+fn open_output(name: &str, args: &Args) -> Result<File, DdError> {
+    let conversions = args.conversions;
+    let truncate = args.seek_records != 0 && conversions & NOTRUNC == 0;
+    let obs = args.output_blocksize;
+    let size = (args.seek_records.checked_mul(obs as u64))
+        .and_then(|size| size.checked_add(args.seek_bytes))
+        .filter(|size| *size <= i64::MAX as u64);
+    if truncate && size.is_none() {
+        return Err(DdError::OffsetTooLarge(args.seek_records, obs));
+    }
+    let mut options = OpenOptions::new();
+    options
+        .write(true)
+        .create(conversions & NOCREAT == 0)
+        .create_new(conversions & EXCL != 0)
+        .truncate(args.seek_records == 0 && conversions & NOTRUNC == 0);
+    if args.output_flags & APPEND != 0 {
+        append_flag(&mut options);
+    }
+    // The output is read to skip it, when it can't seek
+    let file = match args.seek_records {
+        0 => options.open(name),
+        _ => options
+            .clone()
+            .read(true)
+            .open(name)
+            .or_else(|_| options.open(name)),
+    };
+    let file = file.map_err(|e| DdError::Open(name.to_string(), e))?;
+
+    if let Some(size) = size.filter(|_| truncate) {
+        if let Err(e) = file.set_len(size) {
+            // Only the files, that have a size, are to be truncated
+            let metadata = file.metadata();
+            let metadata = metadata.map_err(|e| DdError::Stat(name.to_string(), e))?;
+            if metadata.is_file() || metadata.is_dir() {
+                return Err(DdError::Truncate(size, name.to_string(), e));
+            }
+        }
+    }
+    Ok(file)
+}
+
+/// oflag=append for of=FILE, O_APPEND goes along with O_TRUNC, unlike OpenOptions::append()
+#[cfg(target_family = "unix")]
+fn append_flag(options: &mut OpenOptions) {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.custom_flags(nix::libc::O_APPEND);
+}
+
+#[cfg(not(target_family = "unix"))]
+fn append_flag(options: &mut OpenOptions) {
+    options.append(true);
+}
+
+/// oflag=append for the standard output
+#[cfg(target_family = "unix")]
+fn set_append(fd: i32) {
+    use nix::fcntl::{fcntl, FcntlArg, OFlag};
+    if let Ok(flags) = fcntl(fd, FcntlArg::F_GETFL) {
+        let flags = OFlag::from_bits_truncate(flags) | OFlag::O_APPEND;
+        let _ = fcntl(fd, FcntlArg::F_SETFL(flags));
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn set_append(_fd: i32) {}
+
+/// The standard input or output, unbuffered
+#[cfg(target_family = "unix")]
+fn standard_file(fd: i32) -> io::Result<File> {
+    use std::os::fd::FromRawFd;
+    // Safe: nothing else uses the descriptor
+    Ok(unsafe { File::from_raw_fd(fd) })
+}
+
+#[cfg(not(target_family = "unix"))]
+fn standard_file(_fd: i32) -> io::Result<File> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+/// The numbers of the errors, that dd makes up itself
+#[cfg(target_family = "unix")]
+mod errno {
+    pub use nix::libc::{EINVAL, ENOSPC, ENOSYS, EOVERFLOW, ESPIPE};
+}
+
+/// Linux numbers, elsewhere they only tell the errors apart
+#[cfg(not(target_family = "unix"))]
+mod errno {
+    pub const EINVAL: i32 = 22;
+    pub const ENOSPC: i32 = 28;
+    pub const ENOSYS: i32 = 38;
+    pub const EOVERFLOW: i32 = 75;
+    pub const ESPIPE: i32 = 29;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn swaps_the_pairs_across_the_blocks() {
+        let (mut saved_byte, mut swabbed) = (None, Vec::new());
+        swab(b"abc", &mut saved_byte, &mut swabbed);
+        assert_eq!((&swabbed[..], saved_byte), (&b"ba"[..], Some(b'c')));
+        swab(b"defg", &mut saved_byte, &mut swabbed);
+        assert_eq!((&swabbed[..], saved_byte), (&b"dcfe"[..], Some(b'g')));
+        swab(b"h", &mut saved_byte, &mut swabbed);
+        assert_eq!((&swabbed[..], saved_byte), (&b"hg"[..], None));
+    }
+
+    #[test]
+    fn reports_the_memory_exhausted() {
+        let err = alloc(usize::MAX, "input").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "memory exhausted by input buffer of size 18446744073709551615 bytes (16 EiB)"
+        );
+    }
+}
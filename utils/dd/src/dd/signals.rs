@@ -0,0 +1,96 @@
+// SIGUSR1 makes dd print the statistics and go on, SIGINT makes it print them and die.
+// The handlers just take a note, the copying loop looks at the notes between the reads
+// and the writes. SIGUSR1 doesn't restart the system calls, so a read waiting on a pipe
+// doesn't delay the report. Elsewhere there are no such signals, nothing is ever noted.
+
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+
+#[cfg(target_family = "unix")]
+use nix::libc::c_int;
+#[cfg(target_family = "unix")]
+use nix::sys::signal::{raise, sigaction, signal, SaFlags, SigAction, SigHandler, SigSet, Signal};
+
+static INFO_SIGNALS: AtomicUsize = AtomicUsize::new(0);
+static INTERRUPT_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pending {
+    /// SIGUSR1: print the statistics
+    Info,
+    /// SIGINT: print the statistics and die of the signal
+    Interrupt(i32),
+}
+
+/// The handlers go only where the signals aren't ignored, and SIGPIPE kills dd as it should
+#[cfg(target_family = "unix")]
+pub fn install() {
+    // Safe: the handlers only touch the atomics
+    unsafe {
+        let _ = signal(Signal::SIGPIPE, SigHandler::SigDfl);
+        handle(Signal::SIGUSR1, info_handler, SaFlags::empty());
+        // The handler is reset, so raising the signal again kills dd
+        let flags = SaFlags::SA_NODEFER | SaFlags::SA_RESETHAND;
+        handle(Signal::SIGINT, interrupt_handler, flags);
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+pub fn install() {}
+
+#[cfg(target_family = "unix")]
+unsafe fn handle(signal: Signal, handler: extern "C" fn(c_int), flags: SaFlags) {
+    let action = SigAction::new(SigHandler::Handler(handler), flags, SigSet::empty());
+    if let Ok(old) = sigaction(signal, &action) {
+        if old.handler() == SigHandler::SigIgn {
+            let _ = sigaction(signal, &old);
+        }
+    }
+}
+
+#[cfg(target_family = "unix")]
+extern "C" fn info_handler(_: c_int) {
+    INFO_SIGNALS.fetch_add(1, Ordering::SeqCst);
+}
+
+#[cfg(target_family = "unix")]
+extern "C" fn interrupt_handler(signal: c_int) {
+    INTERRUPT_SIGNAL.store(signal, Ordering::SeqCst);
+}
+
+/// The next signal to process, the interrupt goes first
+pub fn take() -> Option<Pending> {
+    match INTERRUPT_SIGNAL.swap(0, Ordering::SeqCst) {
+        0 => INFO_SIGNALS
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .ok()
+            .map(|_| Pending::Info),
+        signal => Some(Pending::Interrupt(signal)),
+    }
+}
+
+/// Dies of the signal, its handler is the default one by now
+#[cfg(target_family = "unix")]
+pub fn reraise(signal: i32) {
+    if let Ok(signal) = Signal::try_from(signal) {
+        let _ = raise(signal);
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+pub fn reraise(_signal: i32) {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn takes_the_pending_signals() {
+        assert_eq!(take(), None);
+        INFO_SIGNALS.fetch_add(2, Ordering::SeqCst);
+        INTERRUPT_SIGNAL.store(2, Ordering::SeqCst);
+        assert_eq!(take(), Some(Pending::Interrupt(2)));
+        assert_eq!(take(), Some(Pending::Info));
+        assert_eq!(take(), Some(Pending::Info));
+        assert_eq!(take(), None);
+    }
+}
@@ -0,0 +1,341 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+/// status=LEVEL, how much goes to stderr besides the errors
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Status {
+    /// Nothing at all
+    None,
+    /// Only the records in and out
+    NoXfer,
+    #[default]
+    Default,
+    /// The transfer statistics once a second, too
+    Progress,
+}
+
+/// The power letters of human_readable(), k is lowercase in SI
+const POWERS: &[u8] = b"KMGTPEZYRQ";
+
+/** The counts of the records and bytes copied, and the reports of them on stderr.
+ *
+ * The reports are the ones of GNU dd, byte for byte.
+ */
+#[derive(Debug)]
+pub struct Stats {
+    pub status: Status,
+    /// Full and partial input blocks read
+    pub r_full: u64,
+    pub r_partial: u64,
+    /// Full and partial output blocks written
+    pub w_full: u64,
+    pub w_partial: u64,
+    /// The records, that conv=block cut to cbs
+    pub r_truncate: u64,
+    pub w_bytes: u64,
+    /// The bytes written when the statistics were reported the last time
+    reported_w_bytes: Option<u64>,
+    start_time: Instant,
+    next_time: Instant,
+    /// The length of the progress line, it's ended before anything else goes to stderr
+    progress_len: usize,
+}
+
+impl Stats {
+    pub fn new(status: Status) -> Self {
+        let start_time = Instant::now();
+        Stats {
+            status,
+            r_full: 0,
+            r_partial: 0,
+            w_full: 0,
+            w_partial: 0,
+            r_truncate: 0,
+            w_bytes: 0,
+            reported_w_bytes: None,
+            start_time,
+            next_time: start_time + Duration::from_secs(1),
+            progress_len: 0,
+        }
+    }
+
+    /// Prints the error or the warning, on a line of its own
+    pub fn diagnose(&mut self, message: &dyn Display) {
+        self.end_progress();
+        eprintln!("dd: {}", message);
+    }
+
+    /// status=progress reports once a second
+    pub fn progress(&mut self) {
+        if self.status != Status::Progress {
+            return;
+        }
+        let now = Instant::now();
+        if self.next_time <= now {
+            self.print_xfer_stats(Some(now));
+            self.next_time += Duration::from_secs(1);
+        }
+    }
+
+    /// The final progress report, before fsync takes its time
+    pub fn progress_before_sync(&mut self) {
+        let reported = self.reported_w_bytes.is_some_and(|w| w < self.w_bytes);
+        if self.status == Status::Progress && reported {
+            self.print_xfer_stats(None);
+        }
+    }
+
+    pub fn print_stats(&mut self) {
+        if self.status == Status::None {
+            return;
+        }
+        self.end_progress();
+        eprint!(
+            "{}+{} records in\n{}+{} records out\n",
+            self.r_full, self.r_partial, self.w_full, self.w_partial
+        );
+        if self.r_truncate != 0 {
+            let plural = if self.r_truncate == 1 { "" } else { "s" };
+            eprintln!("{} truncated record{}", self.r_truncate, plural);
+        }
+        if self.status != Status::NoXfer {
+            self.print_xfer_stats(None);
+        }
+    }
+
+    /// "N bytes (SI, IEC) copied, T s, R/s", on the progress line or a line of its own
+    fn print_xfer_stats(&mut self, progress_time: Option<Instant>) {
+        let now = progress_time.unwrap_or_else(Instant::now);
+        let si = human_readable(self.w_bytes, 1000, 1, 1);
+        let iec = human_readable(self.w_bytes, 1024, 1, 1);
+        let elapsed = now.saturating_duration_since(self.start_time);
+        let (delta_s, rate) = match u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX) {
+            0 => (0.0, "Infinity B/s".to_string()),
+            nanos => (
+                elapsed.as_secs_f64(),
+                human_readable(self.w_bytes, 1000, 1_000_000_000, nanos) + "/s",
+            ),
+        };
+        let delta_s = match progress_time {
+            Some(_) => format!("{:.0} s", delta_s),
+            None => format!("{} s", format_g(delta_s)),
+        };
+
+        let stats = if lacks_prefix(&si) {
+            let plural = if self.w_bytes == 1 { "" } else { "s" };
+            format!(
+                "{} byte{} copied, {}, {}",
+                self.w_bytes, plural, delta_s, rate
+            )
+        } else if lacks_prefix(&iec) {
+            format!(
+                "{} bytes ({}) copied, {}, {}",
+                self.w_bytes, si, delta_s, rate
+            )
+        } else {
+            let bytes = self.w_bytes;
+            format!(
+                "{} bytes ({}, {}) copied, {}, {}",
+                bytes, si, iec, delta_s, rate
+            )
+        };
+        match progress_time {
+            Some(_) => {
+                // The spaces wipe out whatever is left of the previous report
+                let padding = self.progress_len.saturating_sub(stats.len());
+                eprint!("\r{}{:padding$}", stats, "");
+                self.progress_len = stats.len();
+            }
+            None => eprintln!("{}", stats),
+        }
+        self.reported_w_bytes = Some(self.w_bytes);
+    }
+
+    fn end_progress(&mut self) {
+        if self.progress_len > 0 {
+            eprintln!();
+            self.progress_len = 0;
+        }
+    }
+}
+
+/// "512 B" has no power letter, unlike "1.5 kB"
+fn lacks_prefix(size: &str) -> bool {
+    size.as_bytes()[size.len() - 2] == b' '
+}
+
+/** N * FROM / TO in the powers of BASE, rounded to the nearest: "1.5 kB", "12 MiB", "512 B".
+ *
+ * This is gnulib's human_readable() with dd's options. Values under 10 get a tenths digit,
+ * the ties are rounded to even. When FROM and TO don't divide each other, the value is
+ * computed in floating point and it's never less than the first power: "0.5 kB".
+ */
+pub fn human_readable(n: u64, base: u64, from: u64, to: u64) -> String {
+    let exact = if to <= from && from.is_multiple_of(to) {
+        n.checked_mul(from / to).map(|amt| (amt, 0, 0))
+    } else if to.is_multiple_of(from) {
+        let divisor = (to / from) as u128;
+        let r10 = (n as u128 % divisor) * 10;
+        let r2 = (r10 % divisor) * 2;
+        let remainder = if r2 < divisor {
+            (0 < r2) as u64
+        } else {
+            2 + (divisor < r2) as u64
+        };
+        Some((
+            (n as u128 / divisor) as u64,
+            (r10 / divisor) as u64,
+            remainder,
+        ))
+    } else {
+        None
+    };
+    let Some((mut amt, mut tenths, mut remainder)) = exact else {
+        return human_readable_float(n as f64 * (from as f64 / to as f64), base);
+    };
+
+    let max_exponent = POWERS.len();
+    let mut exponent = 0;
+    let mut point = None;
+    if base <= amt {
+        loop {
+            let r10 = (amt % base) * 10 + tenths;
+            let r2 = (r10 % base) * 2 + (remainder >> 1);
+            amt /= base;
+            tenths = r10 / base;
+            remainder = match r2 < base {
+                true => (r2 + remainder != 0) as u64,
+                false => 2 + (base < r2 + remainder) as u64,
+            };
+            exponent += 1;
+            if amt < base || exponent == max_exponent {
+                break;
+            }
+        }
+        if amt < 10 {
+            if 2 < remainder + (tenths & 1) {
+                tenths += 1;
+                remainder = 0;
+                if tenths == 10 {
+                    amt += 1;
+                    tenths = 0;
+                }
+            }
+            if amt < 10 {
+                point = Some(tenths);
+                tenths = 0;
+                remainder = 0;
+            }
+        }
+    }
+    if 5 < tenths + (0 < remainder + (amt & 1)) as u64 {
+        amt += 1;
+        if amt == base && exponent < max_exponent {
+            exponent += 1;
+            point = Some(0);
+            amt = 1;
+        }
+    }
+
+    let number = match point {
+        Some(tenths) => format!("{}.{}", amt, tenths),
+        None => amt.to_string(),
+    };
+    number + &unit(exponent, base)
+}
+
+fn human_readable_float(amt: f64, base: u64) -> String {
+    let base_f = base as f64;
+    let mut power = 1.0;
+    let mut exponent = 0;
+    loop {
+        power *= base_f;
+        exponent += 1;
+        if amt < power * base_f || exponent == POWERS.len() {
+            break;
+        }
+    }
+    let amt = amt / power;
+    let mut number = format!("{:.1}", amt);
+    // Up to 3 digits before the point, SI can also have "999.9"
+    if number.len() > 3 + (base == 1000) as usize {
+        number = format!("{:.0}", amt);
+    }
+    number + &unit(exponent, base)
+}
+
+/// " B", " kB", " MiB"
+fn unit(exponent: usize, base: u64) -> String {
+    let mut unit = " ".to_string();
+    if exponent > 0 {
+        unit.push(match (base, exponent) {
+            (1000, 1) => 'k',
+            _ => POWERS[exponent - 1] as char,
+        });
+        if base == 1024 {
+            unit.push('i');
+        }
+    }
+    unit + "B"
+}
+
+/// printf("%g"): 6 significant digits, without the trailing zeros
+fn format_g(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    let scientific = format!("{:.5e}", value);
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    if (-4..6).contains(&exponent) {
+        let fixed = format!("{:.*}", (5 - exponent) as usize, value);
+        trim_zeros(&fixed).to_string()
+    } else {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", trim_zeros(mantissa), sign, exponent.abs())
+    }
+}
+
+fn trim_zeros(number: &str) -> &str {
+    match number.contains('.') {
+        true => number.trim_end_matches('0').trim_end_matches('.'),
+        false => number,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn formats_the_sizes() {
+        assert_eq!(human_readable(0, 1000, 1, 1), "0 B");
+        assert_eq!(human_readable(999, 1000, 1, 1), "999 B");
+        assert_eq!(human_readable(1050, 1000, 1, 1), "1.0 kB");
+        assert_eq!(human_readable(1150, 1000, 1, 1), "1.2 kB");
+        assert_eq!(human_readable(1048576, 1024, 1, 1), "1.0 MiB");
+        assert_eq!(human_readable(10485760, 1000, 1, 1), "10 MB");
+        assert_eq!(human_readable(1023999, 1024, 1, 1), "1000 KiB");
+        assert_eq!(human_readable(999999, 1000, 1, 1), "1.0 MB");
+        assert_eq!(human_readable(u64::MAX, 1024, 1, 1), "16 EiB");
+    }
+
+    #[test]
+    fn formats_the_rates() {
+        let second = 1_000_000_000;
+        assert_eq!(human_readable(512, 1000, second, second), "512 B");
+        assert_eq!(human_readable(512, 1000, second, 3 * second), "171 B");
+        assert_eq!(human_readable(1, 1000, second, 28241), "35.4 kB");
+        assert_eq!(human_readable(3, 1000, second, 28241), "106 kB");
+        assert_eq!(human_readable(0, 1000, second, 7), "0.0 kB");
+    }
+
+    #[test]
+    fn formats_like_printf_g() {
+        assert_eq!(format_g(2.8241e-05), "2.8241e-05");
+        assert_eq!(format_g(0.000242148), "0.000242148");
+        assert_eq!(format_g(1.5), "1.5");
+        assert_eq!(format_g(12.34567890), "12.3457");
+        assert_eq!(format_g(1234567.0), "1.23457e+06");
+    }
+}
@@ -0,0 +1,39 @@
+mod args;
+mod dd;
+
+use std::process::ExitCode;
+
+use args::Args;
+use dd::dd_error::DdError;
+use dd::{signals, Dd};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    signals::install();
+    let mut dd = match Dd::open(&args) {
+        Ok(dd) => dd,
+        Err(e) => {
+            eprintln!("dd: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut success = match dd.copy() {
+        Ok(success) => success,
+        Err(e @ DdError::Memory(..)) => {
+            dd.stats.diagnose(&e);
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            // dd quits with the statistics of what was copied so far
+            dd.stats.diagnose(&e);
+            dd.stats.print_stats();
+            return ExitCode::FAILURE;
+        }
+    };
+    success &= dd.synchronize_output();
+    dd.stats.print_stats();
+    match success {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
@@ -0,0 +1,64 @@
+use std::ffi::OsString;
+
+use clap::{CommandFactory, FromArgMatches, Parser};
+use tab_stops::TabStops;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Convert tabs in each FILE to spaces, writing to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input
+    #[arg(default_value = "-")]
+    pub file: Vec<String>,
+
+    /// do not convert tabs after non blanks
+    #[arg(short = 'i', long, default_value_t = false)]
+    pub initial: bool,
+
+    /// have tabs N characters apart, not 8, or at the comma separated LIST
+    /// of positions; the last one can be prefixed with '/' for the tabs every
+    /// N columns after it, or with '+' for the ones every N columns from it
+    #[arg(short = 't', long, value_name = "LIST")]
+    pub tabs: Vec<String>,
+
+    /// All the lists of -t
+    #[arg(skip)]
+    pub tab_stops: TabStops,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let arguments = obsolete_tabs(std::env::args_os());
+        let matches = <Self as CommandFactory>::command().get_matches_from(arguments);
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        args.tab_stops = TabStops::parse(&args.tabs).unwrap_or_else(|err| {
+            eprintln!("expand: {}", err);
+            std::process::exit(1);
+        });
+        args
+    }
+}
+
+/// The obsolete -N and -N,M,.. go as -t, clap doesn't take the digits for options
+fn obsolete_tabs(arguments: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut options = true;
+    arguments
+        .map(|argument| match argument.to_str() {
+            Some("--") => {
+                options = false;
+                argument
+            }
+            Some(list)
+                if options
+                    && list.starts_with('-')
+                    && list[1..].starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                format!("--tabs={}", &list[1..]).into()
+            }
+            _ => argument,
+        })
+        .collect()
+}
@@ -0,0 +1,38 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum ExpandError {
+    /// Opening or reading a FILE, GNU reports both the same way
+    Read(String, io::Error),
+    Write(io::Error),
+}
+
+impl fmt::Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpandError::Read(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            ExpandError::Write(err) => write!(f, "write error: {}", strerror(err)),
+        }
+    }
+}
+
+impl Error for ExpandError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ExpandError::Read(_, err) | ExpandError::Write(err) => Some(err),
+        }
+    }
+}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
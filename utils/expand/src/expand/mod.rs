@@ -0,0 +1,115 @@
+pub mod expand_error;
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+
+use chunk_reader::ChunkReader;
+use tab_stops::{width, Chars, Columns, TabStops};
+
+use expand_error::ExpandError;
+
+/** Replaces the tabs with the spaces up to the next tab stop.
+ *
+ * The FILEs go as one input, so a line can go on in the next FILE.
+ */
+pub struct Expander<'a> {
+    tab_stops: &'a TabStops,
+    /// Only the tabs before the first non-blank character of the line
+    initial: bool,
+    columns: Columns,
+    convert: bool,
+}
+
+impl<'a> Expander<'a> {
+    pub fn new(tab_stops: &'a TabStops, initial: bool) -> Self {
+        Expander {
+            tab_stops,
+            initial,
+            columns: Columns::default(),
+            convert: true,
+        }
+    }
+
+    pub fn expand_file<W: Write>(&mut self, name: &str, output: &mut W) -> Result<(), ExpandError> {
+        let read_error = |e| ExpandError::Read(name.to_string(), e);
+        let input: Box<dyn Read> = match name {
+            "-" => Box::new(io::stdin()),
+            _ => Box::new(File::open(name).map_err(read_error)?),
+        };
+        let mut expanded = Vec::new();
+        for line in BufReader::new(input).chunks(b'\n', 0) {
+            let line = line.map_err(read_error)?;
+            expanded.clear();
+            self.expand(&line, &mut expanded);
+            output.write_all(&expanded).map_err(ExpandError::Write)?;
+        }
+        Ok(())
+    }
+
+    /// Past the last tab stop, the tabs are single spaces
+    fn expand(&mut self, line: &[u8], expanded: &mut Vec<u8>) {
+        for (bytes, c) in Chars::new(line) {
+            if self.convert {
+                match c {
+                    Some('\t') => {
+                        let column = self.columns.column;
+                        let next = self.columns.next_tab(self.tab_stops);
+                        let next = next.unwrap_or(column + 1);
+                        expanded.resize(expanded.len() + next - column, b' ');
+                        self.columns.column = next;
+                        continue;
+                    }
+                    Some('\x08') => self.columns.backspace(),
+                    Some(c) => self.columns.advance(width(c)),
+                    None => self.columns.advance(1),
+                }
+                self.convert &= !self.initial || c == Some(' ');
+            }
+            expanded.extend_from_slice(bytes);
+            if c == Some('\n') {
+                self.columns.reset();
+                self.convert = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn expand(lists: &[&str], initial: bool, input: &[u8]) -> Vec<u8> {
+        let tab_stops = TabStops::parse(lists).unwrap();
+        let mut expander = Expander::new(&tab_stops, initial);
+        let mut expanded = Vec::new();
+        for line in input.split_inclusive(|&byte| byte == b'\n') {
+            expander.expand(line, &mut expanded);
+        }
+        expanded
+    }
+
+    #[test]
+    fn expands_the_tabs() {
+        assert_eq!(expand(&[], false, b"a\tb\n\tc"), b"a       b\n        c");
+        assert_eq!(expand(&["2,5"], false, b"\t\t\tx\n"), b"      x\n");
+        assert_eq!(
+            expand(&["4"], false, b"abc\x08\x08d\te\n"),
+            b"abc\x08\x08d  e\n"
+        );
+        assert_eq!(
+            expand(&["4"], true, b" \ta\tb\n\tc\n"),
+            b"    a\tb\n    c\n"
+        );
+    }
+
+    #[test]
+    fn counts_the_display_width() {
+        let expand_text = |input: &str| expand(&["4"], false, input.as_bytes());
+        assert_eq!(expand_text("日本\tx\n"), "日本    x\n".as_bytes());
+        assert_eq!(expand_text("e\u{301}\tx\n"), "e\u{301}   x\n".as_bytes());
+        assert_eq!(
+            expand(&["4"], false, b"\xc3\xa9\xff\tx"),
+            b"\xc3\xa9\xff  x"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+mod args;
+mod expand;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use expand::expand_error::ExpandError;
+use expand::Expander;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let mut expander = Expander::new(&args.tab_stops, args.initial);
+    let mut status = ExitCode::SUCCESS;
+    for file in &args.file {
+        match expander.expand_file(file, &mut output) {
+            Ok(()) => {}
+            Err(e @ ExpandError::Write(_)) => {
+                eprintln!("expand: {}", e);
+                return ExitCode::FAILURE;
+            }
+            Err(e) => {
+                // Flushing whatever was written before the error, so it goes before the message
+                let _ = output.flush();
+                eprintln!("expand: {}", e);
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Err(e) = output.flush() {
+        eprintln!("expand: {}", ExpandError::Write(e));
+        return ExitCode::FAILURE;
+    }
+    status
+}
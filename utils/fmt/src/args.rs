@@ -0,0 +1,163 @@
+use std::ffi::OsString;
+
+use clap::{CommandFactory, FromArgMatches, Parser};
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+const WIDTH: usize = 75;
+/// The limit of the width, the lines get too long otherwise
+const MAX_WIDTH: usize = 2500;
+/// The goal is that much short of the width, in percents
+const LEEWAY: usize = 7;
+
+/// Reformat each paragraph in the FILE(s), writing to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about, args_override_self = true)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input. -WIDTH is the
+    /// same as -w WIDTH, when it's the first option
+    #[arg(value_name = "FILE", default_value = "-")]
+    pub files: Vec<String>,
+
+    /// preserve indentation of first two lines
+    #[arg(short = 'c', long, default_value_t = false)]
+    pub crown_margin: bool,
+
+    /// reformat only lines beginning with STRING, reattaching the prefix
+    /// to reformatted lines
+    #[arg(short = 'p', long, value_name = "STRING", allow_hyphen_values = true)]
+    pub prefix: Option<String>,
+
+    /// split long lines, but do not refill
+    #[arg(short = 's', long, default_value_t = false)]
+    pub split_only: bool,
+
+    /// indentation of first line different from second
+    #[arg(short = 't', long, default_value_t = false)]
+    pub tagged_paragraph: bool,
+
+    /// one space between words, two after sentences
+    #[arg(short = 'u', long, default_value_t = false)]
+    pub uniform_spacing: bool,
+
+    /// maximum line width (default of 75 columns)
+    #[arg(short = 'w', long, value_name = "WIDTH", allow_hyphen_values = true)]
+    pub width: Option<String>,
+
+    /// goal width (default of 93% of width)
+    #[arg(short = 'g', long, value_name = "WIDTH", allow_hyphen_values = true)]
+    pub goal: Option<String>,
+
+    /// The width of -w, or 10 more than the goal
+    #[arg(skip)]
+    pub max_width: usize,
+
+    #[arg(skip)]
+    pub goal_width: usize,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let arguments = obsolete_width(std::env::args_os().collect());
+        let matches = <Self as CommandFactory>::command().get_matches_from(arguments);
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        args.max_width = WIDTH;
+        if let Some(width) = &args.width {
+            args.max_width = parse_width(width, MAX_WIDTH).unwrap_or_else(|err| fatal(&err));
+        }
+        args.goal_width = match &args.goal {
+            Some(goal) => {
+                let goal_width =
+                    parse_width(goal, args.max_width).unwrap_or_else(|err| fatal(&err));
+                if args.width.is_none() {
+                    args.max_width = goal_width + 10;
+                }
+                goal_width
+            }
+            None => args.max_width * (2 * (100 - LEEWAY) + 1) / 200,
+        };
+        args
+    }
+}
+
+/** The first option -WIDTH goes as -w, the digits are wrong in the other ones.
+ *
+ * GNU takes the rest of the first option as WIDTH, even when it's not all
+ * digits.
+ */
+fn obsolete_width(mut arguments: Vec<OsString>) -> Vec<OsString> {
+    let first = arguments.get(1).and_then(|first| first.to_str());
+    if let Some(width) = first.and_then(|first| first.strip_prefix('-')) {
+        if width.starts_with(|c: char| c.is_ascii_digit()) {
+            arguments[1] = format!("--width={}", width).into();
+        }
+    }
+    let mut value = false;
+    for argument in arguments.iter().skip(1) {
+        let Some(options) = argument.to_str().filter(|_| !value) else {
+            value = false;
+            continue;
+        };
+        if options == "--" {
+            break;
+        }
+        value = matches!(options, "--prefix" | "--width" | "--goal");
+        let Some(options) = options.strip_prefix('-').filter(|o| !o.starts_with('-')) else {
+            continue;
+        };
+        for (i, option) in options.char_indices() {
+            if option.is_ascii_digit() {
+                usage_error(&format!(
+                    "invalid option -- {}; -WIDTH is recognized only when it is the first\n\
+                     option; use -w N instead",
+                    option
+                ));
+            }
+            if "pwg".contains(option) {
+                value = i + 1 == options.len();
+                break;
+            }
+        }
+    }
+    arguments
+}
+
+/// The WIDTH up to MAX, the way GNU xdectoumax() reads it
+fn parse_width(width: &str, max: usize) -> Result<usize, String> {
+    let digits = width.trim_start();
+    let digits = digits.strip_prefix('+').unwrap_or(digits);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(format!("invalid width: {}", quote(width)));
+    }
+    match digits.parse::<usize>() {
+        Ok(value) if value <= max => Ok(value),
+        Ok(value) if value <= i32::MAX as usize / 2 => Err(format!(
+            "invalid width: {}: Numerical result out of range",
+            quote(width)
+        )),
+        _ => Err(format!(
+            "invalid width: {}: Value too large for defined data type",
+            quote(width)
+        )),
+    }
+}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
+
+fn fatal(message: &str) -> ! {
+    eprintln!("fmt: {}", message);
+    std::process::exit(1);
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("fmt: {}", message);
+    eprintln!("Try 'fmt --help' for more information.");
+    std::process::exit(1);
+}
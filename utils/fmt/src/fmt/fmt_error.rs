@@ -0,0 +1,53 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum FmtError {
+    /// The other FILEs are still formatted
+    Open(String, io::Error),
+    Read(String, io::Error),
+    Write(io::Error),
+}
+
+impl fmt::Display for FmtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FmtError::Open(name, err) => write!(
+                f,
+                "cannot open {} for reading: {}",
+                quote_always(name),
+                strerror(err)
+            ),
+            FmtError::Read(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            FmtError::Write(err) => write!(f, "write error: {}", strerror(err)),
+        }
+    }
+}
+
+impl Error for FmtError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FmtError::Open(_, err) | FmtError::Read(_, err) | FmtError::Write(err) => Some(err),
+        }
+    }
+}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
+
+/// The names of the FILEs that can't be opened are always quoted
+fn quote_always(name: &str) -> String {
+    match name.contains('\'') {
+        true => format!("\"{}\"", name),
+        false => format!("'{}'", name),
+    }
+}
@@ -0,0 +1,640 @@
+pub mod fmt_error;
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+
+use tab_stops::{width, Chars};
+
+use crate::args::Args;
+use fmt_error::FmtError;
+
+type Cost = i64;
+
+const MAX_COST: Cost = Cost::MAX;
+/// The paragraphs longer than that are formatted in parts
+const MAX_WORDS: usize = 1000;
+const MAX_CHARS: usize = 5000;
+/// The second line of -t, when it can't be like the first one
+const DEFAULT_INDENT: isize = 3;
+const TAB_WIDTH: isize = 8;
+
+/// The costs go as the square of the columns, so the lines go near the goal
+fn equiv(n: isize) -> Cost {
+    (n as Cost) * (n as Cost)
+}
+
+fn short_cost(n: isize) -> Cost {
+    equiv(n * 10)
+}
+
+fn ragged_cost(n: isize) -> Cost {
+    short_cost(n) / 2
+}
+
+const LINE_COST: Cost = 70 * 70;
+const SENTENCE_BONUS: Cost = 50 * 50;
+const NOBREAK_COST: Cost = 600 * 600;
+const PAREN_BONUS: Cost = 40 * 40;
+const PUNCT_BONUS: Cost = 40 * 40;
+/// The split of a long paragraph goes near its end
+const LINE_CREDIT: Cost = 3 * 3;
+
+/// The last word of a sentence alone on a line
+fn widow_cost(length: isize) -> Cost {
+    equiv(200) / (length as Cost + 2)
+}
+
+/// The first word of a sentence alone at the end of a line
+fn orphan_cost(length: isize) -> Cost {
+    equiv(150) / (length as Cost + 2)
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Word {
+    /// Where the word starts in the paragraph, and its bytes
+    start: usize,
+    bytes: usize,
+    /// The columns of the word, and of the space after it
+    length: isize,
+    space: isize,
+    paren: bool,
+    period: bool,
+    punct: bool,
+    /// The end of a sentence
+    last: bool,
+    /// The width of the line from this word, and the word starting the next one
+    line_length: isize,
+    best_cost: Cost,
+    next_break: usize,
+}
+
+/** Fills the paragraphs, breaking them where the lines cost least, the way GNU fmt does.
+ *
+ * The lines go near the goal width, and the sentences end rather at the
+ * end of the lines than in the middle of them. The paragraphs are split by
+ * the blank lines and the changes of the indentation, the lines without the
+ * prefix go as they are.
+ */
+pub struct Fmt<'a, W: Write> {
+    output: &'a mut W,
+    crown: bool,
+    tagged: bool,
+    split: bool,
+    uniform: bool,
+    /// The prefix with no spaces around, and its columns along with the spaces after it
+    prefix: Vec<u8>,
+    prefix_length: isize,
+    prefix_full_length: isize,
+    prefix_lead_space: isize,
+    max_width: isize,
+    goal_width: isize,
+
+    input: Box<dyn BufRead>,
+    name: String,
+    in_column: isize,
+    out_column: isize,
+    buffer: Vec<u8>,
+    words: Vec<Word>,
+    word_limit: usize,
+    /// There were tabs in the FILE, so the indentation goes with tabs too
+    tabs: bool,
+    prefix_indent: isize,
+    first_indent: isize,
+    other_indent: isize,
+    next_char: Option<u8>,
+    next_prefix_indent: isize,
+    last_line_length: isize,
+}
+
+impl<'a, W: Write> Fmt<'a, W> {
+    pub fn new(args: &Args, output: &'a mut W) -> Self {
+        let prefix = args.prefix.as_deref().unwrap_or_default();
+        let full = prefix.trim_start_matches(' ');
+        let trimmed = full.trim_end_matches(' ');
+        Fmt {
+            output,
+            crown: args.crown_margin,
+            tagged: args.tagged_paragraph,
+            split: args.split_only,
+            uniform: args.uniform_spacing,
+            prefix: trimmed.as_bytes().to_vec(),
+            prefix_length: text_width(trimmed.as_bytes()),
+            prefix_full_length: text_width(full.as_bytes()),
+            prefix_lead_space: (prefix.len() - full.len()) as isize,
+            max_width: args.max_width as isize,
+            goal_width: args.goal_width as isize,
+            input: Box::new(io::empty()),
+            name: String::new(),
+            in_column: 0,
+            out_column: 0,
+            buffer: Vec::with_capacity(MAX_CHARS),
+            words: vec![Word::default(); MAX_WORDS + 1],
+            word_limit: 0,
+            tabs: false,
+            prefix_indent: 0,
+            first_indent: 0,
+            other_indent: 0,
+            next_char: None,
+            next_prefix_indent: 0,
+            last_line_length: 0,
+        }
+    }
+
+    pub fn fmt_file(&mut self, name: &str) -> Result<(), FmtError> {
+        self.input = match name {
+            "-" => Box::new(BufReader::new(io::stdin())),
+            _ => Box::new(BufReader::new(
+                File::open(name).map_err(|e| FmtError::Open(name.to_string(), e))?,
+            )),
+        };
+        self.name = name.to_string();
+        self.tabs = false;
+        self.other_indent = 0;
+        self.next_char = self.get_prefix()?;
+        while self.get_paragraph()? {
+            self.fmt_paragraph();
+            self.put_paragraph(self.word_limit)?;
+        }
+        Ok(())
+    }
+
+    /** Reads the next paragraph, copying the lines before it as they are.
+     *
+     * False at the end of the FILE.
+     */
+    fn get_paragraph(&mut self) -> Result<bool, FmtError> {
+        self.last_line_length = 0;
+        let mut c = self.next_char;
+        while matches!(c, None | Some(b'\n'))
+            || self.next_prefix_indent < self.prefix_lead_space
+            || self.in_column < self.next_prefix_indent + self.prefix_full_length
+        {
+            c = self.copy_rest(c)?;
+            if c.is_none() {
+                self.next_char = None;
+                return Ok(false);
+            }
+            self.put(b"\n")?;
+            c = self.get_prefix()?;
+        }
+        self.prefix_indent = self.next_prefix_indent;
+        self.first_indent = self.in_column;
+        self.buffer.clear();
+        self.word_limit = 0;
+        c = self.get_line(c)?;
+        self.set_other_indent(self.same_paragraph(c));
+        if self.split {
+            // Every line is a paragraph
+        } else if self.crown || (self.tagged && self.in_column != self.first_indent) {
+            if self.same_paragraph(c) {
+                loop {
+                    c = self.get_line(c)?;
+                    if !self.same_paragraph(c) || self.in_column != self.other_indent {
+                        break;
+                    }
+                }
+            }
+        } else if !self.tagged {
+            while self.same_paragraph(c) && self.in_column == self.other_indent {
+                c = self.get_line(c)?;
+            }
+        }
+        let last = &mut self.words[self.word_limit - 1];
+        last.period = true;
+        last.last = true;
+        self.next_char = c;
+        Ok(true)
+    }
+
+    /// The indentation of the lines after the first one
+    fn set_other_indent(&mut self, same_paragraph: bool) {
+        if self.split {
+            self.other_indent = self.first_indent;
+        } else if self.crown {
+            self.other_indent = match same_paragraph {
+                true => self.in_column,
+                false => self.first_indent,
+            };
+        } else if self.tagged {
+            if same_paragraph && self.in_column != self.first_indent {
+                self.other_indent = self.in_column;
+            } else if self.other_indent == self.first_indent {
+                // The indentation of the last paragraph of more lines, when it's different
+                self.other_indent = match self.first_indent {
+                    0 => DEFAULT_INDENT,
+                    _ => 0,
+                };
+            }
+        } else {
+            self.other_indent = self.first_indent;
+        }
+    }
+
+    /// The line after C goes on with the paragraph
+    fn same_paragraph(&self, c: Option<u8>) -> bool {
+        self.next_prefix_indent == self.prefix_indent
+            && self.in_column >= self.next_prefix_indent + self.prefix_full_length
+            && !matches!(c, None | Some(b'\n'))
+    }
+
+    /// Copies the rest of the line that's not formatted, with the indentation and the prefix read
+    fn copy_rest(&mut self, mut c: Option<u8>) -> Result<Option<u8>, FmtError> {
+        self.out_column = 0;
+        let end = matches!(c, None | Some(b'\n'));
+        if self.in_column > self.next_prefix_indent || !end {
+            self.put_space(self.next_prefix_indent)?;
+            let prefix = self.prefix.clone();
+            let mut prefix = Chars::new(&prefix);
+            while self.out_column != self.in_column {
+                let Some((bytes, c)) = prefix.next() else {
+                    break;
+                };
+                self.put(bytes)?;
+                self.out_column += c.map_or(1, |c| width(c) as isize);
+            }
+            if !end {
+                self.put_space(self.in_column - self.out_column)?;
+            }
+            if c.is_none() && self.in_column >= self.next_prefix_indent + self.prefix_length {
+                self.put(b"\n")?;
+            }
+        }
+        while let Some(byte) = c.filter(|&byte| byte != b'\n') {
+            self.put(&[byte])?;
+            c = self.getc()?;
+        }
+        Ok(c)
+    }
+
+    /// Reads the words of a line, the first character is C
+    fn get_line(&mut self, mut c: Option<u8>) -> Result<Option<u8>, FmtError> {
+        loop {
+            self.words[self.word_limit].start = self.buffer.len();
+            while let Some(byte) = c {
+                if self.buffer.len() == MAX_CHARS {
+                    self.set_other_indent(true);
+                    self.flush_paragraph()?;
+                }
+                self.buffer.push(byte);
+                c = self.getc()?;
+                if c.is_none_or(|c| c.is_ascii_whitespace() || c == b'\x0b') {
+                    break;
+                }
+            }
+            let word = &mut self.words[self.word_limit];
+            let text = &self.buffer[word.start..];
+            word.bytes = text.len();
+            word.length = text_width(text);
+            check_punctuation(word, text);
+            self.in_column += word.length;
+            let start = self.in_column;
+            c = self.get_space(c)?;
+            let word = &mut self.words[self.word_limit];
+            word.space = self.in_column - start;
+            word.last = c.is_none() || (word.period && (c == Some(b'\n') || word.space > 1));
+            if matches!(c, None | Some(b'\n')) || self.uniform {
+                word.space = if word.last { 2 } else { 1 };
+            }
+            if self.word_limit == MAX_WORDS - 2 {
+                self.set_other_indent(true);
+                self.flush_paragraph()?;
+            }
+            self.word_limit += 1;
+            if matches!(c, None | Some(b'\n')) {
+                return self.get_prefix();
+            }
+        }
+    }
+
+    /// Reads the indentation and the prefix of the next line
+    fn get_prefix(&mut self) -> Result<Option<u8>, FmtError> {
+        self.in_column = 0;
+        let c = self.getc()?;
+        let mut c = self.get_space(c)?;
+        if self.prefix.is_empty() {
+            self.next_prefix_indent = self.prefix_lead_space.min(self.in_column);
+            return Ok(c);
+        }
+        self.next_prefix_indent = self.in_column;
+        let prefix = self.prefix.clone();
+        for (bytes, p) in Chars::new(&prefix) {
+            for &byte in bytes {
+                if c != Some(byte) {
+                    return Ok(c);
+                }
+                c = self.getc()?;
+            }
+            self.in_column += p.map_or(1, |p| width(p) as isize);
+        }
+        self.get_space(c)
+    }
+
+    /// Skips the blanks from C, counting their columns
+    fn get_space(&mut self, mut c: Option<u8>) -> Result<Option<u8>, FmtError> {
+        loop {
+            match c {
+                Some(b' ') => self.in_column += 1,
+                Some(b'\t') => {
+                    self.tabs = true;
+                    self.in_column = (self.in_column / TAB_WIDTH + 1) * TAB_WIDTH;
+                }
+                _ => return Ok(c),
+            }
+            c = self.getc()?;
+        }
+    }
+
+    /** Prints the start of the paragraph that's too long, the rest is formatted with the next words.
+     *
+     * The split goes where the lines cost least to break, nearer the end.
+     */
+    fn flush_paragraph(&mut self) -> Result<(), FmtError> {
+        if self.word_limit == 0 {
+            // A single word, as it is
+            self.output
+                .write_all(&self.buffer)
+                .map_err(FmtError::Write)?;
+            self.buffer.clear();
+            return Ok(());
+        }
+        self.fmt_paragraph();
+        let mut split_point = self.word_limit;
+        let mut best_break = MAX_COST;
+        let mut w = self.words[0].next_break;
+        while w != self.word_limit {
+            let next = self.words[w].next_break;
+            let cost = self.words[w].best_cost - self.words[next].best_cost;
+            if cost < best_break {
+                split_point = w;
+                best_break = cost;
+            }
+            if best_break <= MAX_COST - LINE_CREDIT {
+                best_break += LINE_CREDIT;
+            }
+            w = next;
+        }
+        self.put_paragraph(split_point)?;
+        let shift = self.words[split_point].start;
+        self.buffer.drain(..shift);
+        for word in &mut self.words[split_point..=self.word_limit] {
+            word.start -= shift;
+        }
+        self.words.copy_within(split_point..=self.word_limit, 0);
+        self.word_limit -= split_point;
+        Ok(())
+    }
+
+    /// Finds the breaks of the lines, from the last word back to the first one
+    fn fmt_paragraph(&mut self) {
+        let limit = self.word_limit;
+        self.words[limit].best_cost = 0;
+        let saved_length = self.words[limit].length;
+        self.words[limit].length = self.max_width;
+        for start in (0..limit).rev() {
+            let mut best = MAX_COST;
+            let mut length = match start {
+                0 => self.first_indent,
+                _ => self.other_indent,
+            };
+            let mut w = start;
+            length += self.words[w].length;
+            loop {
+                w += 1;
+                let mut cost = self.line_cost(w, length) + self.words[w].best_cost;
+                if start == 0 && self.last_line_length > 0 {
+                    cost += ragged_cost(length - self.last_line_length);
+                }
+                if cost < best {
+                    best = cost;
+                    self.words[start].next_break = w;
+                    self.words[start].line_length = length;
+                }
+                if w == limit {
+                    break;
+                }
+                length += self.words[w - 1].space + self.words[w].length;
+                if length >= self.max_width {
+                    break;
+                }
+            }
+            self.words[start].best_cost = best + self.base_cost(start);
+        }
+        self.words[limit].length = saved_length;
+    }
+
+    /// The cost of the line before the word NEXT, LENGTH wide
+    fn line_cost(&self, next: usize, length: isize) -> Cost {
+        if next == self.word_limit {
+            return 0;
+        }
+        let mut cost = short_cost(self.goal_width - length);
+        if self.words[next].next_break != self.word_limit {
+            cost += ragged_cost(length - self.words[next].line_length);
+        }
+        cost
+    }
+
+    /// The cost of the line starting with the word, the sentences rather start the lines
+    fn base_cost(&self, this: usize) -> Cost {
+        let mut cost = LINE_COST;
+        if this > 0 {
+            let before = &self.words[this - 1];
+            if before.period {
+                cost += match before.last {
+                    true => -SENTENCE_BONUS,
+                    false => NOBREAK_COST,
+                };
+            } else if before.punct {
+                cost -= PUNCT_BONUS;
+            } else if this > 1 && self.words[this - 2].last {
+                cost += widow_cost(before.length);
+            }
+        }
+        let word = &self.words[this];
+        if word.paren {
+            cost -= PAREN_BONUS;
+        } else if word.last {
+            cost += orphan_cost(word.length);
+        }
+        cost
+    }
+
+    /// Prints the lines of the paragraph up to the word FINISH
+    fn put_paragraph(&mut self, finish: usize) -> Result<(), FmtError> {
+        self.put_line(0, self.first_indent)?;
+        let mut w = self.words[0].next_break;
+        while w != finish {
+            self.put_line(w, self.other_indent)?;
+            w = self.words[w].next_break;
+        }
+        Ok(())
+    }
+
+    fn put_line(&mut self, mut w: usize, indent: isize) -> Result<(), FmtError> {
+        self.out_column = 0;
+        self.put_space(self.prefix_indent)?;
+        let prefix = std::mem::take(&mut self.prefix);
+        let written = self.put(&prefix);
+        self.prefix = prefix;
+        written?;
+        self.out_column += self.prefix_length;
+        self.put_space(indent - self.out_column)?;
+        let end = self.words[w].next_break - 1;
+        while w != end {
+            self.put_word(w)?;
+            self.put_space(self.words[w].space)?;
+            w += 1;
+        }
+        self.put_word(w)?;
+        self.last_line_length = self.out_column;
+        self.put(b"\n")
+    }
+
+    fn put_word(&mut self, w: usize) -> Result<(), FmtError> {
+        let Word {
+            start,
+            bytes,
+            length,
+            ..
+        } = self.words[w];
+        self.output
+            .write_all(&self.buffer[start..start + bytes])
+            .map_err(FmtError::Write)?;
+        self.out_column += length;
+        Ok(())
+    }
+
+    /// The blanks up to SPACE columns on, with the tabs when the FILE has them
+    fn put_space(&mut self, space: isize) -> Result<(), FmtError> {
+        let space_target = self.out_column + space;
+        if self.tabs {
+            let tab_target = space_target / TAB_WIDTH * TAB_WIDTH;
+            if self.out_column + 1 < tab_target {
+                while self.out_column < tab_target {
+                    self.put(b"\t")?;
+                    self.out_column = (self.out_column / TAB_WIDTH + 1) * TAB_WIDTH;
+                }
+            }
+        }
+        while self.out_column < space_target {
+            self.put(b" ")?;
+            self.out_column += 1;
+        }
+        Ok(())
+    }
+
+    fn getc(&mut self) -> Result<Option<u8>, FmtError> {
+        let read_error = |e| FmtError::Read(self.name.clone(), e);
+        let byte = loop {
+            match self.input.fill_buf() {
+                Ok(buffer) => break buffer.first().copied(),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(read_error(e)),
+            }
+        };
+        if byte.is_some() {
+            self.input.consume(1);
+        }
+        Ok(byte)
+    }
+
+    /// Whatever was printed goes before the messages
+    pub fn flush(&mut self) -> Result<(), FmtError> {
+        self.output.flush().map_err(FmtError::Write)
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), FmtError> {
+        self.output.write_all(bytes).map_err(FmtError::Write)
+    }
+}
+
+/// The brackets and the quotes open a word, the periods end a sentence before them
+fn check_punctuation(word: &mut Word, text: &[u8]) {
+    let (Some(&first), Some(&last)) = (text.first(), text.last()) else {
+        return;
+    };
+    word.paren = b"(['`\"".contains(&first);
+    word.punct = last.is_ascii_punctuation();
+    let end = text[1..]
+        .iter()
+        .rposition(|c| !b")]'\"".contains(c))
+        .map_or(0, |i| i + 1);
+    word.period = b".?!".contains(&text[end]);
+}
+
+/// The columns of the UTF-8 text, the other bytes take one
+fn text_width(text: &[u8]) -> isize {
+    Chars::new(text)
+        .map(|(_, c)| c.map_or(1, width))
+        .sum::<usize>() as isize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fmt_text(text: &str, args: Args) -> String {
+        let args = Args {
+            max_width: 20,
+            goal_width: 18,
+            ..args
+        };
+        let mut output = Vec::new();
+        let mut fmt = Fmt::new(&args, &mut output);
+        fmt.input = Box::new(io::Cursor::new(text.as_bytes().to_vec()));
+        fmt.next_char = fmt.get_prefix().unwrap();
+        while fmt.get_paragraph().unwrap() {
+            fmt.fmt_paragraph();
+            fmt.put_paragraph(fmt.word_limit).unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn fills_the_paragraphs() {
+        assert_eq!(
+            fmt_text(
+                "one two\nthree four five six seven\n\n  eight.  Nine\n",
+                Args::default()
+            ),
+            "one two three four\nfive six seven\n\n  eight.  Nine\n"
+        );
+        assert_eq!(
+            fmt_text("日本語 日本語 日本語 日本語\n", Args::default()),
+            "日本語 日本語\n日本語 日本語\n"
+        );
+    }
+
+    #[test]
+    fn keeps_the_indentation() {
+        let crown = Args {
+            crown_margin: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            fmt_text("  one\ntwo three four five six\n", crown),
+            "  one two three\nfour five six\n"
+        );
+        let tagged = Args {
+            tagged_paragraph: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            fmt_text("- one two three four five six\n", tagged),
+            "- one two three\n   four five six\n"
+        );
+    }
+
+    #[test]
+    fn formats_only_the_prefixed_lines() {
+        let args = Args {
+            prefix: Some("# ".to_string()),
+            uniform_spacing: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            fmt_text("# one  two\n# three four five six\ncode\n", args),
+            "# one two three\n# four five six\ncode\n"
+        );
+    }
+}
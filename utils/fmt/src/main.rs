@@ -0,0 +1,36 @@
+mod args;
+mod fmt;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use fmt::fmt_error::FmtError;
+use fmt::Fmt;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout().lock());
+    let mut fmt = Fmt::new(&args, &mut output);
+    let mut status = ExitCode::SUCCESS;
+    for file in &args.files {
+        match fmt.fmt_file(file) {
+            Ok(()) => {}
+            Err(e @ FmtError::Write(_)) => {
+                eprintln!("fmt: {}", e);
+                return ExitCode::FAILURE;
+            }
+            Err(e) => {
+                // Flushing whatever was written before the error, so it goes before the message
+                let _ = fmt.flush();
+                eprintln!("fmt: {}", e);
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Err(e) = output.flush() {
+        eprintln!("fmt: {}", FmtError::Write(e));
+        return ExitCode::FAILURE;
+    }
+    status
+}
@@ -1,5 +1,5 @@
 use clap::Parser;
-use parse_num::{parse_num, NumValue};
+use parse_num::{num_parser, parse_num, InvalidNumError, NumValue};
 
 // @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
 
@@ -10,11 +10,11 @@ pub struct Args {
     pub file: Vec<String>,
 
     /// print the first NUM bytes of each file; with the leading '-', print all but the last NUM bytes of each file
-    #[arg(short = 'c', long, value_parser=parse_num)]
+    #[arg(short = 'c', long, value_parser=num_parser("bytes", parse_num), allow_hyphen_values = true)]
     pub bytes: Option<NumValue>,
 
     /// print the first NUM lines instead of the first 10; with the leading '-', print all but the last NUM lines of each file
-    #[arg(short = 'n', long, value_parser=parse_num, default_value = "10")]
+    #[arg(short = 'n', long, value_parser=num_parser("lines", parse_num), default_value = "10", allow_hyphen_values = true)]
     pub lines: NumValue,
 
     /// never print headers giving file names
@@ -28,14 +28,26 @@ pub struct Args {
     /// line delimiter is NUL, not newline
     #[arg(short = 'z', long, default_value_t = false)]
     pub zero_terminated: bool,
+
+    // computed based on other args
+    #[arg(skip)]
+    pub print_headers: bool,
 }
 
 impl Args {
     pub fn parse() -> Args {
-        let mut args = <Self as Parser>::parse();
-        if args.file.len() == 0 {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            // GNU reports bad NUM values with its own message, not the generic usage error
+            if let Some(invalid) = InvalidNumError::find(&err) {
+                eprintln!("head: {}", invalid);
+                std::process::exit(1);
+            }
+            err.exit()
+        });
+        if args.file.is_empty() {
             args.file.push("-".into());
         }
+        args.print_headers = (args.file.len() > 1 || args.verbose) && !args.quiet;
         args
     }
 }
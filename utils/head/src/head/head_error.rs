@@ -1,16 +1,24 @@
 use std::error::Error;
 use std::fmt;
+use std::io;
+use strerror::strerror;
 
 #[derive(Debug)]
 pub enum HeadError {
-    Io(std::io::Error),
-    Overflow,
+    Io(io::Error),
+    Open(String, io::Error),
+    Read(String, io::Error),
 }
 impl fmt::Display for HeadError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            HeadError::Io(err) => err.fmt(f),
-            HeadError::Overflow => write!(f, "Value too large for defined data type"),
+            HeadError::Io(err) => write!(f, "{}", strerror(err)),
+            HeadError::Open(name, err) => {
+                write!(f, "cannot open '{}' for reading: {}", name, strerror(err))
+            }
+            HeadError::Read(name, err) => {
+                write!(f, "error reading '{}': {}", name, strerror(err))
+            }
         }
     }
 }
@@ -19,13 +27,30 @@ impl Error for HeadError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
             HeadError::Io(err) => Some(err),
-            HeadError::Overflow => Some(self),
+            HeadError::Open(_, err) | HeadError::Read(_, err) => Some(err),
         }
     }
 }
 
-impl From<std::io::Error> for HeadError {
-    fn from(err: std::io::Error) -> Self {
+impl From<io::Error> for HeadError {
+    fn from(err: io::Error) -> Self {
         HeadError::Io(err)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn open_error_mimics_gnu_message() {
+        let err = HeadError::Open(
+            "x".into(),
+            io::Error::from_raw_os_error(2), // ENOENT
+        );
+        assert_eq!(
+            err.to_string(),
+            "cannot open 'x' for reading: No such file or directory"
+        );
+    }
+}
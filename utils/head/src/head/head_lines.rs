@@ -1,6 +1,5 @@
 use crate::args::Args;
 use chunk_reader::ChunkReader;
-use ibig::UBig;
 use std::io::{Read, Write};
 
 use super::head_error::HeadError;
@@ -13,14 +12,14 @@ pub fn head_lines<R: Read, W: Write>(
     output: &mut W,
 ) -> Result<(), HeadError> {
     let terminator = if args.zero_terminated { b'\0' } else { b'\n' };
-    let mut counter = UBig::from(0_usize);
-    let max = args.lines.to_ubig();
+    let mut counter: u64 = 0;
+    let max = args.lines.to_u64_saturating();
     for chunk in input.chunks(terminator, CHUNK_SIZE) {
         if counter >= max {
             break;
         }
         let chunk = chunk?;
-        output.write(&chunk)?;
+        output.write_all(&chunk)?;
 
         if chunk.ends_with(&[terminator]) {
             counter += 1;
@@ -33,6 +32,8 @@ pub fn head_lines<R: Read, W: Write>(
 mod test {
     use std::io::Cursor;
 
+    use parse_num::parse_num;
+
     use super::*;
 
     #[test]
@@ -60,6 +61,18 @@ mod test {
         assert_eq!(output.into_inner(), b"asd\nb\0c\nq\0");
     }
 
+    #[test]
+    fn takes_everything_if_n_is_huge() {
+        let input = Cursor::new(b"asd\nb\0c\nq\0e");
+        let mut output = Cursor::new(Vec::<u8>::new());
+        let args = Args {
+            lines: parse_num("1E").unwrap(),
+            ..Default::default()
+        };
+        head_lines(&args, input, &mut output).unwrap();
+        assert_eq!(output.into_inner(), b"asd\nb\0c\nq\0e");
+    }
+
     #[test]
     fn handles_longish_input() {
         let input = Cursor::new([b"a".repeat(CHUNK_SIZE + 100), b"\nasdf".to_vec()].concat());
@@ -1,23 +1,71 @@
 use super::head_error::HeadError;
 use crate::args::Args;
-use std::io::{Read, Write};
+use std::collections::VecDeque;
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
+const CHUNK_SIZE: usize = 8192;
+
+/** Printing all but the last N bytes of a stream, that can't be seeked (pipe, stdin).
+ *
+ * Only the trailing N bytes are kept in a ring buffer, everything that falls out
+ * of it is written to the output immediately.
+ */
 pub fn head_negative_bytes<R: Read, W: Write>(
     args: &Args,
     mut input: R,
     output: &mut W,
 ) -> Result<(), HeadError> {
-    let bytes_to_omit = args
-        .bytes
-        .as_ref()
-        .unwrap()
-        .to_usize()
-        .ok_or(HeadError::Overflow)?;
-    let mut buffer: Vec<u8> = Vec::new();
-    input.read_to_end(&mut buffer)?;
-    if bytes_to_omit < buffer.len() {
-        output.write(&buffer[..buffer.len() - bytes_to_omit])?;
+    let bytes_to_omit = match args.bytes.as_ref().unwrap().to_usize() {
+        Some(n) => n,
+        None => {
+            // Can't possibly buffer that much, so everything is going to be omitted anyway
+            io::copy(&mut input, &mut io::sink())?;
+            return Ok(());
+        }
+    };
+    let mut buffer = [0_u8; CHUNK_SIZE];
+    let mut tail: VecDeque<u8> = VecDeque::new();
+    loop {
+        let bytes_read = input.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        tail.extend(&buffer[..bytes_read]);
+        if tail.len() > bytes_to_omit {
+            let excess = tail.len() - bytes_to_omit;
+            let (front, back) = tail.as_slices();
+            if excess <= front.len() {
+                output.write_all(&front[..excess])?;
+            } else {
+                output.write_all(front)?;
+                output.write_all(&back[..excess - front.len()])?;
+            }
+            tail.drain(..excess);
+        }
+    }
+    Ok(())
+}
+
+/** Printing all but the last N bytes of a seekable input (regular file).
+ *
+ * The size is known beforehand, so we can just copy everything up to `size - N`
+ * without buffering anything.
+ */
+pub fn head_negative_bytes_seekable<R: Read + Seek, W: Write>(
+    args: &Args,
+    mut input: R,
+    output: &mut W,
+) -> Result<(), HeadError> {
+    let bytes_to_omit = args.bytes.as_ref().unwrap().to_u64_saturating();
+    let start = input.stream_position()?;
+    let end = input.seek(SeekFrom::End(0))?;
+    let size = end.saturating_sub(start);
+    if bytes_to_omit >= size {
+        return Ok(());
     }
+    let bytes_to_print = size - bytes_to_omit;
+    input.seek(SeekFrom::Start(start))?;
+    io::copy(&mut input.take(bytes_to_print), output)?;
     Ok(())
 }
 
@@ -25,19 +73,22 @@ pub fn head_negative_bytes<R: Read, W: Write>(
 mod test {
     use std::io::Cursor;
 
-    use parse_num::NumValue;
+    use parse_num::{parse_num, NumValue};
 
     use super::*;
 
+    fn args(bytes: NumValue) -> Args {
+        Args {
+            bytes: Some(bytes),
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn calculate_bytes_from_end() {
         let input = Cursor::new(b"1234567890");
         let mut output = Cursor::new(Vec::<u8>::new());
-        let args = Args {
-            bytes: Some(NumValue::from(-2)),
-            ..Default::default()
-        };
-        head_negative_bytes(&args, input, &mut output).unwrap();
+        head_negative_bytes(&args(NumValue::from(-2)), input, &mut output).unwrap();
         assert_eq!(output.into_inner(), b"12345678");
     }
 
@@ -45,11 +96,52 @@ mod test {
     fn writes_nothing_if_n_gt_input_length() {
         let input = Cursor::new(b"1234567890");
         let mut output = Cursor::new(Vec::<u8>::new());
-        let args = Args {
-            bytes: Some(NumValue::from(-15)),
-            ..Default::default()
-        };
-        head_negative_bytes(&args, input, &mut output).unwrap();
+        head_negative_bytes(&args(NumValue::from(-15)), input, &mut output).unwrap();
+        assert_eq!(output.into_inner(), b"");
+    }
+
+    #[test]
+    fn handles_input_longer_than_chunk() {
+        let data = b"x".repeat(CHUNK_SIZE * 3 + 17);
+        let input = Cursor::new(data.clone());
+        let mut output = Cursor::new(Vec::<u8>::new());
+        head_negative_bytes(&args(NumValue::from(-100)), input, &mut output).unwrap();
+        assert_eq!(output.into_inner(), data[..data.len() - 100].to_vec());
+    }
+
+    #[test]
+    fn writes_nothing_if_n_overflows() {
+        let input = Cursor::new(b"1234567890");
+        let mut output = Cursor::new(Vec::<u8>::new());
+        let huge = parse_num("-1Q").unwrap();
+        head_negative_bytes(&args(huge.clone()), input.clone(), &mut output).unwrap();
+        assert_eq!(output.get_ref(), b"");
+        head_negative_bytes_seekable(&args(huge), input, &mut output).unwrap();
+        assert_eq!(output.into_inner(), b"");
+    }
+
+    #[test]
+    fn seekable_calculate_bytes_from_end() {
+        let input = Cursor::new(b"1234567890");
+        let mut output = Cursor::new(Vec::<u8>::new());
+        head_negative_bytes_seekable(&args(NumValue::from(-2)), input, &mut output).unwrap();
+        assert_eq!(output.into_inner(), b"12345678");
+    }
+
+    #[test]
+    fn seekable_starts_from_the_current_position() {
+        let mut input = Cursor::new(b"1234567890");
+        input.set_position(3);
+        let mut output = Cursor::new(Vec::<u8>::new());
+        head_negative_bytes_seekable(&args(NumValue::from(-2)), input, &mut output).unwrap();
+        assert_eq!(output.into_inner(), b"45678");
+    }
+
+    #[test]
+    fn seekable_writes_nothing_if_n_gt_input_length() {
+        let input = Cursor::new(b"1234567890");
+        let mut output = Cursor::new(Vec::<u8>::new());
+        head_negative_bytes_seekable(&args(NumValue::from(-15)), input, &mut output).unwrap();
         assert_eq!(output.into_inner(), b"");
     }
 }
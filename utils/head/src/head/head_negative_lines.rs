@@ -1,91 +1,203 @@
 use crate::args::Args;
 use chunk_reader::ChunkReader;
-use std::io::{Read, Write};
+use std::collections::VecDeque;
+use std::io::{self, Read, Seek, SeekFrom, Write};
 
 use super::head_error::HeadError;
 
+const CHUNK_SIZE: usize = 8192;
+
+/** Printing all but the last N lines of a stream, that can't be seeked (pipe, stdin).
+ *
+ * Only the trailing N lines are kept in a ring buffer, everything that falls out
+ * of it is written to the output immediately.
+ */
 pub fn head_negative_lines<R: Read, W: Write>(
     args: &Args,
-    input: R,
+    mut input: R,
     output: &mut W,
 ) -> Result<(), HeadError> {
     let terminator = if args.zero_terminated { b'\0' } else { b'\n' };
-    let lines_to_omit = args.lines.to_usize().ok_or(HeadError::Overflow)?;
-    let lines: Vec<Vec<u8>> = input
-        .chunks(terminator, 0)
-        .collect::<Result<Vec<Vec<u8>>, _>>()?;
-
-    if lines.len() > lines_to_omit {
-        for line in &lines[..lines.len() - lines_to_omit] {
-            output.write(line)?;
+    let lines_to_omit = match args.lines.to_usize() {
+        Some(n) => n,
+        None => {
+            // Can't possibly buffer that much, so everything is going to be omitted anyway
+            io::copy(&mut input, &mut io::sink())?;
+            return Ok(());
+        }
+    };
+    let mut tail: VecDeque<Vec<u8>> = VecDeque::new();
+    for line in input.chunks(terminator, 0) {
+        tail.push_back(line?);
+        if tail.len() > lines_to_omit {
+            output.write_all(&tail.pop_front().unwrap())?;
         }
     }
     Ok(())
 }
 
+/** Printing all but the last N lines of a seekable input (regular file).
+ *
+ * The input is scanned backwards from its end until N line terminators are found,
+ * then everything before that point is copied without buffering. Trailing line
+ * without a terminator is considered a line too.
+ */
+pub fn head_negative_lines_seekable<R: Read + Seek, W: Write>(
+    args: &Args,
+    mut input: R,
+    output: &mut W,
+) -> Result<(), HeadError> {
+    let terminator = if args.zero_terminated { b'\0' } else { b'\n' };
+    let lines_to_omit = args.lines.to_u64_saturating();
+    let start = input.stream_position()?;
+    let end = input.seek(SeekFrom::End(0))?;
+    if end <= start {
+        return Ok(());
+    }
+    let cut = if lines_to_omit == 0 {
+        Some(end)
+    } else {
+        find_nth_line_from_end(&mut input, start, end, terminator, lines_to_omit)?
+    };
+    if let Some(cut) = cut {
+        input.seek(SeekFrom::Start(start))?;
+        io::copy(&mut input.take(cut - start), output)?;
+    }
+    Ok(())
+}
+
+/** Returns the position where the n-th line from the end starts, or None if there's
+ * not enough lines in the [start, end) range.
+ */
+fn find_nth_line_from_end<R: Read + Seek>(
+    input: &mut R,
+    start: u64,
+    end: u64,
+    terminator: u8,
+    n: u64,
+) -> io::Result<Option<u64>> {
+    let mut buffer = [0_u8; CHUNK_SIZE];
+
+    // The terminator of the last line doesn't start a new one, so it's skipped
+    input.seek(SeekFrom::Start(end - 1))?;
+    input.read_exact(&mut buffer[..1])?;
+    let mut pos = if buffer[0] == terminator {
+        end - 1
+    } else {
+        end
+    };
+
+    let mut remaining = n;
+    while pos > start {
+        let len = (pos - start).min(CHUNK_SIZE as u64) as usize;
+        pos -= len as u64;
+        input.seek(SeekFrom::Start(pos))?;
+        input.read_exact(&mut buffer[..len])?;
+        for (i, b) in buffer[..len].iter().enumerate().rev() {
+            if *b == terminator {
+                remaining -= 1;
+                if remaining == 0 {
+                    return Ok(Some(pos + i as u64 + 1));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
 #[cfg(test)]
 mod test {
     use std::io::Cursor;
 
-    use parse_num::NumValue;
+    use parse_num::{parse_num, NumValue};
 
     use super::*;
 
-    const DATA: &'static [u8] = b"1\n\0\n3\n\0\n";
+    const DATA: &[u8] = b"1\n\0\n3\n\0\n";
 
     fn process(data: &[u8], args: &Args) -> Vec<u8> {
         let input = Cursor::new(data);
         let mut output = Cursor::new(Vec::<u8>::new());
-        head_negative_lines(&args, input, &mut output).unwrap();
+        head_negative_lines(args, input, &mut output).unwrap();
+        output.into_inner()
+    }
+
+    fn process_seekable(data: &[u8], args: &Args) -> Vec<u8> {
+        let input = Cursor::new(data);
+        let mut output = Cursor::new(Vec::<u8>::new());
+        head_negative_lines_seekable(args, input, &mut output).unwrap();
         output.into_inner()
     }
 
     #[test]
     fn calculates_lines_from_end() {
-        let result = process(
-            DATA,
-            &Args {
-                lines: NumValue::from(-2),
-                ..Default::default()
-            },
-        );
-        assert_eq!(result, b"1\n\0\n");
+        let args = Args {
+            lines: NumValue::from(-2),
+            ..Default::default()
+        };
+        assert_eq!(process(DATA, &args), b"1\n\0\n");
+        assert_eq!(process_seekable(DATA, &args), b"1\n\0\n");
     }
 
     #[test]
     fn calculates_lines_from_end_without_terminator() {
-        let result = process(
-            &DATA[..DATA.len() - 1],
-            &Args {
-                lines: NumValue::from(-2),
-                ..Default::default()
-            },
-        );
-        assert_eq!(result, b"1\n\0\n");
+        let args = Args {
+            lines: NumValue::from(-2),
+            ..Default::default()
+        };
+        assert_eq!(process(&DATA[..DATA.len() - 1], &args), b"1\n\0\n");
+        assert_eq!(process_seekable(&DATA[..DATA.len() - 1], &args), b"1\n\0\n");
     }
 
     #[test]
     fn uses_zero_terminated_lines_if_asked() {
-        let result = process(
-            &DATA[..DATA.len() - 1],
-            &Args {
-                lines: NumValue::from(-1),
-                zero_terminated: true,
-                ..Default::default()
-            },
-        );
-        assert_eq!(result, b"1\n\0");
+        let args = Args {
+            lines: NumValue::from(-1),
+            zero_terminated: true,
+            ..Default::default()
+        };
+        assert_eq!(process(&DATA[..DATA.len() - 1], &args), b"1\n\0");
+        assert_eq!(process_seekable(&DATA[..DATA.len() - 1], &args), b"1\n\0");
     }
 
     #[test]
     fn writes_nothing_if_n_gt_lines_in_file() {
-        let result = process(
-            &DATA[..DATA.len() - 1],
-            &Args {
-                lines: NumValue::from(-1000),
-                ..Default::default()
-            },
-        );
-        assert_eq!(result, b"");
+        let args = Args {
+            lines: NumValue::from(-1000),
+            ..Default::default()
+        };
+        assert_eq!(process(&DATA[..DATA.len() - 1], &args), b"");
+        assert_eq!(process_seekable(&DATA[..DATA.len() - 1], &args), b"");
+    }
+
+    #[test]
+    fn writes_nothing_if_n_overflows() {
+        let args = Args {
+            lines: parse_num("-99999999999999999999999").unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(process(DATA, &args), b"");
+        assert_eq!(process_seekable(DATA, &args), b"");
+    }
+
+    #[test]
+    fn seekable_writes_everything_if_n_is_zero() {
+        let args = Args {
+            lines: NumValue::from(0),
+            ..Default::default()
+        };
+        assert_eq!(process_seekable(DATA, &args), DATA);
+    }
+
+    #[test]
+    fn seekable_scans_across_chunk_boundaries() {
+        let line = [b"a".repeat(CHUNK_SIZE - 3), b"\n".to_vec()].concat();
+        let data = line.repeat(5);
+        let args = Args {
+            lines: NumValue::from(-3),
+            ..Default::default()
+        };
+        assert_eq!(process_seekable(&data, &args), line.repeat(2));
+        assert_eq!(process(&data, &args), line.repeat(2));
     }
 }
@@ -8,41 +8,283 @@ mod head_negative_bytes;
 mod head_negative_lines;
 use head_bytes::head_bytes;
 use head_lines::head_lines;
-use head_negative_bytes::head_negative_bytes;
-use head_negative_lines::head_negative_lines;
+use head_negative_bytes::{head_negative_bytes, head_negative_bytes_seekable};
+use head_negative_lines::{head_negative_lines, head_negative_lines_seekable};
 
 use head_error::HeadError;
+use std::cell::Cell;
 use std::fs::File;
 use std::io;
 use std::io::prelude::*;
-use std::io::BufReader;
-use std::io::BufWriter;
-
-pub fn head(args: &Args, name: &str) -> Result<(), HeadError> {
-    let input: Box<dyn Read> = match name {
-        "-" => Box::new(io::stdin()),
-        _ => {
-            let file = File::open(name).map_err(|e| HeadError::Io(e))?;
-            Box::new(file)
+use std::io::{BufReader, SeekFrom};
+
+/** Prints the head of a single file.
+ *
+ * `first_header` tracks, whether a header was already printed for some previous
+ * file, so the sections can be separated by a blank line.
+ */
+pub fn head<W: Write>(
+    args: &Args,
+    name: &str,
+    output: &mut W,
+    first_header: &mut bool,
+) -> Result<(), HeadError> {
+    let file = match name {
+        "-" => None,
+        _ => Some(File::open(name).map_err(|e| HeadError::Open(name.into(), e))?),
+    };
+    if args.print_headers {
+        print_header(output, name, *first_header)?;
+        *first_header = false;
+    }
+    let failed = Cell::new(false);
+    let result = match file {
+        None => head_stream(
+            args,
+            Input::new(BufReader::new(io::stdin()), &failed),
+            output,
+        ),
+        Some(file) => {
+            // Only regular files have a meaningful size, everything else is treated as a pipe
+            let metadata = file.metadata()?;
+            let size = if metadata.is_file() {
+                metadata.len()
+            } else {
+                0
+            };
+            head_file(
+                args,
+                Input::new(BufReader::new(file), &failed),
+                size,
+                output,
+            )
         }
     };
-    let reader = BufReader::new(input);
-    let mut output = BufWriter::new(std::io::stdout());
+    match result {
+        Err(HeadError::Io(err)) if failed.get() => {
+            Err(HeadError::Read(display_name(name).into(), err))
+        }
+        result => result,
+    }
+}
+
+/// The input, that notes its failures, so they are told apart from the ones of the output
+struct Input<'a, R> {
+    inner: R,
+    failed: &'a Cell<bool>,
+}
+
+impl<'a, R> Input<'a, R> {
+    fn new(inner: R, failed: &'a Cell<bool>) -> Self {
+        Input { inner, failed }
+    }
+
+    fn note<T>(&self, result: io::Result<T>) -> io::Result<T> {
+        if let Err(e) = &result {
+            if e.kind() != io::ErrorKind::Interrupted {
+                self.failed.set(true);
+            }
+        }
+        result
+    }
+}
+
+impl<R: Read> Read for Input<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let result = self.inner.read(buf);
+        self.note(result)
+    }
+}
+
+impl<R: Seek> Seek for Input<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let result = self.inner.seek(pos);
+        self.note(result)
+    }
+}
+
+/** Seeks only in the files, that report a size and can seek, like GNU does.
+ *
+ * The files in /proc claim to be empty, so they are read as a stream.
+ */
+fn head_file<R: Read + Seek, W: Write>(
+    args: &Args,
+    mut input: R,
+    size: u64,
+    output: &mut W,
+) -> Result<(), HeadError> {
+    if size > 0 && input.stream_position().is_ok() {
+        head_seekable(args, input, output)
+    } else {
+        head_stream(args, input, output)
+    }
+}
+
+fn print_header<W: Write>(output: &mut W, name: &str, first: bool) -> io::Result<()> {
+    let separator = if first { "" } else { "\n" };
+    writeln!(output, "{}==> {} <==", separator, display_name(name))
+}
+
+fn display_name(name: &str) -> &str {
+    match name {
+        "-" => "standard input",
+        _ => name,
+    }
+}
+
+fn head_stream<R: Read, W: Write>(args: &Args, reader: R, output: &mut W) -> Result<(), HeadError> {
+    match args {
+        Args {
+            bytes: Some(NumValue {
+                prefix: Some('-'), ..
+            }),
+            ..
+        } => head_negative_bytes(args, reader, output),
+        Args { bytes: Some(_), .. } => head_bytes(args, reader, output),
+        Args {
+            lines: NumValue {
+                prefix: Some('-'), ..
+            },
+            ..
+        } => head_negative_lines(args, reader, output),
+        _ => head_lines(args, reader, output),
+    }
+}
 
+fn head_seekable<R: Read + Seek, W: Write>(
+    args: &Args,
+    reader: R,
+    output: &mut W,
+) -> Result<(), HeadError> {
     match args {
         Args {
             bytes: Some(NumValue {
                 prefix: Some('-'), ..
             }),
             ..
-        } => head_negative_bytes(args, reader, &mut output),
-        Args { bytes: Some(_), .. } => head_bytes(args, reader, &mut output),
+        } => head_negative_bytes_seekable(args, reader, output),
         Args {
+            bytes: None,
             lines: NumValue {
                 prefix: Some('-'), ..
             },
             ..
-        } => head_negative_lines(args, reader, &mut output),
-        _ => head_lines(args, reader, &mut output),
+        } => head_negative_lines_seekable(args, reader, output),
+        _ => head_stream(args, reader, output),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// A file of /proc: it reports no size and it can't seek to its end
+    struct ProcFile(Cursor<&'static [u8]>);
+
+    impl Read for ProcFile {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Seek for ProcFile {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            match pos {
+                SeekFrom::End(_) => Err(io::Error::from_raw_os_error(22)), // EINVAL
+                _ => self.0.seek(pos),
+            }
+        }
+    }
+
+    fn process(args: &Args, size: u64) -> Vec<u8> {
+        let input = ProcFile(Cursor::new(b"a\nb\nc\n"));
+        let mut output = Vec::new();
+        head_file(args, input, size, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn streams_the_files_without_a_size() {
+        let args = Args {
+            bytes: Some(NumValue::from(-1)),
+            ..Default::default()
+        };
+        assert_eq!(process(&args, 0), b"a\nb\nc");
+        let args = Args {
+            lines: NumValue::from(-1),
+            ..Default::default()
+        };
+        assert_eq!(process(&args, 0), b"a\nb\n");
+    }
+
+    #[test]
+    fn seeks_the_files_with_a_size() {
+        let args = Args {
+            bytes: Some(NumValue::from(-1)),
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        head_file(&args, Cursor::new(b"a\nb\nc\n"), 6, &mut output).unwrap();
+        assert_eq!(output, b"a\nb\nc");
+        let result = head_file(&args, ProcFile(Cursor::new(b"a\n")), 2, &mut output);
+        assert_eq!(result.unwrap_err().to_string(), "Invalid argument");
+    }
+
+    const MANIFEST: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+
+    /// Runs head over the files the way main() does, the errors are collected
+    fn run(args: &Args, names: &[&str]) -> (String, Vec<String>) {
+        let mut output = Vec::new();
+        let mut errors = Vec::new();
+        let mut first_header = true;
+        for name in names {
+            if let Err(e) = head(args, name, &mut output, &mut first_header) {
+                errors.push(e.to_string());
+            }
+        }
+        (String::from_utf8(output).unwrap(), errors)
+    }
+
+    #[test]
+    fn separates_the_files_with_headers() {
+        let mut args = Args {
+            lines: NumValue::from(1),
+            ..Default::default()
+        };
+        assert_eq!(run(&args, &[MANIFEST]), ("[package]\n".into(), vec![]));
+        args.print_headers = true;
+        let expected = format!(
+            "==> {0} <==\n[package]\n\n==> {0} <==\n[package]\n",
+            MANIFEST
+        );
+        assert_eq!(run(&args, &[MANIFEST, MANIFEST]), (expected, vec![]));
+    }
+
+    #[test]
+    fn continues_past_unreadable_files() {
+        let args = Args {
+            lines: NumValue::from(1),
+            print_headers: true,
+            ..Default::default()
+        };
+        let dir = env!("CARGO_MANIFEST_DIR");
+        let missing = concat!(env!("CARGO_MANIFEST_DIR"), "/missing");
+        let (output, errors) = run(&args, &[dir, missing, MANIFEST]);
+        assert_eq!(
+            output,
+            format!("==> {} <==\n\n==> {} <==\n[package]\n", dir, MANIFEST)
+        );
+        assert_eq!(
+            errors,
+            [
+                format!("error reading '{}': Is a directory", dir),
+                format!(
+                    "cannot open '{}' for reading: No such file or directory",
+                    missing
+                ),
+            ]
+        );
     }
 }
@@ -2,25 +2,26 @@ mod args;
 mod head;
 use args::Args;
 use head::head;
-use head::head_error::HeadError;
+use std::io::{BufWriter, Write};
+use std::process::ExitCode;
+use strerror::strerror;
 
-fn main() -> Result<(), HeadError> {
+fn main() -> ExitCode {
     let args = Args::parse();
-    println!("{:?}", args);
-    let print_headers = (args.file.len() > 1 || args.verbose) && !args.quiet;
+    let mut output = BufWriter::new(std::io::stdout());
+    let mut status = ExitCode::SUCCESS;
+    let mut first_header = true;
     for file in &args.file {
-        if print_headers {
-            print_header(&file);
+        if let Err(e) = head(&args, file, &mut output, &mut first_header) {
+            // Flushing whatever was written before the error, so it goes before the message
+            let _ = output.flush();
+            eprintln!("head: {}", e);
+            status = ExitCode::FAILURE;
         }
-        head(&args, &file)?;
     }
-    Ok(())
-}
-
-fn print_header(name: &str) {
-    let name = match name {
-        "-" => "standard input",
-        _ => name,
-    };
-    println!("==> {} <==", name);
+    if let Err(e) = output.flush() {
+        eprintln!("head: error writing 'standard output': {}", strerror(&e));
+        status = ExitCode::FAILURE;
+    }
+    status
 }
@@ -0,0 +1,308 @@
+use std::error::Error;
+
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+use crate::join::join_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Whether the FILEs are checked to be sorted
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CheckOrder {
+    /// Only after a line, which can't be paired, and the disorder isn't fatal
+    #[default]
+    Default,
+    Enabled,
+    Disabled,
+}
+
+/// A field of -o, the FILE 0 is the join field
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FieldSpec {
+    pub file: usize,
+    /// From 0
+    pub field: usize,
+}
+
+/// One of the -o FORMATs
+#[derive(Clone, Debug, PartialEq)]
+pub enum Format {
+    Auto,
+    Fields(Vec<FieldSpec>),
+}
+
+/// For each pair of input lines with identical join fields, write a line to
+/// standard output. The default join field is the first, delimited by blanks
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// When FILE1 or FILE2 (not both) is -, read standard input
+    #[arg(value_name = "FILE")]
+    pub files: Vec<String>,
+
+    /// also print unpairable lines from file FILENUM, where FILENUM is 1 or 2,
+    /// corresponding to FILE1 or FILE2
+    #[arg(short = 'a', value_name = "FILENUM", value_parser = parse_file_number)]
+    pub unpaired: Vec<usize>,
+
+    /// replace missing input fields with EMPTY
+    #[arg(short = 'e', value_name = "EMPTY")]
+    pub empties: Vec<String>,
+
+    /// ignore differences in case when comparing fields
+    #[arg(short = 'i', long, default_value_t = false)]
+    pub ignore_case: bool,
+
+    /// equivalent to '-1 FIELD -2 FIELD'
+    #[arg(short = 'j', value_name = "FIELD", value_parser = parse_field)]
+    pub join_field: Vec<usize>,
+
+    /// obey FORMAT while constructing output line
+    #[arg(short = 'o', value_name = "FORMAT", value_parser = parse_format)]
+    pub formats: Vec<Format>,
+
+    /// use CHAR as input and output field separator
+    #[arg(short = 't', value_name = "CHAR", value_parser = parse_separator)]
+    pub separators: Vec<u8>,
+
+    /// like -a FILENUM, but suppress joined output lines
+    #[arg(short = 'v', value_name = "FILENUM", value_parser = parse_file_number)]
+    pub only_unpaired: Vec<usize>,
+
+    /// join on this FIELD of file 1
+    #[arg(short = '1', value_name = "FIELD", value_parser = parse_field)]
+    pub field_1: Vec<usize>,
+
+    /// join on this FIELD of file 2
+    #[arg(short = '2', value_name = "FIELD", value_parser = parse_field)]
+    pub field_2: Vec<usize>,
+
+    /// check that the input is correctly sorted, even if all input lines are pairable
+    #[arg(
+        long = "check-order",
+        overrides_with = "nocheck_order",
+        default_value_t = false
+    )]
+    pub check_order_arg: bool,
+
+    /// do not check that the input is correctly sorted
+    #[arg(long, overrides_with = "check_order_arg", default_value_t = false)]
+    pub nocheck_order: bool,
+
+    /// treat the first line in each file as field headers,
+    /// print them without trying to pair them
+    #[arg(long, default_value_t = false)]
+    pub header: bool,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+
+    /// The join fields of both FILEs, from 0
+    #[arg(skip)]
+    pub fields: [usize; 2],
+
+    #[arg(skip)]
+    pub print_unpaired: [bool; 2],
+
+    /// Unless -v
+    #[arg(skip)]
+    pub print_paired: bool,
+
+    #[arg(skip)]
+    pub empty: Option<Vec<u8>>,
+
+    /// Blanks without -t, a newline for the whole line
+    #[arg(skip)]
+    pub separator: Option<u8>,
+
+    /// -o auto, the fields as many as in the first lines
+    #[arg(skip)]
+    pub auto_format: bool,
+
+    /// All the fields of -o
+    #[arg(skip)]
+    pub output_format: Vec<FieldSpec>,
+
+    #[arg(skip)]
+    pub check_order: CheckOrder,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let matches = <Self as CommandFactory>::command()
+            .try_get_matches()
+            .unwrap_or_else(|err| {
+                if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                    fatal(&invalid.0);
+                }
+                err.exit()
+            });
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        args.fields = join_fields(&matches);
+        if args
+            .separators
+            .iter()
+            .any(|&other| other != args.separators[0])
+        {
+            fatal("incompatible tabs");
+        }
+        args.separator = args.separators.first().copied();
+        if args.empties.iter().any(|other| *other != args.empties[0]) {
+            fatal("conflicting empty-field replacement strings");
+        }
+        args.empty = args.empties.first().map(|empty| empty.as_bytes().to_vec());
+        for format in &args.formats {
+            match format {
+                Format::Auto => args.auto_format = true,
+                Format::Fields(fields) => args.output_format.extend(fields),
+            }
+        }
+        for &file in args.unpaired.iter().chain(&args.only_unpaired) {
+            args.print_unpaired[file - 1] = true;
+        }
+        args.print_paired = args.only_unpaired.is_empty();
+        args.check_order = match (args.check_order_arg, args.nocheck_order) {
+            (true, _) => CheckOrder::Enabled,
+            (_, true) => CheckOrder::Disabled,
+            _ => CheckOrder::Default,
+        };
+        match args.files.len() {
+            0 => usage_error("missing operand"),
+            1 => usage_error(&format!("missing operand after {}", quote(&args.files[0]))),
+            2 => {}
+            _ => usage_error(&format!("extra operand {}", quote(&args.files[2]))),
+        }
+        if args.files.iter().all(|file| file == "-") {
+            fatal("both files cannot be standard input");
+        }
+        args
+    }
+
+    pub fn terminator(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+/** The join fields of -j, -1 and -2 in the order of the options.
+ *
+ * The same field may be given again, but not a different one.
+ */
+fn join_fields(matches: &ArgMatches) -> [usize; 2] {
+    let mut given: Vec<(usize, &str, usize)> = ["join_field", "field_1", "field_2"]
+        .into_iter()
+        .filter_map(|id| {
+            let fields = matches.indices_of(id)?.zip(matches.get_many::<usize>(id)?);
+            Some(fields.map(move |(index, &field)| (index, id, field)))
+        })
+        .flatten()
+        .collect();
+    given.sort();
+    let mut fields = [None; 2];
+    for (_, id, field) in given {
+        let files = match id {
+            "field_1" => 0..1,
+            "field_2" => 1..2,
+            _ => 0..2,
+        };
+        for file in files {
+            match fields[file] {
+                Some(other) if other != field => fatal(&format!(
+                    "incompatible join fields {}, {}",
+                    other + 1,
+                    field + 1
+                )),
+                _ => fields[file] = Some(field),
+            }
+        }
+    }
+    fields.map(|field| field.unwrap_or(0))
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("join: {}", message);
+    eprintln!("Try 'join --help' for more information.");
+    std::process::exit(1);
+}
+
+fn fatal(message: &str) -> ! {
+    eprintln!("join: {}", message);
+    std::process::exit(1);
+}
+
+/// A field number from 1, too big ones are as good as the biggest one
+fn parse_field(field: &str) -> Result<usize, ArgError> {
+    let invalid = || ArgError(format!("invalid field number: {}", quote(field)));
+    let trimmed = field.trim_start();
+    let digits = trimmed.strip_prefix('+').unwrap_or(trimmed);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    match digits.parse::<usize>() {
+        Ok(0) => Err(invalid()),
+        Ok(field) => Ok(field - 1),
+        Err(_) => Ok(usize::MAX - 1),
+    }
+}
+
+fn parse_file_number(file: &str) -> Result<usize, ArgError> {
+    match file.trim_start().parse::<i64>() {
+        Ok(file @ (1 | 2)) => Ok(file as usize),
+        _ => Err(ArgError(format!("invalid field number: {}", quote(file)))),
+    }
+}
+
+/// "auto", or the comma or blank separated fields, like 1.2 or 0 for the join field
+fn parse_format(format: &str) -> Result<Format, ArgError> {
+    if format == "auto" {
+        return Ok(Format::Auto);
+    }
+    let specs = format
+        .split([',', ' ', '\t'])
+        .map(|spec| match spec.as_bytes() {
+            [b'0'] => Ok(FieldSpec { file: 0, field: 0 }),
+            [b'0', ..] => Err(ArgError(format!(
+                "invalid field specifier: {}",
+                quote(spec)
+            ))),
+            [file @ (b'1' | b'2'), b'.', ..] => Ok(FieldSpec {
+                file: (file - b'0') as usize,
+                field: parse_field(&spec[2..])?,
+            }),
+            [b'1' | b'2', ..] => Err(ArgError(format!(
+                "invalid field specifier: {}",
+                quote(spec)
+            ))),
+            _ => Err(ArgError(format!(
+                "invalid file number in field spec: {}",
+                quote(spec)
+            ))),
+        });
+    Ok(Format::Fields(specs.collect::<Result<_, _>>()?))
+}
+
+/// A single byte, the empty one stands for the whole line
+fn parse_separator(separator: &str) -> Result<u8, ArgError> {
+    match separator.as_bytes() {
+        [] => Ok(b'\n'),
+        [c] => Ok(*c),
+        b"\\0" => Ok(b'\0'),
+        _ => Err(ArgError(format!(
+            "multi-character tab {}",
+            quote(separator)
+        ))),
+    }
+}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum JoinError {
+    /// Opening or reading a FILE, GNU reports both the same way
+    Read(String, io::Error),
+    Write(io::Error),
+    /// The line, which isn't sorted, with --check-order
+    Unsorted(String),
+    /// Some of the FILEs weren't sorted, after the warnings
+    NotSorted,
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JoinError::Read(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            JoinError::Write(err) => write!(f, "write error: {}", strerror(err)),
+            JoinError::Unsorted(message) => write!(f, "{}", message),
+            JoinError::NotSorted => write!(f, "input is not in sorted order"),
+        }
+    }
+}
+
+impl Error for JoinError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            JoinError::Read(_, err) | JoinError::Write(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Wrong arguments, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
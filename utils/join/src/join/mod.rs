@@ -0,0 +1,431 @@
+pub mod join_error;
+
+use std::cmp::Ordering;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::ops::Range;
+
+use chunk_reader::{ChunkReader, Chunked};
+
+use crate::args::{Args, CheckOrder};
+use join_error::JoinError;
+
+/// A line without the terminator, and where its fields are
+#[derive(Debug, Default, Clone)]
+pub struct Line {
+    bytes: Vec<u8>,
+    fields: Vec<Range<usize>>,
+}
+
+impl Line {
+    /** Splits the line at the SEPARATOR, or at the runs of blanks without it.
+     *
+     * The blanks before the first field don't count, but the ones after the
+     * last one make an empty field, like GNU join does. A newline SEPARATOR
+     * makes the whole line a field.
+     */
+    fn new(bytes: Vec<u8>, separator: Option<u8>) -> Line {
+        let mut fields = Vec::new();
+        let end = bytes.len();
+        match separator {
+            _ if bytes.is_empty() => {}
+            Some(b'\n') => fields.push(0..end),
+            Some(separator) => {
+                let mut start = 0;
+                for (i, _) in bytes.iter().enumerate().filter(|(_, &c)| c == separator) {
+                    fields.push(start..i);
+                    start = i + 1;
+                }
+                fields.push(start..end);
+            }
+            None => {
+                let blank = |c: &u8| matches!(c, b' ' | b'\t' | b'\n');
+                let mut start = bytes.iter().take_while(|c| blank(c)).count();
+                while start < end {
+                    let length = bytes[start..].iter().take_while(|c| !blank(c)).count();
+                    fields.push(start..start + length);
+                    let blanks = bytes[start + length..].iter().take_while(|c| blank(c));
+                    start += length + blanks.count();
+                    if start == end && blank(&bytes[end - 1]) {
+                        fields.push(end..end);
+                    }
+                }
+            }
+        }
+        Line { bytes, fields }
+    }
+
+    fn field(&self, n: usize) -> Option<&[u8]> {
+        self.fields.get(n).map(|field| &self.bytes[field.clone()])
+    }
+}
+
+/// The lines of a FILE, counted for the messages
+pub struct Input {
+    name: String,
+    chunks: Chunked<BufReader<Box<dyn Read>>>,
+    terminator: u8,
+    number: u64,
+    /// The line before, to check the order
+    previous: Option<Line>,
+}
+
+impl Input {
+    pub fn open(name: &str, terminator: u8) -> Result<Input, JoinError> {
+        let input: Box<dyn Read> = match name {
+            "-" => Box::new(io::stdin()),
+            _ => Box::new(File::open(name).map_err(|e| JoinError::Read(name.to_string(), e))?),
+        };
+        Ok(Input::new(name, input, terminator))
+    }
+
+    fn new(name: &str, input: Box<dyn Read>, terminator: u8) -> Input {
+        Input {
+            name: name.to_string(),
+            chunks: BufReader::new(input).chunks(terminator, 0),
+            terminator,
+            number: 0,
+            previous: None,
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Vec<u8>>, JoinError> {
+        match self.chunks.next() {
+            Some(Ok(mut line)) => {
+                if line.last() == Some(&self.terminator) {
+                    line.pop();
+                }
+                self.number += 1;
+                Ok(Some(line))
+            }
+            Some(Err(e)) => Err(JoinError::Read(self.name.clone(), e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/** Joins the lines of the FILEs with the same join fields, the way GNU join does.
+ *
+ * The FILEs are read along, the lines of the same join field are kept, until
+ * a different one comes, and all the pairs of them are written. The order is
+ * checked after the first line, which can't be paired, or from the start with
+ * --check-order.
+ */
+pub struct Join<'a, W: Write> {
+    args: &'a Args,
+    output: &'a mut W,
+    inputs: [Input; 2],
+    /// The fields of the first lines, for -o auto
+    auto_counts: [usize; 2],
+    seen_unpairable: bool,
+    disorder_warned: [bool; 2],
+}
+
+impl<'a, W: Write> Join<'a, W> {
+    pub fn new(args: &'a Args, output: &'a mut W, inputs: [Input; 2]) -> Self {
+        Join {
+            args,
+            output,
+            inputs,
+            auto_counts: [0; 2],
+            seen_unpairable: false,
+            disorder_warned: [false; 2],
+        }
+    }
+
+    pub fn join(&mut self) -> Result<(), JoinError> {
+        let mut lines = [self.next_line(0)?, self.next_line(1)?].map(Vec::from_iter);
+        for (count, lines) in self.auto_counts.iter_mut().zip(&lines) {
+            *count = lines.first().map_or(0, |line| line.fields.len());
+        }
+        if self.args.header && lines.iter().any(|lines| !lines.is_empty()) {
+            self.write_joined(lines[0].first(), lines[1].first())?;
+            for (which, lines) in lines.iter_mut().enumerate() {
+                self.inputs[which].previous = None;
+                if !lines.is_empty() {
+                    *lines = Vec::from_iter(self.next_line(which)?);
+                }
+            }
+        }
+        while !lines[0].is_empty() && !lines[1].is_empty() {
+            let which = match self.compare(&lines[0][0], &lines[1][0]) {
+                Ordering::Less => 0,
+                Ordering::Greater => 1,
+                Ordering::Equal => {
+                    self.join_same(&mut lines)?;
+                    continue;
+                }
+            };
+            if self.args.print_unpaired[which] {
+                self.write_unpaired(which, &lines[which][0])?;
+            }
+            lines[which] = Vec::from_iter(self.next_line(which)?);
+            self.seen_unpairable = true;
+        }
+        // The rest is read to check the order, unless it's known already
+        let check_tail = self.args.check_order != CheckOrder::Disabled
+            && !self.disorder_warned.iter().all(|&warned| warned);
+        for (which, lines) in lines.iter().enumerate() {
+            let print = self.args.print_unpaired[which];
+            let Some(line) = lines.first().filter(|_| print || check_tail) else {
+                continue;
+            };
+            if print {
+                self.write_unpaired(which, line)?;
+            }
+            while let Some(line) = self.next_line(which)? {
+                if print {
+                    self.write_unpaired(which, &line)?;
+                } else if self.disorder_warned[which] {
+                    break;
+                }
+            }
+        }
+        match self.disorder_warned.contains(&true) {
+            true => Err(JoinError::NotSorted),
+            false => Ok(()),
+        }
+    }
+
+    /** Reads the lines of both FILEs, which are the same as the first ones.
+     *
+     * All the pairs of them are written, and the next different lines are kept.
+     */
+    fn join_same(&mut self, lines: &mut [Vec<Line>; 2]) -> Result<(), JoinError> {
+        let mut ended = [false; 2];
+        for which in 0..2 {
+            loop {
+                let Some(line) = self.next_line(which)? else {
+                    ended[which] = true;
+                    break;
+                };
+                let order = match which {
+                    0 => self.compare(&line, &lines[1][0]),
+                    _ => self.compare(&lines[0][0], &line),
+                };
+                lines[which].push(line);
+                if order != Ordering::Equal {
+                    break;
+                }
+            }
+        }
+        if self.args.print_paired {
+            let paired = |which: usize| lines[which].len() - !ended[which] as usize;
+            for line1 in &lines[0][..paired(0)] {
+                for line2 in &lines[1][..paired(1)] {
+                    self.write_joined(Some(line1), Some(line2))?;
+                }
+            }
+        }
+        for (which, ended) in ended.into_iter().enumerate() {
+            match ended {
+                true => lines[which].clear(),
+                false => {
+                    lines[which].drain(..lines[which].len() - 1);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The next line of the FILE, its order is checked against the one before
+    fn next_line(&mut self, which: usize) -> Result<Option<Line>, JoinError> {
+        let Some(bytes) = self.inputs[which].next()? else {
+            return Ok(None);
+        };
+        let line = Line::new(bytes, self.args.separator);
+        if let Some(previous) = self.inputs[which].previous.take() {
+            self.check_order(&previous, &line, which)?;
+        }
+        self.inputs[which].previous = Some(line.clone());
+        Ok(Some(line))
+    }
+
+    /// Only the first disorder of a FILE is reported, it's fatal with --check-order
+    fn check_order(&mut self, previous: &Line, line: &Line, which: usize) -> Result<(), JoinError> {
+        let check = match self.args.check_order {
+            CheckOrder::Enabled => true,
+            CheckOrder::Default => self.seen_unpairable,
+            CheckOrder::Disabled => false,
+        };
+        let field = self.args.fields[which];
+        if !check
+            || self.disorder_warned[which]
+            || self.compare_fields(previous, line, field, field) != Ordering::Greater
+        {
+            return Ok(());
+        }
+        let input = &self.inputs[which];
+        let message = format!(
+            "{}:{}: is not sorted: {}",
+            input.name,
+            input.number,
+            String::from_utf8_lossy(&line.bytes)
+        );
+        if self.args.check_order == CheckOrder::Enabled {
+            return Err(JoinError::Unsorted(message));
+        }
+        self.output.flush().map_err(JoinError::Write)?;
+        eprintln!("join: {}", message);
+        self.disorder_warned[which] = true;
+        Ok(())
+    }
+
+    fn compare(&self, line1: &Line, line2: &Line) -> Ordering {
+        self.compare_fields(line1, line2, self.args.fields[0], self.args.fields[1])
+    }
+
+    /// The missing and empty fields go before the rest
+    fn compare_fields(&self, line1: &Line, line2: &Line, field1: usize, field2: usize) -> Ordering {
+        let field1 = line1.field(field1).unwrap_or_default();
+        let field2 = line2.field(field2).unwrap_or_default();
+        match (field1.is_empty(), field2.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ if self.args.ignore_case => {
+                let upper1 = field1.iter().map(u8::to_ascii_uppercase);
+                upper1.cmp(field2.iter().map(u8::to_ascii_uppercase))
+            }
+            _ => field1.cmp(field2),
+        }
+    }
+
+    fn write_unpaired(&mut self, which: usize, line: &Line) -> Result<(), JoinError> {
+        match which {
+            0 => self.write_joined(Some(line), None),
+            _ => self.write_joined(None, Some(line)),
+        }
+    }
+
+    /** Writes the fields of -o, or the join field and the rest of the fields of both lines.
+     *
+     * The line, which is missing, has no fields, the join field is the one of the other line.
+     */
+    fn write_joined(
+        &mut self,
+        line1: Option<&Line>,
+        line2: Option<&Line>,
+    ) -> Result<(), JoinError> {
+        let args = self.args;
+        let blank = Line::default();
+        let lines = [line1.unwrap_or(&blank), line2.unwrap_or(&blank)];
+        let (join_line, join_field) = match line1 {
+            Some(line1) => (line1, args.fields[0]),
+            None => (lines[1], args.fields[1]),
+        };
+        let separator = args.separator.unwrap_or(b' ');
+        let mut joined = Vec::new();
+        if args.output_format.is_empty() {
+            self.push_field(&mut joined, join_line, join_field);
+            for (which, line) in lines.iter().enumerate() {
+                let count = match args.auto_format {
+                    true => self.auto_counts[which],
+                    false => line.fields.len(),
+                };
+                for field in (0..count).filter(|&field| field != args.fields[which]) {
+                    joined.push(separator);
+                    self.push_field(&mut joined, line, field);
+                }
+            }
+        }
+        for (i, spec) in args.output_format.iter().enumerate() {
+            if i > 0 {
+                joined.push(separator);
+            }
+            match spec.file {
+                0 => self.push_field(&mut joined, join_line, join_field),
+                file => self.push_field(&mut joined, lines[file - 1], spec.field),
+            }
+        }
+        joined.push(args.terminator());
+        self.output.write_all(&joined).map_err(JoinError::Write)
+    }
+
+    /// The missing and empty fields are replaced with -e
+    fn push_field(&self, joined: &mut Vec<u8>, line: &Line, field: usize) {
+        match line.field(field) {
+            Some(field) if !field.is_empty() => joined.extend_from_slice(field),
+            _ => joined.extend_from_slice(self.args.empty.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::args::FieldSpec;
+
+    fn join(args: &Args, file1: &[u8], file2: &[u8]) -> (String, Option<String>) {
+        let input = |name: &str, input: &[u8]| {
+            let input = Box::new(io::Cursor::new(input.to_vec()));
+            Input::new(name, input, args.terminator())
+        };
+        let mut output = Vec::new();
+        let inputs = [input("f1", file1), input("f2", file2)];
+        let result = Join::new(args, &mut output, inputs).join();
+        (
+            String::from_utf8(output).unwrap(),
+            result.err().map(|e| e.to_string()),
+        )
+    }
+
+    fn args() -> Args {
+        Args {
+            print_paired: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn splits_the_fields() {
+        let fields = |line: &[u8], separator| {
+            let line = Line::new(line.to_vec(), separator);
+            (0..line.fields.len())
+                .map(|n| String::from_utf8(line.field(n).unwrap().to_vec()).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fields(b"  a \tb ", None), ["a", "b", ""]);
+        assert_eq!(fields(b":a::b", Some(b':')), ["", "a", "", "b"]);
+        assert_eq!(fields(b"a b", Some(b'\n')), ["a b"]);
+        assert!(fields(b"", Some(b':')).is_empty());
+    }
+
+    #[test]
+    fn joins_the_lines() {
+        let (output, _) = join(&args(), b"a 1\nb 2\nb 3\n", b"b x\nb y\nc z\n");
+        assert_eq!(output, "b 2 x\nb 2 y\nb 3 x\nb 3 y\n");
+        let args = Args {
+            print_unpaired: [true, true],
+            empty: Some(b"-".to_vec()),
+            output_format: vec![
+                FieldSpec { file: 0, field: 0 },
+                FieldSpec { file: 1, field: 1 },
+                FieldSpec { file: 2, field: 1 },
+            ],
+            ..args()
+        };
+        let (output, _) = join(&args, b"a 1\nb 2\n", b"b x\nc\n");
+        assert_eq!(output, "a 1 -\nb 2 x\nc - -\n");
+    }
+
+    #[test]
+    fn checks_the_order() {
+        let (output, error) = join(&args(), b"b 1\na 2\n", b"b x\n");
+        assert_eq!(output, "b 1 x\n");
+        assert_eq!(error, None);
+        let args = Args {
+            print_unpaired: [true, false],
+            ..args()
+        };
+        let (output, error) = join(&args, b"a 1\nc 2\nb 3\n", b"c x\n");
+        assert_eq!(output, "a 1\nc 2 x\nb 3\n");
+        assert_eq!(error.as_deref(), Some("input is not in sorted order"));
+        let args = Args {
+            check_order: CheckOrder::Enabled,
+            ..args
+        };
+        let (_, error) = join(&args, b"b 1\na 2\n", b"b x\n");
+        assert_eq!(error.as_deref(), Some("f1:2: is not sorted: a 2"));
+    }
+}
@@ -0,0 +1,24 @@
+mod args;
+mod join;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use join::join_error::JoinError;
+use join::{Input, Join};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let result = Input::open(&args.files[0], args.terminator())
+        .and_then(|input1| Ok([input1, Input::open(&args.files[1], args.terminator())?]))
+        .and_then(|inputs| Join::new(&args, &mut output, inputs).join());
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(JoinError::Write);
+    if let Err(e) = result.and(flushed) {
+        eprintln!("join: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,199 @@
+use std::error::Error;
+
+use clap::{Parser, ValueEnum};
+use ibig::UBig;
+use parse_num::{parse_size, RoundMode, Scale};
+
+use crate::numfmt::fields::FieldList;
+use crate::numfmt::format::Format;
+use crate::numfmt::numfmt_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum FromUnit {
+    /// no auto-scaling is done; suffixes will trigger an error
+    #[default]
+    None,
+    /// accept optional single/two letter suffix: 1K = 1000, 1Ki = 1024
+    Auto,
+    /// accept optional single letter suffix: 1K = 1000, 1M = 1000000
+    Si,
+    /// accept optional single letter suffix: 1K = 1024, 1M = 1048576
+    Iec,
+    /// accept optional two-letter suffix: 1Ki = 1024, 1Mi = 1048576
+    IecI,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ToUnit {
+    /// no auto-scaling is done
+    #[default]
+    None,
+    /// 1000 is 1.0K, 1000000 is 1.0M
+    Si,
+    /// 1024 is 1.0K, 1048576 is 1.0M
+    Iec,
+    /// 1024 is 1.0Ki, 1048576 is 1.0Mi
+    IecI,
+}
+
+impl ToUnit {
+    pub fn scale(&self) -> Option<Scale> {
+        match self {
+            ToUnit::None => None,
+            ToUnit::Si => Some(Scale::Si),
+            ToUnit::Iec => Some(Scale::Iec),
+            ToUnit::IecI => Some(Scale::IecI),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Round {
+    Up,
+    Down,
+    #[default]
+    FromZero,
+    TowardsZero,
+    Nearest,
+}
+
+impl From<Round> for RoundMode {
+    fn from(round: Round) -> Self {
+        match round {
+            Round::Up => RoundMode::Up,
+            Round::Down => RoundMode::Down,
+            Round::FromZero => RoundMode::FromZero,
+            Round::TowardsZero => RoundMode::TowardsZero,
+            Round::Nearest => RoundMode::Nearest,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Invalid {
+    /// stop at the first invalid number with the exit code 2
+    #[default]
+    Abort,
+    /// report invalid numbers, print them as is, exit with the code 2
+    Fail,
+    /// report invalid numbers and print them as is
+    Warn,
+    /// print invalid numbers as is silently
+    Ignore,
+}
+
+/// Convert numbers from/to human-readable strings
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    pub number: Vec<String>,
+
+    /// use X instead of whitespace for field delimiter
+    #[arg(short = 'd', long, value_parser = parse_delimiter)]
+    pub delimiter: Option<u8>,
+
+    /// replace the numbers in these input fields; see FIELDS in cut(1)
+    #[arg(long, value_parser = FieldList::parse, default_value = "1")]
+    pub field: FieldList,
+
+    /// use printf style floating-point FORMAT
+    #[arg(long, value_parser = Format::parse)]
+    pub format: Option<Format>,
+
+    /// auto-scale input numbers to UNITs
+    #[arg(long, value_enum, default_value_t = FromUnit::None)]
+    pub from: FromUnit,
+
+    /// specify the input unit size
+    #[arg(long, value_parser = parse_unit_size, default_value = "1")]
+    pub from_unit: UBig,
+
+    /// print (without converting) the first N header lines; N defaults to 1 if not specified
+    #[arg(long, value_name = "N", num_args = 0..=1, require_equals = true, default_missing_value = "1", value_parser = parse_header)]
+    pub header: Option<usize>,
+
+    /// what to do with invalid input
+    #[arg(long, value_enum, default_value_t = Invalid::Abort)]
+    pub invalid: Invalid,
+
+    /// pad the output to N characters; positive N will right-align, negative N will left-align
+    #[arg(long, value_name = "N", value_parser = parse_padding, allow_hyphen_values = true)]
+    pub padding: Option<i64>,
+
+    /// use METHOD for rounding when scaling
+    #[arg(long, value_enum, value_name = "METHOD", default_value_t = Round::FromZero)]
+    pub round: Round,
+
+    /// add SUFFIX to output numbers, and accept optional SUFFIX in input numbers
+    #[arg(long)]
+    pub suffix: Option<String>,
+
+    /// auto-scale output numbers to UNITs
+    #[arg(long, value_enum, default_value_t = ToUnit::None)]
+    pub to: ToUnit,
+
+    /// the output unit size
+    #[arg(long, value_parser = parse_unit_size, default_value = "1")]
+    pub to_unit: UBig,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                eprintln!("numfmt: {}", invalid);
+                std::process::exit(1);
+            }
+            err.exit()
+        });
+        // Space padding of the format is the same thing as --padding
+        if let Some(format) = &args.format {
+            if args.padding.is_none() && format.padding != 0 {
+                args.padding = Some(format.padding);
+            }
+        }
+        args
+    }
+
+    pub fn terminator(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+fn parse_delimiter(delimiter: &str) -> Result<u8, ArgError> {
+    match delimiter.as_bytes() {
+        [c] => Ok(*c),
+        _ => Err(ArgError("the delimiter must be a single character".into())),
+    }
+}
+
+fn parse_unit_size(size: &str) -> Result<UBig, ArgError> {
+    match parse_size(size).map(|n| n.to_ubig()) {
+        Ok(n) if n != UBig::from(0_u8) => Ok(n),
+        _ => Err(ArgError(format!("invalid unit size: '{}'", size))),
+    }
+}
+
+fn parse_header(header: &str) -> Result<usize, ArgError> {
+    match header.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(ArgError(format!("invalid header value '{}'", header))),
+    }
+}
+
+fn parse_padding(padding: &str) -> Result<i64, ArgError> {
+    match padding.parse() {
+        Ok(n) if n != 0 => Ok(n),
+        _ => Err(ArgError(format!("invalid padding value '{}'", padding))),
+    }
+}
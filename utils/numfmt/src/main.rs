@@ -0,0 +1,70 @@
+mod args;
+mod numfmt;
+
+use std::io::{self, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::{Args, Invalid};
+use chunk_reader::ChunkReader;
+use numfmt::numfmt;
+use numfmt::numfmt_error::NumfmtError;
+use strerror::strerror;
+
+/// Exit code for the conversion errors, the same as GNU one
+const EXIT_CONVERSION_ERROR: u8 = 2;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let status = match run(&args, &mut output) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) if matches!(args.invalid, Invalid::Warn | Invalid::Ignore) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(EXIT_CONVERSION_ERROR),
+        Err(NumfmtError::Io(e)) => {
+            let _ = output.flush();
+            eprintln!("numfmt: {}", strerror(&e));
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            let _ = output.flush();
+            eprintln!("numfmt: {}", e);
+            ExitCode::from(EXIT_CONVERSION_ERROR)
+        }
+    };
+    if let Err(e) = output.flush() {
+        eprintln!("numfmt: write error: {}", strerror(&e));
+        return ExitCode::FAILURE;
+    }
+    status
+}
+
+/// Converts the numbers from the command line, or the lines of stdin if there are none
+fn run<W: Write>(args: &Args, output: &mut W) -> Result<bool, NumfmtError> {
+    let terminator = args.terminator();
+    let mut valid = true;
+    if !args.number.is_empty() {
+        for number in &args.number {
+            valid &= numfmt(args, number.as_bytes(), output)?;
+            output.write_all(&[terminator])?;
+        }
+        return Ok(valid);
+    }
+
+    let reader = BufReader::new(io::stdin());
+    for (i, line) in reader.chunks(terminator, 0).enumerate() {
+        let line = line?;
+        if i < args.header.unwrap_or(0) {
+            output.write_all(&line)?;
+            continue;
+        }
+        let (line, terminated) = match line.split_last() {
+            Some((last, line)) if *last == terminator => (line, true),
+            _ => (&line[..], false),
+        };
+        valid &= numfmt(args, line, output)?;
+        if terminated {
+            output.write_all(&[terminator])?;
+        }
+    }
+    Ok(valid)
+}
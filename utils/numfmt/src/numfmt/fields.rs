@@ -0,0 +1,91 @@
+use super::numfmt_error::ArgError;
+
+/** Fields to convert, as given to --field: "1", "1,3-5", "-2", "7-" or "-" for all of them.
+ *
+ * Ranges are kept inclusive and 1-based, an open end is usize::MAX.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldList(Vec<(usize, usize)>);
+
+impl Default for FieldList {
+    fn default() -> Self {
+        FieldList(vec![(1, 1)])
+    }
+}
+
+impl FieldList {
+    pub fn parse(list: &str) -> Result<FieldList, ArgError> {
+        let mut ranges = Vec::new();
+        for item in list.split([',', ' ', '\t']) {
+            if item.matches('-').count() > 1 {
+                return Err(ArgError("invalid field range".into()));
+            }
+            let (start, end) = match item.split_once('-') {
+                None => {
+                    let n = parse_field(item)?;
+                    (n, n)
+                }
+                Some(("", "")) => (1, usize::MAX),
+                Some(("", end)) => (1, parse_field(end)?),
+                Some((start, "")) => (parse_field(start)?, usize::MAX),
+                Some((start, end)) => (parse_field(start)?, parse_field(end)?),
+            };
+            if end < start {
+                return Err(ArgError("invalid decreasing range".into()));
+            }
+            ranges.push((start, end));
+        }
+        Ok(FieldList(ranges))
+    }
+
+    pub fn contains(&self, field: usize) -> bool {
+        self.0
+            .iter()
+            .any(|(start, end)| *start <= field && field <= *end)
+    }
+}
+
+fn parse_field(field: &str) -> Result<usize, ArgError> {
+    if let Some(i) = field.find(|c: char| !c.is_ascii_digit()) {
+        return Err(ArgError(format!("invalid field value '{}'", &field[i..])));
+    }
+    match field.parse::<usize>() {
+        Ok(0) => Err(ArgError("fields are numbered from 1".into())),
+        Ok(n) => Ok(n),
+        Err(_) if field.is_empty() => Err(ArgError("fields are numbered from 1".into())),
+        Err(_) => Err(ArgError(format!("field number '{}' is too large", field))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_lists_and_ranges() {
+        let list = FieldList::parse("1,3-4,6-").unwrap();
+        assert!(list.contains(1));
+        assert!(!list.contains(2));
+        assert!(list.contains(3));
+        assert!(list.contains(4));
+        assert!(!list.contains(5));
+        assert!(list.contains(1000));
+
+        let list = FieldList::parse("-2").unwrap();
+        assert!(list.contains(1) && list.contains(2) && !list.contains(3));
+        assert!(FieldList::parse("-").unwrap().contains(usize::MAX));
+        assert!(FieldList::parse("1 3").unwrap().contains(3));
+    }
+
+    #[test]
+    fn rejects_invalid_lists() {
+        let error = |list: &str| FieldList::parse(list).unwrap_err().0;
+        assert_eq!(error("0"), "fields are numbered from 1");
+        assert_eq!(error(""), "fields are numbered from 1");
+        assert_eq!(error("1,,2"), "fields are numbered from 1");
+        assert_eq!(error("3-1"), "invalid decreasing range");
+        assert_eq!(error("x"), "invalid field value 'x'");
+        assert_eq!(error("1-x"), "invalid field value 'x'");
+        assert_eq!(error("--"), "invalid field range");
+    }
+}
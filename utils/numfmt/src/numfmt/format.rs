@@ -0,0 +1,150 @@
+use super::numfmt_error::ArgError;
+
+/** printf-style --format: "%[0]['][-][N][.][N]f" surrounded by an arbitrary text.
+ *
+ * Space padding works the same way as --padding (negative for the left alignment),
+ * so it's kept as such. Zero padding is applied to the number only, not to its suffix.
+ * Thousands grouping (') is accepted, but there is nothing to group in the C locale.
+ */
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Format {
+    pub prefix: String,
+    pub suffix: String,
+    pub padding: i64,
+    pub zero_padding: usize,
+    pub precision: Option<usize>,
+}
+
+impl Format {
+    pub fn parse(format: &str) -> Result<Format, ArgError> {
+        let start = find_directive(format, 0)
+            .ok_or_else(|| ArgError(format!("format '{}' has no % directive", format)))?;
+        let prefix = &format[..start];
+        let bytes = format.as_bytes();
+        let mut i = start + 1;
+        if i == bytes.len() {
+            return Err(ArgError(format!("format '{}' ends in %", format)));
+        }
+
+        let (mut zero, mut left) = (false, false);
+        while i < bytes.len() && matches!(bytes[i], b'0' | b'\'' | b'-') {
+            zero |= bytes[i] == b'0';
+            left |= bytes[i] == b'-';
+            i += 1;
+        }
+        let width = take_number(format, &mut i);
+        let precision = if i < bytes.len() && bytes[i] == b'.' {
+            i += 1;
+            Some(take_number(format, &mut i))
+        } else {
+            None
+        };
+        if i >= bytes.len() || bytes[i] != b'f' {
+            return Err(ArgError(format!(
+                "invalid format '{}', directive must be %[0]['][-][N][.][N]f",
+                format
+            )));
+        }
+        let suffix = &format[i + 1..];
+        if find_directive(format, i + 1).is_some() {
+            return Err(ArgError(format!(
+                "format '{}' has too many % directives",
+                format
+            )));
+        }
+
+        let (padding, zero_padding) = match (left, zero) {
+            (true, _) => (-(width as i64), 0),
+            (false, true) => (0, width),
+            (false, false) => (width as i64, 0),
+        };
+        Ok(Format {
+            prefix: prefix.replace("%%", "%"),
+            suffix: suffix.replace("%%", "%"),
+            padding,
+            zero_padding,
+            precision,
+        })
+    }
+
+    /// Pads the number with zeros after the sign, leaving its unit suffix as is
+    pub fn zero_pad(&self, number: &str) -> String {
+        let digits_end = number
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic())
+            .len();
+        let (value, unit) = number.split_at(digits_end);
+        let (sign, value) = match value.strip_prefix('-') {
+            Some(value) => ("-", value),
+            None => ("", value),
+        };
+        let width = self.zero_padding.saturating_sub(sign.len());
+        format!("{}{:0>width$}{}", sign, value, unit, width = width)
+    }
+}
+
+/// Position of the first % directive from `from`, skipping "%%"
+fn find_directive(format: &str, from: usize) -> Option<usize> {
+    let bytes = format.as_bytes();
+    let mut i = from;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if bytes.get(i + 1) != Some(&b'%') {
+                return Some(i);
+            }
+            i += 1;
+        }
+        i += 1;
+    }
+    None
+}
+
+fn take_number(format: &str, i: &mut usize) -> usize {
+    let start = *i;
+    while *i < format.len() && format.as_bytes()[*i].is_ascii_digit() {
+        *i += 1;
+    }
+    format[start..*i].parse().unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_directive_and_text() {
+        assert_eq!(
+            Format::parse("size: %'-10.2f%%").unwrap(),
+            Format {
+                prefix: "size: ".into(),
+                suffix: "%".into(),
+                padding: -10,
+                zero_padding: 0,
+                precision: Some(2),
+            }
+        );
+        assert_eq!(Format::parse("%10f").unwrap().padding, 10);
+        assert_eq!(Format::parse("%08f").unwrap().zero_padding, 8);
+        assert_eq!(Format::parse("%.f").unwrap().precision, Some(0));
+        assert_eq!(Format::parse("%f").unwrap().precision, None);
+    }
+
+    #[test]
+    fn rejects_invalid_formats() {
+        let error = |format: &str| Format::parse(format).unwrap_err().0;
+        assert_eq!(error("abc"), "format 'abc' has no % directive");
+        assert_eq!(error("%"), "format '%' ends in %");
+        assert_eq!(error("%f%f"), "format '%f%f' has too many % directives");
+        assert_eq!(
+            error("%d"),
+            "invalid format '%d', directive must be %[0]['][-][N][.][N]f"
+        );
+    }
+
+    #[test]
+    fn zero_pads_number_only() {
+        let format = Format::parse("%08.1f").unwrap();
+        assert_eq!(format.zero_pad("1.5K"), "000001.5K");
+        assert_eq!(format.zero_pad("-1.5"), "-00001.5");
+        assert_eq!(format.zero_pad("123456789"), "123456789");
+    }
+}
@@ -0,0 +1,138 @@
+use ibig::UBig;
+use parse_num::{power_of, Fraction};
+
+use super::numfmt_error::NumfmtError;
+use crate::args::FromUnit;
+
+/** Parses the number with an optional unit suffix, according to --from.
+ *
+ * Returns the exact value and its precision: the number of decimal digits in the input,
+ * which is 0 when there was a suffix, since "1.5K" is just 1500 (or 1536).
+ */
+pub fn parse_human(input: &str, from: FromUnit) -> Result<(Fraction, usize), NumfmtError> {
+    let invalid_number = || NumfmtError::InvalidNumber(input.to_string());
+    let (negative, unsigned) = match input.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, input),
+    };
+    let int_len = unsigned.len()
+        - unsigned
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    let mut digits = unsigned[..int_len].to_string();
+    let mut rest = &unsigned[int_len..];
+    let mut precision = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        precision = fraction.len()
+            - fraction
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        if precision == 0 {
+            return Err(invalid_number());
+        }
+        digits.push_str(&fraction[..precision]);
+        rest = &fraction[precision..];
+    }
+    if digits.is_empty() {
+        return Err(invalid_number());
+    }
+    let mut num: UBig = digits.parse().map_err(|_| invalid_number())?;
+    let den = UBig::from(10_u8).pow(precision);
+
+    let mut chars = rest.chars();
+    if let Some(c) = chars.next() {
+        let power = match power_of(c) {
+            Some(power) if c.is_ascii_uppercase() => power,
+            _ => return Err(NumfmtError::InvalidSuffix(input.to_string())),
+        };
+        let base: u32 = match from {
+            FromUnit::None => return Err(NumfmtError::RejectingSuffix(input.to_string())),
+            FromUnit::Si => 1000,
+            FromUnit::Iec => 1024,
+            FromUnit::Auto | FromUnit::IecI => {
+                if chars.as_str().starts_with('i') {
+                    chars.next();
+                    1024
+                } else if from == FromUnit::IecI {
+                    return Err(NumfmtError::MissingISuffix(input.to_string()));
+                } else {
+                    1000
+                }
+            }
+        };
+        if !chars.as_str().is_empty() {
+            return Err(NumfmtError::TrailingGarbage(
+                input.to_string(),
+                chars.as_str().to_string(),
+            ));
+        }
+        num *= UBig::from(base).pow(power as usize);
+        precision = 0;
+    }
+    Ok((Fraction::new(negative, num, den), precision))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(input: &str, from: FromUnit) -> Result<(f64, usize), String> {
+        parse_human(input, from)
+            .map(|(value, precision)| (value.to_f64(), precision))
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn parses_plain_numbers() {
+        assert_eq!(parse("1000", FromUnit::None), Ok((1000.0, 0)));
+        assert_eq!(parse("-1.25", FromUnit::None), Ok((-1.25, 2)));
+        assert_eq!(parse(".5", FromUnit::None), Ok((0.5, 1)));
+        assert_eq!(
+            parse("5.", FromUnit::None),
+            Err("invalid number: '5.'".into())
+        );
+        assert_eq!(
+            parse("+5", FromUnit::None),
+            Err("invalid number: '+5'".into())
+        );
+        assert_eq!(parse("", FromUnit::None), Err("invalid number: ''".into()));
+        assert_eq!(
+            parse("1e3", FromUnit::None),
+            Err("invalid suffix in input: '1e3'".into())
+        );
+    }
+
+    #[test]
+    fn applies_suffixes_by_unit() {
+        assert_eq!(parse("1.5K", FromUnit::Si), Ok((1500.0, 0)));
+        assert_eq!(parse("1.5K", FromUnit::Iec), Ok((1536.0, 0)));
+        assert_eq!(parse("1Ki", FromUnit::IecI), Ok((1024.0, 0)));
+        assert_eq!(parse("1Ki", FromUnit::Auto), Ok((1024.0, 0)));
+        assert_eq!(parse("1K", FromUnit::Auto), Ok((1000.0, 0)));
+        assert_eq!(parse("2Q", FromUnit::Si), Ok((2e30, 0)));
+    }
+
+    #[test]
+    fn reports_bad_suffixes() {
+        assert_eq!(
+            parse("1K", FromUnit::None),
+            Err("rejecting suffix in input: '1K' (consider using --from)".into())
+        );
+        assert_eq!(
+            parse("1k", FromUnit::Si),
+            Err("invalid suffix in input: '1k'".into())
+        );
+        assert_eq!(
+            parse("1K", FromUnit::IecI),
+            Err("missing 'i' suffix in input: '1K' (e.g Ki/Mi/Gi)".into())
+        );
+        assert_eq!(
+            parse("1Ki", FromUnit::Iec),
+            Err("invalid suffix in input '1Ki': 'i'".into())
+        );
+        assert_eq!(
+            parse("12KX", FromUnit::Si),
+            Err("invalid suffix in input '12KX': 'X'".into())
+        );
+    }
+}
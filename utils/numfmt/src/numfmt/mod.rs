@@ -0,0 +1,256 @@
+pub mod fields;
+pub mod format;
+pub mod human;
+pub mod numfmt_error;
+
+use std::io::Write;
+use std::str;
+
+use parse_num::{format_fixed, format_num, Fraction};
+
+use crate::args::{Args, Invalid};
+use human::parse_human;
+use numfmt_error::NumfmtError;
+
+/// Digits, that can be printed without scaling, the same limit GNU has
+const MAX_UNSCALED_DIGITS: usize = 18;
+
+/** Converts the selected fields of the line (without its terminator).
+ *
+ * Invalid fields are handled according to --invalid, they're printed as is, unless
+ * we're told to abort. Returns false, if there were any.
+ */
+pub fn numfmt<W: Write>(args: &Args, line: &[u8], output: &mut W) -> Result<bool, NumfmtError> {
+    let mut valid = true;
+    let mut rest = Some(line);
+    let mut field_number = 0;
+    while let Some(line) = rest {
+        field_number += 1;
+        let (field, next) = next_field(line, args.delimiter);
+        if args.field.contains(field_number) {
+            match convert_field(args, field, field_number) {
+                Ok(converted) => output.write_all(converted.as_bytes())?,
+                Err(e) if args.invalid == Invalid::Abort => return Err(e),
+                Err(e) => {
+                    if args.invalid != Invalid::Ignore {
+                        // Flushing, so the message goes right after the fields before it
+                        output.flush()?;
+                        eprintln!("numfmt: {}", e);
+                    }
+                    output.write_all(field)?;
+                    valid = false;
+                }
+            }
+        } else {
+            output.write_all(field)?;
+        }
+        if next.is_some() {
+            output.write_all(&[args.delimiter.unwrap_or(b' ')])?;
+        }
+        rest = next;
+    }
+    Ok(valid)
+}
+
+/** Splits the next field off the line, returning the rest after the delimiter, if any.
+ *
+ * Whitespace separated fields include their leading blanks, so the original alignment
+ * can be kept, but just one blank separating them is consumed.
+ */
+fn next_field(line: &[u8], delimiter: Option<u8>) -> (&[u8], Option<&[u8]>) {
+    let end = match delimiter {
+        Some(d) => line.iter().position(|c| *c == d).unwrap_or(line.len()),
+        None => {
+            let start = line.iter().take_while(|c| is_blank(**c)).count();
+            line[start..]
+                .iter()
+                .position(|c| is_blank(*c))
+                .map(|i| i + start)
+                .unwrap_or(line.len())
+        }
+    };
+    if end < line.len() {
+        (&line[..end], Some(&line[end + 1..]))
+    } else {
+        (line, None)
+    }
+}
+
+fn is_blank(c: u8) -> bool {
+    c == b' ' || c == b'\t'
+}
+
+fn convert_field(args: &Args, field: &[u8], field_number: usize) -> Result<String, NumfmtError> {
+    let field = str::from_utf8(field)
+        .map_err(|_| NumfmtError::InvalidNumber(String::from_utf8_lossy(field).into()))?;
+    let text = match &args.suffix {
+        Some(suffix) if field.len() > suffix.len() => {
+            field.strip_suffix(suffix.as_str()).unwrap_or(field)
+        }
+        _ => field,
+    };
+    let number = text.trim_start_matches([' ', '\t']);
+    let padding = match args.padding {
+        Some(padding) => padding,
+        // Keeping the width of the whitespace separated fields, unless told otherwise
+        None if args.delimiter.is_none() && (number.len() < text.len() || field_number > 1) => {
+            text.len() as i64
+        }
+        None => 0,
+    };
+
+    let (value, precision) = parse_human(number, args.from)?;
+    let value = Fraction::new(
+        value.negative,
+        value.num * &args.from_unit,
+        value.den * &args.to_unit,
+    );
+    let mut converted = format_value(args, &value, precision)?;
+    if let Some(suffix) = &args.suffix {
+        converted.push_str(suffix);
+    }
+    let width = padding.unsigned_abs() as usize;
+    if padding < 0 {
+        converted = format!("{:<width$}", converted, width = width);
+    } else {
+        converted = format!("{:>width$}", converted, width = width);
+    }
+    match &args.format {
+        Some(format) => Ok(format!("{}{}{}", format.prefix, converted, format.suffix)),
+        None => Ok(converted),
+    }
+}
+
+fn format_value(args: &Args, value: &Fraction, precision: usize) -> Result<String, NumfmtError> {
+    let round = args.round.into();
+    let user_precision = args.format.as_ref().and_then(|f| f.precision);
+    let formatted = match args.to.scale() {
+        Some(scale) => format_num(value, scale, round, user_precision)
+            .ok_or_else(|| NumfmtError::TooLargeForScale(value.to_f64()))?,
+        None => {
+            let precision = user_precision.unwrap_or(precision);
+            let int_digits = (&value.num / &value.den).to_string().len();
+            if precision == 0 && int_digits > MAX_UNSCALED_DIGITS + 1 {
+                return Err(NumfmtError::TooLarge(value.to_f64()));
+            }
+            if precision > 0 && int_digits + precision > MAX_UNSCALED_DIGITS {
+                return Err(NumfmtError::PrecisionTooLarge(value.to_f64(), precision));
+            }
+            format_fixed(value, round, precision)
+        }
+    };
+    match &args.format {
+        Some(format) if format.zero_padding > 0 => Ok(format.zero_pad(&formatted)),
+        _ => Ok(formatted),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ibig::UBig;
+
+    use super::*;
+    use crate::args::{FromUnit, ToUnit};
+    use crate::numfmt::fields::FieldList;
+    use crate::numfmt::format::Format;
+
+    fn default_args() -> Args {
+        Args {
+            from_unit: UBig::from(1_u8),
+            to_unit: UBig::from(1_u8),
+            field: FieldList::default(),
+            to: ToUnit::Si,
+            ..Default::default()
+        }
+    }
+
+    fn run(args: &Args, line: &str) -> (String, bool) {
+        let mut output = Vec::new();
+        let valid = numfmt(args, line.as_bytes(), &mut output).unwrap();
+        (String::from_utf8(output).unwrap(), valid)
+    }
+
+    #[test]
+    fn keeps_whitespace_alignment() {
+        let args = Args {
+            field: FieldList::parse("-").unwrap(),
+            ..default_args()
+        };
+        assert_eq!(run(&args, "  1000   2000 3000").0, "  1.0K   2.0K 3.0K");
+        assert_eq!(run(&args, "1000\t2000").0, "1.0K 2.0K");
+    }
+
+    #[test]
+    fn converts_selected_fields_only() {
+        let args = Args {
+            delimiter: Some(b':'),
+            field: FieldList::parse("2,4").unwrap(),
+            ..default_args()
+        };
+        assert_eq!(run(&args, "a:1000:2000:3000").0, "a:1.0K:2000:3.0K");
+        assert_eq!(run(&args, "a:1000").0, "a:1.0K");
+    }
+
+    #[test]
+    fn applies_padding_suffix_and_format() {
+        let args = Args {
+            padding: Some(-6),
+            suffix: Some("B".into()),
+            ..default_args()
+        };
+        assert_eq!(run(&args, "2048B").0, "2.1KB ");
+        let args = Args {
+            format: Some(Format::parse("[%08.1f]").unwrap()),
+            ..default_args()
+        };
+        assert_eq!(run(&args, "1500").0, "[000001.5K]");
+    }
+
+    #[test]
+    fn converts_units() {
+        let args = Args {
+            from: FromUnit::Iec,
+            to: ToUnit::None,
+            to_unit: UBig::from(512_u32),
+            ..default_args()
+        };
+        assert_eq!(run(&args, "1M").0, "2048");
+        let args = Args {
+            to: ToUnit::None,
+            ..default_args()
+        };
+        assert_eq!(run(&args, "1.25").0, "1.25");
+    }
+
+    #[test]
+    fn handles_invalid_fields() {
+        let args = Args {
+            invalid: Invalid::Ignore,
+            field: FieldList::parse("1,2").unwrap(),
+            ..default_args()
+        };
+        assert_eq!(run(&args, "x 3000"), ("x 3.0K".to_string(), false));
+        let args = Args {
+            invalid: Invalid::Abort,
+            ..default_args()
+        };
+        let mut output = Vec::new();
+        let err = numfmt(&args, b"x", &mut output).unwrap_err();
+        assert_eq!(err.to_string(), "invalid number: 'x'");
+    }
+
+    #[test]
+    fn refuses_too_large_values() {
+        let args = Args {
+            to: ToUnit::None,
+            ..default_args()
+        };
+        let mut output = Vec::new();
+        assert!(numfmt(&args, b"9999999999999999999", &mut output).unwrap());
+        let err = numfmt(&args, b"12345678901234567890", &mut output).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "value too large to be printed: '1.23457e+19' (consider using --to)"
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+/// Conversion errors, reported according to the --invalid mode
+#[derive(Debug)]
+pub enum NumfmtError {
+    Io(io::Error),
+    InvalidNumber(String),
+    InvalidSuffix(String),
+    /// The input and the part of it after a valid suffix
+    TrailingGarbage(String, String),
+    RejectingSuffix(String),
+    MissingISuffix(String),
+    TooLarge(f64),
+    TooLargeForScale(f64),
+    PrecisionTooLarge(f64, usize),
+}
+
+impl fmt::Display for NumfmtError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumfmtError::Io(err) => write!(f, "{}", strerror(err)),
+            NumfmtError::InvalidNumber(input) => write!(f, "invalid number: '{}'", input),
+            NumfmtError::InvalidSuffix(input) => write!(f, "invalid suffix in input: '{}'", input),
+            NumfmtError::TrailingGarbage(input, rest) => {
+                write!(f, "invalid suffix in input '{}': '{}'", input, rest)
+            }
+            NumfmtError::RejectingSuffix(input) => write!(
+                f,
+                "rejecting suffix in input: '{}' (consider using --from)",
+                input
+            ),
+            NumfmtError::MissingISuffix(input) => {
+                write!(f, "missing 'i' suffix in input: '{}' (e.g Ki/Mi/Gi)", input)
+            }
+            NumfmtError::TooLarge(value) => write!(
+                f,
+                "value too large to be printed: '{}' (consider using --to)",
+                format_g(*value)
+            ),
+            NumfmtError::TooLargeForScale(value) => write!(
+                f,
+                "value too large to be printed: '{}' (cannot handle values > 999Q)",
+                format_g(*value)
+            ),
+            NumfmtError::PrecisionTooLarge(value, precision) => write!(
+                f,
+                "value/precision too large to be printed: '{}/{}' (consider using --to)",
+                format_g(*value),
+                precision
+            ),
+        }
+    }
+}
+
+impl Error for NumfmtError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NumfmtError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NumfmtError {
+    fn from(err: io::Error) -> Self {
+        NumfmtError::Io(err)
+    }
+}
+
+/// Invalid option value, reported with GNU message instead of the clap one
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// printf("%g") lookalike, that's how GNU shows the values in diagnostics
+fn format_g(value: f64) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return value.to_string();
+    }
+    let exponent = value.abs().log10().floor() as i32;
+    let trim = |s: String| {
+        if s.contains('.') {
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            s
+        }
+    };
+    if (-4..6).contains(&exponent) {
+        let decimals = (5 - exponent).max(0) as usize;
+        trim(format!("{:.*}", decimals, value))
+    } else {
+        let formatted = format!("{:.5e}", value);
+        let (mantissa, exponent) = formatted.split_once('e').unwrap();
+        let exponent: i32 = exponent.parse().unwrap();
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!(
+            "{}e{}{:02}",
+            trim(mantissa.to_string()),
+            sign,
+            exponent.abs()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn format_g_mimics_printf() {
+        assert_eq!(format_g(1.2345678e19), "1.23457e+19");
+        assert_eq!(format_g(1e21), "1e+21");
+        assert_eq!(format_g(1.12345678), "1.12346");
+        assert_eq!(format_g(123456.0), "123456");
+        assert_eq!(format_g(-0.0001), "-0.0001");
+        assert_eq!(format_g(0.00001), "1e-05");
+    }
+}
@@ -0,0 +1,267 @@
+use std::error::Error;
+
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser, ValueEnum};
+use parse_num::{parse_size, ParseNumError};
+
+use crate::od::format::{Format, Kind, Types};
+use crate::od::od_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Bytes per line without -w
+const DEFAULT_WIDTH: usize = 16;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Endian {
+    Big,
+    Little,
+}
+
+/// Write an unambiguous representation, octal bytes by default,
+/// of FILE to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input; with -r, the dump
+    /// and the file to patch, instead of the standard output
+    #[arg(default_value = "-")]
+    pub file: Vec<String>,
+
+    /// output format for file offsets; RADIX is one of [doxn], for Decimal, Octal, Hex or None
+    #[arg(short = 'A', long, value_name = "RADIX", value_parser = parse_radix, default_value = "o")]
+    pub address_radix: char,
+
+    /// swap input bytes according the specified order
+    #[arg(long, value_enum, value_name = "ORDER")]
+    pub endian: Option<Endian>,
+
+    /// skip BYTES input bytes first
+    #[arg(short = 'j', long, value_name = "BYTES", value_parser = parse_skip_bytes, default_value_t = 0)]
+    pub skip_bytes: u64,
+
+    /// limit dump to BYTES input bytes
+    #[arg(short = 'N', long, value_name = "BYTES", value_parser = parse_read_bytes)]
+    pub read_bytes: Option<u64>,
+
+    /// select output format or formats
+    #[arg(short = 't', long, value_name = "TYPE", value_parser = Types::parse)]
+    pub format: Vec<Types>,
+
+    /// output all lines, do not use * to mark line suppression
+    #[arg(short = 'v', long, default_value_t = false)]
+    pub output_duplicates: bool,
+
+    /// output BYTES bytes per output line; 32 is implied when BYTES is not specified
+    #[arg(short = 'w', long = "width", value_name = "BYTES", value_parser = parse_width, num_args = 0..=1, require_equals = true, default_missing_value = "32")]
+    pub width_arg: Option<u64>,
+
+    /// same as -t a, select named characters, ignoring high-order bit
+    #[arg(short = 'a', action = ArgAction::Count)]
+    pub named_chars: u8,
+
+    /// same as -t o1, select octal bytes
+    #[arg(short = 'b', action = ArgAction::Count)]
+    pub octal_bytes: u8,
+
+    /// same as -t c, select printable characters or backslash escapes
+    #[arg(short = 'c', action = ArgAction::Count)]
+    pub chars: u8,
+
+    /// same as -t u2, select unsigned decimal 2-byte units
+    #[arg(short = 'd', action = ArgAction::Count)]
+    pub unsigned_shorts: u8,
+
+    /// same as -t fF, select floats
+    #[arg(short = 'f', action = ArgAction::Count)]
+    pub floats: u8,
+
+    /// same as -t dI, select decimal ints
+    #[arg(short = 'i', action = ArgAction::Count)]
+    pub ints: u8,
+
+    /// same as -t dL, select decimal longs
+    #[arg(short = 'l', action = ArgAction::Count)]
+    pub longs: u8,
+
+    /// same as -t o2, select octal 2-byte units
+    #[arg(short = 'o', action = ArgAction::Count)]
+    pub octal_shorts: u8,
+
+    /// same as -t d2, select decimal 2-byte units
+    #[arg(short = 's', action = ArgAction::Count)]
+    pub shorts: u8,
+
+    /// same as -t x2, select hexadecimal 2-byte units
+    #[arg(short = 'x', action = ArgAction::Count)]
+    pub hex_shorts: u8,
+
+    /// reverse operation: convert an xxd hex dump into binary
+    #[arg(short = 'r', long, default_value_t = false)]
+    pub reverse: bool,
+
+    /// with -r, read a plain hex dump without addresses
+    #[arg(short = 'p', long, default_value_t = false, requires = "reverse")]
+    pub plain: bool,
+
+    /// All the formats in the order of the options, -t oS without them
+    #[arg(skip)]
+    pub formats: Vec<Format>,
+
+    /// Bytes per line, a multiple of all the format sizes
+    #[arg(skip)]
+    pub width: usize,
+
+    #[arg(skip)]
+    pub big_endian: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        // The width is optional, so it's only -w5 or -w, and "-w 5" is -w and the FILE 5.
+        // clap needs the equals sign to tell that.
+        let arguments = std::env::args_os().map(|argument| match argument.to_str() {
+            Some(width)
+                if width.starts_with("-w") && width.len() > 2 && !width.starts_with("-w=") =>
+            {
+                format!("-w={}", &width[2..]).into()
+            }
+            _ => argument,
+        });
+        let matches = <Self as CommandFactory>::command()
+            .try_get_matches_from(arguments)
+            .unwrap_or_else(|err| {
+                if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                    eprintln!("od: {}", invalid);
+                    std::process::exit(1);
+                }
+                err.exit()
+            });
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        if args.reverse && args.file.len() > 2 {
+            usage_error(&format!("extra operand '{}'", args.file[2]));
+        }
+        args.formats = args.ordered_formats(&matches);
+        if args.formats.is_empty() {
+            args.formats = Types::parse("oS").map(|types| types.0).unwrap_or_default();
+        }
+        let sizes_multiple = args
+            .formats
+            .iter()
+            .fold(1, |multiple, format| lcm(multiple, format.size));
+        args.width = match args.width_arg {
+            Some(width) if width != 0 && width % sizes_multiple as u64 == 0 => width as usize,
+            Some(width) => {
+                eprintln!(
+                    "od: warning: invalid width {}; using {} instead",
+                    width, sizes_multiple
+                );
+                sizes_multiple
+            }
+            None => sizes_multiple.max(DEFAULT_WIDTH / sizes_multiple * sizes_multiple),
+        };
+        args.big_endian = match args.endian {
+            Some(endian) => endian == Endian::Big,
+            None => cfg!(target_endian = "big"),
+        };
+        args
+    }
+
+    /// The formats of -t and of the traditional options, which go in the order they are given
+    fn ordered_formats(&self, matches: &ArgMatches) -> Vec<Format> {
+        let mut formats: Vec<(usize, Vec<Format>)> = matches
+            .indices_of("format")
+            .into_iter()
+            .flatten()
+            .zip(&self.format)
+            .map(|(index, types)| (index, types.0.clone()))
+            .collect();
+        let traditional = [
+            ("named_chars", Kind::Named, 1),
+            ("octal_bytes", Kind::Octal, 1),
+            ("chars", Kind::Char, 1),
+            ("unsigned_shorts", Kind::Unsigned, 2),
+            ("floats", Kind::Float, 4),
+            ("ints", Kind::Signed, 4),
+            ("longs", Kind::Signed, 8),
+            ("octal_shorts", Kind::Octal, 2),
+            ("shorts", Kind::Signed, 2),
+            ("hex_shorts", Kind::Hex, 2),
+        ];
+        for (id, kind, size) in traditional {
+            // The counters have the default value otherwise, and an index for it
+            if matches.value_source(id) != Some(ValueSource::CommandLine) {
+                continue;
+            }
+            for index in matches.indices_of(id).into_iter().flatten() {
+                let format = Format {
+                    kind,
+                    size,
+                    trailer: false,
+                };
+                formats.push((index, vec![format]));
+            }
+        }
+        formats.sort_by_key(|(index, _)| *index);
+        formats
+            .into_iter()
+            .flat_map(|(_, formats)| formats)
+            .collect()
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("od: {}", message);
+    eprintln!("Try 'od --help' for more information.");
+    std::process::exit(1);
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let gcd = |mut a: usize, mut b: usize| {
+        while b != 0 {
+            (a, b) = (b, a % b);
+        }
+        a
+    };
+    a / gcd(a, b) * b
+}
+
+fn parse_radix(radix: &str) -> Result<char, ArgError> {
+    match radix {
+        "d" | "o" | "x" | "n" => Ok(radix.chars().next().unwrap_or('o')),
+        _ => Err(ArgError(format!(
+            "invalid output address radix '{}'; it must be one character from [doxn]",
+            radix
+        ))),
+    }
+}
+
+/// BYTES in any of the C bases, with the multiplier suffixes
+fn parse_bytes(bytes: &str, option: char) -> Result<u64, ArgError> {
+    match parse_size(bytes) {
+        Ok(size) => u64::try_from(&size.to_ubig())
+            .map_err(|_| ArgError(format!("-{} argument '{}' too large", option, bytes))),
+        Err(ParseNumError::BadMultiplierValue(_)) => Err(ArgError(format!(
+            "invalid suffix in -{} argument '{}'",
+            option, bytes
+        ))),
+        Err(_) => Err(ArgError(format!(
+            "invalid -{} argument '{}'",
+            option, bytes
+        ))),
+    }
+}
+
+fn parse_skip_bytes(bytes: &str) -> Result<u64, ArgError> {
+    parse_bytes(bytes, 'j')
+}
+
+fn parse_read_bytes(bytes: &str) -> Result<u64, ArgError> {
+    parse_bytes(bytes, 'N')
+}
+
+fn parse_width(bytes: &str) -> Result<u64, ArgError> {
+    parse_bytes(bytes, 'w')
+}
@@ -0,0 +1,65 @@
+mod args;
+mod od;
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use od::od_error::OdError;
+use od::reverse::{reverse, ForwardOnly};
+use od::{dump, Inputs};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let result = match args.reverse {
+        true => run_reverse(&args).map(|_| true),
+        false => run_dump(&args),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("od: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Whether all the FILEs were read
+fn run_dump(args: &Args) -> Result<bool, OdError> {
+    let mut inputs = Inputs::new(&args.file);
+    let mut output = BufWriter::new(io::stdout());
+    let result = dump(args, &mut inputs, &mut output);
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(OdError::Write);
+    result.and(flushed).map(|_| !inputs.failed)
+}
+
+/// The file to patch isn't truncated, only the bytes of the dump are written there
+fn run_reverse(args: &Args) -> Result<(), OdError> {
+    let name = &args.file[0];
+    let input: Box<dyn Read> = match name.as_str() {
+        "-" => Box::new(io::stdin()),
+        _ => Box::new(File::open(name).map_err(|e| OdError::Read(name.clone(), e))?),
+    };
+    match args.file.get(1) {
+        Some(patched) => {
+            let file = OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(patched)
+                .map_err(|e| OdError::Create(patched.clone(), e))?;
+            let mut output = BufWriter::new(file);
+            reverse(name, input, &mut output, args.plain)?;
+            output.flush().map_err(OdError::Write)
+        }
+        None => {
+            let mut output = ForwardOnly::new(BufWriter::new(io::stdout()));
+            let result = reverse(name, input, &mut output, args.plain);
+            let flushed = output.flush().map_err(OdError::Write);
+            result.and(flushed)
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use std::fmt::Write;
+
+use super::od_error::ArgError;
+
+/// What the bytes are shown as
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Named characters of -t a, like "nul" or "sp"
+    Named,
+    /// Characters of -t c, with the C escapes or in octal
+    Char,
+    Signed,
+    Unsigned,
+    Octal,
+    Hex,
+    Float,
+}
+
+/// A single output format of -t TYPE
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Format {
+    pub kind: Kind,
+    /// Bytes per value
+    pub size: usize,
+    /// The printable characters of the line follow it, for the 'z' suffix
+    pub trailer: bool,
+}
+
+/// All the formats of a single TYPE string, like "x1z" or "d2u4"
+#[derive(Clone, Debug, PartialEq)]
+pub struct Types(pub Vec<Format>);
+
+impl Types {
+    pub fn parse(types: &str) -> Result<Types, ArgError> {
+        let mut formats = Vec::new();
+        let mut chars = types.char_indices().peekable();
+        while let Some((_, c)) = chars.next() {
+            let (kind, default_size) = match c {
+                'a' => (Kind::Named, 1),
+                'c' => (Kind::Char, 1),
+                'd' => (Kind::Signed, 4),
+                'u' => (Kind::Unsigned, 4),
+                'o' => (Kind::Octal, 4),
+                'x' => (Kind::Hex, 4),
+                'f' => (Kind::Float, 8),
+                _ => {
+                    return Err(ArgError(format!(
+                        "invalid character '{}' in type string '{}'",
+                        c, types
+                    )))
+                }
+            };
+            let mut size = default_size;
+            if !matches!(kind, Kind::Named | Kind::Char) {
+                let named_size = match (kind, chars.peek().map(|&(_, c)| c)) {
+                    (Kind::Float, Some('F')) => Some(4),
+                    (Kind::Float, Some('D')) => Some(8),
+                    (Kind::Float, Some('L')) => Some(16),
+                    (Kind::Float, _) => None,
+                    (_, Some('C')) => Some(1),
+                    (_, Some('S')) => Some(2),
+                    (_, Some('I')) => Some(4),
+                    (_, Some('L')) => Some(8),
+                    _ => None,
+                };
+                if let Some(named_size) = named_size {
+                    chars.next();
+                    size = named_size;
+                } else if let Some(&(start, c)) = chars.peek() {
+                    if c.is_ascii_digit() {
+                        let mut end = start;
+                        while let Some(&(i, c)) = chars.peek() {
+                            if !c.is_ascii_digit() {
+                                break;
+                            }
+                            end = i + 1;
+                            chars.next();
+                        }
+                        size = types[start..end].parse().unwrap_or(usize::MAX);
+                    }
+                }
+                let (valid, what): (&[usize], _) = match kind {
+                    Kind::Float => (&[4, 8], "floating point"),
+                    _ => (&[1, 2, 4, 8], "integral"),
+                };
+                if !valid.contains(&size) {
+                    return Err(ArgError(format!(
+                        "invalid type string '{}';\nthis system doesn't provide a {}-byte {} type",
+                        types, size, what
+                    )));
+                }
+            }
+            let trailer = chars.next_if(|&(_, c)| c == 'z').is_some();
+            formats.push(Format {
+                kind,
+                size,
+                trailer,
+            });
+        }
+        Ok(Types(formats))
+    }
+}
+
+impl Format {
+    /// The widest value, the fields are aligned to it
+    pub fn width(&self) -> usize {
+        let index = self.size.trailing_zeros() as usize;
+        match self.kind {
+            Kind::Named | Kind::Char => 3,
+            Kind::Signed => [4, 6, 11, 20][index],
+            Kind::Unsigned => [3, 5, 10, 20][index],
+            Kind::Octal => [3, 6, 11, 22][index],
+            Kind::Hex => [2, 4, 8, 16][index],
+            Kind::Float => match self.size {
+                4 => 15,
+                _ => 24,
+            },
+        }
+    }
+
+    /// Writes the value of the bytes, right-aligned to the width
+    pub fn write_value(&self, text: &mut String, bytes: &[u8], big_endian: bool, width: usize) {
+        let bits = bytes.iter().enumerate().fold(0_u64, |bits, (i, &byte)| {
+            let shift = match big_endian {
+                true => (bytes.len() - 1 - i) * 8,
+                false => i * 8,
+            };
+            bits | (byte as u64) << shift
+        });
+        let digits = self.width();
+        let _ = match self.kind {
+            Kind::Named => write!(text, "{:>width$}", named_char(bytes[0])),
+            Kind::Char => write!(text, "{:>width$}", escaped_char(bytes[0])),
+            Kind::Signed => {
+                let shift = 64 - self.size * 8;
+                write!(text, "{:>width$}", ((bits << shift) as i64) >> shift)
+            }
+            Kind::Unsigned => write!(text, "{:>width$}", bits),
+            Kind::Octal => write!(text, "{:>width$}", format!("{:0digits$o}", bits)),
+            Kind::Hex => write!(text, "{:>width$}", format!("{:0digits$x}", bits)),
+            Kind::Float => {
+                let value = match self.size {
+                    4 => format_float(f32::from_bits(bits as u32) as f64, true),
+                    _ => format_float(f64::from_bits(bits), false),
+                };
+                write!(text, "{:>width$}", value)
+            }
+        };
+    }
+}
+
+/// The ASCII names of -t a, the high bit is ignored
+fn named_char(byte: u8) -> String {
+    const NAMES: [&str; 33] = [
+        "nul", "soh", "stx", "etx", "eot", "enq", "ack", "bel", "bs", "ht", "nl", "vt", "ff", "cr",
+        "so", "si", "dle", "dc1", "dc2", "dc3", "dc4", "nak", "syn", "etb", "can", "em", "sub",
+        "esc", "fs", "gs", "rs", "us", "sp",
+    ];
+    match byte & 0x7f {
+        c @ 0..=32 => NAMES[c as usize].to_string(),
+        127 => "del".to_string(),
+        c => (c as char).to_string(),
+    }
+}
+
+/// The characters of -t c: printable ones, C escapes, or octal numbers
+fn escaped_char(byte: u8) -> String {
+    match byte {
+        b'\0' => "\\0".to_string(),
+        b'\x07' => "\\a".to_string(),
+        b'\x08' => "\\b".to_string(),
+        b'\x0c' => "\\f".to_string(),
+        b'\n' => "\\n".to_string(),
+        b'\r' => "\\r".to_string(),
+        b'\t' => "\\t".to_string(),
+        b'\x0b' => "\\v".to_string(),
+        b' '..=b'~' => (byte as char).to_string(),
+        _ => format!("{:03o}", byte),
+    }
+}
+
+/** The shortest "%g" of the value, that reads back as the same value.
+ *
+ * There are at least as many digits as always survive the round trip, for f32
+ * when it's single, or a single one for the subnormal numbers, the way GNU does it.
+ */
+pub fn format_float(value: f64, single: bool) -> String {
+    let sign = if value.is_sign_negative() { "-" } else { "" };
+    if value.is_nan() {
+        return format!("{}nan", sign);
+    }
+    if value.is_infinite() {
+        return format!("{}inf", sign);
+    }
+    let (subnormal, digits) = match single {
+        true => ((value as f32).is_subnormal(), f32::DIGITS),
+        false => (value.is_subnormal(), f64::DIGITS),
+    };
+    let round_trips = |text: &str| match single {
+        true => text.parse::<f32>() == Ok(value as f32),
+        false => text.parse::<f64>() == Ok(value),
+    };
+    let mut precision = if subnormal { 1 } else { digits as usize };
+    let scientific = loop {
+        // f32 is exact as f64, so it has the same digits
+        let scientific = format!("{:.*e}", precision - 1, value);
+        if precision >= 17 || round_trips(&scientific) {
+            break scientific;
+        }
+        precision += 1;
+    };
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    if exponent < -4 || exponent >= precision as i32 {
+        let mut mantissa = format!("{}.{}", &digits[..1], &digits[1..]);
+        trim_fraction(&mut mantissa);
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+        return format!(
+            "{}{}e{}{:02}",
+            sign,
+            mantissa,
+            exponent_sign,
+            exponent.abs()
+        );
+    }
+    let mut fixed = match exponent {
+        0.. => {
+            let point = exponent as usize + 1;
+            format!("{}.{}", &digits[..point], &digits[point..])
+        }
+        _ => format!("0.{}{}", "0".repeat((-exponent - 1) as usize), digits),
+    };
+    trim_fraction(&mut fixed);
+    format!("{}{}", sign, fixed)
+}
+
+/// No trailing zeros after the decimal point, and no point without the digits after it
+fn trim_fraction(number: &mut String) {
+    if number.contains('.') {
+        let trimmed = number.trim_end_matches('0').trim_end_matches('.').len();
+        number.truncate(trimmed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_types() {
+        let format = |kind, size, trailer| Format {
+            kind,
+            size,
+            trailer,
+        };
+        assert_eq!(
+            Types::parse("x1zdSfa").unwrap().0,
+            [
+                format(Kind::Hex, 1, true),
+                format(Kind::Signed, 2, false),
+                format(Kind::Float, 8, false),
+                format(Kind::Named, 1, false),
+            ]
+        );
+        assert_eq!(
+            Types::parse("fFoLc").unwrap().0[..2],
+            [format(Kind::Float, 4, false), format(Kind::Octal, 8, false),]
+        );
+        assert_eq!(
+            Types::parse("x1q").unwrap_err().0,
+            "invalid character 'q' in type string 'x1q'"
+        );
+        assert_eq!(
+            Types::parse("d3").unwrap_err().0,
+            "invalid type string 'd3';\nthis system doesn't provide a 3-byte integral type"
+        );
+    }
+
+    #[test]
+    fn writes_values() {
+        let value = |types: &str, bytes: &[u8], big_endian: bool| {
+            let format = Types::parse(types).unwrap().0[0];
+            let mut text = String::new();
+            format.write_value(&mut text, bytes, big_endian, format.width() + 1);
+            text
+        };
+        assert_eq!(value("d2", &[0xfe, 0xff], false), "     -2");
+        assert_eq!(value("u2", &[0xfe, 0xff], false), " 65534");
+        assert_eq!(value("o2", &[0x01, 0x00], true), " 000400");
+        assert_eq!(value("x4", &[0x0a, 0, 0, 0], false), " 0000000a");
+        assert_eq!(value("c", b"\n", false), "  \\n");
+        assert_eq!(value("c", b"\x80", false), " 200");
+        assert_eq!(value("a", b"\xa0", false), "  sp");
+    }
+
+    #[test]
+    fn formats_floats() {
+        assert_eq!(format_float(1.0, true), "1");
+        assert_eq!(format_float(0.1, true), "0.1");
+        assert_eq!(format_float(1.1431391e27, true), "1.1431391e+27");
+        assert_eq!(format_float(f32::from_bits(1) as f64, true), "1e-45");
+        assert_eq!(format_float(-0.0001, false), "-0.0001");
+        assert_eq!(format_float(123456.0, false), "123456");
+        assert_eq!(format_float(1e15, false), "1e+15");
+        assert_eq!(
+            format_float(7.30915479882693e-304, false),
+            "7.30915479882693e-304"
+        );
+        assert_eq!(format_float(f64::NEG_INFINITY, false), "-inf");
+    }
+}
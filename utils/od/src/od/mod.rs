@@ -0,0 +1,273 @@
+pub mod format;
+pub mod od_error;
+pub mod reverse;
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::args::Args;
+use format::Format;
+use od_error::OdError;
+
+/** The FILEs read one after another, as a single input.
+ *
+ * The FILEs, that can't be read, are reported and left out.
+ */
+pub struct Inputs<'a> {
+    files: &'a [String],
+    next: usize,
+    current: Option<(&'a str, Box<dyn Read>)>,
+    /// Bytes of -j, which are still to be skipped
+    skip: u64,
+    /// Whether some of the FILEs couldn't be read
+    pub failed: bool,
+}
+
+impl<'a> Inputs<'a> {
+    pub fn new(files: &'a [String]) -> Self {
+        Inputs {
+            files,
+            next: 0,
+            current: None,
+            skip: 0,
+            failed: false,
+        }
+    }
+
+    /// Skips the bytes, seeking in the regular files, and reading the rest of them
+    pub fn skip(&mut self, bytes: u64) -> Result<(), OdError> {
+        self.skip = bytes;
+        while self.skip > 0 {
+            if self.current.is_none() && !self.open_next() {
+                return Err(OdError::Skip);
+            }
+            if let Some((name, reader)) = &mut self.current {
+                match io::copy(&mut reader.take(self.skip), &mut io::sink()) {
+                    Ok(skipped) if skipped < self.skip => {
+                        self.skip -= skipped;
+                        self.current = None;
+                    }
+                    Ok(_) => self.skip = 0,
+                    Err(e) => {
+                        let error = OdError::Read(name.to_string(), e);
+                        self.fail(error);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fills the buffer, unless all the FILEs are over, returns how much is there
+    pub fn read(&mut self, buffer: &mut [u8]) -> usize {
+        let mut filled = 0;
+        while filled < buffer.len() {
+            let Some((name, reader)) = &mut self.current else {
+                if self.open_next() {
+                    continue;
+                }
+                break;
+            };
+            match reader.read(&mut buffer[filled..]) {
+                Ok(0) => self.current = None,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    let error = OdError::Read(name.to_string(), e);
+                    self.fail(error);
+                }
+            }
+        }
+        filled
+    }
+
+    /// Opens the next FILE, which can be read, seeking over the bytes to skip in the regular ones
+    fn open_next(&mut self) -> bool {
+        while let Some(name) = self.files.get(self.next) {
+            self.next += 1;
+            if name == "-" {
+                self.current = Some((name, Box::new(io::stdin())));
+                return true;
+            }
+            let opened = File::open(name).and_then(|mut file| {
+                let metadata = file.metadata()?;
+                if self.skip > 0 && metadata.is_file() {
+                    let skipped = self.skip.min(metadata.len());
+                    file.seek(SeekFrom::Start(skipped))?;
+                    self.skip -= skipped;
+                }
+                Ok(file)
+            });
+            match opened {
+                Ok(file) => {
+                    self.current = Some((name, Box::new(file)));
+                    return true;
+                }
+                Err(e) => self.fail(OdError::Read(name.to_string(), e)),
+            }
+        }
+        false
+    }
+
+    /// Reports the FILE, going on with the next one
+    fn fail(&mut self, error: OdError) {
+        eprintln!("od: {}", error);
+        self.failed = true;
+        self.current = None;
+    }
+}
+
+/** Dumps the input in the formats, a line of each one per block of -w bytes.
+ *
+ * The blocks, which are the same as the previous one, are shown as a single
+ * "*", unless -v. The offset after the input ends the dump.
+ */
+pub fn dump<W: Write>(args: &Args, inputs: &mut Inputs, output: &mut W) -> Result<(), OdError> {
+    inputs.skip(args.skip_bytes)?;
+    let width = args.width;
+    let pads = pads(&args.formats, width);
+    let mut block = vec![0; width];
+    let mut previous = vec![0; width];
+    let mut offset = args.skip_bytes;
+    let mut left = args.read_bytes.unwrap_or(u64::MAX);
+    let mut first = true;
+    let mut squeezed = false;
+    while left > 0 {
+        let wanted = width.min(usize::try_from(left).unwrap_or(usize::MAX));
+        let read = inputs.read(&mut block[..wanted]);
+        if read == 0 {
+            break;
+        }
+        // The values, that are cut by the end, are padded with zeros
+        block[read..].fill(0);
+        if !args.output_duplicates && !first && read == width && block == previous {
+            if !squeezed {
+                output.write_all(b"*\n").map_err(OdError::Write)?;
+                squeezed = true;
+            }
+        } else {
+            squeezed = false;
+            let text = format_block(args, &pads, offset, &block, read);
+            output.write_all(text.as_bytes()).map_err(OdError::Write)?;
+        }
+        first = false;
+        previous.copy_from_slice(&block);
+        offset += read as u64;
+        left -= read as u64;
+        if read < wanted {
+            break;
+        }
+    }
+    if let Some(address) = format_address(args.address_radix, offset) {
+        writeln!(output, "{}", address).map_err(OdError::Write)?;
+    }
+    Ok(())
+}
+
+/// The spaces, that every format needs in a block, so the fields of all of them line up
+fn pads(formats: &[Format], width: usize) -> Vec<usize> {
+    let block_width = |format: &Format, extra| (format.width() + extra) * (width / format.size);
+    let widest = formats
+        .iter()
+        .map(|format| block_width(format, 1))
+        .max()
+        .unwrap_or(0);
+    formats
+        .iter()
+        .map(|format| widest - block_width(format, 0))
+        .collect()
+}
+
+/// The width of the address, the lines of the formats after the first one start with as many spaces
+fn address_width(radix: char) -> usize {
+    match radix {
+        'd' | 'o' => 7,
+        'x' => 6,
+        _ => 0,
+    }
+}
+
+fn format_address(radix: char, offset: u64) -> Option<String> {
+    match radix {
+        'd' => Some(format!("{:07}", offset)),
+        'o' => Some(format!("{:07o}", offset)),
+        'x' => Some(format!("{:06x}", offset)),
+        _ => None,
+    }
+}
+
+/** A line of each format for the block, with the address before the first one.
+ *
+ * The pad is spread between the fields, so they are aligned to the widest format.
+ */
+fn format_block(args: &Args, pads: &[usize], offset: u64, block: &[u8], read: usize) -> String {
+    let mut text = String::new();
+    let address = format_address(args.address_radix, offset).unwrap_or_default();
+    let indent = " ".repeat(address_width(args.address_radix));
+    for (i, (format, &pad)) in args.formats.iter().zip(pads).enumerate() {
+        text.push_str(if i == 0 { &address } else { &indent });
+        let fields = block.len() / format.size;
+        let blank = (block.len() - read) / format.size;
+        let mut pad_left = pad;
+        for (field, bytes) in block.chunks(format.size).take(fields - blank).enumerate() {
+            let next_pad = pad * (fields - field - 1) / fields;
+            let width = pad_left - next_pad + format.width();
+            format.write_value(&mut text, bytes, args.big_endian, width);
+            pad_left = next_pad;
+        }
+        if format.trailer {
+            let width = blank * format.width() + pad * blank / fields;
+            text.push_str(&" ".repeat(width));
+            text.push_str("  >");
+            for &byte in &block[..read] {
+                text.push(match byte {
+                    b' '..=b'~' => byte as char,
+                    _ => '.',
+                });
+            }
+            text.push('<');
+        }
+        text.push('\n');
+    }
+    text
+}
+
+#[cfg(test)]
+mod test {
+    use super::format::Types;
+    use super::*;
+
+    fn block(types: &[&str], width: usize, block: &[u8], read: usize) -> String {
+        let args = Args {
+            formats: types
+                .iter()
+                .flat_map(|types| Types::parse(types).unwrap().0)
+                .collect(),
+            address_radix: 'o',
+            width,
+            ..Default::default()
+        };
+        let pads = pads(&args.formats, width);
+        format_block(&args, &pads, 8, block, read)
+    }
+
+    #[test]
+    fn aligns_the_formats() {
+        assert_eq!(
+            block(&["x1z", "d2"], 4, b"ab\0\x80", 4),
+            concat!("0000010  61 62  00 80  >ab..<\n", "         25185 -32768\n")
+        );
+        assert_eq!(
+            block(&["c", "o2"], 4, b"\n\0\0\0", 1),
+            "0000010  \\n\n         000012\n"
+        );
+    }
+
+    #[test]
+    fn pads_the_trailer_of_short_blocks() {
+        assert_eq!(
+            block(&["x2z"], 8, b"abc\0\0\0\0\0", 3),
+            "0000010 6261 0063            >abc<\n"
+        );
+    }
+}
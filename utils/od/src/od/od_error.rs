@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum OdError {
+    /// Opening or reading a FILE, GNU reports both the same way
+    Read(String, io::Error),
+    /// Opening the file to patch with -r
+    Create(String, io::Error),
+    Write(io::Error),
+    /// -j goes beyond all the FILEs
+    Skip,
+}
+
+impl fmt::Display for OdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OdError::Read(name, err) | OdError::Create(name, err) => {
+                write!(f, "{}: {}", quote(name), strerror(err))
+            }
+            OdError::Write(err) => write!(f, "write error: {}", strerror(err)),
+            OdError::Skip => write!(f, "cannot skip past end of combined input"),
+        }
+    }
+}
+
+impl Error for OdError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            OdError::Read(_, err) | OdError::Create(_, err) | OdError::Write(err) => Some(err),
+            OdError::Skip => None,
+        }
+    }
+}
+
+/// Wrong arguments, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
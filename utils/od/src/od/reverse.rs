@@ -0,0 +1,160 @@
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+
+use chunk_reader::ChunkReader;
+
+use super::od_error::OdError;
+
+/** Writes the bytes of an xxd dump back, for -r.
+ *
+ * The lines are "ADDRESS: HEX HEX...  TEXT", the bytes go to the address, so
+ * a file can be patched in place, and the text is ignored. The lines without
+ * the address are ignored too. With plain, all the hex digits are the bytes
+ * one after another, the way "xxd -r -p" reads them.
+ */
+pub fn reverse<W: Write + Seek>(
+    name: &str,
+    input: Box<dyn Read>,
+    output: &mut W,
+    plain: bool,
+) -> Result<(), OdError> {
+    let mut position = 0;
+    let mut high_digit = None;
+    for line in BufReader::new(input).chunks(b'\n', 0) {
+        let line = line.map_err(|e| OdError::Read(name.to_string(), e))?;
+        if plain {
+            for digit in line.iter().filter_map(|&c| hex_digit(c)) {
+                match high_digit.take() {
+                    Some(high) => output
+                        .write_all(&[high << 4 | digit])
+                        .map_err(OdError::Write)?,
+                    None => high_digit = Some(digit),
+                }
+            }
+            continue;
+        }
+        let Some((address, bytes)) = parse_line(&line) else {
+            continue;
+        };
+        if address != position {
+            output
+                .seek(SeekFrom::Start(address))
+                .map_err(OdError::Write)?;
+        }
+        output.write_all(&bytes).map_err(OdError::Write)?;
+        position = address + bytes.len() as u64;
+    }
+    Ok(())
+}
+
+/// The address and the bytes of a dump line, the groups of hex digits end with two spaces
+fn parse_line(line: &[u8]) -> Option<(u64, Vec<u8>)> {
+    let colon = line.iter().position(|&c| c == b':')?;
+    let address = std::str::from_utf8(&line[..colon]).ok()?.trim();
+    let address = u64::from_str_radix(address, 16).ok()?;
+    let mut bytes = Vec::new();
+    let mut rest = &line[colon + 1..];
+    loop {
+        match rest {
+            [b' ' | b'\t', b' ' | b'\t', ..] => break,
+            [b' ' | b'\t', after @ ..] => rest = after,
+            _ => {}
+        }
+        match rest {
+            [high, low, after @ ..] => match (hex_digit(*high), hex_digit(*low)) {
+                (Some(high), Some(low)) => {
+                    bytes.push(high << 4 | low);
+                    rest = after;
+                }
+                _ => break,
+            },
+            _ => break,
+        }
+    }
+    Some((address, bytes))
+}
+
+fn hex_digit(c: u8) -> Option<u8> {
+    (c as char).to_digit(16).map(|digit| digit as u8)
+}
+
+/** A writer, that can only seek forward, filling the gap with zeros.
+ *
+ * That's what the standard output can do, when it's a pipe.
+ */
+pub struct ForwardOnly<W> {
+    writer: W,
+    position: u64,
+}
+
+impl<W: Write> ForwardOnly<W> {
+    pub fn new(writer: W) -> Self {
+        ForwardOnly {
+            writer,
+            position: 0,
+        }
+    }
+}
+
+impl<W: Write> Write for ForwardOnly<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.position += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+impl<W: Write> Seek for ForwardOnly<W> {
+    fn seek(&mut self, to: SeekFrom) -> io::Result<u64> {
+        match to {
+            SeekFrom::Start(position) if position >= self.position => {
+                let gap = position - self.position;
+                io::copy(&mut io::repeat(0).take(gap), self)?;
+                Ok(self.position)
+            }
+            _ => Err(io::Error::other("cannot seek backwards")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn reversed(dump: &'static str, plain: bool) -> Vec<u8> {
+        let mut output = Cursor::new(b"0123456789".to_vec());
+        reverse("-", Box::new(Cursor::new(dump)), &mut output, plain).unwrap();
+        output.into_inner()
+    }
+
+    #[test]
+    fn patches_at_the_addresses() {
+        let dump = concat!(
+            "00000000: 6865 6c6c 6f0a                           hello.\n",
+            "not a dump line\n",
+            "00000008: 4142  AB\n",
+        );
+        assert_eq!(reversed(dump, false), b"hello\n67AB");
+        assert_eq!(reversed("0000000c: 21", false), b"0123456789\0\0!");
+    }
+
+    #[test]
+    fn reads_plain_hex() {
+        assert_eq!(reversed("68 65\n6c6c6\n", true), b"hell456789");
+    }
+
+    #[test]
+    fn fills_the_gaps_going_forward() {
+        let mut output = ForwardOnly::new(Vec::new());
+        output.write_all(b"ab").unwrap();
+        output.seek(SeekFrom::Start(4)).unwrap();
+        output.write_all(b"c").unwrap();
+        assert!(output.seek(SeekFrom::Start(1)).is_err());
+        assert_eq!(output.writer, b"ab\0\0c");
+    }
+}
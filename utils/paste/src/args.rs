@@ -0,0 +1,42 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use crate::paste::delimiters::Delimiters;
+use crate::paste::paste_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Write lines consisting of the sequentially corresponding lines from
+/// each FILE, separated by TABs, to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input
+    #[arg(default_value = "-")]
+    pub file: Vec<String>,
+
+    /// reuse characters from LIST instead of TABs
+    #[arg(short = 'd', long, value_name = "LIST", value_parser = Delimiters::parse, default_value = "\\t")]
+    pub delimiters: Delimiters,
+
+    /// paste one file at a time instead of in parallel
+    #[arg(short = 's', long, default_value_t = false)]
+    pub serial: bool,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                eprintln!("paste: {}", invalid);
+                std::process::exit(1);
+            }
+            err.exit()
+        })
+    }
+}
@@ -0,0 +1,26 @@
+mod args;
+mod paste;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use paste::paste_error::PasteError;
+use paste::{paste_parallel, paste_serial, Inputs};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let terminator = if args.zero_terminated { b'\0' } else { b'\n' };
+    let mut output = BufWriter::new(io::stdout());
+    let result = Inputs::open(&args.file, terminator).and_then(|mut inputs| match args.serial {
+        true => paste_serial(&mut inputs, &args.delimiters, terminator, &mut output),
+        false => paste_parallel(&mut inputs, &args.delimiters, terminator, &mut output),
+    });
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(PasteError::Write);
+    if let Err(e) = result.and(flushed) {
+        eprintln!("paste: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
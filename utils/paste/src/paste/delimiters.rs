@@ -0,0 +1,75 @@
+use super::paste_error::ArgError;
+
+/** The delimiters to reuse one after another, as given to -d.
+ *
+ * Each one is a single character, \0 stands for no delimiter at all.
+ * Unlike GNU, a multibyte UTF-8 character is kept whole.
+ */
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delimiters(Vec<String>);
+
+impl Default for Delimiters {
+    fn default() -> Self {
+        Delimiters(vec!["\t".into()])
+    }
+}
+
+impl Delimiters {
+    pub fn parse(list: &str) -> Result<Delimiters, ArgError> {
+        let mut delimiters = Vec::new();
+        let mut chars = list.chars();
+        while let Some(c) = chars.next() {
+            let delimiter = match c {
+                '\\' => match chars.next() {
+                    Some('0') => String::new(),
+                    Some('b') => "\x08".into(),
+                    Some('f') => "\x0c".into(),
+                    Some('n') => "\n".into(),
+                    Some('r') => "\r".into(),
+                    Some('t') => "\t".into(),
+                    Some('v') => "\x0b".into(),
+                    Some(c) => c.into(),
+                    None => {
+                        return Err(ArgError(format!(
+                            "delimiter list ends with an unescaped backslash: {}",
+                            list
+                        )))
+                    }
+                },
+                c => c.into(),
+            };
+            delimiters.push(delimiter);
+        }
+        // An empty list is the same as \0
+        if delimiters.is_empty() {
+            delimiters.push(String::new());
+        }
+        Ok(Delimiters(delimiters))
+    }
+
+    /// The delimiter after the given item of a line, they go round
+    pub fn get(&self, index: usize) -> &[u8] {
+        self.0[index % self.0.len()].as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_escapes() {
+        let delimiters = Delimiters::parse(r"a\n\t\\\0\qé").unwrap();
+        let all: Vec<&[u8]> = (0..7).map(|i| delimiters.get(i)).collect();
+        assert_eq!(
+            all,
+            [&b"a"[..], b"\n", b"\t", b"\\", b"", b"q", "é".as_bytes()]
+        );
+        assert_eq!(delimiters.get(7), b"a");
+        assert_eq!(Delimiters::parse("").unwrap().get(3), b"");
+        assert_eq!(
+            Delimiters::parse(r"a\").unwrap_err().0,
+            r"delimiter list ends with an unescaped backslash: a\"
+        );
+    }
+}
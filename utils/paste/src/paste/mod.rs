@@ -0,0 +1,184 @@
+pub mod delimiters;
+pub mod paste_error;
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::iter::Fuse;
+
+use chunk_reader::{ChunkReader, Chunked};
+
+use delimiters::Delimiters;
+use paste_error::PasteError;
+
+/// A single input, read line by line
+pub struct Input {
+    name: String,
+    lines: Fuse<Chunked<BufReader<Box<dyn Read>>>>,
+    terminator: u8,
+}
+
+impl Input {
+    pub fn new(name: &str, reader: Box<dyn Read>, terminator: u8) -> Self {
+        Input {
+            name: name.to_string(),
+            // Fused, so the standard input isn't read any more after its end
+            lines: BufReader::new(reader).chunks(terminator, 0).fuse(),
+            terminator,
+        }
+    }
+
+    /// The next line without its terminator
+    fn next_line(&mut self) -> Result<Option<Vec<u8>>, PasteError> {
+        match self.lines.next() {
+            Some(Ok(mut line)) => {
+                if line.last() == Some(&self.terminator) {
+                    line.pop();
+                }
+                Ok(Some(line))
+            }
+            Some(Err(e)) => Err(PasteError::Read(self.name.clone(), e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/** The inputs to paste, with every FILE operand pointing to one of them.
+ *
+ * All the "-" operands share the single standard input, taking its lines in turn.
+ */
+pub struct Inputs {
+    inputs: Vec<Input>,
+    operands: Vec<usize>,
+}
+
+impl Inputs {
+    /// Opens all the files first, so a missing one is reported before any output
+    pub fn open(files: &[String], terminator: u8) -> Result<Self, PasteError> {
+        let mut inputs = Vec::new();
+        let mut operands = Vec::new();
+        let mut stdin = None;
+        for name in files {
+            let index = match name.as_str() {
+                "-" => *stdin.get_or_insert_with(|| {
+                    inputs.push(Input::new(name, Box::new(io::stdin()), terminator));
+                    inputs.len() - 1
+                }),
+                _ => {
+                    let file =
+                        File::open(name).map_err(|e| PasteError::Read(name.to_string(), e))?;
+                    inputs.push(Input::new(name, Box::new(file), terminator));
+                    inputs.len() - 1
+                }
+            };
+            operands.push(index);
+        }
+        Ok(Inputs { inputs, operands })
+    }
+
+    #[cfg(test)]
+    fn from_inputs(inputs: Vec<Input>, operands: Vec<usize>) -> Self {
+        Inputs { inputs, operands }
+    }
+}
+
+/** Joins the corresponding lines of all the inputs, until all of them are over.
+ *
+ * An input, that is over, still gets its delimiter, as if its line was empty.
+ */
+pub fn paste_parallel<W: Write>(
+    inputs: &mut Inputs,
+    delimiters: &Delimiters,
+    terminator: u8,
+    output: &mut W,
+) -> Result<(), PasteError> {
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let mut any = false;
+        for (i, &index) in inputs.operands.iter().enumerate() {
+            if i > 0 {
+                line.extend_from_slice(delimiters.get(i - 1));
+            }
+            if let Some(part) = inputs.inputs[index].next_line()? {
+                line.extend_from_slice(&part);
+                any = true;
+            }
+        }
+        if !any {
+            return Ok(());
+        }
+        line.push(terminator);
+        output.write_all(&line).map_err(PasteError::Write)?;
+    }
+}
+
+/// Joins all the lines of each input into a single one
+pub fn paste_serial<W: Write>(
+    inputs: &mut Inputs,
+    delimiters: &Delimiters,
+    terminator: u8,
+    output: &mut W,
+) -> Result<(), PasteError> {
+    for &index in &inputs.operands {
+        let input = &mut inputs.inputs[index];
+        let mut count = 0;
+        while let Some(line) = input.next_line()? {
+            if count > 0 {
+                output
+                    .write_all(delimiters.get(count - 1))
+                    .map_err(PasteError::Write)?;
+            }
+            output.write_all(&line).map_err(PasteError::Write)?;
+            count += 1;
+        }
+        output.write_all(&[terminator]).map_err(PasteError::Write)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn inputs(contents: &[&'static str], operands: Vec<usize>) -> Inputs {
+        let inputs = contents
+            .iter()
+            .map(|content| Input::new("-", Box::new(Cursor::new(*content)), b'\n'))
+            .collect();
+        Inputs::from_inputs(inputs, operands)
+    }
+
+    fn paste(serial: bool, delimiters: &str, mut inputs: Inputs) -> String {
+        let delimiters = Delimiters::parse(delimiters).unwrap();
+        let mut output = Vec::new();
+        if serial {
+            paste_serial(&mut inputs, &delimiters, b'\n', &mut output).unwrap();
+        } else {
+            paste_parallel(&mut inputs, &delimiters, b'\n', &mut output).unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn pastes_in_parallel() {
+        let files = || inputs(&["1\n2\n3\n", "a\nb", ""], vec![0, 1, 2]);
+        assert_eq!(paste(false, r"\t", files()), "1\ta\t\n2\tb\t\n3\t\t\n");
+        assert_eq!(paste(false, r"\0x", files()), "1ax\n2bx\n3x\n");
+    }
+
+    #[test]
+    fn pastes_serially() {
+        let files = || inputs(&["1\n2\n3\n", "a\nb", ""], vec![0, 1, 2]);
+        assert_eq!(paste(true, r"\t", files()), "1\t2\t3\na\tb\n\n");
+        assert_eq!(paste(true, ":,", files()), "1:2,3\na:b\n\n");
+    }
+
+    #[test]
+    fn shares_the_same_input() {
+        let stdin = || inputs(&["1\n2\n3\n4\n5\n"], vec![0, 0]);
+        assert_eq!(paste(false, r"\t", stdin()), "1\t2\n3\t4\n5\t\n");
+        assert_eq!(paste(true, r"\t", stdin()), "1\t2\t3\t4\t5\n\n");
+    }
+}
@@ -0,0 +1,462 @@
+use std::error::Error;
+use std::ffi::OsString;
+
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgMatches, CommandFactory, FromArgMatches, Parser};
+
+use crate::pr::pr_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+const PAGE_LENGTH: usize = 66;
+const PAGE_WIDTH: usize = 72;
+const NUMBER_DIGITS: usize = 5;
+
+/// Paginate or columnate FILE(s) for printing
+#[derive(Parser, Debug, Default)]
+#[command(
+    author,
+    version,
+    about,
+    long_about,
+    args_override_self = true,
+    disable_help_flag = true
+)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input. +FIRST_PAGE[:LAST_PAGE]
+    /// is the same as --pages
+    #[arg(value_name = "FILE")]
+    pub files: Vec<String>,
+
+    /// begin [stop] printing with page FIRST_[LAST_]PAGE
+    #[arg(long, value_name = "FIRST_PAGE[:LAST_PAGE]", value_parser = parse_pages_option)]
+    pub pages: Option<(u64, u64)>,
+
+    /// output COLUMN columns and print columns down, unless -a is used;
+    /// balance number of lines in the columns on each page; -COLUMN is the same
+    #[arg(long, value_name = "COLUMN", value_parser = parse_columns)]
+    pub columns: Option<usize>,
+
+    /// print columns across rather than down, used together with -COLUMN
+    #[arg(short = 'a', long, default_value_t = false)]
+    pub across: bool,
+
+    /// double space the output
+    #[arg(short = 'd', long, default_value_t = false)]
+    pub double_space: bool,
+
+    /// use a centered HEADER instead of filename in page header,
+    /// -h "" prints a blank line, don't use -h""
+    #[arg(short = 'h', long, value_name = "HEADER")]
+    pub header: Option<String>,
+
+    /// set the page length to PAGE_LENGTH (66) lines (default number of lines
+    /// of text 56, and with -F 63); implies -t if PAGE_LENGTH <= 10
+    #[arg(short = 'l', long, value_name = "PAGE_LENGTH", value_parser = parse_length, default_value_t = PAGE_LENGTH)]
+    pub length: usize,
+
+    /// print all files in parallel, one in each column, truncate lines,
+    /// but join lines of full length with -J
+    #[arg(short = 'm', long, default_value_t = false)]
+    pub merge: bool,
+
+    /// number lines, use DIGITS (5) digits, then SEP (TAB), default counting
+    /// starts with 1st line of input file
+    #[arg(short = 'n', long, value_name = "[SEP[DIGITS]]", num_args = 0..=1, require_equals = true, default_missing_value = "")]
+    pub number_lines: Option<String>,
+
+    /// offset each line with MARGIN (zero) spaces
+    #[arg(short = 'o', long, value_name = "MARGIN", value_parser = parse_indent, default_value_t = 0)]
+    pub indent: usize,
+
+    /// omit page headers and trailers; implied if PAGE_LENGTH <= 10
+    #[arg(short = 't', long, default_value_t = false)]
+    pub omit_header: bool,
+
+    /// omit page headers and trailers, eliminate any pagination by form feeds
+    /// set in input files
+    #[arg(short = 'T', long, default_value_t = false)]
+    pub omit_pagination: bool,
+
+    /// set page width to PAGE_WIDTH (72) characters for multiple text-column
+    /// output only
+    #[arg(short = 'w', long, value_name = "PAGE_WIDTH", value_parser = parse_width)]
+    pub width: Option<usize>,
+
+    /// set page width to PAGE_WIDTH (72) characters always, truncate lines
+    #[arg(short = 'W', long, value_name = "PAGE_WIDTH", value_parser = parse_page_width)]
+    pub page_width: Option<usize>,
+
+    /// Print help, there's no -h, it's --header
+    #[arg(long, action = ArgAction::Help)]
+    pub help: Option<bool>,
+
+    /// The characters of the page, the header is centered in them
+    #[arg(skip)]
+    pub chars_per_line: usize,
+
+    /// The lines are cut at the column width
+    #[arg(skip)]
+    pub truncate_lines: bool,
+
+    /// -w without the columns, the lines aren't cut then
+    #[arg(skip)]
+    pub join_lines: bool,
+
+    /// The page headers and trailers, unless -t or -T
+    #[arg(skip)]
+    pub extremities: bool,
+
+    /// The form feeds of the input are written, with -t
+    #[arg(skip)]
+    pub keep_form_feeds: bool,
+
+    /// The separator after the numbers of -n, and their digits
+    #[arg(skip)]
+    pub line_numbers: Option<(char, usize)>,
+
+    #[arg(skip)]
+    pub first_page: u64,
+
+    #[arg(skip)]
+    pub last_page: u64,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let arguments = traditional_options(std::env::args_os());
+        let matches = <Self as CommandFactory>::command()
+            .try_get_matches_from(arguments)
+            .unwrap_or_else(|err| {
+                if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                    fatal(&invalid.0);
+                }
+                err.exit()
+            });
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        if let Some(number_lines) = &args.number_lines {
+            args.line_numbers = Some(parse_number_lines(number_lines).unwrap_or_else(|err| {
+                usage_error(&err.0);
+            }));
+        }
+        (args.first_page, args.last_page) = args.pages.unwrap_or((1, u64::MAX));
+        if args.merge && args.columns.is_some() {
+            fatal("cannot specify number of columns when printing in parallel");
+        }
+        if args.merge && args.across {
+            fatal("cannot specify both printing across and printing in parallel");
+        }
+        args.page_widths(&matches);
+        // The last one of -t and -T tells if the form feeds are kept
+        args.extremities = !args.omit_header && !args.omit_pagination;
+        args.keep_form_feeds =
+            last_index(&matches, "omit_header") > last_index(&matches, "omit_pagination");
+        args
+    }
+
+    /** The widths of -w and -W, the way GNU takes them in order.
+     *
+     * -w sets the width only for the columns, and only when -W didn't set it
+     * before; -W always does, and cuts the lines. -w without the columns lets
+     * the lines be, even after -W.
+     */
+    fn page_widths(&mut self, matches: &ArgMatches) {
+        self.chars_per_line = PAGE_WIDTH;
+        let mut widths = Vec::new();
+        if let Some(index) = last_index(matches, "width") {
+            widths.push((index, false, self.width.unwrap_or(PAGE_WIDTH)));
+        }
+        if let Some(index) = last_index(matches, "page_width") {
+            widths.push((index, true, self.page_width.unwrap_or(PAGE_WIDTH)));
+        }
+        widths.sort();
+        let mut old_w = false;
+        for (_, page_width, width) in widths {
+            old_w = !page_width;
+            if page_width {
+                self.truncate_lines = true;
+                self.chars_per_line = width;
+            } else if !self.truncate_lines {
+                self.chars_per_line = width;
+            }
+        }
+        if old_w {
+            match self.merge || self.columns.is_some() {
+                true => self.truncate_lines = true,
+                false => self.join_lines = true,
+            }
+        }
+    }
+}
+
+/// Where the option was given last, the default values don't count
+fn last_index(matches: &ArgMatches, id: &str) -> Option<usize> {
+    match matches.value_source(id) {
+        Some(ValueSource::CommandLine) => matches
+            .indices_of(id)
+            .and_then(|mut indices| indices.next_back()),
+        _ => None,
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("pr: {}", message);
+    eprintln!("Try 'pr --help' for more information.");
+    std::process::exit(1);
+}
+
+fn fatal(message: &str) -> ! {
+    eprintln!("pr: {}", message);
+    std::process::exit(1);
+}
+
+/** The traditional options go as the long ones, which clap takes.
+ *
+ * The digits of -COLUMN can be mixed with the other options, "-3a" is -3 -a,
+ * and the first +FIRST_PAGE[:LAST_PAGE] is --pages, unless it's given before.
+ * The values of the short options go with the equals sign, so that they may
+ * start with a hyphen, and the optional one of -n is only the rest of the
+ * cluster.
+ */
+fn traditional_options(arguments: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut arguments = arguments.peekable();
+    let mut traditional: Vec<OsString> = arguments.next().into_iter().collect();
+    let mut columns: Option<String> = None;
+    let mut after_digit = false;
+    let mut pages = false;
+    let mut end_of_options = None;
+    while let Some(argument) = arguments.next() {
+        let Some(text) = argument.to_str().filter(|_| end_of_options.is_none()) else {
+            traditional.push(argument);
+            continue;
+        };
+        if text == "--" {
+            end_of_options = Some(traditional.len());
+            traditional.push(argument);
+        } else if let Some(long) = text.strip_prefix("--") {
+            after_digit = false;
+            let name = long.split('=').next().unwrap_or_default();
+            match name {
+                "columns" => columns = None,
+                "pages" => pages = true,
+                _ => {}
+            }
+            let with_value = [
+                "columns",
+                "header",
+                "indent",
+                "length",
+                "page-width",
+                "pages",
+                "width",
+            ];
+            match arguments.peek() {
+                Some(value) if !long.contains('=') && with_value.contains(&name) => {
+                    let mut option = OsString::from(format!("--{}=", name));
+                    option.push(value);
+                    traditional.push(option);
+                    arguments.next();
+                }
+                _ => traditional.push(argument),
+            }
+        } else if let Some(range) = text.strip_prefix('+').filter(|_| !pages) {
+            match parse_pages(range, "+") {
+                Ok(Some(_)) => {
+                    pages = true;
+                    traditional.push(format!("--pages={}", range).into());
+                }
+                Ok(None) => traditional.push(argument),
+                Err(err) => fatal(&err.0),
+            }
+        } else if text.starts_with('-') && text.len() > 1 {
+            let chars = text[1..].char_indices();
+            for (i, option) in chars {
+                if option.is_ascii_digit() {
+                    let digits = columns.get_or_insert_with(String::new);
+                    if !after_digit {
+                        digits.clear();
+                    }
+                    digits.push(option);
+                    after_digit = true;
+                    continue;
+                }
+                after_digit = false;
+                let rest = &text[1 + i + option.len_utf8()..];
+                let long = match option {
+                    'h' => "header",
+                    'l' => "length",
+                    'o' => "indent",
+                    'w' => "width",
+                    'W' => "page-width",
+                    'n' if rest.is_empty() => {
+                        traditional.push("--number-lines".into());
+                        continue;
+                    }
+                    'n' => "number-lines",
+                    _ => {
+                        traditional.push(format!("-{}", option).into());
+                        continue;
+                    }
+                };
+                match (rest, arguments.peek()) {
+                    ("", Some(value)) if option != 'n' => {
+                        let mut with_value = OsString::from(format!("--{}=", long));
+                        with_value.push(value);
+                        traditional.push(with_value);
+                        arguments.next();
+                    }
+                    ("", None) => traditional.push(format!("-{}", option).into()),
+                    _ => traditional.push(format!("--{}={}", long, rest).into()),
+                }
+                break;
+            }
+        } else {
+            traditional.push(argument);
+        }
+    }
+    if let Some(digits) = columns {
+        let at = end_of_options.unwrap_or(traditional.len());
+        traditional.insert(at, format!("--columns={}", digits).into());
+    }
+    traditional
+}
+
+/// The number the way strtoimax() reads it, but without a suffix
+fn parse_int(number: &str, min: i64, message: &str) -> Result<usize, ArgError> {
+    let trimmed = number.trim_start();
+    let (negative, digits) = match trimmed.as_bytes().first() {
+        Some(b'-') => (true, &trimmed[1..]),
+        Some(b'+') => (false, &trimmed[1..]),
+        _ => (false, trimmed),
+    };
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(ArgError(format!("{}: {}", message, quote(number))));
+    }
+    let magnitude = digits.parse::<i64>().unwrap_or(i64::MAX);
+    let value = if negative { -magnitude } else { magnitude };
+    let max = i32::MAX as i64;
+    if value < min || value > max {
+        let reason = match value > max / 2 || value < -max / 2 {
+            true => "Value too large for defined data type",
+            false => "Numerical result out of range",
+        };
+        return Err(ArgError(format!(
+            "{}: {}: {}",
+            message,
+            quote(number),
+            reason
+        )));
+    }
+    Ok(value as usize)
+}
+
+fn parse_columns(columns: &str) -> Result<usize, ArgError> {
+    parse_int(columns, 1, "invalid number of columns")
+}
+
+fn parse_length(length: &str) -> Result<usize, ArgError> {
+    parse_int(length, 1, "'-l PAGE_LENGTH' invalid number of lines")
+}
+
+fn parse_indent(indent: &str) -> Result<usize, ArgError> {
+    parse_int(indent, 0, "'-o MARGIN' invalid line offset")
+}
+
+fn parse_width(width: &str) -> Result<usize, ArgError> {
+    parse_int(width, 1, "'-w PAGE_WIDTH' invalid number of characters")
+}
+
+fn parse_page_width(width: &str) -> Result<usize, ArgError> {
+    parse_int(width, 1, "'-W PAGE_WIDTH' invalid number of characters")
+}
+
+/// [SEP[DIGITS]], the separator is anything but a digit
+fn parse_number_lines(number_lines: &str) -> Result<(char, usize), ArgError> {
+    let mut chars = number_lines.chars();
+    let separator = match chars.next() {
+        Some(separator) if !separator.is_ascii_digit() => separator,
+        _ => {
+            chars = number_lines.chars();
+            '\t'
+        }
+    };
+    let digits = chars.as_str();
+    if digits.is_empty() {
+        return Ok((separator, NUMBER_DIGITS));
+    }
+    let invalid = |reason: &str| {
+        ArgError(format!(
+            "'-n' extra characters or invalid number in the argument: {}{}",
+            quote(digits),
+            reason
+        ))
+    };
+    match parse_int(digits, 1, "") {
+        Ok(digits) => Ok((separator, digits)),
+        Err(err) if err.0.ends_with("too large for defined data type") => {
+            Err(invalid(": Value too large for defined data type"))
+        }
+        Err(_) => Err(invalid("")),
+    }
+}
+
+fn parse_pages_option(pages: &str) -> Result<(u64, u64), ArgError> {
+    parse_pages(pages, "--pages")?
+        .ok_or_else(|| ArgError(format!("invalid page range {}", quote(pages))))
+}
+
+/// Why strtoumax() doesn't take a page number
+enum PageError {
+    Invalid,
+    Suffix,
+    TooLarge,
+}
+
+/// The page number the way strtoumax() reads it, and the rest after it
+fn parse_page(number: &str) -> Result<(u64, &str), PageError> {
+    let trimmed = number.trim_start();
+    let unsigned = trimmed.strip_prefix('+').unwrap_or(trimmed);
+    let digits = unsigned.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return Err(PageError::Invalid);
+    }
+    let page = unsigned[..digits]
+        .parse()
+        .map_err(|_| PageError::TooLarge)?;
+    Ok((page, &unsigned[digits..]))
+}
+
+/** FIRST_PAGE[:LAST_PAGE], None if it's not a range of pages.
+ *
+ * The numbers that can't be read are errors, they're told with the OPTION.
+ */
+fn parse_pages(pages: &str, option: &str) -> Result<Option<(u64, u64)>, ArgError> {
+    let error = |error| {
+        ArgError(match error {
+            PageError::Invalid => format!("invalid {} argument {}", option, quote(pages)),
+            PageError::Suffix => format!("invalid suffix in {} argument {}", option, quote(pages)),
+            PageError::TooLarge => format!("{} argument {} too large", option, quote(pages)),
+        })
+    };
+    let (first, rest) = parse_page(pages).map_err(error)?;
+    let (last, rest) = match rest.strip_prefix(':') {
+        Some(last) => match parse_page(last).map_err(error)? {
+            (last, "") => (last, ""),
+            _ => return Err(error(PageError::Suffix)),
+        },
+        None => (u64::MAX, rest),
+    };
+    match first != 0 && first <= last && rest.is_empty() {
+        true => Ok(Some((first, last))),
+        false => Ok(None),
+    }
+}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
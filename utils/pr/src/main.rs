@@ -0,0 +1,73 @@
+mod args;
+mod pr;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+use std::time::SystemTime;
+
+use args::Args;
+use pr::input::Input;
+use pr::pr_error::PrError;
+use pr::Pr;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout().lock());
+    let result = print_files(&args, &mut output);
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(PrError::Write);
+    match result.and_then(|opened| flushed.map(|_| opened)) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("pr: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/** Prints the FILEs one after the other, or side by side with -m.
+ *
+ * The FILEs that can't be opened are reported and left out, false then.
+ */
+fn print_files<W: Write>(args: &Args, output: &mut W) -> Result<bool, PrError> {
+    let files = match args.files.is_empty() {
+        true => vec!["-".to_string()],
+        false => args.files.clone(),
+    };
+    let parallel = args.merge && !args.files.is_empty();
+    let mut pr = Pr::new(args, files.len(), parallel, SystemTime::now(), output)?;
+    let mut opened = true;
+    let mut open = |name: &str, pr: &mut Pr<W>| match Input::open(name) {
+        Ok(input) => Ok(Some(input)),
+        Err(e @ PrError::Open(..)) => {
+            pr.flush()?;
+            eprintln!("pr: {}", e);
+            opened = false;
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    };
+    let print = |inputs, pr: &mut Pr<W>| match pr.print(inputs) {
+        Err(e @ PrError::PageCount { .. }) => {
+            pr.flush()?;
+            eprintln!("pr: {}", e);
+            Ok(())
+        }
+        result => result,
+    };
+    if parallel {
+        let mut inputs = Vec::new();
+        for name in &files {
+            inputs.extend(open(name, &mut pr)?);
+        }
+        print(inputs, &mut pr)?;
+    } else {
+        for name in &files {
+            if let Some(input) = open(name, &mut pr)? {
+                print(vec![input], &mut pr)?;
+            }
+        }
+    }
+    Ok(opened)
+}
@@ -0,0 +1,98 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::time::SystemTime;
+
+use chunk_reader::{ChunkReader, Chunked};
+use tab_stops::Chars;
+
+use super::pr_error::PrError;
+
+/// A character of the input, or a byte that isn't UTF-8
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Char {
+    bytes: [u8; 4],
+    len: usize,
+    pub c: Option<char>,
+}
+
+impl Char {
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    pub fn is(&self, byte: u8) -> bool {
+        self.bytes() == [byte]
+    }
+}
+
+/// Whether the character was read, and is the BYTE
+pub fn is(c: Option<Char>, byte: u8) -> bool {
+    c.is_some_and(|c| c.is(byte))
+}
+
+/** A FILE read a character at a time, the last one can be put back.
+ *
+ * The lines are read whole, so the characters don't get split between them.
+ */
+pub struct Input {
+    pub name: String,
+    /// None for the standard input, the time it's printed is used then
+    pub modified: Option<SystemTime>,
+    lines: Chunked<BufReader<Box<dyn Read>>>,
+    line: Vec<u8>,
+    at: usize,
+}
+
+impl Input {
+    pub fn open(name: &str) -> Result<Input, PrError> {
+        let open_error = |e| PrError::Open(name.to_string(), e);
+        let (input, modified): (Box<dyn Read>, _) = match name {
+            "-" => (Box::new(io::stdin()), None),
+            _ => {
+                let file = File::open(name).map_err(open_error)?;
+                let modified = file.metadata().and_then(|m| m.modified()).ok();
+                (Box::new(file), modified)
+            }
+        };
+        Ok(Input::new(name, modified, input))
+    }
+
+    pub fn new(name: &str, modified: Option<SystemTime>, input: Box<dyn Read>) -> Input {
+        Input {
+            name: name.to_string(),
+            modified,
+            lines: BufReader::new(input).chunks(b'\n', 0),
+            line: Vec::new(),
+            at: 0,
+        }
+    }
+
+    /// The next character, None at the end
+    pub fn getc(&mut self) -> Result<Option<Char>, PrError> {
+        while self.at == self.line.len() {
+            match self.lines.next() {
+                None => return Ok(None),
+                Some(line) => {
+                    self.line = line.map_err(|e| PrError::Read(self.name.clone(), e))?;
+                    self.at = 0;
+                }
+            }
+        }
+        let (bytes, c) = Chars::new(&self.line[self.at..]).next().unwrap_or_default();
+        let mut char = Char {
+            bytes: [0; 4],
+            len: bytes.len(),
+            c,
+        };
+        char.bytes[..bytes.len()].copy_from_slice(bytes);
+        self.at += char.len;
+        Ok(Some(char))
+    }
+
+    /// Puts back the character just read, there's nothing to put back at the end
+    pub fn ungetc(&mut self, c: Option<Char>) {
+        if let Some(c) = c {
+            self.at -= c.len;
+        }
+    }
+}
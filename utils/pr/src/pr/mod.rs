@@ -0,0 +1,1167 @@
+pub mod input;
+pub mod pr_error;
+
+use std::io::Write;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use line_number::line_number;
+use tab_stops::{width, Chars, TabStops};
+
+use crate::args::Args;
+use input::{is, Char, Input};
+use pr_error::PrError;
+
+const LINES_PER_HEADER: usize = 5;
+const LINES_PER_FOOTER: usize = 5;
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// The columns are separated by it, or by the tabs it's converted to
+const COLUMN_SEPARATOR: &[u8] = b" ";
+/// The start of a column that goes right after the previous one
+const ANYWHERE: isize = 0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Status {
+    Open,
+    /// A form feed ended the page of the columns down
+    FormFeed,
+    /// A form feed ended the page, the rest goes on the next one
+    OnHold,
+    Closed,
+}
+
+/// A column of the page, with the input it's read from
+struct Column {
+    input: usize,
+    status: Status,
+    /// The columns down are stored before a page is printed, to balance them
+    stored: bool,
+    current_line: usize,
+    lines_stored: usize,
+    lines_to_print: isize,
+    start_position: isize,
+    numbered: bool,
+    /// The page ended with it, so a form feed right after doesn't make an empty page
+    full_page_printed: bool,
+}
+
+/// The text of a character as it's printed, and the columns it takes
+enum Clump {
+    Char(Char, isize),
+    Spaces(isize),
+    /// A backspace at the start of the line
+    Nothing,
+}
+
+/** Prints the FILEs in pages, the way GNU pr does.
+ *
+ * The lines go through the columns one by one, each column is read up to its
+ * width, and the rest of the line is skipped. The padding between the columns
+ * is only written before something that's printed after it, with the tabs
+ * where they fit. The columns down are read a page at a time, and balanced.
+ */
+pub struct Pr<'a, W: Write> {
+    output: &'a mut W,
+    now: SystemTime,
+    header: Option<String>,
+    margin: isize,
+    chars_per_line: isize,
+    chars_per_column: isize,
+    columns: usize,
+    parallel: bool,
+    storing: bool,
+    double_space: bool,
+    extremities: bool,
+    keep_form_feeds: bool,
+    lines_per_body: usize,
+    truncate_lines: bool,
+    untabify_input: bool,
+    tabify_output: bool,
+    column_separator: &'static [u8],
+    /// The separator after the numbers of -n, and their digits
+    line_numbers: Option<(char, usize)>,
+    number_width: isize,
+    first_page: u64,
+    last_page: u64,
+    tab_stops: TabStops,
+
+    inputs: Vec<Input>,
+    column_vector: Vec<Column>,
+    files_ready_to_read: isize,
+    page_number: u64,
+    line_number: usize,
+    line_count: usize,
+    date_text: String,
+    file_text: String,
+    header_width_available: isize,
+    output_position: isize,
+    input_position: isize,
+    spaces_not_printed: isize,
+    separators_not_printed: isize,
+    padding_not_printed: isize,
+    pad_vertically: bool,
+    align_empty_columns: bool,
+    empty_line: bool,
+    form_feed_only: bool,
+    print_a_header: bool,
+    print_a_form_feed: bool,
+    last_line: bool,
+    clump: Clump,
+    /// The lines of the stored columns, where each one starts, and its width
+    buffer: Vec<u8>,
+    line_starts: Vec<usize>,
+    line_ends: Vec<isize>,
+}
+
+impl<'a, W: Write> Pr<'a, W> {
+    /// The layout of the page for the FILES, each one in its column when PARALLEL
+    pub fn new(
+        args: &Args,
+        files: usize,
+        parallel: bool,
+        now: SystemTime,
+        output: &'a mut W,
+    ) -> Result<Self, PrError> {
+        let mut extremities = args.extremities;
+        let mut keep_form_feeds = args.keep_form_feeds;
+        if args.length <= LINES_PER_HEADER + LINES_PER_FOOTER {
+            extremities = false;
+            keep_form_feeds = true;
+        }
+        let mut lines_per_body = match extremities {
+            true => args.length - LINES_PER_HEADER - LINES_PER_FOOTER,
+            false => args.length,
+        };
+        if args.double_space {
+            lines_per_body /= 2;
+        }
+        let columns = match parallel {
+            true => files,
+            false => args.columns.unwrap_or(1),
+        };
+        let multiple = columns > 1;
+        let column_separator = match multiple {
+            true => COLUMN_SEPARATOR,
+            false => b"",
+        };
+        let tab_stops = TabStops::default();
+        let mut number_width = 0;
+        let mut chars_used_by_number = 0;
+        if let Some((separator, digits)) = args.line_numbers {
+            number_width = digits as isize
+                + match separator {
+                    '\t' => tab_width(&tab_stops, digits as isize),
+                    _ => 1,
+                };
+            if parallel {
+                chars_used_by_number = number_width;
+            }
+        }
+        let separators = (columns as isize - 1) * column_separator.len() as isize;
+        let useful_chars = args.chars_per_line as isize - chars_used_by_number - separators;
+        let chars_per_column = useful_chars.max(0) / columns as isize;
+        if chars_per_column < 1 {
+            return Err(PrError::TooNarrow);
+        }
+        Ok(Pr {
+            output,
+            now,
+            header: args.header.clone(),
+            margin: args.indent as isize,
+            chars_per_line: args.chars_per_line as isize,
+            chars_per_column,
+            columns,
+            parallel,
+            storing: multiple && !args.across && !parallel,
+            double_space: args.double_space,
+            extremities,
+            keep_form_feeds,
+            lines_per_body,
+            truncate_lines: (multiple || args.truncate_lines) && !args.join_lines,
+            untabify_input: multiple,
+            tabify_output: multiple,
+            column_separator,
+            line_numbers: args.line_numbers,
+            number_width,
+            first_page: args.first_page,
+            last_page: args.last_page,
+            tab_stops,
+            inputs: Vec::new(),
+            column_vector: Vec::new(),
+            files_ready_to_read: 0,
+            page_number: 1,
+            line_number: 1,
+            line_count: 1,
+            date_text: String::new(),
+            file_text: String::new(),
+            header_width_available: 0,
+            output_position: 0,
+            input_position: 0,
+            spaces_not_printed: 0,
+            separators_not_printed: 0,
+            padding_not_printed: 0,
+            pad_vertically: false,
+            align_empty_columns: false,
+            empty_line: false,
+            form_feed_only: false,
+            print_a_header: false,
+            print_a_form_feed: false,
+            last_line: false,
+            clump: Clump::Nothing,
+            buffer: Vec::new(),
+            line_starts: Vec::new(),
+            line_ends: Vec::new(),
+        })
+    }
+
+    /// Prints the INPUTS, the ones that could be opened
+    pub fn print(&mut self, inputs: Vec<Input>) -> Result<(), PrError> {
+        if inputs.is_empty() {
+            return Ok(());
+        }
+        let column = |input| Column {
+            input,
+            status: Status::Open,
+            stored: false,
+            current_line: 0,
+            lines_stored: 0,
+            lines_to_print: 0,
+            start_position: 0,
+            numbered: false,
+            full_page_printed: false,
+        };
+        if self.parallel {
+            self.columns = inputs.len();
+            self.column_vector = (0..inputs.len()).map(column).collect();
+            self.init_header(None);
+        } else {
+            self.column_vector = (0..self.columns).map(|_| column(0)).collect();
+            self.init_header(Some(&inputs[0]));
+        }
+        self.files_ready_to_read = inputs.len() as isize;
+        self.inputs = inputs;
+        self.page_number = 1;
+        self.line_count = 1;
+        if self.first_page > 1 {
+            self.skip_to_page(self.first_page)?;
+            self.page_number = self.first_page;
+        }
+        self.init_columns();
+        self.line_number = self.line_count;
+        while self.print_page()? {}
+        Ok(())
+    }
+
+    /// The date of the INPUT, or the current one for the standard input and the parallel FILEs
+    fn init_header(&mut self, input: Option<&Input>) {
+        let time = input.and_then(|input| input.modified).unwrap_or(self.now);
+        self.date_text = DateTime::<Local>::from(time)
+            .format(DATE_FORMAT)
+            .to_string();
+        self.file_text = match (&self.header, input) {
+            (Some(header), _) => header.clone(),
+            (None, Some(input)) if input.name != "-" => input.name.clone(),
+            (None, _) => String::new(),
+        };
+        self.header_width_available =
+            self.chars_per_line - text_width(&self.date_text) - text_width(&self.file_text);
+    }
+
+    /// The pages before the first one are read, and only counted
+    fn skip_to_page(&mut self, page: u64) -> Result<(), PrError> {
+        for n in 1..page {
+            for _ in 1..self.lines_per_body {
+                for j in 0..self.columns {
+                    if self.column_vector[j].status == Status::Open {
+                        self.skip_read(j)?;
+                    }
+                }
+            }
+            self.last_line = true;
+            for j in 0..self.columns {
+                if self.column_vector[j].status == Status::Open {
+                    self.skip_read(j)?;
+                }
+            }
+            if self.storing {
+                for column in &mut self.column_vector {
+                    if column.status != Status::Closed {
+                        column.status = Status::Open;
+                    }
+                }
+            }
+            self.reset_status();
+            self.last_line = false;
+            if self.files_ready_to_read < 1 {
+                return Err(PrError::PageCount {
+                    first: page,
+                    count: n,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips a line of the column P, it's counted for -n
+    fn skip_read(&mut self, p: usize) -> Result<(), PrError> {
+        let mut c = self.getc(p)?;
+        if is(c, b'\x0c') && self.column_vector[p].full_page_printed {
+            c = self.getc(p)?;
+            if is(c, b'\n') {
+                c = self.getc(p)?;
+            }
+        }
+        self.column_vector[p].full_page_printed = false;
+        // A form feed alone isn't a line
+        let single_form_feed = is(c, b'\x0c');
+        if self.last_line {
+            self.column_vector[p].full_page_printed = true;
+        }
+        while !is(c, b'\n') {
+            if is(c, b'\x0c') {
+                if self.last_line {
+                    for (j, column) in self.column_vector.iter_mut().enumerate() {
+                        if !self.parallel || j == p {
+                            column.full_page_printed = false;
+                        }
+                    }
+                }
+                self.skip_newline(p)?;
+                self.hold_file(p);
+                break;
+            } else if c.is_none() {
+                self.close_file(p);
+                break;
+            }
+            c = self.getc(p)?;
+        }
+        if (!self.parallel || p == 0) && !single_form_feed {
+            self.line_count += 1;
+        }
+        Ok(())
+    }
+
+    /// Where the columns start, and how they're read
+    fn init_columns(&mut self) {
+        let separator = self.column_separator.len() as isize;
+        let mut h = self.margin;
+        let mut h_next = match (
+            self.truncate_lines,
+            self.parallel && self.line_numbers.is_some(),
+        ) {
+            (false, _) => ANYWHERE,
+            // The first column of the parallel FILEs is wider for the number
+            (true, true) => h + self.chars_per_column + self.number_width,
+            (true, false) => h + self.chars_per_column,
+        };
+        h += separator;
+        let last = self.columns - 1;
+        for (i, column) in self.column_vector.iter_mut().enumerate() {
+            column.stored = self.storing;
+            // Only the first column of the parallel FILEs is numbered, the columns down all are
+            column.numbered = self.line_numbers.is_some() && (!self.parallel || i == 0);
+            column.start_position = h;
+            if i == last {
+                // GNU numbers the last column only if it's the first one too
+                column.numbered = self.line_numbers.is_some() && (!self.parallel || last == 0);
+            } else if !self.truncate_lines {
+                h = ANYWHERE;
+                h_next = ANYWHERE;
+            } else {
+                h = h_next + separator;
+                h_next = h + self.chars_per_column;
+            }
+        }
+    }
+
+    /// Prints a page, false when there are no more of them
+    fn print_page(&mut self) -> Result<bool, PrError> {
+        self.init_page()?;
+        if self.columns_ready_to_print() == 0 {
+            return Ok(false);
+        }
+        if self.extremities {
+            self.print_a_header = true;
+        }
+        // Whether a line was printed on the page, so it's padded
+        self.pad_vertically = false;
+        let mut printed = false;
+        let mut lines_left_on_page = self.lines_per_body as isize;
+        if self.double_space {
+            lines_left_on_page *= 2;
+        }
+        while lines_left_on_page > 0 && self.columns_ready_to_print() > 0 {
+            self.output_position = 0;
+            self.spaces_not_printed = 0;
+            self.separators_not_printed = 0;
+            self.pad_vertically = false;
+            self.align_empty_columns = false;
+            self.empty_line = true;
+            for j in 0..self.columns {
+                self.input_position = 0;
+                let column = &self.column_vector[j];
+                if column.lines_to_print > 0 || column.status == Status::FormFeed {
+                    self.form_feed_only = false;
+                    self.padding_not_printed = column.start_position;
+                    let whole = match column.stored {
+                        true => self.print_stored(j)?,
+                        false => self.read_line(j)?,
+                    };
+                    if !whole {
+                        self.read_rest_of_line(j)?;
+                    }
+                    printed |= self.pad_vertically;
+                    self.column_vector[j].lines_to_print -= 1;
+                    if self.column_vector[j].lines_to_print <= 0
+                        && self.columns_ready_to_print() == 0
+                    {
+                        break;
+                    }
+                    let status = self.column_vector[j].status;
+                    if self.parallel && status != Status::Open {
+                        if self.empty_line {
+                            self.align_empty_columns = true;
+                        } else if status == Status::Closed
+                            || (status == Status::OnHold && self.form_feed_only)
+                        {
+                            self.align_column(j)?;
+                        }
+                    }
+                } else if self.parallel {
+                    if self.empty_line {
+                        self.align_empty_columns = true;
+                    } else {
+                        self.align_column(j)?;
+                    }
+                }
+                if !self.column_separator.is_empty() {
+                    self.separators_not_printed += 1;
+                }
+            }
+            if self.pad_vertically {
+                self.put(b"\n")?;
+                lines_left_on_page -= 1;
+            }
+            if self.columns_ready_to_print() == 0 && !self.extremities {
+                break;
+            }
+            if self.double_space && printed {
+                self.put(b"\n")?;
+                lines_left_on_page -= 1;
+            }
+        }
+        if lines_left_on_page == 0 {
+            for column in &mut self.column_vector {
+                if column.status == Status::Open {
+                    column.full_page_printed = true;
+                }
+            }
+        }
+        if printed && self.extremities {
+            let lines = (lines_left_on_page.max(0) as usize) + LINES_PER_FOOTER;
+            self.put(&b"\n".repeat(lines))?;
+        } else if self.keep_form_feeds && self.print_a_form_feed {
+            self.put(b"\x0c")?;
+            self.print_a_form_feed = false;
+        }
+        self.page_number += 1;
+        if self.last_page < self.page_number {
+            return Ok(false);
+        }
+        self.reset_status();
+        Ok(true)
+    }
+
+    /// How many lines of each column go on the page
+    fn init_page(&mut self) -> Result<(), PrError> {
+        if self.storing {
+            self.store_columns()?;
+            for column in &mut self.column_vector {
+                column.lines_to_print = column.lines_stored as isize;
+            }
+        } else {
+            for column in &mut self.column_vector {
+                column.lines_to_print = match column.status {
+                    Status::Open => self.lines_per_body as isize,
+                    _ => 0,
+                };
+            }
+        }
+        Ok(())
+    }
+
+    fn columns_ready_to_print(&self) -> usize {
+        self.column_vector
+            .iter()
+            .filter(|column| {
+                matches!(column.status, Status::Open | Status::FormFeed)
+                    || (self.storing && column.lines_stored > 0 && column.lines_to_print > 0)
+            })
+            .count()
+    }
+
+    /// Reads the lines of the page of the columns down, they're balanced then
+    fn store_columns(&mut self) -> Result<(), PrError> {
+        self.buffer.clear();
+        self.line_starts.clear();
+        self.line_ends.clear();
+        let mut start = 0;
+        for column in &mut self.column_vector {
+            column.lines_stored = 0;
+        }
+        for i in 0..self.columns {
+            if self.files_ready_to_read <= 0 {
+                break;
+            }
+            self.column_vector[i].current_line = self.line_ends.len();
+            for _ in 0..self.lines_per_body {
+                if self.files_ready_to_read <= 0 {
+                    break;
+                }
+                if self.column_vector[i].status == Status::Open {
+                    self.input_position = 0;
+                    if !self.read_line(i)? {
+                        self.read_rest_of_line(i)?;
+                    }
+                    if self.column_vector[i].status == Status::Open || start != self.buffer.len() {
+                        self.column_vector[i].lines_stored += 1;
+                        self.line_starts.push(start);
+                        self.line_ends.push(self.input_position);
+                        start = self.buffer.len();
+                    }
+                }
+            }
+        }
+        self.line_starts.push(start);
+        // As many lines in each column, the first ones get one more
+        let total = self.line_ends.len();
+        let mut first_line = 0;
+        for (i, column) in self.column_vector.iter_mut().enumerate() {
+            let lines = total / self.columns + usize::from(i < total % self.columns);
+            column.lines_stored = lines;
+            column.current_line = first_line;
+            first_line += lines;
+        }
+        Ok(())
+    }
+
+    /// Prints the next stored line of the column P
+    fn print_stored(&mut self, p: usize) -> Result<bool, PrError> {
+        let line = self.column_vector[p].current_line;
+        self.column_vector[p].current_line += 1;
+        self.pad_vertically = true;
+        if self.print_a_header {
+            self.print_header()?;
+        }
+        if self.column_vector[p].status == Status::FormFeed {
+            for column in &mut self.column_vector {
+                column.status = Status::OnHold;
+            }
+            if self.column_vector[0].lines_to_print <= 0 {
+                if !self.extremities {
+                    self.pad_vertically = false;
+                }
+                // Only the header
+                return Ok(true);
+            }
+        }
+        let separator = self.column_separator.len() as isize;
+        if self.padding_not_printed - separator > 0 {
+            self.pad_across_to(self.padding_not_printed - separator)?;
+            self.padding_not_printed = ANYWHERE;
+        }
+        if separator > 0 {
+            self.print_separator()?;
+        }
+        let (Some(&start), Some(&end)) =
+            (self.line_starts.get(line), self.line_starts.get(line + 1))
+        else {
+            return Ok(true);
+        };
+        let stored = self.buffer[start..end].to_vec();
+        for (bytes, c) in Chars::new(&stored) {
+            self.print_char(bytes, char_width(c))?;
+        }
+        if self.spaces_not_printed == 0 {
+            let start_position = self.column_vector[p].start_position;
+            self.output_position = start_position + self.line_ends[line];
+            if start_position - separator == self.margin {
+                self.output_position -= separator;
+            }
+        }
+        Ok(true)
+    }
+
+    /** Reads a line of the column P, and prints or stores it.
+     *
+     * False when the line is wider than the column, the rest of it is to be
+     * skipped then.
+     */
+    fn read_line(&mut self, p: usize) -> Result<bool, PrError> {
+        let mut c = self.getc(p)?;
+        let last_input_position = self.input_position;
+        if is(c, b'\x0c') && self.column_vector[p].full_page_printed {
+            c = self.getc(p)?;
+            if is(c, b'\n') {
+                c = self.getc(p)?;
+            }
+        }
+        self.column_vector[p].full_page_printed = false;
+        match c {
+            None => {
+                self.close_file(p);
+                return Ok(true);
+            }
+            Some(c) if c.is(b'\x0c') => {
+                self.skip_newline(p)?;
+                self.form_feed_only = true;
+                if self.print_a_header && !self.storing {
+                    self.pad_vertically = true;
+                    self.print_header()?;
+                } else if self.keep_form_feeds {
+                    self.print_a_form_feed = true;
+                }
+                self.hold_file(p);
+                return Ok(true);
+            }
+            Some(c) if c.is(b'\n') => {}
+            Some(c) => self.char_to_clump(c),
+        }
+        if self.truncate_lines && self.input_position > self.chars_per_column {
+            self.input_position = last_input_position;
+            return Ok(false);
+        }
+        if !self.column_vector[p].stored {
+            self.pad_vertically = true;
+            if self.print_a_header && !self.storing {
+                self.print_header()?;
+            }
+            if self.parallel && self.align_empty_columns {
+                // The empty columns before this one on the line
+                let empty = self.separators_not_printed;
+                self.separators_not_printed = 0;
+                for q in 0..empty.max(0) as usize {
+                    self.align_column(q)?;
+                    self.separators_not_printed += 1;
+                }
+                self.padding_not_printed = self.column_vector[p].start_position;
+                self.spaces_not_printed = match self.truncate_lines {
+                    true => self.chars_per_column,
+                    false => 0,
+                };
+                self.align_empty_columns = false;
+            }
+            let separator = self.column_separator.len() as isize;
+            if self.padding_not_printed - separator > 0 {
+                self.pad_across_to(self.padding_not_printed - separator)?;
+                self.padding_not_printed = ANYWHERE;
+            }
+            if separator > 0 {
+                self.print_separator()?;
+            }
+        }
+        if self.column_vector[p].numbered {
+            self.add_line_number(p)?;
+        }
+        self.empty_line = false;
+        if is(c, b'\n') {
+            return Ok(true);
+        }
+        self.print_clump(p)?;
+        loop {
+            let c = self.getc(p)?;
+            match c {
+                None => {
+                    self.close_file(p);
+                    return Ok(true);
+                }
+                Some(c) if c.is(b'\n') => return Ok(true),
+                Some(c) if c.is(b'\x0c') => {
+                    self.skip_newline(p)?;
+                    if self.keep_form_feeds {
+                        self.print_a_form_feed = true;
+                    }
+                    self.hold_file(p);
+                    return Ok(true);
+                }
+                Some(c) => {
+                    let last_input_position = self.input_position;
+                    self.char_to_clump(c);
+                    if self.truncate_lines && self.input_position > self.chars_per_column {
+                        self.input_position = last_input_position;
+                        return Ok(false);
+                    }
+                    self.print_clump(p)?;
+                }
+            }
+        }
+    }
+
+    /// Skips the rest of the line wider than the column
+    fn read_rest_of_line(&mut self, p: usize) -> Result<(), PrError> {
+        loop {
+            let c = self.getc(p)?;
+            if is(c, b'\n') {
+                return Ok(());
+            } else if is(c, b'\x0c') {
+                self.skip_newline(p)?;
+                if self.keep_form_feeds {
+                    self.print_a_form_feed = true;
+                }
+                self.hold_file(p);
+                return Ok(());
+            } else if c.is_none() {
+                self.close_file(p);
+                return Ok(());
+            }
+        }
+    }
+
+    /// A newline right after a form feed goes with it
+    fn skip_newline(&mut self, p: usize) -> Result<(), PrError> {
+        let c = self.getc(p)?;
+        if !is(c, b'\n') {
+            self.inputs[self.column_vector[p].input].ungetc(c);
+        }
+        Ok(())
+    }
+
+    fn getc(&mut self, p: usize) -> Result<Option<Char>, PrError> {
+        self.inputs[self.column_vector[p].input].getc()
+    }
+
+    /// The input of the column P ended, all the columns of the same FILE
+    fn close_file(&mut self, p: usize) {
+        if self.column_vector[p].status == Status::Closed {
+            return;
+        }
+        if self.parallel {
+            self.column_vector[p].status = Status::Closed;
+            self.column_vector[p].lines_to_print = 0;
+        } else {
+            for column in &mut self.column_vector {
+                column.status = Status::Closed;
+                if column.lines_stored == 0 {
+                    column.lines_to_print = 0;
+                }
+            }
+        }
+        self.files_ready_to_read -= 1;
+    }
+
+    /// A form feed ended the page of the column P, the FILE goes on with the next one
+    fn hold_file(&mut self, p: usize) {
+        if self.parallel {
+            self.column_vector[p].status = Status::OnHold;
+        } else {
+            let status = match self.storing {
+                true => Status::FormFeed,
+                false => Status::OnHold,
+            };
+            for column in &mut self.column_vector {
+                column.status = status;
+            }
+        }
+        self.column_vector[p].lines_to_print = 0;
+        self.files_ready_to_read -= 1;
+    }
+
+    /// The FILEs held by the form feeds are read again on the next page
+    fn reset_status(&mut self) {
+        for column in &mut self.column_vector {
+            if column.status == Status::OnHold {
+                column.status = Status::Open;
+                self.files_ready_to_read += 1;
+            }
+        }
+        if self.storing {
+            self.files_ready_to_read = match self.column_vector[0].status {
+                Status::Closed => 0,
+                _ => 1,
+            };
+        }
+    }
+
+    /// The padding, the separator and the number of an empty column P
+    fn align_column(&mut self, p: usize) -> Result<(), PrError> {
+        let separator = self.column_separator.len() as isize;
+        self.padding_not_printed = self.column_vector[p].start_position;
+        if separator < self.padding_not_printed {
+            self.pad_across_to(self.padding_not_printed - separator)?;
+            self.padding_not_printed = ANYWHERE;
+        }
+        if separator > 0 {
+            self.print_separator()?;
+        }
+        if self.column_vector[p].numbered {
+            self.add_line_number(p)?;
+        }
+        Ok(())
+    }
+
+    /// The number of the line, with only the low-order digits that fit
+    fn add_line_number(&mut self, p: usize) -> Result<(), PrError> {
+        let Some((separator, digits)) = self.line_numbers else {
+            return Ok(());
+        };
+        let number = line_number(self.line_number, digits, true);
+        self.line_number += 1;
+        for byte in number.bytes() {
+            self.char_func(p, &[byte], 1)?;
+        }
+        let mut bytes = [0; 4];
+        let separator_bytes = separator.encode_utf8(&mut bytes).as_bytes();
+        if self.columns > 1 {
+            // The tab goes as spaces, the columns are aligned with the tabs of the output
+            if separator == '\t' {
+                for _ in 0..self.number_width - digits as isize {
+                    self.char_func(p, b" ", 1)?;
+                }
+            } else {
+                self.char_func(p, separator_bytes, width(separator) as isize)?;
+            }
+        } else {
+            self.char_func(p, separator_bytes, width(separator) as isize)?;
+            if separator == '\t' {
+                self.output_position += tab_width(&self.tab_stops, self.output_position);
+            }
+        }
+        if self.truncate_lines && !self.parallel {
+            self.input_position += self.number_width;
+        }
+        Ok(())
+    }
+
+    /// The clump of the character, the input position goes past it
+    fn char_to_clump(&mut self, c: Char) {
+        let width = match c.c {
+            Some('\t') => tab_width(&self.tab_stops, self.input_position),
+            c => char_width(c),
+        };
+        self.clump = match c.c {
+            Some('\t') if self.untabify_input => Clump::Spaces(width),
+            _ => Clump::Char(c, width),
+        };
+        // Too many backspaces go back to the start of the line
+        if width < 0 && self.input_position == 0 {
+            self.clump = Clump::Nothing;
+        } else if width < 0 && self.input_position <= -width {
+            self.input_position = 0;
+        } else {
+            self.input_position += width;
+        }
+    }
+
+    fn print_clump(&mut self, p: usize) -> Result<(), PrError> {
+        match std::mem::replace(&mut self.clump, Clump::Nothing) {
+            Clump::Char(c, width) => self.char_func(p, c.bytes(), width),
+            Clump::Spaces(spaces) => {
+                for _ in 0..spaces {
+                    self.char_func(p, b" ", 1)?;
+                }
+                Ok(())
+            }
+            Clump::Nothing => Ok(()),
+        }
+    }
+
+    /// Prints the character, or stores it for the columns down
+    fn char_func(&mut self, p: usize, bytes: &[u8], width: isize) -> Result<(), PrError> {
+        match self.column_vector[p].stored {
+            true => {
+                self.buffer.extend_from_slice(bytes);
+                Ok(())
+            }
+            false => self.print_char(bytes, width),
+        }
+    }
+
+    /// The spaces aren't printed yet when the output is tabified, they may go as tabs
+    fn print_char(&mut self, bytes: &[u8], width: isize) -> Result<(), PrError> {
+        if self.tabify_output {
+            if bytes == b" " {
+                self.spaces_not_printed += 1;
+                return Ok(());
+            } else if self.spaces_not_printed > 0 {
+                self.print_white_space()?;
+            }
+            self.output_position += width.max(-1);
+        }
+        self.put(bytes)
+    }
+
+    /// The spaces not printed yet, as tabs where they fit
+    fn print_white_space(&mut self) -> Result<(), PrError> {
+        let mut h_old = self.output_position;
+        let goal = h_old + self.spaces_not_printed;
+        let mut white_space = Vec::new();
+        while goal - h_old > 1 {
+            let h_new = h_old + tab_width(&self.tab_stops, h_old);
+            if h_new > goal {
+                break;
+            }
+            white_space.push(b'\t');
+            h_old = h_new;
+        }
+        white_space.resize(white_space.len() + (goal - h_old).max(0) as usize, b' ');
+        self.output_position = goal;
+        self.spaces_not_printed = 0;
+        self.put(&white_space)
+    }
+
+    fn pad_across_to(&mut self, position: isize) -> Result<(), PrError> {
+        if self.tabify_output {
+            self.spaces_not_printed = position - self.output_position;
+            return Ok(());
+        }
+        let padding = (position - self.output_position).max(0) as usize;
+        self.output_position = position;
+        self.put(&b" ".repeat(padding))
+    }
+
+    /// The separators of the columns before, the empty ones too
+    fn print_separator(&mut self) -> Result<(), PrError> {
+        if self.separators_not_printed <= 0 {
+            if self.spaces_not_printed > 0 {
+                self.print_white_space()?;
+            }
+            return Ok(());
+        }
+        // GNU writes the separator only once, for all the columns
+        let mut separator = self.column_separator.iter();
+        while self.separators_not_printed > 0 {
+            for &byte in separator.by_ref() {
+                if byte == b' ' {
+                    self.spaces_not_printed += 1;
+                } else {
+                    if self.spaces_not_printed > 0 {
+                        self.print_white_space()?;
+                    }
+                    self.put(&[byte])?;
+                    self.output_position += 1;
+                }
+            }
+            if self.spaces_not_printed > 0 {
+                self.print_white_space()?;
+            }
+            self.separators_not_printed -= 1;
+        }
+        Ok(())
+    }
+
+    /// The date, the FILE centered, and the page number
+    fn print_header(&mut self) -> Result<(), PrError> {
+        self.output_position = 0;
+        self.pad_across_to(self.margin)?;
+        self.print_white_space()?;
+        let page_text = format!("Page {}", self.page_number);
+        let available = (self.header_width_available - text_width(&page_text)).max(0);
+        let left = (available / 2).max(1) as usize;
+        let right = (available - available / 2).max(1) as usize;
+        let header = format!(
+            "\n\n{}{}{}{}{}{}\n\n\n",
+            " ".repeat(self.margin as usize),
+            self.date_text,
+            " ".repeat(left),
+            self.file_text,
+            " ".repeat(right),
+            page_text
+        );
+        self.put(header.as_bytes())?;
+        self.print_a_header = false;
+        self.output_position = 0;
+        Ok(())
+    }
+
+    /// Whatever was printed goes before the messages
+    pub fn flush(&mut self) -> Result<(), PrError> {
+        self.output.flush().map_err(PrError::Write)
+    }
+
+    fn put(&mut self, bytes: &[u8]) -> Result<(), PrError> {
+        self.output.write_all(bytes).map_err(PrError::Write)
+    }
+}
+
+/// The columns to the next tab stop
+fn tab_width(tab_stops: &TabStops, position: isize) -> isize {
+    let position = position.max(0) as usize;
+    tab_stops.next(position, &mut 0).unwrap_or(position + 1) as isize - position as isize
+}
+
+/// The backspace goes back a column, the other control characters take none
+fn char_width(c: Option<char>) -> isize {
+    match c {
+        Some('\u{8}') => -1,
+        Some(c) if c.is_control() => 0,
+        Some(c) => width(c) as isize,
+        None => 0,
+    }
+}
+
+fn text_width(text: &str) -> isize {
+    text.chars().map(width).sum::<usize>() as isize
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    fn pr(args: Args, texts: &[&str], parallel: bool) -> String {
+        let args = Args {
+            chars_per_line: 72,
+            first_page: args.first_page.max(1),
+            last_page: match args.last_page {
+                0 => u64::MAX,
+                last => last,
+            },
+            ..args
+        };
+        let inputs = texts
+            .iter()
+            .map(|text| Input::new("text", None, Box::new(Cursor::new(text.to_string()))))
+            .collect();
+        let mut output = Vec::new();
+        Pr::new(
+            &args,
+            texts.len(),
+            parallel,
+            SystemTime::UNIX_EPOCH,
+            &mut output,
+        )
+        .unwrap()
+        .print(inputs)
+        .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn balances_the_columns_down() {
+        let args = Args {
+            length: 3,
+            columns: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            pr(args, &["1\n2\n3\n4\n5\n6\n7\n"], false),
+            "1\t\t\t4\t\t\t6\n2\t\t\t5\t\t\t7\n3\n"
+        );
+    }
+
+    #[test]
+    fn merges_the_files() {
+        let args = Args {
+            length: 66,
+            line_numbers: Some((':', 2)),
+            ..Default::default()
+        };
+        assert_eq!(
+            pr(args, &["a\nb\n", "c\n"], true),
+            " 1:a\t\t\t\t      c\n 2:b\t\t\t\t      \n"
+        );
+    }
+
+    #[test]
+    fn prints_the_header_and_the_footer() {
+        let args = Args {
+            length: 12,
+            extremities: true,
+            header: Some("title".to_string()),
+            ..Default::default()
+        };
+        let page = pr(args, &["a\n"], false);
+        let lines: Vec<&str> = page.split('\n').collect();
+        assert_eq!(lines.len(), 13);
+        assert!(lines[2].ends_with("title                       Page 1"));
+        assert_eq!(&lines[5..], ["a", "", "", "", "", "", "", ""]);
+    }
+
+    const TEN_LINES: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+
+    #[test]
+    fn prints_the_page_range() {
+        let args = |first_page, last_page| Args {
+            length: 3,
+            first_page,
+            last_page,
+            ..Default::default()
+        };
+        assert_eq!(pr(args(2, 3), &[TEN_LINES], false), "4\n5\n6\n7\n8\n9\n");
+        assert_eq!(pr(args(3, 0), &[TEN_LINES], false), "7\n8\n9\n10\n");
+    }
+
+    #[test]
+    fn numbers_the_pages_of_the_range() {
+        let args = Args {
+            length: 12,
+            extremities: true,
+            header: Some("title".to_string()),
+            first_page: 2,
+            last_page: 2,
+            ..Default::default()
+        };
+        let page = pr(args, &[TEN_LINES], false);
+        let lines: Vec<&str> = page.split('\n').collect();
+        assert_eq!(lines.len(), 13);
+        assert!(lines[2].ends_with("title                       Page 2"));
+        assert_eq!(&lines[5..8], ["3", "4", ""]);
+    }
+
+    #[test]
+    fn fills_the_columns_across() {
+        let args = Args {
+            length: 5,
+            columns: Some(3),
+            across: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            pr(args, &["1\n2\n3\n4\n5\n6\n7\n"], false),
+            "1\t\t\t2\t\t\t3\n4\t\t\t5\t\t\t6\n7\n"
+        );
+    }
+
+    #[test]
+    fn indents_and_double_spaces() {
+        let args = Args {
+            length: 66,
+            indent: 3,
+            ..Default::default()
+        };
+        assert_eq!(pr(args, &["a\nb\n"], false), "   a\n   b\n");
+        let args = Args {
+            length: 66,
+            double_space: true,
+            ..Default::default()
+        };
+        assert_eq!(pr(args, &["a\nb\n"], false), "a\n\nb\n\n");
+    }
+
+    #[test]
+    fn keeps_the_form_feeds_only_with_t() {
+        let args = |keep_form_feeds| Args {
+            length: 66,
+            keep_form_feeds,
+            ..Default::default()
+        };
+        assert_eq!(pr(args(true), &["a\u{c}b\n"], false), "a\n\u{c}b\n");
+        assert_eq!(pr(args(false), &["a\u{c}b\n"], false), "a\nb\n");
+    }
+
+    #[test]
+    fn keeps_the_low_digits_of_the_line_numbers() {
+        let args = Args {
+            length: 66,
+            line_numbers: Some((':', 1)),
+            ..Default::default()
+        };
+        let numbered = pr(args, &[&format!("{}11\n12\n", TEN_LINES)], false);
+        let numbers: Vec<&str> = numbered.lines().map(|line| &line[..2]).collect();
+        assert_eq!(
+            numbers,
+            ["1:", "2:", "3:", "4:", "5:", "6:", "7:", "8:", "9:", "0:", "1:", "2:"]
+        );
+        assert!(numbered.ends_with("0:10\n1:11\n2:12\n"));
+    }
+}
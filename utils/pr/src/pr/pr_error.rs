@@ -0,0 +1,66 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum PrError {
+    /// The other FILEs are still printed
+    Open(String, io::Error),
+    Read(String, io::Error),
+    Write(io::Error),
+    TooNarrow,
+    /// Not an error, GNU only warns that nothing was printed
+    PageCount {
+        first: u64,
+        count: u64,
+    },
+}
+
+impl fmt::Display for PrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrError::Open(name, err) | PrError::Read(name, err) => {
+                write!(f, "{}: {}", quote(name), strerror(err))
+            }
+            PrError::Write(err) => write!(f, "write error: {}", strerror(err)),
+            PrError::TooNarrow => write!(f, "page width too narrow"),
+            PrError::PageCount { first, count } => write!(
+                f,
+                "starting page number {} exceeds page count {}",
+                first, count
+            ),
+        }
+    }
+}
+
+impl Error for PrError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PrError::Open(_, err) | PrError::Read(_, err) | PrError::Write(err) => Some(err),
+            PrError::TooNarrow | PrError::PageCount { .. } => None,
+        }
+    }
+}
+
+/// Wrong arguments, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
@@ -0,0 +1,34 @@
+use clap::Parser;
+
+/// reverse lines characterwise
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    pub file: Vec<String>,
+
+    /// zero line delimiter, use NUL instead of newline
+    #[arg(short = '0', long, default_value_t = false)]
+    pub zero: bool,
+
+    /// reverse bytes instead of characters
+    #[arg(short = 'b', long, default_value_t = false)]
+    pub bytes: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::parse();
+        if args.file.is_empty() {
+            args.file.push("-".to_string());
+        }
+        args
+    }
+
+    pub fn terminator(&self) -> u8 {
+        if self.zero {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
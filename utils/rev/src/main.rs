@@ -1,55 +1,40 @@
-use clap::Parser;
+mod args;
+mod rev;
+
 use std::fs::File;
-use std::io::{BufRead, BufReader, Read, Write};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::process::ExitCode;
 
-/// reverse lines characterwise
-#[derive(Parser, Debug)]
-#[command(author, version, about, long_about)]
-pub struct Args {
-    pub file: Vec<String>,
-}
+use args::Args;
+use rev::rev;
+use strerror::strerror;
 
-fn main() -> Result<(), std::io::Error> {
+fn main() -> ExitCode {
     let args = Args::parse();
-    let mut output = std::io::stdout();
-    if args.file.len() == 0 {
-        rev(std::io::stdin(), &mut output)?;
-    } else {
-        for filename in args.file {
-            let file = File::open(filename)?;
-            rev(file, &mut output)?;
-        }
-    }
-    Ok(())
-}
-
-fn rev<R: Read, W: Write>(input: R, output: &mut W) -> Result<(), std::io::Error> {
-    let reader = BufReader::new(input);
-    let mut first = true;
-    for line in reader.split(b'\n') {
-        let mut line = line?;
-        line.reverse();
-        if !first {
-            output.write(b"\n")?;
+    let mut output = BufWriter::new(io::stdout());
+    let mut status = ExitCode::SUCCESS;
+    for file in &args.file {
+        let input: Box<dyn Read> = match file.as_str() {
+            "-" => Box::new(io::stdin()),
+            _ => match File::open(file) {
+                Ok(f) => Box::new(f),
+                Err(e) => {
+                    let _ = output.flush();
+                    eprintln!("rev: cannot open {}: {}", file, strerror(&e));
+                    status = ExitCode::FAILURE;
+                    continue;
+                }
+            },
+        };
+        if let Err(e) = rev(&args, BufReader::new(input), &mut output) {
+            let _ = output.flush();
+            eprintln!("rev: {}: {}", file, strerror(&e));
+            status = ExitCode::FAILURE;
         }
-        output.write(&line)?;
-        first = false;
     }
-    Ok(())
-}
-
-#[cfg(test)]
-mod test {
-    use std::io::Cursor;
-
-    use super::*;
-
-    #[test]
-    fn reverses_stirngs() {
-        let data = b"some data\nhere and there";
-        let input = Cursor::new(data);
-        let mut output = Cursor::new(Vec::<u8>::new());
-        rev(input, &mut output).unwrap();
-        assert_eq!(output.into_inner(), b"atad emos\nereht dna ereh".to_vec());
+    if let Err(e) = output.flush() {
+        eprintln!("rev: write error: {}", strerror(&e));
+        status = ExitCode::FAILURE;
     }
+    status
 }
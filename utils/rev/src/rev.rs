@@ -0,0 +1,130 @@
+use std::io::{self, BufRead, Write};
+use std::str;
+
+use chunk_reader::ChunkReader;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::args::Args;
+
+/** Reverses every line of the input, leaving its terminator in place.
+ *
+ * A "\r\n" terminator is kept as a whole when lines are separated with newlines,
+ * so CRLF files stay CRLF files.
+ */
+pub fn rev<R: BufRead, W: Write>(args: &Args, input: R, output: &mut W) -> io::Result<()> {
+    let terminator = args.terminator();
+    for line in input.chunks(terminator, 0) {
+        let line = line?;
+        let (body, ending) = split_terminator(&line, terminator);
+        if args.bytes {
+            let mut reversed = body.to_vec();
+            reversed.reverse();
+            output.write_all(&reversed)?;
+        } else {
+            output.write_all(&reverse_graphemes(body))?;
+        }
+        output.write_all(ending)?;
+    }
+    Ok(())
+}
+
+fn split_terminator(line: &[u8], terminator: u8) -> (&[u8], &[u8]) {
+    let len = match line.last() {
+        Some(&c) if c == terminator => {
+            if terminator == b'\n' && line.ends_with(b"\r\n") {
+                2
+            } else {
+                1
+            }
+        }
+        _ => 0,
+    };
+    line.split_at(line.len() - len)
+}
+
+/** Reverses the order of grapheme clusters, so "e\u{301}" or a flag emoji survive.
+ *
+ * Bytes that aren't valid UTF-8 can't be the part of any cluster, each of them
+ * is moved as a separate unit.
+ */
+fn reverse_graphemes(mut line: &[u8]) -> Vec<u8> {
+    let mut units: Vec<&[u8]> = Vec::new();
+    while !line.is_empty() {
+        match str::from_utf8(line) {
+            Ok(valid) => {
+                units.extend(valid.graphemes(true).map(str::as_bytes));
+                break;
+            }
+            Err(e) => {
+                let (valid, rest) = line.split_at(e.valid_up_to());
+                let valid = str::from_utf8(valid).expect("checked by from_utf8 above");
+                units.extend(valid.graphemes(true).map(str::as_bytes));
+                let invalid_len = e.error_len().unwrap_or(rest.len());
+                units.extend(rest[..invalid_len].chunks(1));
+                line = &rest[invalid_len..];
+            }
+        }
+    }
+    units
+        .iter()
+        .rev()
+        .flat_map(|unit| unit.iter().copied())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn run(args: &Args, data: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        rev(args, Cursor::new(data), &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn reverses_strings() {
+        let output = run(&Args::default(), b"some data\nhere and there");
+        assert_eq!(output, b"atad emos\nereht dna ereh".to_vec());
+    }
+
+    #[test]
+    fn keeps_terminators_in_place() {
+        let output = run(&Args::default(), b"abc\r\ndef\n\nxy\n");
+        assert_eq!(output, b"cba\r\nfed\n\nyx\n".to_vec());
+    }
+
+    #[test]
+    fn reverses_grapheme_clusters() {
+        let output = run(&Args::default(), "añe\u{301}🇺🇦\n".as_bytes());
+        assert_eq!(output, "🇺🇦e\u{301}ña\n".as_bytes().to_vec());
+    }
+
+    #[test]
+    fn moves_invalid_utf8_bytewise() {
+        let output = run(&Args::default(), b"a\xff\xfeb\xc3\xa9\xe2\x82\n");
+        assert_eq!(output, b"\x82\xe2\xc3\xa9b\xfe\xffa\n".to_vec());
+    }
+
+    #[test]
+    fn reverses_bytes_in_byte_mode() {
+        let args = Args {
+            bytes: true,
+            ..Default::default()
+        };
+        let output = run(&args, "añ\n".as_bytes());
+        assert_eq!(output, b"\xb1\xc3a\n".to_vec());
+    }
+
+    #[test]
+    fn splits_on_nul_with_zero() {
+        let args = Args {
+            zero: true,
+            ..Default::default()
+        };
+        let output = run(&args, b"abc\0d\nef\0gh");
+        assert_eq!(output, b"cba\0fe\nd\0hg".to_vec());
+    }
+}
@@ -0,0 +1,149 @@
+use std::ffi::OsString;
+
+use clap::{CommandFactory, FromArgMatches, Parser};
+
+use crate::seq::format::Format;
+use crate::seq::number::NumberError;
+use crate::seq::{default_format, Operand};
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Print numbers from FIRST to LAST, in steps of INCREMENT
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about, args_override_self = true)]
+pub struct Args {
+    /// LAST, FIRST LAST or FIRST INCREMENT LAST; FIRST and INCREMENT default
+    /// to 1, an omitted INCREMENT is 1 even when LAST is smaller than FIRST
+    #[arg(value_name = "NUMBER")]
+    pub numbers: Vec<String>,
+
+    /// use printf style floating-point FORMAT
+    #[arg(short = 'f', long, value_name = "FORMAT")]
+    pub format: Option<String>,
+
+    /// use STRING to separate numbers
+    #[arg(short = 's', long, value_name = "STRING", default_value = "\n")]
+    pub separator: String,
+
+    /// equalize width by padding with leading zeroes
+    #[arg(short = 'w', long, default_value_t = false)]
+    pub equal_width: bool,
+
+    #[arg(skip)]
+    pub first: Operand,
+
+    #[arg(skip)]
+    pub step: Operand,
+
+    #[arg(skip)]
+    pub last: Operand,
+
+    /// FORMAT, or the one that fits the numbers
+    #[arg(skip)]
+    pub output_format: Format,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let arguments = end_of_options(std::env::args_os());
+        let matches = <Self as CommandFactory>::command().get_matches_from(arguments);
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        match args.numbers.len() {
+            0 => usage_error("missing operand"),
+            1..=3 => {}
+            _ => usage_error(&format!("extra operand {}", quote(&args.numbers[3]))),
+        }
+        let format = args
+            .format
+            .as_deref()
+            .map(|format| Format::parse(format).unwrap_or_else(|err| fatal(&err)));
+        if format.is_some() && args.equal_width {
+            usage_error("format string may not be specified when printing equal width strings");
+        }
+        // INCREMENT is checked before LAST is read, as GNU does
+        let one = Operand::parse("1").unwrap_or_default();
+        let (first, step) = match &args.numbers[..] {
+            [_] => (one.clone(), one),
+            [first, _] => (parse_operand(first), one),
+            [first, step, _] => (parse_operand(first), parse_operand(step)),
+            _ => unreachable!(),
+        };
+        if step.number.is_zero() {
+            usage_error(&format!(
+                "invalid Zero increment value: {}",
+                quote(&args.numbers[1])
+            ));
+        }
+        args.last = parse_operand(&args.numbers[args.numbers.len() - 1]);
+        (args.first, args.step) = (first, step);
+        args.output_format = format.unwrap_or_else(|| {
+            default_format(&args.first, &args.step, &args.last, args.equal_width)
+        });
+        args
+    }
+}
+
+fn parse_operand(number: &str) -> Operand {
+    Operand::parse(number).unwrap_or_else(|err| match err {
+        NumberError::Invalid => usage_error(&format!(
+            "invalid floating point argument: {}",
+            quote(number)
+        )),
+        NumberError::NotANumber => usage_error(&format!(
+            "invalid 'not-a-number' argument: {}",
+            quote(number)
+        )),
+    })
+}
+
+/** The options end at the first NUMBER, the way getopt() is told with "+".
+ *
+ * "--" goes before it, so that clap takes the negative numbers, and the
+ * options after them, for NUMBERs.
+ */
+fn end_of_options(arguments: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut arguments: Vec<OsString> = arguments.collect();
+    let mut i = 1;
+    while i < arguments.len() {
+        let argument = arguments[i].to_string_lossy();
+        if argument == "--" {
+            break;
+        }
+        let option = argument.starts_with('-')
+            && argument.len() > 1
+            && !argument[1..].starts_with(|c: char| c.is_ascii_digit() || c == '.');
+        if !option {
+            arguments.insert(i, "--".into());
+            break;
+        }
+        let with_value = match argument.strip_prefix("--") {
+            Some(long) => long == "format" || long == "separator",
+            // The value of -f or -s is the rest of the cluster, or the next argument
+            None => argument[1..]
+                .find(['f', 's'])
+                .is_some_and(|at| at + 2 == argument.len()),
+        };
+        i += if with_value { 2 } else { 1 };
+    }
+    arguments
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("seq: {}", message);
+    eprintln!("Try 'seq --help' for more information.");
+    std::process::exit(1);
+}
+
+fn fatal(message: &str) -> ! {
+    eprintln!("seq: {}", message);
+    std::process::exit(1);
+}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
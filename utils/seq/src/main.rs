@@ -0,0 +1,26 @@
+mod args;
+mod seq;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use seq::seq;
+use seq::seq_error::SeqError;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("seq: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+fn run(args: &Args) -> Result<(), SeqError> {
+    let mut output = BufWriter::new(io::stdout().lock());
+    let result = seq(args, &mut output);
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(SeqError::Write);
+    result.and(flushed)
+}
@@ -0,0 +1,291 @@
+use ibig::ops::{DivRem, UnsignedAbs};
+use ibig::UBig;
+
+use super::number::{pow10, Number};
+
+/// A printf() directive for long double: %[flags][width][.precision][L]{eEfFgG}
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Spec {
+    pub left: bool,
+    pub plus: bool,
+    pub space: bool,
+    pub alternate: bool,
+    pub zero: bool,
+    pub width: usize,
+    pub precision: Option<usize>,
+    pub conversion: char,
+}
+
+/// The text of -f FORMAT around its only directive
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Format {
+    prefix: String,
+    spec: Spec,
+    suffix: String,
+}
+
+impl Format {
+    /// The way GNU seq checks the FORMAT, the messages are its ones
+    pub fn parse(format: &str) -> Result<Format, String> {
+        let quoted = quote(format);
+        let (prefix, directive) = split_directive(format)
+            .ok_or_else(|| format!("format {} has no % directive", quoted))?;
+        let mut chars = directive.chars().peekable();
+        let mut spec = Spec::default();
+        while let Some(flag) = chars.next_if(|c| "-+#0 '".contains(*c)) {
+            match flag {
+                '-' => spec.left = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                '#' => spec.alternate = true,
+                '0' => spec.zero = true,
+                // Grouping of thousands, there are none in the C locale
+                _ => {}
+            }
+        }
+        let number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            let mut number = 0_usize;
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                number = number
+                    .saturating_mul(10)
+                    .saturating_add(digit as usize - '0' as usize);
+            }
+            number
+        };
+        spec.width = number(&mut chars);
+        if chars.next_if_eq(&'.').is_some() {
+            spec.precision = Some(number(&mut chars));
+        }
+        chars.next_if_eq(&'L');
+        spec.conversion = match chars.next() {
+            None => return Err(format!("format {} ends in %", quoted)),
+            Some(c) if "eEfFgG".contains(c) => c,
+            Some(c) => return Err(format!("format {} has unknown %{} directive", quoted, c)),
+        };
+        let rest: String = chars.collect();
+        if split_directive(&rest).is_some() {
+            return Err(format!("format {} has too many % directives", quoted));
+        }
+        Ok(Format {
+            prefix: prefix.replace("%%", "%"),
+            spec,
+            suffix: rest.replace("%%", "%"),
+        })
+    }
+
+    /// %.PRECISIONf, or %0WIDTH.PRECISIONf for -w
+    pub fn fixed(precision: usize, width: usize) -> Format {
+        Format::new(Spec {
+            zero: width > 0,
+            width,
+            precision: Some(precision),
+            conversion: 'f',
+            ..Default::default()
+        })
+    }
+
+    /// %g
+    pub fn general() -> Format {
+        Format::new(Spec {
+            conversion: 'g',
+            ..Default::default()
+        })
+    }
+
+    fn new(spec: Spec) -> Format {
+        Format {
+            prefix: String::new(),
+            spec,
+            suffix: String::new(),
+        }
+    }
+
+    pub fn format(&self, number: &Number) -> String {
+        format!("{}{}{}", self.prefix, self.number(number), self.suffix)
+    }
+
+    /// The number only, without the text around it
+    pub fn number(&self, number: &Number) -> String {
+        format_number(&self.spec, number)
+    }
+}
+
+/// The text before the first directive, and the directive without %
+fn split_directive(format: &str) -> Option<(&str, &str)> {
+    let mut i = 0;
+    while i < format.len() {
+        match format[i..].strip_prefix('%') {
+            Some(rest) if rest.starts_with('%') => i += 2,
+            Some(rest) => return Some((&format[..i], rest)),
+            None => i += format[i..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    None
+}
+
+/** Formats the number like printf() does with the SPEC.
+ *
+ * The number is exact, so the digits are rounded half to even, like glibc
+ * does with the exact binary value.
+ */
+pub fn format_number(spec: &Spec, number: &Number) -> String {
+    let uppercase = spec.conversion.is_ascii_uppercase();
+    let body = match number {
+        Number::Infinite { .. } if uppercase => "INF".to_string(),
+        Number::Infinite { .. } => "inf".to_string(),
+        Number::Finite(decimal) => {
+            let magnitude = (&decimal.value).unsigned_abs();
+            let precision = spec.precision.unwrap_or(6);
+            let body = match spec.conversion.to_ascii_lowercase() {
+                'e' => exponential(&magnitude, decimal.scale, precision, spec.alternate),
+                'g' => general(&magnitude, decimal.scale, precision, spec.alternate),
+                _ => fixed(&magnitude, decimal.scale, precision, spec.alternate),
+            };
+            match uppercase {
+                true => body.to_ascii_uppercase(),
+                false => body,
+            }
+        }
+    };
+    let sign = match (number.is_negative(), spec.plus, spec.space) {
+        (true, _, _) => "-",
+        (false, true, _) => "+",
+        (false, false, true) => " ",
+        _ => "",
+    };
+    let padding = spec.width.saturating_sub(sign.len() + body.len());
+    let zero = spec.zero && !spec.left && matches!(number, Number::Finite(_));
+    match (spec.left, zero) {
+        (true, _) => format!("{}{}{}", sign, body, " ".repeat(padding)),
+        (false, true) => format!("{}{}{}", sign, "0".repeat(padding), body),
+        (false, false) => format!("{}{}{}", " ".repeat(padding), sign, body),
+    }
+}
+
+/// MAGNITUDE / 10^SCALE with DECIMALS digits after the point, rounded half to even
+fn round(magnitude: &UBig, scale: usize, decimals: i64) -> UBig {
+    let shift = decimals - scale as i64;
+    if shift >= 0 {
+        return magnitude * pow10(shift as usize);
+    }
+    let divisor = pow10(shift.unsigned_abs() as usize);
+    let (quotient, remainder) = magnitude.div_rem(&divisor);
+    let twice = remainder * 2_u8;
+    if twice > divisor || (twice == divisor && quotient.bit(0)) {
+        quotient + 1_u8
+    } else {
+        quotient
+    }
+}
+
+/// The digits with the point before the last PRECISION of them
+fn with_point(digits: String, precision: usize, alternate: bool) -> String {
+    let digits = format!("{:0>width$}", digits, width = precision + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - precision);
+    match (precision, alternate) {
+        (0, false) => integer.to_string(),
+        _ => format!("{}.{}", integer, fraction),
+    }
+}
+
+/// %f
+fn fixed(magnitude: &UBig, scale: usize, precision: usize, alternate: bool) -> String {
+    with_point(
+        round(magnitude, scale, precision as i64).to_string(),
+        precision,
+        alternate,
+    )
+}
+
+/// The significant digits, PRECISION of them after the first one, and the decimal exponent
+fn significant(magnitude: &UBig, scale: usize, precision: usize) -> (String, i64) {
+    if *magnitude == UBig::from(0_u8) {
+        return ("0".repeat(precision + 1), 0);
+    }
+    let mut exponent = magnitude.to_string().len() as i64 - 1 - scale as i64;
+    let mut digits = round(magnitude, scale, precision as i64 - exponent).to_string();
+    // Rounded up to the next power of 10
+    if digits.len() > precision + 1 {
+        digits.pop();
+        exponent += 1;
+    }
+    (digits, exponent)
+}
+
+/// %e
+fn exponential(magnitude: &UBig, scale: usize, precision: usize, alternate: bool) -> String {
+    let (digits, exponent) = significant(magnitude, scale, precision);
+    let mantissa = with_point(digits, precision, alternate);
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{}e{}{:02}", mantissa, sign, exponent.unsigned_abs())
+}
+
+/// %g, %e or %f by the exponent, without the trailing zeros
+fn general(magnitude: &UBig, scale: usize, precision: usize, alternate: bool) -> String {
+    let precision = precision.max(1);
+    let (_, exponent) = significant(magnitude, scale, precision - 1);
+    let body = if exponent < -4 || exponent >= precision as i64 {
+        exponential(magnitude, scale, precision - 1, alternate)
+    } else {
+        let decimals = (precision as i64 - 1 - exponent) as usize;
+        fixed(magnitude, scale, decimals, alternate)
+    };
+    if alternate {
+        return body;
+    }
+    let (mantissa, exponent) = match body.find('e') {
+        Some(e) => body.split_at(e),
+        None => (body.as_str(), ""),
+    };
+    let mantissa = match mantissa.contains('.') {
+        true => mantissa.trim_end_matches('0').trim_end_matches('.'),
+        false => mantissa,
+    };
+    format!("{}{}", mantissa, exponent)
+}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::seq::number::parse_number;
+
+    fn printf(format: &str, number: &str) -> String {
+        Format::parse(format)
+            .unwrap()
+            .format(&parse_number(number).unwrap())
+    }
+
+    #[test]
+    fn formats_like_printf() {
+        assert_eq!(printf("%f", "1.5"), "1.500000");
+        assert_eq!(printf("%.0f", "2.5"), "2");
+        assert_eq!(printf("%.0f", "3.5"), "4");
+        assert_eq!(printf("%+08.2f", "-1.005"), "-0001.00");
+        assert_eq!(printf("%-6gx", "0.5"), "0.5   x");
+        assert_eq!(printf("%e", "123456"), "1.234560e+05");
+        assert_eq!(printf("%.2E", "0.0009999"), "1.00E-03");
+        assert_eq!(printf("%g", "1e30"), "1e+30");
+        assert_eq!(printf("%g", "0.0001"), "0.0001");
+        assert_eq!(printf("%#g", "2"), "2.00000");
+        assert_eq!(printf("%5f", "-inf"), " -inf");
+        assert_eq!(printf("%G", "inf"), "INF");
+        assert_eq!(printf("%%%Lg%%", "7"), "%7%");
+    }
+
+    #[test]
+    fn checks_the_format() {
+        let error = |format| Format::parse(format).unwrap_err();
+        assert_eq!(error("x%%"), "format 'x%%' has no % directive");
+        assert_eq!(error("%-"), "format '%-' ends in %");
+        assert_eq!(error("%d"), "format '%d' has unknown %d directive");
+        assert_eq!(error("%g%g"), "format '%g%g' has too many % directives");
+        assert_eq!(error("'%x"), "format \"'%x\" has unknown %x directive");
+    }
+}
@@ -0,0 +1,253 @@
+pub mod format;
+pub mod number;
+pub mod seq_error;
+
+use std::io::Write;
+
+use crate::args::Args;
+use format::Format;
+use number::{parse_number, Number, NumberError};
+use seq_error::SeqError;
+
+/// FIRST, INCREMENT or LAST, with the decimals and the width it's written with
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Operand {
+    pub number: Number,
+    /// None for the hexadecimal floats, they go with %g
+    pub precision: Option<usize>,
+    pub width: usize,
+}
+
+impl Operand {
+    pub fn parse(operand: &str) -> Result<Operand, NumberError> {
+        let number = parse_number(operand)?;
+        // Neither the blanks nor the plus signs are written
+        let operand = operand.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '+');
+        let (precision, width) = match number {
+            Number::Infinite { .. } => (Some(0), 0),
+            _ if operand.contains(['x', 'X']) => match operand.contains(['.', 'p', 'P']) {
+                true => (None, 0),
+                false => (Some(0), 0),
+            },
+            _ => precision_and_width(operand),
+        };
+        Ok(Operand {
+            number,
+            precision,
+            width,
+        })
+    }
+}
+
+/** The decimals of the number, and how wide it is with them, the way GNU seq counts.
+ *
+ * The exponent moves the point, "1.5e1" is 15 of width 2 and "15e-1" is 1.5
+ * of width 4 (sic).
+ */
+fn precision_and_width(operand: &str) -> (Option<usize>, usize) {
+    let e = operand.find(['e', 'E']);
+    let point = operand.find('.');
+    let mut width = operand.len() as i64;
+    let mut fraction = 0;
+    if let Some(point) = point {
+        fraction = operand[point + 1..e.unwrap_or(operand.len())].len() as i64;
+        let digit_before = operand[..point].ends_with(|c: char| c.is_ascii_digit());
+        width += match (fraction, digit_before) {
+            (0, _) => -1,
+            (_, false) => 1,
+            (_, true) => 0,
+        };
+    }
+    let mut precision = fraction;
+    if let Some(e) = e {
+        let exponent = exponent(&operand[e + 1..]);
+        width -= (operand.len() - e) as i64;
+        if exponent < 0 {
+            precision = precision.saturating_add(exponent.saturating_neg());
+            if point.is_none_or(|point| point + 1 == e) {
+                width += 1;
+            }
+            width = width.saturating_add(exponent.saturating_neg());
+        } else {
+            precision -= fraction.min(exponent);
+            if fraction > 0 && precision == 0 {
+                width -= 1;
+            }
+            width = width.saturating_add(exponent - fraction.min(exponent));
+        }
+    }
+    (Some(precision.max(0) as usize), width.max(0) as usize)
+}
+
+/// The exponent like strtol() reads it
+fn exponent(exponent: &str) -> i64 {
+    let digits = exponent.trim_start_matches(['+', '-']);
+    let magnitude = digits.parse::<i64>().unwrap_or(i64::MAX);
+    match exponent.starts_with('-') {
+        true => -magnitude,
+        false => magnitude,
+    }
+}
+
+/** %.Nf with the most decimals of FIRST and INCREMENT, or %g for the hexadecimal ones.
+ *
+ * With EQUAL_WIDTH the numbers are padded with zeros, to the width of FIRST
+ * or LAST, with those decimals.
+ */
+pub fn default_format(
+    first: &Operand,
+    step: &Operand,
+    last: &Operand,
+    equal_width: bool,
+) -> Format {
+    let (Some(precision), Some(first_precision), Some(last_precision)) = (
+        first
+            .precision
+            .max(step.precision)
+            .filter(|_| step.precision.is_some()),
+        first.precision,
+        last.precision,
+    ) else {
+        return Format::general();
+    };
+    if !equal_width {
+        return Format::fixed(precision, 0);
+    }
+    let point = |operand_precision: usize| match (operand_precision, precision) {
+        (0, 1..) => 1,
+        (1.., 0) => -1,
+        _ => 0,
+    };
+    let first_width =
+        first.width + (precision - first_precision) + point(first_precision).max(0) as usize;
+    let last_width =
+        last.width as i64 + precision as i64 - last_precision as i64 + point(last_precision);
+    Format::fixed(precision, first_width.max(last_width.max(0) as usize))
+}
+
+/** Writes the numbers from FIRST by INCREMENT while they don't go past LAST.
+ *
+ * The numbers are exact, so they come to LAST exactly, but the one past LAST
+ * is written too when the FORMAT rounds it to LAST, as GNU seq does for the
+ * sake of the floating point errors.
+ */
+pub fn seq<W: Write>(args: &Args, output: &mut W) -> Result<(), SeqError> {
+    let [first, step, last] = [&args.first, &args.step, &args.last].map(|operand| &operand.number);
+    let past_last = |number: &Number| match step.is_negative() {
+        true => number < last,
+        false => number > last,
+    };
+    if past_last(first) {
+        return Ok(());
+    }
+    let format = &args.output_format;
+    let mut number = first.clone();
+    let mut past = false;
+    loop {
+        output
+            .write_all(format.format(&number).as_bytes())
+            .map_err(SeqError::Write)?;
+        if past {
+            break;
+        }
+        let next = number.add(step);
+        past = past_last(&next);
+        if past && !rounds_to_last(format, &number, &next, last) {
+            break;
+        }
+        output
+            .write_all(args.separator.as_bytes())
+            .map_err(SeqError::Write)?;
+        number = next;
+    }
+    output.write_all(b"\n").map_err(SeqError::Write)
+}
+
+/// NEXT is written as LAST, and not as NUMBER before it
+fn rounds_to_last(format: &Format, number: &Number, next: &Number, last: &Number) -> bool {
+    let text = format.number(next);
+    parse_number(&text).is_ok_and(|next| next == *last) && text != format.number(number)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn seq_lines(numbers: &[&str], format: Option<&str>, equal_width: bool) -> String {
+        let operands: Vec<Operand> = numbers.iter().map(|n| Operand::parse(n).unwrap()).collect();
+        let one = Operand::parse("1").unwrap();
+        let (first, step, last) = match operands.len() {
+            1 => (one.clone(), one, operands[0].clone()),
+            2 => (operands[0].clone(), one, operands[1].clone()),
+            _ => (
+                operands[0].clone(),
+                operands[1].clone(),
+                operands[2].clone(),
+            ),
+        };
+        let output_format = match format {
+            Some(format) => Format::parse(format).unwrap(),
+            None => default_format(&first, &step, &last, equal_width),
+        };
+        let args = Args {
+            separator: " ".to_string(),
+            first,
+            step,
+            last,
+            output_format,
+            ..Default::default()
+        };
+        let mut output = Vec::new();
+        seq(&args, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn counts_exactly() {
+        assert_eq!(seq_lines(&["3"], None, false), "1 2 3\n");
+        assert_eq!(
+            seq_lines(&["0.1", "0.1", "0.3"], None, false),
+            "0.1 0.2 0.3\n"
+        );
+        assert_eq!(seq_lines(&["5", "-2", "0"], None, false), "5 3 1\n");
+        assert_eq!(seq_lines(&["2", "1"], None, false), "");
+        assert_eq!(
+            seq_lines(&["1e30", "1000000000000000000000000000001"], None, false),
+            "1000000000000000000000000000000 1000000000000000000000000000001\n"
+        );
+        assert_eq!(
+            seq_lines(&["0", "0.3", "1"], Some("%.0f"), false),
+            "0 0 1 1\n"
+        );
+        assert_eq!(
+            seq_lines(&["0", "0.34", "1"], Some("%.1f"), false),
+            "0.0 0.3 0.7 1.0\n"
+        );
+    }
+
+    #[test]
+    fn pads_to_equal_width() {
+        assert_eq!(seq_lines(&["8", "10"], None, true), "08 09 10\n");
+        assert_eq!(seq_lines(&["-1", "1"], None, true), "-1 00 01\n");
+        assert_eq!(
+            seq_lines(&["0.5", "0.25", "1"], None, true),
+            "0.50 0.75 1.00\n"
+        );
+        assert_eq!(seq_lines(&["1", "15e-1"], None, true), "01\n");
+        assert_eq!(seq_lines(&["1", "1.5e1", "16"], None, true), "01 16\n");
+    }
+
+    #[test]
+    fn measures_the_operands() {
+        let measure = |operand| {
+            let operand = Operand::parse(operand).unwrap();
+            (operand.precision, operand.width)
+        };
+        assert_eq!(measure("+1.50"), (Some(2), 4));
+        assert_eq!(measure("-.5"), (Some(1), 4));
+        assert_eq!(measure("1.25e1"), (Some(1), 4));
+        assert_eq!(measure("100.e-1"), (Some(1), 5));
+        assert_eq!(measure("0x1p-1"), (None, 0));
+        assert_eq!(measure("inf"), (Some(0), 0));
+    }
+}
@@ -0,0 +1,256 @@
+use std::cmp::Ordering;
+
+use ibig::{IBig, UBig};
+
+/// Beyond these decimal exponents long double overflows, or it's as good as 0
+const MAX_EXPONENT: i64 = 4932;
+const MIN_EXPONENT: i64 = -4970;
+
+/// VALUE / 10^SCALE, exactly
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decimal {
+    pub value: IBig,
+    pub scale: usize,
+}
+
+impl Decimal {
+    pub fn new(value: IBig, scale: usize) -> Decimal {
+        Decimal { value, scale }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.value < IBig::from(0_u8)
+    }
+
+    /// Both values with the same scale
+    fn aligned(&self, other: &Decimal) -> (IBig, IBig, usize) {
+        let scale = self.scale.max(other.scale);
+        let rescale = |decimal: &Decimal| &decimal.value * IBig::from(pow10(scale - decimal.scale));
+        (rescale(self), rescale(other), scale)
+    }
+}
+
+/// A number of the operands, exact, or an infinity
+#[derive(Clone, Debug)]
+pub enum Number {
+    Finite(Decimal),
+    Infinite { negative: bool },
+}
+
+impl Default for Number {
+    fn default() -> Number {
+        Number::Finite(Decimal::new(IBig::from(0_u8), 0))
+    }
+}
+
+impl Number {
+    pub fn is_negative(&self) -> bool {
+        match self {
+            Number::Finite(decimal) => decimal.is_negative(),
+            Number::Infinite { negative } => *negative,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self, Number::Finite(decimal) if decimal.value == IBig::from(0_u8))
+    }
+
+    /// The infinities stay as they are
+    pub fn add(&self, other: &Number) -> Number {
+        match (self, other) {
+            (Number::Finite(a), Number::Finite(b)) => {
+                let (a, b, scale) = a.aligned(b);
+                Number::Finite(Decimal::new(a + b, scale))
+            }
+            (Number::Infinite { .. }, _) => self.clone(),
+            (_, Number::Infinite { .. }) => other.clone(),
+        }
+    }
+}
+
+/// 1.50 is 1.5, whatever the scale
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for Number {
+    fn partial_cmp(&self, other: &Number) -> Option<Ordering> {
+        let infinity = |negative: bool| match negative {
+            true => Ordering::Less,
+            false => Ordering::Greater,
+        };
+        Some(match (self, other) {
+            (Number::Finite(a), Number::Finite(b)) => {
+                let (a, b, _) = a.aligned(b);
+                a.cmp(&b)
+            }
+            (Number::Infinite { negative: a }, Number::Infinite { negative: b }) => b.cmp(a),
+            (Number::Infinite { negative }, _) => infinity(*negative),
+            (_, Number::Infinite { negative }) => infinity(*negative).reverse(),
+        })
+    }
+}
+
+/// Why an operand isn't a number
+#[derive(Debug, PartialEq)]
+pub enum NumberError {
+    Invalid,
+    NotANumber,
+}
+
+/** Parses the number the way strtold() does, but exactly.
+ *
+ * Decimal and hexadecimal floats, "inf" and "infinity" are accepted, after
+ * blanks and a sign. The numbers too big for long double are invalid.
+ */
+pub fn parse_number(number: &str) -> Result<Number, NumberError> {
+    let trimmed = number.trim_start();
+    let (negative, unsigned) = match trimmed.as_bytes().first() {
+        Some(b'-') => (true, &trimmed[1..]),
+        Some(b'+') => (false, &trimmed[1..]),
+        _ => (false, trimmed),
+    };
+    let lowercase = unsigned.to_ascii_lowercase();
+    if lowercase == "inf" || lowercase == "infinity" {
+        return Ok(Number::Infinite { negative });
+    }
+    if lowercase.starts_with("nan") {
+        return Err(NumberError::NotANumber);
+    }
+    let decimal = match lowercase.strip_prefix("0x") {
+        Some(hex) => parse_hex(hex),
+        None => parse_decimal(&lowercase),
+    }
+    .ok_or(NumberError::Invalid)?;
+    Ok(Number::Finite(match negative {
+        true => Decimal::new(-decimal.value, decimal.scale),
+        false => decimal,
+    }))
+}
+
+/// DIGITS[.DIGITS][e[+-]DIGITS]
+fn parse_decimal(number: &str) -> Option<Decimal> {
+    let (mantissa, exponent) = match number.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, parse_exponent(exponent)?),
+        None => (number, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", integer, fraction);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    scaled(
+        UBig::from_str_radix(&digits, 10).ok()?,
+        fraction.len() as i64 - exponent,
+    )
+}
+
+/// HEXDIGITS[.HEXDIGITS][p[+-]DIGITS], the exponent is of 2
+fn parse_hex(number: &str) -> Option<Decimal> {
+    let (mantissa, exponent) = match number.split_once('p') {
+        Some((mantissa, exponent)) => (mantissa, parse_exponent(exponent)?),
+        None => (number, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = format!("{}{}", integer, fraction);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = UBig::from_str_radix(&digits, 16).ok()?;
+    let exponent = exponent.saturating_sub(4 * fraction.len() as i64);
+    // 2^-n is 5^n / 10^n, and 2^n is about 10^(n * 0.3)
+    if exponent >= 0 {
+        scaled(value << exponent.min(4 * MAX_EXPONENT) as usize, 0)
+    } else {
+        let exponent = exponent.max(4 * MIN_EXPONENT).unsigned_abs() as usize;
+        scaled(value * UBig::from(5_u8).pow(exponent), exponent as i64)
+    }
+}
+
+/// The digits of the exponent, too many of them are as good as a lot
+fn parse_exponent(exponent: &str) -> Option<i64> {
+    let digits = exponent.trim_start_matches(['+', '-']);
+    if digits.is_empty()
+        || exponent.len() - digits.len() > 1
+        || !digits.bytes().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let magnitude = digits.parse::<i64>().unwrap_or(i64::MAX / 2);
+    match exponent.starts_with('-') {
+        true => Some(-magnitude),
+        false => Some(magnitude),
+    }
+}
+
+/// VALUE / 10^SCALE, unless it's too big; too small ones are 0
+fn scaled(value: UBig, scale: i64) -> Option<Decimal> {
+    if value == UBig::from(0_u8) {
+        return Some(Decimal::new(IBig::from(0_u8), 0));
+    }
+    let digits = value.to_string().len() as i64;
+    let exponent = digits - 1 - scale;
+    if exponent > MAX_EXPONENT {
+        return None;
+    }
+    if exponent < MIN_EXPONENT {
+        return Some(Decimal::new(IBig::from(0_u8), 0));
+    }
+    Some(match scale {
+        scale if scale < 0 => {
+            Decimal::new(IBig::from(value * pow10(scale.unsigned_abs() as usize)), 0)
+        }
+        scale => Decimal::new(IBig::from(value), scale as usize),
+    })
+}
+
+pub fn pow10(n: usize) -> UBig {
+    UBig::from(10_u8).pow(n)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn decimal(value: i64, scale: usize) -> Number {
+        Number::Finite(Decimal::new(IBig::from(value), scale))
+    }
+
+    #[test]
+    fn parses_the_numbers() {
+        assert_eq!(parse_number(" +1.50"), Ok(decimal(150, 2)));
+        assert_eq!(parse_number("-.5e-1"), Ok(decimal(-5, 2)));
+        assert_eq!(parse_number("12e2"), Ok(decimal(1200, 0)));
+        assert_eq!(parse_number("0x1.8p1"), Ok(decimal(3, 0)));
+        assert_eq!(parse_number("0x1p-2"), Ok(decimal(25, 2)));
+        assert_eq!(
+            parse_number("-Inf"),
+            Ok(Number::Infinite { negative: true })
+        );
+        assert_eq!(parse_number("nan"), Err(NumberError::NotANumber));
+        for invalid in ["", ".", "1e", "1e+", "0x", "5 ", "1e4933", "--1", "1e--1"] {
+            assert_eq!(
+                parse_number(invalid),
+                Err(NumberError::Invalid),
+                "{}",
+                invalid
+            );
+        }
+        let huge = parse_number("1e30").unwrap().add(&decimal(5, 0));
+        assert_eq!(
+            huge,
+            Number::Finite(Decimal::new(IBig::from(10_u8).pow(30) + 5, 0))
+        );
+    }
+
+    #[test]
+    fn compares_the_numbers() {
+        assert!(decimal(15, 1) < decimal(2, 0));
+        assert!(decimal(-1, 0) < decimal(-5, 1));
+        assert!(Number::Infinite { negative: false } > decimal(1, 0));
+        assert!(Number::Infinite { negative: true } < decimal(-1, 0));
+        assert_eq!(decimal(1, 0).add(&decimal(-25, 1)), decimal(-15, 1));
+    }
+}
@@ -0,0 +1,25 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum SeqError {
+    Write(io::Error),
+}
+
+impl fmt::Display for SeqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SeqError::Write(err) => write!(f, "write error: {}", strerror(err)),
+        }
+    }
+}
+
+impl Error for SeqError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SeqError::Write(err) => Some(err),
+        }
+    }
+}
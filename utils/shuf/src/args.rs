@@ -0,0 +1,191 @@
+use std::error::Error;
+use std::ops::RangeInclusive;
+
+use clap::{CommandFactory, FromArgMatches, Parser};
+
+use crate::shuf::shuf_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Write a random permutation of the input lines to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input. The ARGs with -e
+    #[arg(value_name = "FILE")]
+    pub operands: Vec<String>,
+
+    /// treat each ARG as an input line
+    #[arg(short = 'e', long, default_value_t = false)]
+    pub echo: bool,
+
+    /// treat each number LO through HI as an input line
+    #[arg(short = 'i', long, value_name = "LO-HI", value_parser = parse_range, allow_hyphen_values = true)]
+    pub input_range: Vec<RangeInclusive<u64>>,
+
+    /// output at most COUNT lines
+    #[arg(short = 'n', long, value_name = "COUNT", value_parser = parse_count, allow_hyphen_values = true)]
+    pub head_count: Vec<usize>,
+
+    /// write result to FILE instead of standard output
+    #[arg(short = 'o', long, value_name = "FILE")]
+    pub output: Vec<String>,
+
+    /// get random bytes from FILE
+    #[arg(long, value_name = "FILE")]
+    pub random_source: Vec<String>,
+
+    /// output lines can be repeated
+    #[arg(short = 'r', long, default_value_t = false)]
+    pub repeat: bool,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+
+    /// The smallest one of -n, all the lines without it
+    #[arg(skip)]
+    pub count: Option<usize>,
+
+    /// The lines are the numbers, instead of FILE or ARGs
+    #[arg(skip)]
+    pub range: Option<RangeInclusive<u64>>,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let matches = <Self as CommandFactory>::command()
+            .try_get_matches()
+            .unwrap_or_else(|err| {
+                if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                    fatal(&invalid.0);
+                }
+                err.exit()
+            });
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        if args.input_range.len() > 1 {
+            fatal("multiple -i options specified");
+        }
+        if args.output.len() > 1 && args.output.iter().any(|other| *other != args.output[0]) {
+            fatal("multiple output files specified");
+        }
+        if args
+            .random_source
+            .iter()
+            .any(|other| *other != args.random_source[0])
+        {
+            fatal("multiple random sources specified");
+        }
+        args.range = args.input_range.pop();
+        args.count = args.head_count.iter().copied().min();
+        if args.echo && args.range.is_some() {
+            usage_error("cannot combine -e and -i options");
+        }
+        let operands = match (args.echo, &args.range) {
+            (true, _) => &[][..],
+            (false, Some(_)) => &args.operands[..],
+            (false, None) => args.operands.get(1..).unwrap_or_default(),
+        };
+        if let Some(extra) = operands.first() {
+            usage_error(&format!("extra operand {}", quote(extra)));
+        }
+        args
+    }
+
+    pub fn terminator(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("shuf: {}", message);
+    eprintln!("Try 'shuf --help' for more information.");
+    std::process::exit(1);
+}
+
+fn fatal(message: &str) -> ! {
+    eprintln!("shuf: {}", message);
+    std::process::exit(1);
+}
+
+/// Why a number isn't good, like strtoumax() tells
+#[derive(Debug, PartialEq)]
+enum NumberError {
+    Invalid,
+    Overflow,
+}
+
+/// An unsigned decimal number, maybe after blanks and a plus sign
+fn parse_number(number: &str) -> Result<u64, NumberError> {
+    let trimmed = number.trim_start();
+    let digits = trimmed.strip_prefix('+').unwrap_or(trimmed);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(NumberError::Invalid);
+    }
+    digits.parse().map_err(|_| NumberError::Overflow)
+}
+
+/// Too many lines are as good as all of them
+fn parse_count(count: &str) -> Result<usize, ArgError> {
+    match parse_number(count) {
+        Ok(count) => Ok(usize::try_from(count).unwrap_or(usize::MAX)),
+        Err(NumberError::Overflow) => Ok(usize::MAX),
+        Err(NumberError::Invalid) => Err(ArgError(format!("invalid line count: {}", quote(count)))),
+    }
+}
+
+/// LO-HI, where HI may be one less than LO for no lines at all
+fn parse_range(range: &str) -> Result<RangeInclusive<u64>, ArgError> {
+    let invalid = |part: &str, error| {
+        let reason = match error {
+            NumberError::Overflow => ": Value too large for defined data type",
+            NumberError::Invalid => "",
+        };
+        ArgError(format!("invalid input range: {}{}", quote(part), reason))
+    };
+    let (low, high) = range.split_once('-').unwrap_or((range, ""));
+    let low = parse_number(low).map_err(|e| invalid(low, e))?;
+    let high = parse_number(high).map_err(|e| invalid(high, e))?;
+    // All the numbers must be counted by u64
+    if high.checked_add(1).map_or(low == 0, |end| low > end) {
+        return Err(invalid(range, NumberError::Invalid));
+    }
+    Ok(low..=high)
+}
+
+fn quote(text: &str) -> String {
+    match text.contains('\'') {
+        true => format!("\"{}\"", text),
+        false => format!("'{}'", text),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_the_range() {
+        assert_eq!(parse_range(" 1-+3"), Ok(1..=3));
+        assert_eq!(parse_range("5-4").map(|range| range.count()), Ok(0));
+        assert_eq!(parse_range("1-18446744073709551615"), Ok(1..=u64::MAX));
+        let error = |range| parse_range(range).unwrap_err().0;
+        assert_eq!(error("5-3"), "invalid input range: '5-3'");
+        assert_eq!(
+            error("0-18446744073709551615"),
+            "invalid input range: '0-18446744073709551615'"
+        );
+        assert_eq!(error("1x-3"), "invalid input range: '1x'");
+        assert_eq!(error("-3"), "invalid input range: ''");
+        assert_eq!(
+            error("1-99999999999999999999"),
+            "invalid input range: '99999999999999999999': Value too large for defined data type"
+        );
+    }
+}
@@ -0,0 +1,57 @@
+mod args;
+mod shuf;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use shuf::random_source::RandomSource;
+use shuf::shuf_error::ShufError;
+use shuf::{read_lines, shuf, Lines};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("shuf: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// The input is read first, so the output may go to the same FILE
+fn run(args: &Args) -> Result<(), ShufError> {
+    if args.count == Some(0) {
+        return Ok(());
+    }
+    let mut rng = RandomSource::open(args.random_source.first().map(String::as_str))?;
+    let lines = match &args.range {
+        Some(range) => Lines::Range(range.clone()),
+        None if args.echo => Lines::Lines(
+            args.operands
+                .iter()
+                .map(|arg| arg.as_bytes().to_vec())
+                .collect(),
+        ),
+        None => {
+            let name = args.operands.first().map_or("-", String::as_str);
+            let input: Box<dyn Read> = match name {
+                "-" => Box::new(io::stdin()),
+                _ => Box::new(File::open(name).map_err(|e| ShufError::Open(name.to_string(), e))?),
+            };
+            let sample = args.count.filter(|_| !args.repeat);
+            Lines::Lines(read_lines(input, args.terminator(), sample, &mut rng)?)
+        }
+    };
+    let output: Box<dyn Write> = match args.output.first() {
+        None => Box::new(io::stdout()),
+        Some(name) => {
+            Box::new(File::create(name).map_err(|e| ShufError::Create(name.to_string(), e))?)
+        }
+    };
+    let mut output = BufWriter::new(output);
+    let result = shuf(args, &lines, &mut output, &mut rng);
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(ShufError::Write);
+    result.and(flushed)
+}
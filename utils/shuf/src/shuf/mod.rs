@@ -0,0 +1,176 @@
+pub mod random_source;
+pub mod shuf_error;
+
+use std::io::{BufReader, Read, Write};
+use std::ops::RangeInclusive;
+
+use chunk_reader::ChunkReader;
+use rand::seq::index;
+
+use crate::args::Args;
+use random_source::RandomSource;
+use shuf_error::ShufError;
+
+/// What's shuffled: the lines of FILE or ARGs, or the numbers of -i
+pub enum Lines {
+    Lines(Vec<Vec<u8>>),
+    Range(RangeInclusive<u64>),
+}
+
+impl Lines {
+    fn len(&self) -> usize {
+        match self {
+            Lines::Lines(lines) => lines.len(),
+            // An empty range is one past the end
+            Lines::Range(range) => {
+                range.end().wrapping_sub(*range.start()).wrapping_add(1) as usize
+            }
+        }
+    }
+
+    fn write<W: Write>(&self, i: usize, terminator: u8, output: &mut W) -> Result<(), ShufError> {
+        match self {
+            Lines::Lines(lines) => output.write_all(&lines[i]),
+            Lines::Range(range) => write!(output, "{}", range.start() + i as u64),
+        }
+        .and_then(|_| output.write_all(&[terminator]))
+        .map_err(ShufError::Write)
+    }
+}
+
+/** Reads the lines without the terminators.
+ *
+ * With the SAMPLE size, only as many random lines are kept, the others are
+ * dropped as they come (reservoir sampling), so the input may be of any size.
+ */
+pub fn read_lines(
+    input: Box<dyn Read>,
+    terminator: u8,
+    sample: Option<usize>,
+    rng: &mut RandomSource,
+) -> Result<Vec<Vec<u8>>, ShufError> {
+    let mut lines = Vec::new();
+    for (n, line) in BufReader::new(input).chunks(terminator, 0).enumerate() {
+        let mut line = line.map_err(ShufError::Read)?;
+        if line.last() == Some(&terminator) {
+            line.pop();
+        }
+        match sample {
+            Some(size) if n >= size => {
+                let i = rng.below(n + 1);
+                if i < size {
+                    lines[i] = line;
+                }
+            }
+            _ => lines.push(line),
+        }
+    }
+    rng.check()?;
+    Ok(lines)
+}
+
+/// Writes the LINES in random order, or as many random ones as -n tells, maybe repeated
+pub fn shuf<W: Write>(
+    args: &Args,
+    lines: &Lines,
+    output: &mut W,
+    rng: &mut RandomSource,
+) -> Result<(), ShufError> {
+    let count = args.count.unwrap_or(usize::MAX);
+    let len = lines.len();
+    if args.repeat {
+        if len == 0 && count > 0 {
+            return Err(ShufError::NoLines);
+        }
+        for _ in 0..count {
+            let i = rng.below(len);
+            rng.check()?;
+            lines.write(i, args.terminator(), output)?;
+        }
+        return Ok(());
+    }
+    let amount = count.min(len);
+    let permutation = match len {
+        0 | 1 => index::IndexVec::from((0..amount).collect::<Vec<_>>()),
+        _ => index::sample(rng, len, amount),
+    };
+    rng.check()?;
+    for i in permutation {
+        lines.write(i, args.terminator(), output)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    fn random_source(bytes: &[u8]) -> RandomSource {
+        RandomSource::new("random", Box::new(Cursor::new(bytes.to_vec())))
+    }
+
+    /// Some bytes, that look random enough
+    fn random_bytes(len: usize) -> Vec<u8> {
+        let mut state = 1_u32;
+        let mut next = || {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        };
+        (0..len).map(|_| next()).collect()
+    }
+
+    fn shuf_lines(args: &Args, lines: &Lines, random: &[u8]) -> Result<String, String> {
+        let mut output = Vec::new();
+        shuf(args, lines, &mut output, &mut random_source(random)).map_err(|e| e.to_string())?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn shuffles_with_the_random_source() {
+        let random = random_bytes(64);
+        let lines = Lines::Range(1..=5);
+        let shuffled = shuf_lines(&Args::default(), &lines, &random).unwrap();
+        assert_eq!(
+            shuffled,
+            shuf_lines(&Args::default(), &lines, &random).unwrap()
+        );
+        let mut numbers: Vec<&str> = shuffled.lines().collect();
+        numbers.sort();
+        assert_eq!(numbers, ["1", "2", "3", "4", "5"]);
+        let args = Args {
+            count: Some(3),
+            repeat: true,
+            zero_terminated: true,
+            ..Default::default()
+        };
+        let lines = Lines::Lines(vec![b"a".to_vec()]);
+        assert_eq!(shuf_lines(&args, &lines, b"").unwrap(), "a\0a\0a\0");
+        let lines = Lines::Lines(vec![]);
+        assert_eq!(
+            shuf_lines(&args, &lines, b""),
+            Err("no lines to repeat".to_string())
+        );
+    }
+
+    #[test]
+    fn stops_when_the_random_source_ends() {
+        let lines = Lines::Lines(vec![b"a".to_vec(), b"b".to_vec()]);
+        let error = shuf_lines(&Args::default(), &lines, b"").unwrap_err();
+        assert_eq!(error, "'random': end of file");
+    }
+
+    #[test]
+    fn samples_the_lines() {
+        let input = (0..1000).map(|n| format!("{}\n", n)).collect::<String>();
+        let mut rng = random_source(&random_bytes(100_000));
+        let lines = read_lines(Box::new(Cursor::new(input)), b'\n', Some(10), &mut rng).unwrap();
+        assert_eq!(lines.len(), 10);
+        let numbers: Vec<usize> = lines
+            .iter()
+            .map(|line| String::from_utf8_lossy(line).parse().unwrap())
+            .collect();
+        assert!(numbers.iter().all(|&n| n < 1000));
+        assert!(numbers.iter().any(|&n| n >= 10));
+    }
+}
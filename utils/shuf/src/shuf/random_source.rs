@@ -0,0 +1,93 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+
+use rand::rngs::ThreadRng;
+use rand::{Error, Rng, RngCore};
+
+use super::shuf_error::ShufError;
+
+/** The random bytes, of the thread generator or of --random-source FILE.
+ *
+ * The same FILE always gives the same output. It can end too early, then
+ * the rest of the bytes are zeros, and the error is kept for `check()`.
+ */
+pub enum RandomSource {
+    Thread(ThreadRng),
+    File {
+        name: String,
+        input: Box<dyn Read>,
+        error: Option<io::Error>,
+    },
+}
+
+impl RandomSource {
+    pub fn open(name: Option<&str>) -> Result<RandomSource, ShufError> {
+        match name {
+            None => Ok(RandomSource::Thread(rand::thread_rng())),
+            Some(name) => {
+                let file = File::open(name).map_err(|e| ShufError::Open(name.to_string(), e))?;
+                Ok(RandomSource::new(name, Box::new(BufReader::new(file))))
+            }
+        }
+    }
+
+    pub fn new(name: &str, input: Box<dyn Read>) -> RandomSource {
+        RandomSource::File {
+            name: name.to_string(),
+            input,
+            error: None,
+        }
+    }
+
+    /// A random number below N, it takes no bytes, when there's no choice
+    pub fn below(&mut self, n: usize) -> usize {
+        match n {
+            0 | 1 => 0,
+            _ => self.gen_range(0..n),
+        }
+    }
+
+    /// Whether the bytes, used so far, were all read
+    pub fn check(&mut self) -> Result<(), ShufError> {
+        match self {
+            RandomSource::File { name, error, .. } => match error.take() {
+                Some(e) => Err(ShufError::RandomSource(name.clone(), e)),
+                None => Ok(()),
+            },
+            RandomSource::Thread(_) => Ok(()),
+        }
+    }
+}
+
+impl RngCore for RandomSource {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        match self {
+            RandomSource::Thread(rng) => rng.fill_bytes(dest),
+            RandomSource::File { input, error, .. } => {
+                if error.is_some() {
+                    dest.fill(0);
+                } else if let Err(e) = input.read_exact(dest) {
+                    dest.fill(0);
+                    *error = Some(e);
+                }
+            }
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
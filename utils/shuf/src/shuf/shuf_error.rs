@@ -0,0 +1,68 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum ShufError {
+    Open(String, io::Error),
+    /// GNU doesn't tell the name here
+    Read(io::Error),
+    Create(String, io::Error),
+    Write(io::Error),
+    /// Reading --random-source, it may end too early
+    RandomSource(String, io::Error),
+    NoLines,
+}
+
+impl fmt::Display for ShufError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShufError::Open(name, err) | ShufError::Create(name, err) => {
+                write!(f, "{}: {}", quote(name), strerror(err))
+            }
+            ShufError::Read(err) => write!(f, "read error: {}", strerror(err)),
+            ShufError::Write(err) => write!(f, "write error: {}", strerror(err)),
+            ShufError::RandomSource(name, err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                write!(f, "'{}': end of file", name)
+            }
+            ShufError::RandomSource(name, err) => write!(f, "'{}': {}", name, strerror(err)),
+            ShufError::NoLines => write!(f, "no lines to repeat"),
+        }
+    }
+}
+
+impl Error for ShufError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ShufError::Open(_, err)
+            | ShufError::Read(err)
+            | ShufError::Create(_, err)
+            | ShufError::Write(err)
+            | ShufError::RandomSource(_, err) => Some(err),
+            ShufError::NoLines => None,
+        }
+    }
+}
+
+/// Wrong arguments, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
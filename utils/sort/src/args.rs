@@ -0,0 +1,349 @@
+use std::error::Error;
+use std::fs;
+use std::thread;
+
+use clap::{ArgAction, Parser, ValueEnum};
+use parse_num::{parse_num, ParseNumError};
+
+use crate::sort::compare::Comparator;
+use crate::sort::key::{Ignore, Key};
+use crate::sort::sort_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Without -S, when the size of the memory is unknown
+const DEFAULT_BUFFER_SIZE: usize = 64 << 20;
+
+/// Even with a tiny -S, the runs shouldn't be a line or two
+const MIN_BUFFER_SIZE: usize = 16 << 10;
+
+/// Threads used by default, if there are as many processors
+const MAX_DEFAULT_THREADS: usize = 8;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum SortWord {
+    GeneralNumeric,
+    HumanNumeric,
+    Month,
+    Numeric,
+    Version,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum CheckMode {
+    /// report the first disorder
+    DiagnoseFirst,
+    /// only the exit status tells
+    Quiet,
+    /// same as quiet
+    Silent,
+}
+
+/// Write sorted concatenation of all FILE(s) to standard output
+#[derive(Parser, Debug, Default)]
+#[command(
+    author,
+    version,
+    about,
+    long_about,
+    disable_help_flag = true,
+    disable_version_flag = true
+)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input
+    #[arg(default_value = "-")]
+    pub file: Vec<String>,
+
+    /// ignore leading blanks
+    #[arg(short = 'b', long, default_value_t = false)]
+    pub ignore_leading_blanks: bool,
+
+    /// consider only blanks and alphanumeric characters
+    #[arg(short = 'd', long, default_value_t = false)]
+    pub dictionary_order: bool,
+
+    /// fold lower case to upper case characters
+    #[arg(short = 'f', long, default_value_t = false)]
+    pub ignore_case: bool,
+
+    /// compare according to general numerical value
+    #[arg(short = 'g', long, default_value_t = false)]
+    pub general_numeric_sort: bool,
+
+    /// consider only printable characters
+    #[arg(short = 'i', long, default_value_t = false)]
+    pub ignore_nonprinting: bool,
+
+    /// compare (unknown) < 'JAN' < ... < 'DEC'
+    #[arg(short = 'M', long, default_value_t = false)]
+    pub month_sort: bool,
+
+    /// compare human readable numbers (e.g., 2K 1G)
+    #[arg(short = 'h', long, default_value_t = false)]
+    pub human_numeric_sort: bool,
+
+    /// compare according to string numerical value
+    #[arg(short = 'n', long, default_value_t = false)]
+    pub numeric_sort: bool,
+
+    /// reverse the result of comparisons
+    #[arg(short = 'r', long, default_value_t = false)]
+    pub reverse: bool,
+
+    /// natural sort of (version) numbers within text
+    #[arg(short = 'V', long, default_value_t = false)]
+    pub version_sort: bool,
+
+    /// sort according to WORD
+    #[arg(long, value_enum, value_name = "WORD")]
+    pub sort: Option<SortWord>,
+
+    /// check for sorted input; do not sort
+    #[arg(short = 'c', long, value_enum, value_name = "WHEN", num_args = 0..=1, require_equals = true, default_missing_value = "diagnose-first")]
+    pub check: Option<CheckMode>,
+
+    /// like -c, but do not report first bad line
+    #[arg(short = 'C', default_value_t = false)]
+    pub check_quiet: bool,
+
+    /// sort via a key; KEYDEF gives location and type
+    #[arg(short = 'k', long, value_name = "KEYDEF", value_parser = Key::parse)]
+    pub key: Vec<Key>,
+
+    /// merge already sorted files; do not sort
+    #[arg(short = 'm', long, default_value_t = false)]
+    pub merge: bool,
+
+    /// write result to FILE instead of standard output
+    #[arg(short = 'o', long, value_name = "FILE")]
+    pub output: Option<String>,
+
+    /// stabilize sort by disabling last-resort comparison
+    #[arg(short = 's', long, default_value_t = false)]
+    pub stable: bool,
+
+    /// use SIZE for main memory buffer, in KiB without a suffix, or % of the memory
+    #[arg(short = 'S', long, value_name = "SIZE", value_parser = parse_buffer_size)]
+    pub buffer_size_arg: Option<usize>,
+
+    /// use SEP instead of non-blank to blank transition
+    #[arg(short = 't', long, value_name = "SEP", value_parser = parse_separator)]
+    pub field_separator: Option<u8>,
+
+    /// use DIR for temporaries, not $TMPDIR or /tmp; multiple options specify multiple directories
+    #[arg(short = 'T', long, value_name = "DIR")]
+    pub temporary_directory: Vec<String>,
+
+    /// output only the first of an equal run
+    #[arg(short = 'u', long, default_value_t = false)]
+    pub unique: bool,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+
+    /// change the number of sorts run concurrently to N
+    #[arg(long = "parallel", value_name = "N", value_parser = parse_parallel)]
+    pub parallel_arg: Option<usize>,
+
+    /// Print help, there's no -h, it's --human-numeric-sort
+    #[arg(long, action = ArgAction::Help)]
+    pub help: Option<bool>,
+
+    /// Print version, there's no -V, it's --version-sort
+    #[arg(long, action = ArgAction::Version)]
+    pub version: Option<bool>,
+
+    #[arg(skip)]
+    pub comparator: Comparator,
+
+    #[arg(skip)]
+    pub buffer_size: usize,
+
+    #[arg(skip)]
+    pub parallel: usize,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                arg_error(invalid);
+            }
+            err.exit()
+        });
+        args.init().unwrap_or_else(|e| arg_error(&e));
+        args
+    }
+
+    /// None without -c, or whether it's quiet
+    pub fn check_mode(&self) -> Option<bool> {
+        match self.check {
+            _ if self.check_quiet => Some(true),
+            Some(mode) => Some(mode != CheckMode::DiagnoseFirst),
+            None => None,
+        }
+    }
+
+    pub fn terminator(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+
+    fn init(&mut self) -> Result<(), ArgError> {
+        if self.check.is_some() && self.check_quiet {
+            return Err(ArgError("options '-cC' are incompatible".into()));
+        }
+        if self.check_mode().is_some() && self.file.len() > 1 {
+            let option = if self.check_quiet { 'C' } else { 'c' };
+            return Err(ArgError(format!(
+                "extra operand '{}' not allowed with -{}",
+                self.file[1], option
+            )));
+        }
+        let global = self.global_key();
+        let mut keys = std::mem::take(&mut self.key);
+        for key in &mut keys {
+            // Even -r alone means the key has its own options
+            if key.is_default() && !key.reverse {
+                key.inherit(&global);
+            }
+        }
+        if keys.is_empty() && !global.is_default() {
+            keys.push(global.clone());
+        }
+        for key in &keys {
+            key.check_compatibility()?;
+        }
+        self.comparator = Comparator {
+            keys,
+            tab: self.field_separator,
+            stable: self.stable || self.unique,
+            reverse: global.reverse,
+        };
+        self.buffer_size = self
+            .buffer_size_arg
+            .unwrap_or_else(|| physical_memory().map_or(DEFAULT_BUFFER_SIZE, |size| size / 8))
+            .max(MIN_BUFFER_SIZE);
+        self.parallel = self.parallel_arg.unwrap_or_else(|| {
+            thread::available_parallelism().map_or(1, |n| n.get().min(MAX_DEFAULT_THREADS))
+        });
+        Ok(())
+    }
+
+    /// The options, that go to the keys without their own ones
+    fn global_key(&self) -> Key {
+        let sort = |word| self.sort == Some(word);
+        Key {
+            skip_start_blanks: self.ignore_leading_blanks,
+            skip_end_blanks: self.ignore_leading_blanks,
+            ignore: match (self.dictionary_order, self.ignore_nonprinting) {
+                // The last one wins in GNU, which isn't known here
+                (_, true) => Some(Ignore::NonPrinting),
+                (true, false) => Some(Ignore::Dictionary),
+                _ => None,
+            },
+            fold_case: self.ignore_case,
+            numeric: self.numeric_sort || sort(SortWord::Numeric),
+            general_numeric: self.general_numeric_sort || sort(SortWord::GeneralNumeric),
+            human_numeric: self.human_numeric_sort || sort(SortWord::HumanNumeric),
+            month: self.month_sort || sort(SortWord::Month),
+            version: self.version_sort || sort(SortWord::Version),
+            reverse: self.reverse,
+            ..Default::default()
+        }
+    }
+}
+
+fn arg_error(error: &ArgError) -> ! {
+    eprintln!("sort: {}", error);
+    std::process::exit(2);
+}
+
+/** Parses -S: KiB without a suffix, b for bytes, K, M, G... or % of the memory.
+ *
+ * Only the single letter suffixes are there, unlike the other NUMs.
+ */
+fn parse_buffer_size(size: &str) -> Result<usize, ArgError> {
+    let invalid = || ArgError(format!("invalid -S argument '{}'", size));
+    let invalid_suffix = || ArgError(format!("invalid suffix in -S argument '{}'", size));
+    if size.starts_with(['-', '+']) {
+        return Err(invalid());
+    }
+    let digits = size.trim_end_matches(|c: char| !c.is_ascii_digit());
+    if digits.is_empty() {
+        return Err(invalid());
+    }
+    let number = parse_num(digits).map_err(|_| invalid_suffix())?;
+    let number = number.to_usize();
+    let bytes = match &size[digits.len()..] {
+        "" => number.and_then(|n| n.checked_mul(1024)),
+        "b" => number,
+        "%" => number.map(|percent| {
+            let memory = physical_memory().unwrap_or(DEFAULT_BUFFER_SIZE * 8) as u128;
+            (memory * percent as u128 / 100).min(usize::MAX as u128) as usize
+        }),
+        suffix if suffix.len() == 1 => match parse_num(size) {
+            Ok(value) => value.to_usize(),
+            Err(ParseNumError::BadMultiplierValue(_)) => return Err(invalid_suffix()),
+            Err(_) => return Err(invalid()),
+        },
+        _ => return Err(invalid_suffix()),
+    };
+    bytes.ok_or_else(|| ArgError(format!("-S argument '{}' too large", size)))
+}
+
+/// Total memory, where the system tells it in /proc
+fn physical_memory() -> Option<usize> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemTotal:"))?;
+    let kilobytes: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    kilobytes.checked_mul(1024)
+}
+
+/// A single byte, "\0" stands for NUL
+fn parse_separator(separator: &str) -> Result<u8, ArgError> {
+    match separator.as_bytes() {
+        [] => Err(ArgError("empty tab".into())),
+        [c] => Ok(*c),
+        b"\\0" => Ok(b'\0'),
+        _ => Err(ArgError(format!("multi-character tab '{}'", separator))),
+    }
+}
+
+fn parse_parallel(threads: &str) -> Result<usize, ArgError> {
+    match threads.parse::<usize>() {
+        Ok(0) => Err(ArgError("number in parallel must be nonzero".into())),
+        Ok(n) => Ok(n),
+        Err(_) => Err(ArgError(format!(
+            "invalid --parallel argument '{}'",
+            threads
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_buffer_size() {
+        assert_eq!(parse_buffer_size("10"), Ok(10 << 10));
+        assert_eq!(parse_buffer_size("10b"), Ok(10));
+        assert_eq!(parse_buffer_size("2M"), Ok(2 << 20));
+        assert_eq!(parse_buffer_size("1k"), Ok(1 << 10));
+        let message = |size| parse_buffer_size(size).unwrap_err().0;
+        assert_eq!(message("x"), "invalid -S argument 'x'");
+        assert_eq!(message("-1"), "invalid -S argument '-1'");
+        assert_eq!(message("1kB"), "invalid suffix in -S argument '1kB'");
+        assert_eq!(message("1.5"), "invalid suffix in -S argument '1.5'");
+        assert_eq!(message("1x"), "invalid suffix in -S argument '1x'");
+        assert_eq!(
+            message("1000000000000000000000"),
+            "-S argument '1000000000000000000000' too large"
+        );
+    }
+}
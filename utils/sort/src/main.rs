@@ -0,0 +1,35 @@
+mod args;
+mod sort;
+
+use std::process::ExitCode;
+
+use args::Args;
+use sort::temp_files::TempFiles;
+use sort::{check_file, merge_files, sort_files};
+
+/// GNU sort exits with 1 only for the unsorted input of -c
+const SORT_FAILURE: u8 = 2;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Some(quiet) = args.check_mode() {
+        return match check_file(&args, quiet) {
+            Ok(true) => ExitCode::SUCCESS,
+            Ok(false) => ExitCode::FAILURE,
+            Err(e) => {
+                eprintln!("sort: {}", e);
+                ExitCode::from(SORT_FAILURE)
+            }
+        };
+    }
+    let mut temp = TempFiles::new(&args.temporary_directory);
+    let result = match args.merge {
+        true => merge_files(&args, &mut temp),
+        false => sort_files(&args, &mut temp),
+    };
+    if let Err(e) = result {
+        eprintln!("sort: {}", e);
+        return ExitCode::from(SORT_FAILURE);
+    }
+    ExitCode::SUCCESS
+}
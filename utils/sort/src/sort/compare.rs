@@ -0,0 +1,339 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+
+use parse_num::power_of;
+
+use super::key::{is_blank, skip_blanks, Ignore, Key};
+use super::version::compare_versions;
+
+/// Compares the lines by the keys, with the whole line as the last resort
+#[derive(Clone, Debug, Default)]
+pub struct Comparator {
+    pub keys: Vec<Key>,
+    /// Field separator of -t, blank-separated fields otherwise
+    pub tab: Option<u8>,
+    /// No last resort comparison, for -s and -u
+    pub stable: bool,
+    /// The last resort goes in reverse with the global -r
+    pub reverse: bool,
+}
+
+impl Comparator {
+    pub fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        if !self.keys.is_empty() {
+            let order = self.compare_keys(a, b);
+            if order.is_ne() || self.stable {
+                return order;
+            }
+        }
+        match self.reverse {
+            true => b.cmp(a),
+            false => a.cmp(b),
+        }
+    }
+
+    fn compare_keys(&self, a: &[u8], b: &[u8]) -> Ordering {
+        for key in &self.keys {
+            let order = compare_key(key, key.span(a, self.tab), key.span(b, self.tab));
+            if order.is_ne() {
+                return if key.reverse { order.reverse() } else { order };
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+fn compare_key(key: &Key, a: &[u8], b: &[u8]) -> Ordering {
+    let ignored = |c: &u8| match key.ignore {
+        Some(Ignore::Dictionary) => !c.is_ascii_alphanumeric() && !matches!(c, b' ' | b'\t'),
+        Some(Ignore::NonPrinting) => !matches!(c, b' '..=b'~'),
+        None => false,
+    };
+    let translate = |c: &u8| match key.fold_case {
+        true => c.to_ascii_uppercase(),
+        false => *c,
+    };
+    let special = key.numeric || key.general_numeric || key.human_numeric;
+    if special || key.month || key.version {
+        let convert = |text: &[u8]| -> Vec<u8> {
+            text.iter().filter(|c| !ignored(c)).map(translate).collect()
+        };
+        let (a, b) = match key.ignore.is_some() || key.fold_case {
+            true => (Cow::Owned(convert(a)), Cow::Owned(convert(b))),
+            false => (Cow::Borrowed(a), Cow::Borrowed(b)),
+        };
+        return if key.numeric {
+            compare_numbers(&a, &b)
+        } else if key.general_numeric {
+            compare_general_numbers(&a, &b)
+        } else if key.human_numeric {
+            compare_human_numbers(&a, &b)
+        } else if key.month {
+            month(&a).cmp(&month(&b))
+        } else {
+            compare_versions(&a, &b)
+        };
+    }
+    if key.ignore.is_some() || key.fold_case {
+        let a = a.iter().filter(|c| !ignored(c)).map(translate);
+        let b = b.iter().filter(|c| !ignored(c)).map(translate);
+        return a.cmp(b);
+    }
+    a.cmp(b)
+}
+
+/// A number, as -n sees it, without the leading zeros and the trailing zeros of the fraction
+struct Number<'a> {
+    negative: bool,
+    integer: &'a [u8],
+    fraction: &'a [u8],
+    /// Whatever follows the number
+    rest: &'a [u8],
+}
+
+impl Number<'_> {
+    /// Leading blanks are skipped, anything that isn't a number is zero
+    fn parse(text: &[u8]) -> Number<'_> {
+        let text = &text[skip_blanks(text, 0)..];
+        let (negative, text) = match text.strip_prefix(b"-") {
+            Some(text) => (true, text),
+            None => (false, text),
+        };
+        let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+        let integer_end = digits(text);
+        let integer = &text[..integer_end];
+        let (fraction, rest) = match text[integer_end..].strip_prefix(b".") {
+            Some(after) => after.split_at(digits(after)),
+            None => (&text[integer_end..integer_end], &text[integer_end..]),
+        };
+        let integer = &integer[integer.iter().take_while(|c| **c == b'0').count()..];
+        let fraction =
+            &fraction[..fraction.len() - fraction.iter().rev().take_while(|c| **c == b'0').count()];
+        Number {
+            // There's no negative zero
+            negative: negative && !(integer.is_empty() && fraction.is_empty()),
+            integer,
+            fraction,
+            rest,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.integer.is_empty() && self.fraction.is_empty()
+    }
+
+    fn cmp(&self, other: &Number) -> Ordering {
+        let magnitude = || {
+            self.integer
+                .len()
+                .cmp(&other.integer.len())
+                .then_with(|| self.integer.cmp(other.integer))
+                .then_with(|| self.fraction.cmp(other.fraction))
+        };
+        match (self.negative, other.negative) {
+            (false, false) => magnitude(),
+            (true, true) => magnitude().reverse(),
+            (negative, _) => match negative {
+                true => Ordering::Less,
+                false => Ordering::Greater,
+            },
+        }
+    }
+}
+
+/// -n, arbitrary long decimal numbers without exponents
+pub fn compare_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    Number::parse(a).cmp(&Number::parse(b))
+}
+
+/** -h, the numbers with the K, M, G... suffixes.
+ *
+ * A bigger suffix wins, whatever the number is, so 2K is after 1000, and 0.5M
+ * is after 900K.
+ */
+pub fn compare_human_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    let (a, b) = (Number::parse(a), Number::parse(b));
+    let order = |number: &Number| {
+        let power = match number.rest.first() {
+            // Lowercase 'k' is the only lowercase suffix of GNU
+            Some(&c) if c == b'k' || c.is_ascii_uppercase() => power_of(c as char).unwrap_or(0),
+            _ => 0,
+        } as i32;
+        match number {
+            _ if number.is_zero() => 0,
+            Number { negative: true, .. } => -power,
+            _ => power,
+        }
+    };
+    order(&a).cmp(&order(&b)).then_with(|| a.cmp(&b))
+}
+
+/** -g, the floating point numbers.
+ *
+ * Anything that isn't a number goes first, then NaNs, then the numbers from -inf.
+ * GNU orders NaNs by their bytes, so only the sign is taken from that, nan before -nan.
+ */
+pub fn compare_general_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    match (parse_float(a), parse_float(b)) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        (Some(a), Some(b)) => match (a.is_nan(), b.is_nan()) {
+            (true, true) => a.is_sign_negative().cmp(&b.is_sign_negative()),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            _ => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        },
+    }
+}
+
+/// Floating point number at the start of the text, the way strtod() finds it
+fn parse_float(text: &[u8]) -> Option<f64> {
+    let start = text
+        .iter()
+        .take_while(|c| is_blank(**c) || matches!(c, b'\x0b' | b'\x0c' | b'\r'))
+        .count();
+    let text = &text[start..];
+    let (negative, text) = match text.first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let starts_with = |prefix: &[u8]| {
+        text.len() >= prefix.len() && text[..prefix.len()].eq_ignore_ascii_case(prefix)
+    };
+    let value = if starts_with(b"inf") {
+        f64::INFINITY
+    } else if starts_with(b"nan") {
+        f64::NAN
+    } else if starts_with(b"0x") {
+        parse_hex_float(&text[2..]).unwrap_or(0.0)
+    } else {
+        let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+        let mut end = digits(text);
+        let mut mantissa = end;
+        if text.get(end) == Some(&b'.') {
+            let fraction = digits(&text[end + 1..]);
+            mantissa += fraction;
+            end += 1 + fraction;
+        }
+        if mantissa == 0 {
+            return None;
+        }
+        if let Some(b'e' | b'E') = text.get(end) {
+            let sign = matches!(text.get(end + 1), Some(b'+' | b'-')) as usize;
+            let exponent = digits(&text[end + 1 + sign..]);
+            if exponent > 0 {
+                end += 1 + sign + exponent;
+            }
+        }
+        let number = std::str::from_utf8(&text[..end]).unwrap_or("0");
+        number.parse().unwrap_or(0.0)
+    };
+    Some(if negative { -value } else { value })
+}
+
+/// The digits after 0x, with an optional binary exponent after p
+fn parse_hex_float(text: &[u8]) -> Option<f64> {
+    let mut value = 0.0;
+    let mut scale = 0;
+    let mut in_fraction = false;
+    let mut i = 0;
+    while let Some(&c) = text.get(i) {
+        match c {
+            b'.' if !in_fraction => in_fraction = true,
+            _ => match (c as char).to_digit(16) {
+                Some(digit) => {
+                    value = value * 16.0 + digit as f64;
+                    scale -= 4 * in_fraction as i32;
+                }
+                None => break,
+            },
+        }
+        i += 1;
+    }
+    if let Some(b'p' | b'P') = text.get(i) {
+        let rest = std::str::from_utf8(&text[i + 1..]).ok()?;
+        let end = rest
+            .char_indices()
+            .find(|&(j, c)| !(c.is_ascii_digit() || (j == 0 && (c == '-' || c == '+'))))
+            .map_or(rest.len(), |(j, _)| j);
+        scale += rest[..end].parse::<i32>().unwrap_or(0);
+    }
+    Some(value * 2_f64.powi(scale))
+}
+
+/// 1 to 12 for the month names, 0 for anything else, which goes first
+fn month(text: &[u8]) -> usize {
+    const MONTHS: [&[u8]; 12] = [
+        b"JAN", b"FEB", b"MAR", b"APR", b"MAY", b"JUN", b"JUL", b"AUG", b"SEP", b"OCT", b"NOV",
+        b"DEC",
+    ];
+    let text = &text[skip_blanks(text, 0)..];
+    let name = &text[..text.len().min(3)];
+    MONTHS
+        .iter()
+        .position(|month| name.eq_ignore_ascii_case(month))
+        .map_or(0, |i| i + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sorted(compare: fn(&[u8], &[u8]) -> Ordering, values: &[&str]) {
+        for (i, a) in values.iter().enumerate() {
+            for (j, b) in values.iter().enumerate() {
+                let order = compare(a.as_bytes(), b.as_bytes());
+                assert_eq!(order, i.cmp(&j), "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn compares_numbers() {
+        sorted(
+            compare_numbers,
+            &[
+                "-10", "-9.5", "-1", "x", "0.05", "0.5", " 1", "9", "10.25", "100",
+            ],
+        );
+        assert_eq!(compare_numbers(b"-0", b"0"), Ordering::Equal);
+        assert_eq!(compare_numbers(b"007.50", b"7.5x"), Ordering::Equal);
+    }
+
+    #[test]
+    fn compares_human_numbers() {
+        sorted(
+            compare_human_numbers,
+            &[
+                "-1M", "-5K", "-900", "0", "5", "1000", "1k", "900K", "0.5M", "1G",
+            ],
+        );
+    }
+
+    #[test]
+    fn compares_general_numbers() {
+        sorted(
+            compare_general_numbers,
+            &[
+                "x", "nan", "-inf", "-1e3", "-0x10", "0", "1.5", "2e1", "1e300", "inf",
+            ],
+        );
+    }
+
+    #[test]
+    fn compares_by_the_keys() {
+        let comparator = Comparator {
+            keys: vec![Key::parse("2n").unwrap(), Key::parse("1Mr").unwrap()],
+            tab: Some(b':'),
+            ..Default::default()
+        };
+        let mut lines = ["jan:10", "feb:9", "Feb:10", "x:9"];
+        lines.sort_by(|a, b| comparator.compare(a.as_bytes(), b.as_bytes()));
+        assert_eq!(lines, ["feb:9", "x:9", "Feb:10", "jan:10"]);
+
+        let key = Key::parse("1df").unwrap();
+        assert_eq!(compare_key(&key, b"a-B", b"A+b"), Ordering::Equal);
+    }
+}
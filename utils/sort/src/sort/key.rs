@@ -0,0 +1,321 @@
+use super::sort_error::ArgError;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ignore {
+    /// -d, everything except blanks and alphanumerics
+    Dictionary,
+    /// -i, everything except printable characters
+    NonPrinting,
+}
+
+/// Start or end of a key: a field and a character in it, both 0-based
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Position {
+    pub field: usize,
+    pub char: usize,
+}
+
+/** A sort key, as given to -k, or the whole line with the global options.
+ *
+ * The orderings are flags, not an enum, as giving several of them has to be
+ * reported, the same way GNU does.
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Key {
+    /// None is the start of the line
+    pub start: Option<Position>,
+    /// None is the end of the line, the char of 0 is the end of the field
+    pub end: Option<Position>,
+    pub skip_start_blanks: bool,
+    pub skip_end_blanks: bool,
+    pub ignore: Option<Ignore>,
+    pub fold_case: bool,
+    pub numeric: bool,
+    pub general_numeric: bool,
+    pub human_numeric: bool,
+    pub month: bool,
+    pub version: bool,
+    pub reverse: bool,
+}
+
+impl Key {
+    /// Parses KEYDEF: F[.C][OPTS][,F[.C][OPTS]]
+    pub fn parse(spec: &str) -> Result<Key, ArgError> {
+        let invalid = |message: &str| {
+            ArgError(format!(
+                "{}: invalid field specification '{}'",
+                message, spec
+            ))
+        };
+        let mut key = Key::default();
+        let (field, rest) = count(spec, "invalid number at field start")?;
+        if field == 0 {
+            return Err(invalid("field number is zero"));
+        }
+        let (char, rest) = match rest.strip_prefix('.') {
+            Some(rest) => count(rest, "invalid number after '.'")?,
+            None => (1, rest),
+        };
+        if char == 0 {
+            return Err(invalid("character offset is zero"));
+        }
+        if field > 1 || char > 1 {
+            key.start = Some(Position {
+                field: field - 1,
+                char: char - 1,
+            });
+        }
+        let mut rest = key.set_options(rest, true);
+        if let Some(end) = rest.strip_prefix(',') {
+            let (field, end) = count(end, "invalid number after ','")?;
+            if field == 0 {
+                return Err(invalid("field number is zero"));
+            }
+            let (char, end) = match end.strip_prefix('.') {
+                Some(end) => count(end, "invalid number after '.'")?,
+                None => (0, end),
+            };
+            key.end = Some(Position {
+                field: field - 1,
+                char,
+            });
+            rest = key.set_options(end, false);
+        }
+        if !rest.is_empty() {
+            return Err(invalid("stray character in field spec"));
+        }
+        Ok(key)
+    }
+
+    /// Takes the ordering options, returning what's left after them
+    fn set_options<'a>(&mut self, options: &'a str, start: bool) -> &'a str {
+        let rest = options.trim_start_matches(|c| "bdfghiMnrV".contains(c));
+        for option in options[..options.len() - rest.len()].chars() {
+            match option {
+                'b' if start => self.skip_start_blanks = true,
+                'b' => self.skip_end_blanks = true,
+                'd' => self.ignore = Some(Ignore::Dictionary),
+                'f' => self.fold_case = true,
+                'g' => self.general_numeric = true,
+                'h' => self.human_numeric = true,
+                'i' => self.ignore = Some(Ignore::NonPrinting),
+                'M' => self.month = true,
+                'n' => self.numeric = true,
+                'r' => self.reverse = true,
+                _ => self.version = true,
+            }
+        }
+        rest
+    }
+
+    /// Whether the key compares in the default way, whatever its direction is
+    pub fn is_default(&self) -> bool {
+        let Key {
+            start,
+            end,
+            reverse,
+            ..
+        } = *self;
+        *self
+            == Key {
+                start,
+                end,
+                reverse,
+                ..Default::default()
+            }
+    }
+
+    /// The key gets the global options, keeping its positions
+    pub fn inherit(&mut self, global: &Key) {
+        *self = Key {
+            start: self.start,
+            end: self.end,
+            ..global.clone()
+        };
+    }
+
+    /// Only one way to compare may be given, like '-gn' is incompatible
+    pub fn check_compatibility(&self) -> Result<(), ArgError> {
+        let orderings = [
+            self.numeric,
+            self.general_numeric,
+            self.human_numeric,
+            self.month,
+            self.version || self.ignore.is_some(),
+        ];
+        if orderings.iter().filter(|set| **set).count() < 2 {
+            return Ok(());
+        }
+        let options = [
+            (self.ignore == Some(Ignore::Dictionary), 'd'),
+            (self.fold_case, 'f'),
+            (self.general_numeric, 'g'),
+            (self.human_numeric, 'h'),
+            (self.ignore == Some(Ignore::NonPrinting), 'i'),
+            (self.month, 'M'),
+            (self.numeric, 'n'),
+            (self.version, 'V'),
+        ];
+        let options: String = options.iter().filter(|o| o.0).map(|o| o.1).collect();
+        Err(ArgError(format!("options '-{}' are incompatible", options)))
+    }
+
+    /// The part of the line, that the key compares
+    pub fn span<'a>(&self, line: &'a [u8], tab: Option<u8>) -> &'a [u8] {
+        let start = match self.start {
+            None if self.skip_start_blanks => skip_blanks(line, 0),
+            None => 0,
+            Some(position) => self.begin(line, tab, position),
+        };
+        let end = match self.end {
+            None => line.len(),
+            Some(position) => self.limit(line, tab, position),
+        };
+        &line[start..end.max(start)]
+    }
+
+    fn begin(&self, line: &[u8], tab: Option<u8>, position: Position) -> usize {
+        let mut i = 0;
+        for _ in 0..position.field {
+            if i >= line.len() {
+                break;
+            }
+            i = match tab {
+                Some(tab) => (skip_until(line, i, tab) + 1).min(line.len()),
+                None => skip_nonblanks(line, skip_blanks(line, i)),
+            };
+        }
+        if self.skip_start_blanks {
+            i = skip_blanks(line, i);
+        }
+        i.saturating_add(position.char).min(line.len())
+    }
+
+    /// The end of the key, the separator after the last field isn't included
+    fn limit(&self, line: &[u8], tab: Option<u8>, position: Position) -> usize {
+        let mut fields = match position.char {
+            0 => position.field.saturating_add(1),
+            _ => position.field,
+        };
+        let mut i = 0;
+        while i < line.len() && fields > 0 {
+            fields -= 1;
+            i = match tab {
+                Some(tab) => {
+                    let end = skip_until(line, i, tab);
+                    match end < line.len() && (fields > 0 || position.char > 0) {
+                        true => end + 1,
+                        false => end,
+                    }
+                }
+                None => skip_nonblanks(line, skip_blanks(line, i)),
+            };
+        }
+        if position.char > 0 {
+            if self.skip_end_blanks {
+                i = skip_blanks(line, i);
+            }
+            i = i.saturating_add(position.char).min(line.len());
+        }
+        i
+    }
+}
+
+/// Newline is a blank too, it can be inside a line with -z
+pub fn is_blank(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n')
+}
+
+pub fn skip_blanks(line: &[u8], from: usize) -> usize {
+    from + line[from..].iter().take_while(|c| is_blank(**c)).count()
+}
+
+fn skip_nonblanks(line: &[u8], from: usize) -> usize {
+    from + line[from..].iter().take_while(|c| !is_blank(**c)).count()
+}
+
+fn skip_until(line: &[u8], from: usize, tab: u8) -> usize {
+    from + line[from..].iter().take_while(|c| **c != tab).count()
+}
+
+/// Leading number of the key spec, too big ones are the same as the biggest one
+fn count<'a>(spec: &'a str, message: &str) -> Result<(usize, &'a str), ArgError> {
+    let rest = spec.trim_start_matches(|c: char| c.is_ascii_digit());
+    let digits = &spec[..spec.len() - rest.len()];
+    if digits.is_empty() {
+        return Err(ArgError(format!(
+            "{}: invalid count at start of '{}'",
+            message, spec
+        )));
+    }
+    Ok((digits.parse().unwrap_or(usize::MAX), rest))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span<'a>(spec: &str, tab: Option<u8>, line: &'a str) -> &'a str {
+        let key = Key::parse(spec).unwrap();
+        std::str::from_utf8(key.span(line.as_bytes(), tab)).unwrap()
+    }
+
+    #[test]
+    fn finds_blank_separated_fields() {
+        assert_eq!(span("2", None, "a  b c"), "  b c");
+        assert_eq!(span("2b", None, "a  b c"), "b c");
+        assert_eq!(span("2,2", None, "a  b c"), "  b");
+        assert_eq!(span("1.2,1.3", None, "abcd"), "bc");
+        assert_eq!(span("2.2b,3", None, "a   xy z"), "y z");
+        assert_eq!(span("3", None, "a b"), "");
+        assert_eq!(span("1,1.2", None, "  ab"), "  ");
+    }
+
+    #[test]
+    fn finds_tab_separated_fields() {
+        let tab = Some(b':');
+        assert_eq!(span("2", tab, "a:b:c"), "b:c");
+        assert_eq!(span("2,2", tab, "a:b:c"), "b");
+        assert_eq!(span("2,2.1", tab, "a:bc:d"), "b");
+        assert_eq!(span("3,3", tab, "a::c"), "c");
+        assert_eq!(span("2,2", tab, "a::c"), "");
+    }
+
+    #[test]
+    fn parses_options_and_rejects_specs_the_gnu_way() {
+        let key = Key::parse("2.3nr,4b").unwrap();
+        assert_eq!(key.start, Some(Position { field: 1, char: 2 }));
+        assert_eq!(key.end, Some(Position { field: 3, char: 0 }));
+        assert!(key.numeric && key.reverse && key.skip_end_blanks && !key.skip_start_blanks);
+        assert!(Key::parse("1r").unwrap().is_default());
+
+        let message = |spec| Key::parse(spec).unwrap_err().0;
+        assert_eq!(
+            message("0"),
+            "field number is zero: invalid field specification '0'"
+        );
+        assert_eq!(
+            message("x"),
+            "invalid number at field start: invalid count at start of 'x'"
+        );
+        assert_eq!(
+            message("1.0"),
+            "character offset is zero: invalid field specification '1.0'"
+        );
+        assert_eq!(
+            message("1,x"),
+            "invalid number after ',': invalid count at start of 'x'"
+        );
+        assert_eq!(
+            message("1.1x"),
+            "stray character in field spec: invalid field specification '1.1x'"
+        );
+        let mut key = Key::parse("1Mn").unwrap();
+        assert_eq!(
+            key.check_compatibility().unwrap_err().0,
+            "options '-Mn' are incompatible"
+        );
+        key.month = false;
+        assert!(key.check_compatibility().is_ok());
+    }
+}
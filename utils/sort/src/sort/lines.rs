@@ -0,0 +1,137 @@
+use std::mem::size_of;
+use std::thread;
+
+use super::compare::Comparator;
+
+/// Below that, splitting the sort between the threads isn't worth it
+const MIN_LINES_PER_THREAD: usize = 4096;
+
+type Range = (usize, usize);
+
+/// Lines kept in memory for sorting, all of them in a single allocation
+#[derive(Debug, Default)]
+pub struct Lines {
+    data: Vec<u8>,
+    ranges: Vec<Range>,
+}
+
+impl Lines {
+    pub fn push(&mut self, line: &[u8]) {
+        let start = self.data.len();
+        self.data.extend_from_slice(line);
+        self.ranges.push((start, self.data.len()));
+    }
+
+    /// Memory taken by the lines, to be compared with the -S limit
+    pub fn size(&self) -> usize {
+        self.data.len() + self.ranges.len() * size_of::<Range>()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.ranges.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.ranges
+            .iter()
+            .map(|&(start, end)| &self.data[start..end])
+    }
+
+    /** Stable sort, split between the threads, with the sorted parts merged in pairs.
+     *
+     * The threads only get the line positions, the data itself is shared.
+     */
+    pub fn sort(&mut self, comparator: &Comparator, threads: usize) {
+        let data = &self.data;
+        let compare = |a: &Range, b: &Range| comparator.compare(&data[a.0..a.1], &data[b.0..b.1]);
+        let threads = threads.min(self.ranges.len() / MIN_LINES_PER_THREAD);
+        if threads <= 1 {
+            self.ranges.sort_by(compare);
+            return;
+        }
+        let part = self.ranges.len().div_ceil(threads);
+        thread::scope(|scope| {
+            for ranges in self.ranges.chunks_mut(part) {
+                scope.spawn(move || ranges.sort_by(compare));
+            }
+        });
+        let mut parts: Vec<Vec<Range>> = self.ranges.chunks(part).map(<[_]>::to_vec).collect();
+        while parts.len() > 1 {
+            parts = thread::scope(|scope| {
+                let merging: Vec<_> = parts
+                    .chunks(2)
+                    .map(|pair| scope.spawn(move || merge_sorted(pair, compare)))
+                    .collect();
+                merging
+                    .into_iter()
+                    .map(|merged| {
+                        merged
+                            .join()
+                            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+                    })
+                    .collect()
+            });
+        }
+        self.ranges = parts.pop().unwrap_or_default();
+    }
+}
+
+/// Merges one or two sorted parts, the first one wins for the equal lines
+fn merge_sorted(
+    parts: &[Vec<Range>],
+    compare: impl Fn(&Range, &Range) -> std::cmp::Ordering,
+) -> Vec<Range> {
+    let [a, b] = parts else {
+        return parts.concat();
+    };
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if compare(&b[j], &a[i]).is_lt() {
+            merged.push(b[j]);
+            j += 1;
+        } else {
+            merged.push(a[i]);
+            i += 1;
+        }
+    }
+    merged.extend_from_slice(&a[i..]);
+    merged.extend_from_slice(&b[j..]);
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::key::Key;
+    use super::*;
+
+    #[test]
+    fn parallel_sort_is_stable() {
+        let comparator = Comparator {
+            keys: vec![Key::parse("1,1n").unwrap()],
+            stable: true,
+            ..Default::default()
+        };
+        let mut lines = Lines::default();
+        let count = MIN_LINES_PER_THREAD * 5 + 7;
+        for i in 0..count {
+            lines.push(format!("{} {}", (count - i) % 10, i).as_bytes());
+        }
+        lines.sort(&comparator, 4);
+        let sorted: Vec<(usize, usize)> = lines
+            .iter()
+            .map(|line| {
+                let line = std::str::from_utf8(line).unwrap();
+                let (key, position) = line.split_once(' ').unwrap();
+                (key.parse().unwrap(), position.parse().unwrap())
+            })
+            .collect();
+        assert_eq!(sorted.len(), count);
+        assert!(sorted.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::{BufReader, Read, Write};
+use std::path::PathBuf;
+
+use chunk_reader::{ChunkReader, Chunked};
+
+use super::compare::Comparator;
+use super::sort_error::SortError;
+
+/// A sorted input of a merge: a run in a temporary file, or a FILE of -m
+pub struct Source {
+    name: String,
+    lines: Chunked<BufReader<Box<dyn Read>>>,
+    terminator: u8,
+    /// The run file, to be removed once it's merged
+    pub temp: Option<PathBuf>,
+}
+
+impl Source {
+    pub fn new(name: &str, reader: Box<dyn Read>, terminator: u8) -> Self {
+        Source {
+            name: name.to_string(),
+            lines: BufReader::new(reader).chunks(terminator, 0),
+            terminator,
+            temp: None,
+        }
+    }
+
+    /// The next line without its terminator
+    fn next_line(&mut self) -> Result<Option<Vec<u8>>, SortError> {
+        match self.lines.next() {
+            Some(Ok(mut line)) => {
+                if line.last() == Some(&self.terminator) {
+                    line.pop();
+                }
+                Ok(Some(line))
+            }
+            Some(Err(e)) => Err(SortError::Read(self.name.clone(), e)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Where the sorted lines go, only the first one of the equal lines with -u
+pub struct Output<'a, W> {
+    name: String,
+    writer: W,
+    terminator: u8,
+    comparator: &'a Comparator,
+    unique: bool,
+    last: Option<Vec<u8>>,
+}
+
+impl<'a, W: Write> Output<'a, W> {
+    pub fn new(
+        name: &str,
+        writer: W,
+        terminator: u8,
+        comparator: &'a Comparator,
+        unique: bool,
+    ) -> Self {
+        Output {
+            name: name.to_string(),
+            writer,
+            terminator,
+            comparator,
+            unique,
+            last: None,
+        }
+    }
+
+    pub fn write_line(&mut self, line: &[u8]) -> Result<(), SortError> {
+        if self.unique {
+            if let Some(last) = &self.last {
+                if self.comparator.compare(last, line).is_eq() {
+                    return Ok(());
+                }
+            }
+            self.last = Some(line.to_vec());
+        }
+        self.writer
+            .write_all(line)
+            .and_then(|_| self.writer.write_all(&[self.terminator]))
+            .map_err(|e| SortError::Write(self.name.clone(), e))
+    }
+
+    pub fn finish(mut self) -> Result<(), SortError> {
+        self.writer
+            .flush()
+            .map_err(|e| SortError::Write(self.name.clone(), e))
+    }
+}
+
+/** Merges the sorted sources into the output.
+ *
+ * The current lines of the sources are kept in order, the smallest one last,
+ * and the equal lines go in the order of the sources.
+ */
+pub fn merge<W: Write>(
+    mut sources: Vec<Source>,
+    comparator: &Comparator,
+    output: &mut Output<W>,
+) -> Result<(), SortError> {
+    let mut heads: Vec<Option<Vec<u8>>> = Vec::with_capacity(sources.len());
+    for source in &mut sources {
+        heads.push(source.next_line()?);
+    }
+    let mut order: Vec<usize> = Vec::with_capacity(sources.len());
+    for source in 0..sources.len() {
+        insert(&mut order, &heads, source, comparator);
+    }
+    while let Some(source) = order.pop() {
+        if let Some(line) = heads[source].take() {
+            output.write_line(&line)?;
+        }
+        heads[source] = sources[source].next_line()?;
+        insert(&mut order, &heads, source, comparator);
+    }
+    Ok(())
+}
+
+/// Puts the source to its place by its current line, if it has one
+fn insert(
+    order: &mut Vec<usize>,
+    heads: &[Option<Vec<u8>>],
+    source: usize,
+    comparator: &Comparator,
+) {
+    let Some(line) = &heads[source] else {
+        return;
+    };
+    let position = order.partition_point(|&other| {
+        let other_line = heads[other].as_deref().unwrap_or_default();
+        comparator
+            .compare(other_line, line)
+            .then(other.cmp(&source))
+            .is_gt()
+    });
+    order.insert(position, source);
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::super::key::Key;
+    use super::*;
+
+    fn source(content: &'static str) -> Source {
+        Source::new("-", Box::new(Cursor::new(content)), b'\n')
+    }
+
+    fn merged(comparator: &Comparator, unique: bool, contents: &[&'static str]) -> String {
+        let mut result = Vec::new();
+        let mut output = Output::new("-", &mut result, b'\n', comparator, unique);
+        merge(
+            contents.iter().map(|c| source(c)).collect(),
+            comparator,
+            &mut output,
+        )
+        .unwrap();
+        output.finish().unwrap();
+        String::from_utf8(result).unwrap()
+    }
+
+    #[test]
+    fn merges_sorted_sources() {
+        let comparator = Comparator::default();
+        assert_eq!(
+            merged(&comparator, false, &["a\nc\ne", "", "b\nd\n"]),
+            "a\nb\nc\nd\ne\n"
+        );
+        assert_eq!(
+            merged(&comparator, true, &["a\nb\n", "a\nb\nc\n"]),
+            "a\nb\nc\n"
+        );
+    }
+
+    #[test]
+    fn equal_lines_go_in_the_order_of_sources() {
+        let comparator = Comparator {
+            keys: vec![Key::parse("1,1").unwrap()],
+            stable: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            merged(&comparator, false, &["1 c\n2 c\n", "1 b\n2 b\n", "1 a\n"]),
+            "1 c\n1 b\n1 a\n2 c\n2 b\n"
+        );
+        assert_eq!(
+            merged(&comparator, true, &["1 c\n", "1 b\n2 b\n"]),
+            "1 c\n2 b\n"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+pub mod compare;
+pub mod key;
+mod lines;
+mod merge;
+pub mod sort_error;
+pub mod temp_files;
+mod version;
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::PathBuf;
+
+use chunk_reader::ChunkReader;
+
+use crate::args::Args;
+use lines::Lines;
+use merge::{merge, Output, Source};
+use sort_error::SortError;
+use temp_files::TempFiles;
+
+/// Number of the sorted runs merged at once, so there are not too many open files
+const MERGE_BATCH: usize = 16;
+
+/** Sorts the FILEs into the output.
+ *
+ * The lines are sorted in the buffer of -S size, when it's full, they go to
+ * a temporary file as a sorted run, and the runs are merged in the end.
+ */
+pub fn sort_files(args: &Args, temp: &mut TempFiles) -> Result<(), SortError> {
+    let terminator = args.terminator();
+    let mut lines = Lines::default();
+    let mut runs = Vec::new();
+    for name in &args.file {
+        for line in BufReader::new(open(name)?).chunks(terminator, 0) {
+            let line = line.map_err(|e| SortError::Read(name.clone(), e))?;
+            lines.push(line.strip_suffix(&[terminator]).unwrap_or(&line));
+            if lines.size() >= args.buffer_size {
+                runs.push(write_run(args, &mut lines, temp)?);
+            }
+        }
+    }
+    if runs.is_empty() {
+        lines.sort(&args.comparator, args.parallel);
+        let mut output = create_output(args)?;
+        for line in lines.iter() {
+            output.write_line(line)?;
+        }
+        return output.finish();
+    }
+    if !lines.is_empty() {
+        runs.push(write_run(args, &mut lines, temp)?);
+    }
+    let sources = runs
+        .into_iter()
+        .map(|path| open_run(path, terminator))
+        .collect::<Result<_, _>>()?;
+    merge_all(args, sources, temp)
+}
+
+/// Merges the FILEs, which are sorted already, for -m
+pub fn merge_files(args: &Args, temp: &mut TempFiles) -> Result<(), SortError> {
+    let terminator = args.terminator();
+    let output_path = args.output.as_ref().and_then(|o| fs::canonicalize(o).ok());
+    let mut sources = Vec::new();
+    for name in &args.file {
+        let input = open(name)?;
+        // The output would be truncated before the input is read
+        let is_output =
+            name != "-" && output_path.is_some() && fs::canonicalize(name).ok() == output_path;
+        let source = match is_output {
+            true => {
+                let (path, mut file) = temp.create()?;
+                io::copy(&mut BufReader::new(input), &mut file)
+                    .map_err(|e| SortError::Write(path.display().to_string(), e))?;
+                open_run(path, terminator)?
+            }
+            false => Source::new(name, input, terminator),
+        };
+        sources.push(source);
+    }
+    merge_all(args, sources, temp)
+}
+
+/// Checks, whether the FILE is sorted, for -c and -C, reporting the first disorder unless quiet
+pub fn check_file(args: &Args, quiet: bool) -> Result<bool, SortError> {
+    let name = &args.file[0];
+    let terminator = args.terminator();
+    let mut previous: Option<Vec<u8>> = None;
+    for (number, line) in BufReader::new(open(name)?)
+        .chunks(terminator, 0)
+        .enumerate()
+    {
+        let mut line = line.map_err(|e| SortError::Read(name.clone(), e))?;
+        if line.last() == Some(&terminator) {
+            line.pop();
+        }
+        if let Some(previous) = &previous {
+            let order = args.comparator.compare(previous, &line);
+            if order.is_gt() || (args.unique && order.is_eq()) {
+                if !quiet {
+                    let mut stderr = io::stderr();
+                    let _ = write!(stderr, "sort: {}:{}: disorder: ", name, number + 1);
+                    let _ = stderr.write_all(&line);
+                    let _ = stderr.write_all(b"\n");
+                }
+                return Ok(false);
+            }
+        }
+        previous = Some(line);
+    }
+    Ok(true)
+}
+
+fn open(name: &str) -> Result<Box<dyn Read>, SortError> {
+    match name {
+        "-" => Ok(Box::new(io::stdin())),
+        _ => match File::open(name) {
+            Ok(file) => Ok(Box::new(file)),
+            Err(e) => Err(SortError::Open(name.to_string(), e)),
+        },
+    }
+}
+
+fn open_run(path: PathBuf, terminator: u8) -> Result<Source, SortError> {
+    let name = path.display().to_string();
+    let file = File::open(&path).map_err(|e| SortError::Open(name.clone(), e))?;
+    let mut source = Source::new(&name, Box::new(file), terminator);
+    source.temp = Some(path);
+    Ok(source)
+}
+
+/// Only opened, when all the input is read, as it may be one of the FILEs
+fn create_output(args: &Args) -> Result<Output<'_, BufWriter<Box<dyn Write>>>, SortError> {
+    let (name, writer): (&str, Box<dyn Write>) = match &args.output {
+        Some(name) => match File::create(name) {
+            Ok(file) => (name, Box::new(file)),
+            Err(e) => return Err(SortError::Create(name.clone(), e)),
+        },
+        None => ("standard output", Box::new(io::stdout())),
+    };
+    Ok(Output::new(
+        name,
+        BufWriter::new(writer),
+        args.terminator(),
+        &args.comparator,
+        args.unique,
+    ))
+}
+
+/// Sorts the buffered lines into a new run
+fn write_run(args: &Args, lines: &mut Lines, temp: &mut TempFiles) -> Result<PathBuf, SortError> {
+    lines.sort(&args.comparator, args.parallel);
+    let (path, file) = temp.create()?;
+    let name = path.display().to_string();
+    let writer = BufWriter::new(file);
+    let mut output = Output::new(
+        &name,
+        writer,
+        args.terminator(),
+        &args.comparator,
+        args.unique,
+    );
+    for line in lines.iter() {
+        output.write_line(line)?;
+    }
+    output.finish()?;
+    lines.clear();
+    Ok(path)
+}
+
+/// Merges the sources in batches, while there are too many of them, then into the output
+fn merge_all(args: &Args, mut sources: Vec<Source>, temp: &mut TempFiles) -> Result<(), SortError> {
+    let terminator = args.terminator();
+    while sources.len() > MERGE_BATCH {
+        let batch: Vec<Source> = sources.drain(..MERGE_BATCH).collect();
+        let merged: Vec<PathBuf> = batch.iter().filter_map(|s| s.temp.clone()).collect();
+        let (path, file) = temp.create()?;
+        let name = path.display().to_string();
+        let mut output = Output::new(
+            &name,
+            BufWriter::new(file),
+            terminator,
+            &args.comparator,
+            args.unique,
+        );
+        merge(batch, &args.comparator, &mut output)?;
+        output.finish()?;
+        for run in merged {
+            temp.remove(&run);
+        }
+        // The batch goes first, so the equal lines stay in the input order
+        sources.insert(0, open_run(path, terminator)?);
+    }
+    let mut output = create_output(args)?;
+    merge(sources, &args.comparator, &mut output)?;
+    output.finish()
+}
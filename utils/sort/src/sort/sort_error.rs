@@ -0,0 +1,74 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum SortError {
+    Open(String, io::Error),
+    Read(String, io::Error),
+    /// Opening the -o file
+    Create(String, io::Error),
+    Write(String, io::Error),
+    /// The directory, where it failed
+    TempFile(String, io::Error),
+}
+
+impl fmt::Display for SortError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortError::Open(name, err) => {
+                write!(f, "cannot read: {}: {}", quote(name), strerror(err))
+            }
+            SortError::Read(name, err) => {
+                write!(f, "read failed: {}: {}", quote(name), strerror(err))
+            }
+            SortError::Create(name, err) => {
+                write!(f, "open failed: {}: {}", quote(name), strerror(err))
+            }
+            SortError::Write(name, err) => {
+                write!(f, "write failed: {}: {}", quote(name), strerror(err))
+            }
+            SortError::TempFile(dir, err) => write!(
+                f,
+                "cannot create temporary file in '{}': {}",
+                dir,
+                strerror(err)
+            ),
+        }
+    }
+}
+
+impl Error for SortError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SortError::Open(_, err)
+            | SortError::Read(_, err)
+            | SortError::Create(_, err)
+            | SortError::Write(_, err)
+            | SortError::TempFile(_, err) => Some(err),
+        }
+    }
+}
+
+/// Wrong arguments, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
@@ -0,0 +1,64 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use super::sort_error::SortError;
+
+/** Temporary files for the sorted runs, removed when they're merged, or at the end.
+ *
+ * The files go to the directories of -T in turn, or to $TMPDIR, or /tmp.
+ */
+#[derive(Debug)]
+pub struct TempFiles {
+    dirs: Vec<PathBuf>,
+    next_dir: usize,
+    counter: usize,
+    created: Vec<PathBuf>,
+}
+
+impl TempFiles {
+    pub fn new(dirs: &[String]) -> Self {
+        let dirs = match dirs.is_empty() {
+            true => vec![env::temp_dir()],
+            false => dirs.iter().map(PathBuf::from).collect(),
+        };
+        TempFiles {
+            dirs,
+            next_dir: 0,
+            counter: 0,
+            created: Vec::new(),
+        }
+    }
+
+    pub fn create(&mut self) -> Result<(PathBuf, File), SortError> {
+        let dir = &self.dirs[self.next_dir % self.dirs.len()];
+        self.next_dir += 1;
+        loop {
+            let path = dir.join(format!("sort{}.{}", process::id(), self.counter));
+            self.counter += 1;
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    self.created.push(path.clone());
+                    return Ok((path, file));
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(SortError::TempFile(dir.display().to_string(), e)),
+            }
+        }
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        let _ = fs::remove_file(path);
+        self.created.retain(|created| created != path);
+    }
+}
+
+impl Drop for TempFiles {
+    fn drop(&mut self) {
+        for path in &self.created {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
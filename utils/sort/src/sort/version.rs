@@ -0,0 +1,138 @@
+use std::cmp::Ordering;
+
+/** Compares file names with versions in them, GNU filevercmp() for -V.
+ *
+ * Digit sequences are compared as numbers, '~' sorts before everything, even the end,
+ * and the file suffixes, like ".tar.gz", only matter if the rest is the same.
+ */
+pub fn compare_versions(a: &[u8], b: &[u8]) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        _ => {}
+    }
+    // ".", "..", then the other hidden files, then everything else
+    match (a[0] == b'.', b[0] == b'.') {
+        (true, true) => {
+            let special = |s: &[u8]| match s {
+                b"." => 0,
+                b".." => 1,
+                _ => 2,
+            };
+            let order = special(a).cmp(&special(b));
+            if order.is_ne() || special(a) < 2 {
+                return order;
+            }
+        }
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        _ => {}
+    }
+    let a_prefix = prefix_len(a);
+    let b_prefix = prefix_len(b);
+    let order = compare_parts(&a[..a_prefix], &b[..b_prefix]);
+    if order.is_ne() || (a_prefix == a.len() && b_prefix == b.len()) {
+        return order;
+    }
+    compare_parts(a, b)
+}
+
+/// Length of the name without its suffix, which matches (\.[A-Za-z~][A-Za-z0-9~]*)*$
+fn prefix_len(s: &[u8]) -> usize {
+    let mut prefix = 0;
+    let mut i = 0;
+    while i < s.len() {
+        i += 1;
+        prefix = i;
+        while i + 1 < s.len()
+            && s[i] == b'.'
+            && (s[i + 1].is_ascii_alphabetic() || s[i + 1] == b'~')
+        {
+            i += 2;
+            while i < s.len() && (s[i].is_ascii_alphanumeric() || s[i] == b'~') {
+                i += 1;
+            }
+        }
+    }
+    prefix
+}
+
+/// Weight of a non-digit: the end and digits are 0, '~' goes before them, letters after
+fn weight(s: &[u8], i: usize) -> i32 {
+    match s.get(i) {
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => *c as i32,
+        Some(b'~') => -1,
+        Some(c) => *c as i32 + 256,
+    }
+}
+
+fn compare_parts(a: &[u8], b: &[u8]) -> Ordering {
+    let is_digit = |s: &[u8], i: usize| s.get(i).is_some_and(|c| c.is_ascii_digit());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !is_digit(a, i)) || (j < b.len() && !is_digit(b, j)) {
+            let order = weight(a, i).cmp(&weight(b, j));
+            if order.is_ne() {
+                return order;
+            }
+            i += 1;
+            j += 1;
+        }
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+        let mut first_difference = Ordering::Equal;
+        while is_digit(a, i) && is_digit(b, j) {
+            if first_difference.is_eq() {
+                first_difference = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if is_digit(a, i) {
+            return Ordering::Greater;
+        }
+        if is_digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_difference.is_ne() {
+            return first_difference;
+        }
+    }
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sorts_like_filevercmp() {
+        let sorted: [&[u8]; 12] = [
+            b"",
+            b".",
+            b"..",
+            b".a",
+            b"a~1",
+            b"a",
+            b"a1",
+            b"a1.tar.gz",
+            b"a2",
+            b"a10",
+            b"a10b",
+            b"b",
+        ];
+        for (i, a) in sorted.iter().enumerate() {
+            for (j, b) in sorted.iter().enumerate() {
+                assert_eq!(compare_versions(a, b), i.cmp(&j), "{:?} {:?}", a, b);
+            }
+        }
+        assert_eq!(compare_versions(b"a01", b"a1"), Ordering::Equal);
+    }
+}
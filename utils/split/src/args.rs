@@ -0,0 +1,346 @@
+use std::error::Error;
+
+use clap::Parser;
+use parse_num::{parse_num, NumValue};
+
+use crate::split::split_error::ArgError;
+use crate::split::suffixes::Alphabet;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+const DEFAULT_LINES: u64 = 1000;
+const DEFAULT_SUFFIX_LENGTH: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChunkKind {
+    /// N: split into N files by size
+    Bytes,
+    /// l/N: split into N files by size, without splitting lines
+    Lines,
+    /// r/N: distribute the lines round robin
+    RoundRobin,
+}
+
+/** CHUNKS of -n: the kind of splitting, the number of chunks and maybe the only one to output */
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Chunks {
+    pub kind: ChunkKind,
+    /// K of K/N, the chunk to print to stdout instead of writing all the files
+    pub number: Option<u64>,
+    pub total: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+    Bytes(u64),
+    LineBytes(u64),
+    Lines(u64),
+    Chunks(Chunks),
+}
+
+impl Default for Mode {
+    fn default() -> Self {
+        Mode::Lines(DEFAULT_LINES)
+    }
+}
+
+/// Output pieces of FILE to PREFIXaa, PREFIXab, ...
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// with no FILE, or when FILE is -, read standard input
+    #[arg(default_value = "-")]
+    pub file: String,
+
+    #[arg(default_value = "x")]
+    pub prefix: String,
+
+    /// generate suffixes of length N (default 2)
+    #[arg(short = 'a', long, value_name = "N", value_parser = parse_suffix_length, allow_hyphen_values = true, default_value = "0")]
+    pub suffix_length: usize,
+
+    /// append an additional SUFFIX to file names
+    #[arg(long, value_name = "SUFFIX", default_value = "")]
+    pub additional_suffix: String,
+
+    /// put SIZE bytes per output file
+    #[arg(short = 'b', long, value_name = "SIZE", value_parser = count_parser("bytes"))]
+    pub bytes: Option<u64>,
+
+    /// put at most SIZE bytes of records per output file
+    #[arg(short = 'C', long, value_name = "SIZE", value_parser = count_parser("bytes"))]
+    pub line_bytes: Option<u64>,
+
+    /// use numeric suffixes starting at 0, not alphabetic
+    #[arg(short = 'd', default_value_t = false)]
+    pub numeric: bool,
+
+    /// same as -d, but allow setting the start value
+    #[arg(long, value_name = "FROM", num_args = 0..=1, require_equals = true)]
+    pub numeric_suffixes: Option<Option<String>>,
+
+    /// use hex suffixes starting at 0, not alphabetic
+    #[arg(short = 'x', default_value_t = false)]
+    pub hex: bool,
+
+    /// same as -x, but allow setting the start value
+    #[arg(long, value_name = "FROM", num_args = 0..=1, require_equals = true)]
+    pub hex_suffixes: Option<Option<String>>,
+
+    /// do not generate empty output files with '-n'
+    #[arg(short = 'e', long, default_value_t = false)]
+    pub elide_empty_files: bool,
+
+    /// write to shell COMMAND; file name is $FILE
+    #[arg(long, value_name = "COMMAND")]
+    pub filter: Option<String>,
+
+    /// put NUMBER lines/records per output file
+    #[arg(short = 'l', long, value_name = "NUMBER", value_parser = count_parser("lines"))]
+    pub lines: Option<u64>,
+
+    /// generate CHUNKS output files: N, K/N, l/N, l/K/N, r/N, r/K/N
+    #[arg(short = 'n', long, value_name = "CHUNKS", value_parser = parse_chunks)]
+    pub number: Option<Chunks>,
+
+    /// use SEP instead of newline as the record separator; '\0' (zero) specifies the NUL character
+    #[arg(short = 't', long, value_name = "SEP", value_parser = parse_separator, default_value = "\n", hide_default_value = true)]
+    pub separator: u8,
+
+    // computed based on other args
+    #[arg(skip)]
+    pub mode: Mode,
+    #[arg(skip)]
+    pub alphabet: Alphabet,
+    #[arg(skip)]
+    pub suffix_start: u64,
+    /// whether the suffixes get longer instead of running out, the GNU way
+    #[arg(skip)]
+    pub widening: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                eprintln!("split: {}", invalid);
+                std::process::exit(1);
+            }
+            err.exit()
+        });
+        args.mode = match (args.bytes, args.line_bytes, args.lines, args.number) {
+            (None, None, None, None) => Mode::default(),
+            (Some(size), None, None, None) => Mode::Bytes(size),
+            (None, Some(size), None, None) => Mode::LineBytes(size),
+            (None, None, Some(lines), None) => Mode::Lines(lines),
+            (None, None, None, Some(chunks)) => Mode::Chunks(chunks),
+            _ => usage_error("cannot split in more than one way"),
+        };
+        if args.additional_suffix.contains('/') {
+            usage_error(&format!(
+                "invalid suffix '{}', contains directory separator",
+                args.additional_suffix
+            ));
+        }
+        let start = args.resolve_alphabet();
+        args.suffix_start = match &start {
+            Some(start) => u64::from_str_radix(start, args.alphabet.radix())
+                .unwrap_or_else(|_| usage_error(&start_error(start, args.alphabet))),
+            None => 0,
+        };
+        args.resolve_suffix_length(start.is_some());
+        args
+    }
+
+    /// Picks the suffix alphabet, returning FROM if there is one
+    fn resolve_alphabet(&mut self) -> Option<String> {
+        let (alphabet, start) = match (&self.hex_suffixes, &self.numeric_suffixes) {
+            (Some(start), _) => (Alphabet::Hex, start.clone()),
+            (None, Some(start)) => (Alphabet::Decimal, start.clone()),
+            _ if self.hex => (Alphabet::Hex, None),
+            _ if self.numeric => (Alphabet::Decimal, None),
+            _ => (Alphabet::Alphabetic, None),
+        };
+        self.alphabet = alphabet;
+        start
+    }
+
+    fn resolve_suffix_length(&mut self, has_start: bool) {
+        let explicit = self.suffix_length > 0;
+        self.widening = !explicit && !has_start && !matches!(self.mode, Mode::Chunks(_));
+        if !explicit {
+            self.suffix_length = DEFAULT_SUFFIX_LENGTH;
+        }
+        // Every chunk has to get its own name, so there have to be enough suffixes for them
+        if let Mode::Chunks(chunks) = self.mode {
+            let required = self.alphabet.digits_needed(chunks.total - 1);
+            if explicit && self.suffix_length < required {
+                eprintln!("split: the suffix length needs to be at least {}", required);
+                std::process::exit(1);
+            }
+            self.suffix_length = self.suffix_length.max(required);
+        }
+        if self.alphabet.digits_needed(self.suffix_start) > self.suffix_length {
+            usage_error("numerical suffix start value is too large for the suffix length");
+        }
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("split: {}", message);
+    eprintln!("Try 'split --help' for more information.");
+    std::process::exit(1);
+}
+
+fn start_error(start: &str, alphabet: Alphabet) -> String {
+    let kind = match alphabet {
+        Alphabet::Hex => "hexadecimal",
+        _ => "numerical",
+    };
+    format!("'{}': invalid start value for {} suffix", start, kind)
+}
+
+/// Positive count, that must fit u64, as GNU requires
+fn parse_count(input: &str, quantity: &str) -> Result<u64, ArgError> {
+    let invalid = |details: &str| {
+        ArgError(format!(
+            "invalid number of {}: '{}'{}",
+            quantity, input, details
+        ))
+    };
+    let num = match parse_num(input) {
+        Ok(NumValue {
+            prefix: Some('-'), ..
+        })
+        | Err(_) => return Err(invalid("")),
+        Ok(num) => num,
+    };
+    match u64::try_from(&num.to_ubig()) {
+        Ok(0) => Err(invalid(": Numerical result out of range")),
+        Ok(n) => Ok(n),
+        Err(_) => Err(invalid(": Value too large for defined data type")),
+    }
+}
+
+fn count_parser(
+    quantity: &'static str,
+) -> impl Fn(&str) -> Result<u64, ArgError> + Clone + Send + Sync + 'static {
+    move |input: &str| parse_count(input, quantity)
+}
+
+fn parse_suffix_length(length: &str) -> Result<usize, ArgError> {
+    length
+        .parse()
+        .map_err(|_| ArgError(format!("invalid suffix length: '{}'", length)))
+}
+
+/// N, K/N, l/N, l/K/N, r/N, r/K/N
+fn parse_chunks(chunks: &str) -> Result<Chunks, ArgError> {
+    let (kind, rest) = match chunks.split_once('/') {
+        Some(("l", rest)) => (ChunkKind::Lines, rest),
+        Some(("r", rest)) => (ChunkKind::RoundRobin, rest),
+        _ => (ChunkKind::Bytes, chunks),
+    };
+    let (number, total) = match rest.split_once('/') {
+        Some((number, total)) => (Some(number), total),
+        None => (None, rest),
+    };
+    let total = parse_count(total, "chunks")?;
+    let number = match number {
+        Some(number) => match parse_count(number, "chunks") {
+            Ok(k) if k <= total => Some(k),
+            Ok(_) => {
+                return Err(ArgError(format!(
+                    "invalid chunk number: '{}': Numerical result out of range",
+                    number
+                )))
+            }
+            Err(ArgError(message)) => {
+                return Err(ArgError(
+                    message.replace("number of chunks", "chunk number"),
+                ))
+            }
+        },
+        None => None,
+    };
+    Ok(Chunks {
+        kind,
+        number,
+        total,
+    })
+}
+
+fn parse_separator(separator: &str) -> Result<u8, ArgError> {
+    match separator.as_bytes() {
+        [] => Err(ArgError("empty record separator".into())),
+        [c] => Ok(*c),
+        b"\\0" => Ok(b'\0'),
+        _ => Err(ArgError(format!(
+            "multi-character separator '{}'",
+            separator.replace('\\', "\\\\")
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_chunks() {
+        assert_eq!(
+            parse_chunks("5").unwrap(),
+            Chunks {
+                kind: ChunkKind::Bytes,
+                number: None,
+                total: 5
+            }
+        );
+        assert_eq!(
+            parse_chunks("l/2/5").unwrap(),
+            Chunks {
+                kind: ChunkKind::Lines,
+                number: Some(2),
+                total: 5
+            }
+        );
+        assert_eq!(parse_chunks("r/3").unwrap().kind, ChunkKind::RoundRobin);
+        assert_eq!(parse_chunks("3/5").unwrap().number, Some(3));
+    }
+
+    #[test]
+    fn rejects_bad_chunks_the_gnu_way() {
+        let message = |chunks| parse_chunks(chunks).unwrap_err().to_string();
+        assert_eq!(
+            message("0"),
+            "invalid number of chunks: '0': Numerical result out of range"
+        );
+        assert_eq!(
+            message("6/5"),
+            "invalid chunk number: '6': Numerical result out of range"
+        );
+        assert_eq!(message("r/1/2/3"), "invalid number of chunks: '2/3'");
+        assert_eq!(message("l/x/3"), "invalid chunk number: 'x'");
+    }
+
+    #[test]
+    fn parses_counts() {
+        assert_eq!(parse_count("2K", "bytes").unwrap(), 2048);
+        assert!(parse_count("-1", "lines").is_err());
+        assert_eq!(
+            parse_count("99999999999999999999999", "lines")
+                .unwrap_err()
+                .to_string(),
+            "invalid number of lines: '99999999999999999999999': Value too large for defined data type"
+        );
+    }
+
+    #[test]
+    fn parses_separators() {
+        assert_eq!(parse_separator(","), Ok(b','));
+        assert_eq!(parse_separator("\\0"), Ok(b'\0'));
+        assert!(parse_separator("ab").is_err());
+        assert!(parse_separator("").is_err());
+    }
+}
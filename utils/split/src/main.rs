@@ -0,0 +1,26 @@
+mod args;
+mod split;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use split::outputs::Outputs;
+use split::split;
+use strerror::strerror;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let mut outputs = Outputs::new(&args);
+    if let Err(e) = split(&args, &mut outputs, &mut output) {
+        let _ = output.flush();
+        eprintln!("split: {}", e);
+        return ExitCode::from(e.exit_code());
+    }
+    if let Err(e) = output.flush() {
+        eprintln!("split: write error: {}", strerror(&e));
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,281 @@
+pub mod outputs;
+pub mod split_error;
+pub mod suffixes;
+
+use std::fs::File;
+use std::io::{self, BufReader, ErrorKind, Read, Seek, Write};
+
+use chunk_reader::ChunkReader;
+
+use crate::args::{Args, ChunkKind, Chunks, Mode};
+use outputs::{Outputs, Sink};
+use split_error::SplitError;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/** Splits the input file into the outputs, the K/N chunks go to the stdout instead */
+pub fn split<W: Write>(
+    args: &Args,
+    outputs: &mut Outputs,
+    stdout: &mut W,
+) -> Result<(), SplitError> {
+    let name = args.file.as_str();
+    let (input, size) = open(name).map_err(|e| SplitError::Open(name.to_string(), e))?;
+    let read_error = |e| SplitError::Read(name.to_string(), e);
+    match args.mode {
+        Mode::Bytes(size) => split_bytes(input, size, outputs, read_error),
+        Mode::LineBytes(size) => {
+            // A record longer than SIZE is cut into SIZE pieces, exactly what the cap does
+            let limit = usize::try_from(size).unwrap_or(usize::MAX);
+            let records = BufReader::new(input).chunks(args.separator, limit);
+            let mut used = 0;
+            for record in records {
+                let record = record.map_err(read_error)?;
+                if !outputs.is_open() || used + record.len() as u64 > size {
+                    outputs.next_file()?;
+                    used = 0;
+                }
+                outputs.write(&record)?;
+                used += record.len() as u64;
+            }
+            outputs.close()
+        }
+        Mode::Lines(lines) => {
+            let records = BufReader::new(input).chunks(args.separator, 0);
+            for (i, record) in records.enumerate() {
+                let record = record.map_err(read_error)?;
+                if (i as u64).is_multiple_of(lines) {
+                    outputs.next_file()?;
+                }
+                outputs.write(&record)?;
+            }
+            outputs.close()
+        }
+        Mode::Chunks(chunks) if chunks.kind == ChunkKind::RoundRobin => {
+            let records = BufReader::new(input).chunks(args.separator, 0);
+            let mut sinks: Vec<Option<Sink>> = Vec::new();
+            if chunks.number.is_none() && !args.elide_empty_files {
+                for _ in 0..chunks.total {
+                    sinks.push(Some(outputs.create()?));
+                }
+            }
+            for (i, record) in records.enumerate() {
+                let record = record.map_err(read_error)?;
+                let k = i as u64 % chunks.total;
+                match chunks.number {
+                    Some(number) if number == k + 1 => {
+                        stdout.write_all(&record).map_err(SplitError::Output)?
+                    }
+                    Some(_) => {}
+                    None => {
+                        // The names go in order anyway, as the files are taken in turns
+                        if sinks.len() as u64 <= k {
+                            sinks.push(Some(outputs.create()?));
+                        }
+                        if let Some(sink) = &mut sinks[k as usize] {
+                            sink.write(&record)?;
+                        }
+                    }
+                }
+            }
+            sinks.into_iter().flatten().try_for_each(Sink::finish)
+        }
+        Mode::Chunks(chunks) => {
+            let size = size.ok_or_else(|| SplitError::UnknownSize(name.to_string()))?;
+            let bounds = ChunkBounds { chunks, size };
+            match chunks.kind {
+                ChunkKind::Lines => {
+                    split_chunk_lines(input, args, bounds, outputs, stdout, read_error)
+                }
+                _ => split_chunk_bytes(input, args, bounds, outputs, stdout, read_error),
+            }
+        }
+    }
+}
+
+/** The input and the bytes left in it, if that's known, as the chunks need it */
+fn open(name: &str) -> io::Result<(Box<dyn Read>, Option<u64>)> {
+    let file = match name {
+        "-" => match stdin_file() {
+            Some(file) => file,
+            None => return Ok((Box::new(io::stdin()), None)),
+        },
+        _ => File::open(name)?,
+    };
+    let metadata = file.metadata()?;
+    let size = match metadata.is_file() {
+        true => Some(metadata.len().saturating_sub((&file).stream_position()?)),
+        false => None,
+    };
+    Ok((Box::new(file), size))
+}
+
+#[cfg(target_family = "unix")]
+fn stdin_file() -> Option<File> {
+    use std::os::fd::AsFd;
+    let fd = io::stdin().as_fd().try_clone_to_owned().ok()?;
+    Some(File::from(fd))
+}
+
+#[cfg(not(target_family = "unix"))]
+fn stdin_file() -> Option<File> {
+    None
+}
+
+fn split_bytes<R: Read>(
+    mut input: R,
+    size: u64,
+    outputs: &mut Outputs,
+    read_error: impl Fn(io::Error) -> SplitError,
+) -> Result<(), SplitError> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut left = 0;
+    loop {
+        let bytes_read = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(read_error(e)),
+        };
+        let mut data = &buffer[..bytes_read];
+        while !data.is_empty() {
+            if left == 0 {
+                outputs.next_file()?;
+                left = size;
+            }
+            let part = data.len().min(usize::try_from(left).unwrap_or(usize::MAX));
+            outputs.write(&data[..part])?;
+            left -= part as u64;
+            data = &data[part..];
+        }
+    }
+    outputs.close()
+}
+
+/** Where the chunks of -n end: N-1 of them are of equal size, the last one gets the remainder */
+#[derive(Clone, Copy)]
+struct ChunkBounds {
+    chunks: Chunks,
+    size: u64,
+}
+
+impl ChunkBounds {
+    fn end(&self, k: u64) -> u64 {
+        match k {
+            k if k >= self.chunks.total => self.size,
+            // With fewer bytes than chunks, those get a byte each, and the rest are empty
+            k => (k * (self.size / self.chunks.total).max(1)).min(self.size),
+        }
+    }
+}
+
+fn split_chunk_bytes<R: Read, W: Write>(
+    mut input: R,
+    args: &Args,
+    bounds: ChunkBounds,
+    outputs: &mut Outputs,
+    stdout: &mut W,
+    read_error: impl Fn(io::Error) -> SplitError,
+) -> Result<(), SplitError> {
+    let mut buffer = vec![0; BUFFER_SIZE];
+    for k in 1..=bounds.chunks.total {
+        let mut left = bounds.end(k) - bounds.end(k - 1);
+        let selected = bounds.chunks.number.map(|number| number == k);
+        if matches!(bounds.chunks.number, Some(number) if number < k) {
+            break;
+        }
+        match selected {
+            None if left > 0 || !args.elide_empty_files => outputs.next_file()?,
+            _ => {}
+        }
+        while left > 0 {
+            let limit = buffer
+                .len()
+                .min(usize::try_from(left).unwrap_or(usize::MAX));
+            let bytes_read = match input.read(&mut buffer[..limit]) {
+                // The file got shorter, those chunks stay empty
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(read_error(e)),
+            };
+            match selected {
+                None => outputs.write(&buffer[..bytes_read])?,
+                Some(true) => stdout
+                    .write_all(&buffer[..bytes_read])
+                    .map_err(SplitError::Output)?,
+                Some(false) => {}
+            }
+            left -= bytes_read as u64;
+        }
+    }
+    outputs.close()
+}
+
+/** l/N: the lines go to the chunk, where they start, so they're never split.
+ *
+ * A line, that goes over the end of the chunk, makes the next one start after it.
+ * It might go over several chunks that way, those are left empty.
+ */
+fn split_chunk_lines<R: Read, W: Write>(
+    input: R,
+    args: &Args,
+    bounds: ChunkBounds,
+    outputs: &mut Outputs,
+    stdout: &mut W,
+    read_error: impl Fn(io::Error) -> SplitError,
+) -> Result<(), SplitError> {
+    let total = bounds.chunks.total;
+    let mut k = 1;
+    let mut opened = 0;
+    let mut offset = 0;
+    for line in BufReader::new(input).chunks(args.separator, 0) {
+        let line = line.map_err(&read_error)?;
+        match bounds.chunks.number {
+            Some(number) if number < k => return Ok(()),
+            Some(number) if number == k => stdout.write_all(&line).map_err(SplitError::Output)?,
+            Some(_) => {}
+            None => {
+                while opened < k {
+                    if args.elide_empty_files {
+                        opened = k - 1;
+                    }
+                    outputs.next_file()?;
+                    opened += 1;
+                }
+                outputs.write(&line)?;
+            }
+        }
+        offset += line.len() as u64;
+        while k < total && offset >= bounds.end(k) {
+            k += 1;
+        }
+    }
+    if bounds.chunks.number.is_none() && !args.elide_empty_files {
+        while opened < total {
+            outputs.next_file()?;
+            opened += 1;
+        }
+    }
+    outputs.close()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn last_chunk_gets_the_rest() {
+        let chunks = Chunks {
+            kind: ChunkKind::Bytes,
+            number: None,
+            total: 4,
+        };
+        let bounds = ChunkBounds { chunks, size: 54 };
+        let ends: Vec<u64> = (0..=4).map(|k| bounds.end(k)).collect();
+        assert_eq!(ends, [0, 13, 26, 39, 54]);
+        let bounds = ChunkBounds { chunks, size: 2 };
+        let ends: Vec<u64> = (0..=4).map(|k| bounds.end(k)).collect();
+        assert_eq!(ends, [0, 1, 2, 2, 2]);
+    }
+}
@@ -0,0 +1,174 @@
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+use super::split_error::SplitError;
+use super::suffixes::Suffixes;
+use crate::args::Args;
+
+/** Output file, or the --filter command, that gets the file name in $FILE */
+pub struct Sink {
+    name: String,
+    target: Target,
+}
+
+enum Target {
+    File(BufWriter<File>),
+    Filter {
+        command: String,
+        child: Child,
+        /// None once the command stopped reading, the rest of its input is discarded
+        input: Option<BufWriter<ChildStdin>>,
+    },
+}
+
+impl Sink {
+    pub fn write(&mut self, data: &[u8]) -> Result<(), SplitError> {
+        match &mut self.target {
+            Target::File(file) => file
+                .write_all(data)
+                .map_err(|e| SplitError::Write(self.name.clone(), e)),
+            Target::Filter { input, .. } => {
+                if let Some(stdin) = input {
+                    match stdin.write_all(data) {
+                        Ok(()) => {}
+                        // It's fine for the command to need only a part of the input, like head
+                        Err(e) if e.kind() == ErrorKind::BrokenPipe => *input = None,
+                        Err(e) => return Err(SplitError::Write(self.name.clone(), e)),
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn finish(self) -> Result<(), SplitError> {
+        match self.target {
+            Target::File(mut file) => file.flush().map_err(|e| SplitError::Write(self.name, e)),
+            Target::Filter {
+                command,
+                mut child,
+                input,
+            } => {
+                if let Some(mut stdin) = input {
+                    match stdin.flush() {
+                        Err(e) if e.kind() != ErrorKind::BrokenPipe => {
+                            return Err(SplitError::Write(self.name, e))
+                        }
+                        _ => {}
+                    }
+                }
+                let status = child
+                    .wait()
+                    .map_err(|e| SplitError::RunFilter(command.clone(), e))?;
+                if let Some(code) = status.code().filter(|c| *c != 0) {
+                    return Err(SplitError::FilterExit(self.name, code, command));
+                }
+                match exit_signal(&status) {
+                    Some(signal) if signal != SIGPIPE => {
+                        Err(SplitError::FilterSignal(self.name, signal, command))
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+const SIGPIPE: i32 = 13;
+
+#[cfg(target_family = "unix")]
+fn exit_signal(status: &std::process::ExitStatus) -> Option<i32> {
+    use std::os::unix::process::ExitStatusExt;
+    status.signal()
+}
+
+#[cfg(not(target_family = "unix"))]
+fn exit_signal(_status: &std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+/** Maker of the output files, named PREFIX, suffix and the additional suffix.
+ *
+ * Most of the modes write the files one after another, so there's the current one
+ * to write to. Round robin needs all of them at once, those are created separately.
+ */
+pub struct Outputs {
+    prefix: String,
+    additional_suffix: String,
+    suffixes: Suffixes,
+    filter: Option<String>,
+    current: Option<Sink>,
+}
+
+impl Outputs {
+    pub fn new(args: &Args) -> Self {
+        Outputs {
+            prefix: args.prefix.clone(),
+            additional_suffix: args.additional_suffix.clone(),
+            suffixes: Suffixes::new(
+                args.alphabet,
+                args.suffix_length,
+                args.suffix_start,
+                args.widening,
+            ),
+            filter: args.filter.clone(),
+            current: None,
+        }
+    }
+
+    pub fn create(&mut self) -> Result<Sink, SplitError> {
+        let suffix = self.suffixes.next().ok_or(SplitError::SuffixesExhausted)?;
+        let name = format!("{}{}{}", self.prefix, suffix, self.additional_suffix);
+        let target = match &self.filter {
+            Some(command) => {
+                let shell = env::var("SHELL").unwrap_or_else(|_| "/bin/sh".into());
+                let mut child = Command::new(shell)
+                    .arg("-c")
+                    .arg(command)
+                    .env("FILE", &name)
+                    .stdin(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| SplitError::RunFilter(command.clone(), e))?;
+                let input = child.stdin.take().map(BufWriter::new);
+                Target::Filter {
+                    command: command.clone(),
+                    child,
+                    input,
+                }
+            }
+            None => match File::create(&name) {
+                Ok(file) => Target::File(BufWriter::new(file)),
+                Err(e) => return Err(SplitError::Create(name, e)),
+            },
+        };
+        Ok(Sink { name, target })
+    }
+
+    /// Finishes the current file, if any, and starts the next one
+    pub fn next_file(&mut self) -> Result<(), SplitError> {
+        self.close()?;
+        self.current = Some(self.create()?);
+        Ok(())
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.current.is_some()
+    }
+
+    /// Writes to the current file, that must be open
+    pub fn write(&mut self, data: &[u8]) -> Result<(), SplitError> {
+        self.current
+            .as_mut()
+            .expect("next_file() goes first")
+            .write(data)
+    }
+
+    pub fn close(&mut self) -> Result<(), SplitError> {
+        match self.current.take() {
+            Some(sink) => sink.finish(),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum SplitError {
+    Open(String, io::Error),
+    Read(String, io::Error),
+    Create(String, io::Error),
+    Write(String, io::Error),
+    /// Writing K/N chunk to stdout
+    Output(io::Error),
+    UnknownSize(String),
+    SuffixesExhausted,
+    RunFilter(String, io::Error),
+    /// The output file name, the exit code and the command
+    FilterExit(String, i32, String),
+    /// The output file name, the signal and the command
+    FilterSignal(String, i32, String),
+}
+
+impl SplitError {
+    /// GNU exits with the status of the filter, that failed
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            SplitError::FilterExit(_, code, _) => *code as u8,
+            SplitError::FilterSignal(_, signal, _) => 128 + *signal as u8,
+            _ => 1,
+        }
+    }
+}
+
+impl fmt::Display for SplitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SplitError::Open(name, err) => {
+                write!(f, "cannot open '{}' for reading: {}", name, strerror(err))
+            }
+            SplitError::Read(name, err)
+            | SplitError::Create(name, err)
+            | SplitError::Write(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            SplitError::Output(err) => write!(f, "write error: {}", strerror(err)),
+            SplitError::UnknownSize(name) => {
+                write!(f, "{}: cannot determine file size", quote(name))
+            }
+            SplitError::SuffixesExhausted => write!(f, "output file suffixes exhausted"),
+            SplitError::RunFilter(command, err) => {
+                write!(f, "failed to run command: '{}': {}", command, strerror(err))
+            }
+            SplitError::FilterExit(name, code, command) => write!(
+                f,
+                "with FILE={}, exit {} from command: {}",
+                name, code, command
+            ),
+            SplitError::FilterSignal(name, signal, command) => write!(
+                f,
+                "with FILE={}, signal {} from command: {}",
+                name,
+                signal_name(*signal),
+                command
+            ),
+        }
+    }
+}
+
+impl Error for SplitError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SplitError::Open(_, err)
+            | SplitError::Read(_, err)
+            | SplitError::Create(_, err)
+            | SplitError::Write(_, err)
+            | SplitError::Output(err)
+            | SplitError::RunFilter(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Wrong argument values, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
+
+/// Signal names without SIG, the way GNU reports them, for the usual suspects
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        1 => "HUP",
+        2 => "INT",
+        3 => "QUIT",
+        6 => "ABRT",
+        9 => "KILL",
+        11 => "SEGV",
+        13 => "PIPE",
+        15 => "TERM",
+        _ => return signal.to_string(),
+    };
+    name.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn displays_gnu_messages() {
+        let err = SplitError::FilterSignal("xaa".into(), 15, "kill $$".into());
+        assert_eq!(
+            err.to_string(),
+            "with FILE=xaa, signal TERM from command: kill $$"
+        );
+        assert_eq!(err.exit_code(), 143);
+        let err = SplitError::Create("a b/xaa".into(), io::Error::from_raw_os_error(2));
+        assert_eq!(err.to_string(), "'a b/xaa': No such file or directory");
+    }
+}
@@ -0,0 +1,137 @@
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Alphabet {
+    #[default]
+    Alphabetic,
+    Decimal,
+    Hex,
+}
+
+impl Alphabet {
+    fn chars(&self) -> &'static [u8] {
+        match self {
+            Alphabet::Alphabetic => b"abcdefghijklmnopqrstuvwxyz",
+            Alphabet::Decimal => b"0123456789",
+            Alphabet::Hex => b"0123456789abcdef",
+        }
+    }
+
+    pub fn radix(&self) -> u32 {
+        self.chars().len() as u32
+    }
+
+    /// Suffix length, that is enough to write the value
+    pub fn digits_needed(&self, mut value: u64) -> usize {
+        let mut digits = 1;
+        while value >= self.radix() as u64 {
+            value /= self.radix() as u64;
+            digits += 1;
+        }
+        digits
+    }
+}
+
+/** Generator of the output file suffixes: aa, ab, ... zz, or 00, 01, ... 99, and so on.
+ *
+ * When widening, the suffixes never run out: once the first character is about to become
+ * the last one of the alphabet, that character is fixed and the rest gets one longer,
+ * so yz is followed by zaaa, and 89 by 9000. Sorting the names keeps the order of the files.
+ */
+pub struct Suffixes {
+    alphabet: Alphabet,
+    digits: Vec<usize>,
+    widened: String,
+    widening: bool,
+    started: bool,
+}
+
+impl Suffixes {
+    pub fn new(alphabet: Alphabet, length: usize, start: u64, widening: bool) -> Self {
+        let radix = alphabet.radix() as u64;
+        let mut digits = vec![0; length];
+        let mut value = start;
+        for digit in digits.iter_mut().rev() {
+            *digit = (value % radix) as usize;
+            value /= radix;
+        }
+        Suffixes {
+            alphabet,
+            digits,
+            widened: String::new(),
+            widening,
+            started: false,
+        }
+    }
+
+    fn increment(&mut self) -> bool {
+        let radix = self.alphabet.radix() as usize;
+        for digit in self.digits.iter_mut().rev() {
+            *digit += 1;
+            if *digit < radix {
+                return true;
+            }
+            *digit = 0;
+        }
+        false
+    }
+}
+
+impl Iterator for Suffixes {
+    type Item = String;
+
+    /// None means the suffixes are exhausted
+    fn next(&mut self) -> Option<String> {
+        if self.started && !self.increment() {
+            return None;
+        }
+        self.started = true;
+        let last = self.alphabet.radix() as usize - 1;
+        if self.widening && self.digits[0] == last {
+            self.widened.push(self.alphabet.chars()[last] as char);
+            self.digits = vec![0; self.digits.len() + 1];
+        }
+        let chars = self.alphabet.chars();
+        let suffix = self.digits.iter().map(|d| chars[*d] as char);
+        Some(self.widened.chars().chain(suffix).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn runs_out_without_widening() {
+        let suffixes = Suffixes::new(Alphabet::Alphabetic, 1, 0, false);
+        let all: Vec<String> = suffixes.collect();
+        assert_eq!(all.len(), 26);
+        assert_eq!(all[25], "z");
+        let mut suffixes = Suffixes::new(Alphabet::Decimal, 2, 98, false);
+        assert_eq!(suffixes.next().as_deref(), Some("98"));
+        assert_eq!(suffixes.next().as_deref(), Some("99"));
+        assert_eq!(suffixes.next(), None);
+    }
+
+    #[test]
+    fn widens_the_gnu_way() {
+        let all: Vec<String> = Suffixes::new(Alphabet::Alphabetic, 2, 0, true)
+            .take(700)
+            .collect();
+        assert_eq!(all[0], "aa");
+        assert_eq!(all[649], "yz");
+        assert_eq!(all[650], "zaaa");
+        assert_eq!(all[699], "zabx");
+        let decimal: Vec<String> = Suffixes::new(Alphabet::Decimal, 2, 0, true)
+            .take(91)
+            .collect();
+        assert_eq!(decimal[89], "89");
+        assert_eq!(decimal[90], "9000");
+    }
+
+    #[test]
+    fn counts_digits_needed() {
+        assert_eq!(Alphabet::Alphabetic.digits_needed(0), 1);
+        assert_eq!(Alphabet::Alphabetic.digits_needed(675), 2);
+        assert_eq!(Alphabet::Alphabetic.digits_needed(676), 3);
+        assert_eq!(Alphabet::Hex.digits_needed(0xfe), 2);
+    }
+}
@@ -1,5 +1,6 @@
 use clap::{Parser, ValueEnum};
-use parse_num::{parse_num, NumValue};
+use parse_num::{num_parser, parse_duration, parse_num, InvalidNumError, NumValue};
+use std::time::Duration;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum FollowMode {
@@ -18,7 +19,7 @@ pub struct Args {
     pub file: Vec<String>,
 
     /// output the last NUM bytes; or use -c +NUM to output starting with byte NUM of each file
-    #[arg(short = 'c', long, value_parser=parse_num)]
+    #[arg(short = 'c', long, value_parser=num_parser("bytes", parse_num))]
     pub bytes: Option<NumValue>,
 
     /// output appended data as the file grows
@@ -36,7 +37,7 @@ pub struct Args {
     pub fr: bool,
 
     /// output the last NUM lines, instead of the last 10; or use -n +NUM to output starting with line NUM
-    #[arg(short = 'n', long, value_parser=parse_num, default_value = "10")]
+    #[arg(short = 'n', long, value_parser=num_parser("lines", parse_num), default_value = "10")]
     pub lines: NumValue,
 
     /// with -f, terminate after process ID, PID dies
@@ -52,8 +53,8 @@ pub struct Args {
     pub retry: bool,
 
     /// with --pid=P check process P at least once every N seconds
-    #[arg(short = 's', long, default_value_t = 1.0, requires = "pid")]
-    pub sleep_interval: f32,
+    #[arg(short = 's', long, value_parser=num_parser("seconds", parse_duration), default_value = "1", requires = "pid")]
+    pub sleep_interval: Duration,
 
     /// always print headers giving file names
     #[arg(short = 'v', long, default_value_t = false)]
@@ -72,8 +73,15 @@ pub struct Args {
 
 impl Args {
     pub fn parse() -> Args {
-        let mut args = <Self as Parser>::parse();
-        if args.file.len() == 0 {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            // GNU reports bad NUM values with its own message, not the generic usage error
+            if let Some(invalid) = InvalidNumError::find(&err) {
+                eprintln!("tail: {}", invalid);
+                std::process::exit(1);
+            }
+            err.exit()
+        });
+        if args.file.is_empty() {
             args.file.push("-".into());
         }
         if args.fr {
@@ -15,7 +15,6 @@ use crate::tail::tail;
 
 use futures::FutureExt;
 use smol::Timer;
-use std::time::Duration;
 
 use crate::pid::PidChecker;
 
@@ -40,7 +39,7 @@ pub async fn follow(args: &Args) -> Result<(), Box<dyn Error>> {
 }
 
 async fn poll_pid(args: &Args) -> Result<(), Box<dyn Error>> {
-    let period = Duration::from_secs_f32(args.sleep_interval);
+    let period = args.sleep_interval;
     let checker = PidChecker::new(args.pid.unwrap()).unwrap();
     while checker.check_pid() {
         Timer::interval(period).await;
@@ -1,37 +1,40 @@
 use super::tail_error::TailError;
 use crate::args::Args;
 use std::collections::VecDeque;
-use std::io::{BufRead, Write};
+use std::io::{Read, Write};
 
-pub fn tail_bytes<R: BufRead>(args: &Args, input: R) -> Result<(), TailError> {
-    let bytes = get_bytes(&args, input)?;
+const CHUNK_SIZE: usize = 8192;
+
+pub fn tail_bytes<R: Read>(args: &Args, input: R) -> Result<(), TailError> {
+    let bytes = get_bytes(args, input)?;
     let (slice1, slice2) = bytes.as_slices();
-    std::io::stdout().write(slice1)?;
-    std::io::stdout().write(slice2)?;
+    let mut stdout = std::io::stdout();
+    stdout.write_all(slice1)?;
+    stdout.write_all(slice2)?;
     Ok(())
 }
 
-pub fn get_bytes<R: BufRead>(args: &Args, input: R) -> Result<VecDeque<u8>, TailError> {
-    let n = args
-        .bytes
-        .as_ref()
-        .unwrap()
-        .to_usize()
-        .ok_or(TailError::Overflow)?;
-    let mut bytes: VecDeque<u8> = VecDeque::with_capacity(n);
-    for byte in input.bytes() {
-        let byte = byte?;
-        if bytes.len() >= n {
-            bytes.pop_front();
+pub fn get_bytes<R: Read>(args: &Args, mut input: R) -> Result<VecDeque<u8>, TailError> {
+    // Anything that doesn't fit usize can't fit in memory either, so it means "everything"
+    let n = args.bytes.as_ref().unwrap().to_usize_saturating();
+    let mut buffer = [0_u8; CHUNK_SIZE];
+    let mut bytes: VecDeque<u8> = VecDeque::new();
+    loop {
+        let bytes_read = input.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+        bytes.extend(&buffer[..bytes_read]);
+        if bytes.len() > n {
+            bytes.drain(..bytes.len() - n);
         }
-        bytes.push_back(byte);
     }
     Ok(bytes)
 }
 
 #[cfg(test)]
 mod test {
-    use parse_num::NumValue;
+    use parse_num::{parse_num, NumValue};
 
     use super::*;
     use std::io::{BufReader, Cursor};
@@ -42,14 +45,32 @@ mod test {
         let cursor = Cursor::new(data);
         let input = BufReader::new(cursor);
         let args = Args {
-            bytes: Some(NumValue {
-                prefix: None,
-                value: 2,
-                multiplier: None,
-            }),
+            bytes: Some(NumValue::from(2_usize)),
             ..Args::default()
         };
         let result = get_bytes(&args, input).unwrap();
         assert_eq!(result, VecDeque::from(b"89".to_vec()))
     }
+
+    #[test]
+    fn reads_everything_if_n_is_huge() {
+        let data = b"123456789";
+        let args = Args {
+            bytes: Some(parse_num("99999999999999999999999").unwrap()),
+            ..Args::default()
+        };
+        let result = get_bytes(&args, Cursor::new(data)).unwrap();
+        assert_eq!(result, VecDeque::from(data.to_vec()))
+    }
+
+    #[test]
+    fn handles_input_longer_than_chunk() {
+        let data = [b"x".repeat(CHUNK_SIZE * 2), b"yz".to_vec()].concat();
+        let args = Args {
+            bytes: Some(NumValue::from(3_usize)),
+            ..Args::default()
+        };
+        let result = get_bytes(&args, Cursor::new(data)).unwrap();
+        assert_eq!(result, VecDeque::from(b"xyz".to_vec()))
+    }
 }
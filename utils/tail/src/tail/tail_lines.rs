@@ -7,29 +7,33 @@ use std::io::{Read, Write};
 
 pub fn tail_lines<R: Read>(args: &Args, input: R) -> Result<(), TailError> {
     let lines = get_lines(args, input)?;
+    let mut stdout = std::io::stdout();
     for line in lines {
-        std::io::stdout().write(&line)?;
+        stdout.write_all(&line)?;
     }
     Ok(())
 }
 
 fn get_lines<R: Read>(args: &Args, input: R) -> Result<VecDeque<Vec<u8>>, TailError> {
-    let n = args.lines.to_usize().ok_or(TailError::Overflow)?;
-    let mut q: VecDeque<Vec<u8>> = VecDeque::with_capacity(n);
+    // Anything that doesn't fit usize can't fit in memory either, so it means "everything"
+    let n = args.lines.to_usize_saturating();
+    let mut q: VecDeque<Vec<u8>> = VecDeque::new();
 
     for line in input.chunks(args.terminator, 0) {
         let line = line?;
         if q.len() >= n {
             q.pop_front();
         }
-        q.push_back(line);
+        if n > 0 {
+            q.push_back(line);
+        }
     }
     Ok(q)
 }
 
 #[cfg(test)]
 mod test {
-    use parse_num::NumValue;
+    use parse_num::{parse_num, NumValue};
 
     use super::*;
     use std::io::Cursor;
@@ -40,11 +44,7 @@ mod test {
         let cursor = Cursor::new(data);
         let args = Args {
             terminator: b'\n',
-            lines: NumValue {
-                prefix: None,
-                value: 2,
-                multiplier: None,
-            },
+            lines: NumValue::from(2_usize),
             ..Args::default()
         };
         let result = get_lines(&args, cursor).unwrap();
@@ -57,11 +57,7 @@ mod test {
         let cursor = Cursor::new(data);
         let args = Args {
             terminator: b'\n',
-            lines: NumValue {
-                prefix: None,
-                value: 10,
-                multiplier: None,
-            },
+            lines: NumValue::from(10_usize),
             ..Args::default()
         };
         let result = get_lines(&args, cursor).unwrap();
@@ -76,4 +72,27 @@ mod test {
             ])
         );
     }
+
+    #[test]
+    fn outputs_all_if_lines_overflow() {
+        let data = b"1\n2\n";
+        let args = Args {
+            terminator: b'\n',
+            lines: parse_num("1E").unwrap(),
+            ..Args::default()
+        };
+        let result = get_lines(&args, Cursor::new(data)).unwrap();
+        assert_eq!(result, VecDeque::from([b"1\n".to_vec(), b"2\n".to_vec()]));
+    }
+
+    #[test]
+    fn outputs_nothing_if_lines_is_zero() {
+        let args = Args {
+            terminator: b'\n',
+            lines: NumValue::from(0_usize),
+            ..Args::default()
+        };
+        let result = get_lines(&args, Cursor::new(b"1\n2\n")).unwrap();
+        assert_eq!(result, VecDeque::new());
+    }
 }
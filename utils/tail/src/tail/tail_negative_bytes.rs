@@ -1,29 +1,55 @@
 use super::tail_error::TailError;
 use crate::args::Args;
-use chunk_reader::ChunkReader;
-use ibig::{ops::DivRem, UBig};
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 
-const CHUNK_SIZE: usize = 8192;
+pub fn tail_negative_bytes<R: Read>(args: &Args, input: R) -> Result<(), TailError> {
+    output_from_byte(args, input, &mut io::stdout())
+}
+
+/** Outputs everything starting with the byte +NUM (1-based, +0 is the same as +1) */
+fn output_from_byte<R: Read, W: Write>(
+    args: &Args,
+    mut input: R,
+    output: &mut W,
+) -> Result<(), TailError> {
+    let bytes_to_omit = args
+        .bytes
+        .as_ref()
+        .unwrap()
+        .to_u64_saturating()
+        .saturating_sub(1);
+    io::copy(&mut (&mut input).take(bytes_to_omit), &mut io::sink())?;
+    io::copy(&mut input, output)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use parse_num::parse_num;
 
-pub fn tail_negative_bytes<R: Read>(args: &Args, mut input: R) -> Result<(), TailError> {
-    let bytes_to_omit = args.bytes.clone().unwrap().to_ubig();
-    let (n_full_chunks, partial_read) = bytes_to_omit.clone().div_rem(CHUNK_SIZE);
-    let mut counter = UBig::from(0_usize);
+    use super::*;
+    use std::io::Cursor;
 
-    if bytes_to_omit > UBig::from(0_usize) {
-        let mut buffer = [0_u8; CHUNK_SIZE];
-        while counter < n_full_chunks {
-            input.read(&mut buffer)?;
-            counter += 1;
-        }
-        if partial_read > 0 {
-            input.read(&mut buffer[..partial_read])?;
-        }
+    fn process(num: &str, data: &[u8]) -> Vec<u8> {
+        let args = Args {
+            bytes: Some(parse_num(num).unwrap()),
+            ..Args::default()
+        };
+        let mut output = Vec::new();
+        output_from_byte(&args, Cursor::new(data), &mut output).unwrap();
+        output
     }
-    for chunk in input.chunks(args.terminator, CHUNK_SIZE) {
-        let chunk = chunk?;
-        std::io::stdout().write(&chunk)?;
+
+    #[test]
+    fn starts_with_the_specified_byte() {
+        assert_eq!(process("+3", b"123456789"), b"3456789");
+        assert_eq!(process("+1", b"123456789"), b"123456789");
+        assert_eq!(process("+0", b"123456789"), b"123456789");
+    }
+
+    #[test]
+    fn outputs_nothing_if_n_is_huge() {
+        assert_eq!(process("+20", b"123456789"), b"");
+        assert_eq!(process("+99999999999999999999999", b"123456789"), b"");
     }
-    Ok(())
 }
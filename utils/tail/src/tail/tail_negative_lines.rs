@@ -1,15 +1,23 @@
 use super::tail_error::TailError;
 use crate::args::Args;
-use std::io::{Read, Write};
+use std::io::{self, Read, Write};
 
 use chunk_reader::ChunkReader;
-use ibig::UBig;
 
 const CHUNK_SIZE: usize = 8192;
 
 pub fn tail_negative_lines<R: Read>(args: &Args, input: R) -> Result<(), TailError> {
-    let lines_to_omit = args.lines.to_ubig();
-    let mut counter = UBig::from(0_usize);
+    output_from_line(args, input, &mut io::stdout())
+}
+
+/** Outputs everything starting with the line +NUM (1-based, +0 is the same as +1) */
+fn output_from_line<R: Read, W: Write>(
+    args: &Args,
+    input: R,
+    output: &mut W,
+) -> Result<(), TailError> {
+    let lines_to_omit = args.lines.to_u64_saturating().saturating_sub(1);
+    let mut counter: u64 = 0;
 
     for chunk in input.chunks(args.terminator, CHUNK_SIZE) {
         let chunk = chunk?;
@@ -18,8 +26,40 @@ pub fn tail_negative_lines<R: Read>(args: &Args, input: R) -> Result<(), TailErr
                 counter += 1;
             }
         } else {
-            std::io::stdout().write(&chunk)?;
+            output.write_all(&chunk)?;
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use parse_num::parse_num;
+
+    use super::*;
+    use std::io::Cursor;
+
+    fn process(num: &str, data: &[u8]) -> Vec<u8> {
+        let args = Args {
+            lines: parse_num(num).unwrap(),
+            terminator: b'\n',
+            ..Args::default()
+        };
+        let mut output = Vec::new();
+        output_from_line(&args, Cursor::new(data), &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn starts_with_the_specified_line() {
+        assert_eq!(process("+2", b"1\n2\n3\n"), b"2\n3\n");
+        assert_eq!(process("+1", b"1\n2\n3\n"), b"1\n2\n3\n");
+        assert_eq!(process("+0", b"1\n2\n3\n"), b"1\n2\n3\n");
+    }
+
+    #[test]
+    fn outputs_nothing_if_n_is_huge() {
+        assert_eq!(process("+5", b"1\n2\n3\n"), b"");
+        assert_eq!(process("+1E", b"1\n2\n3\n"), b"");
+    }
+}
@@ -0,0 +1,49 @@
+use clap::{Parser, ValueEnum};
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum OutputError {
+    /// diagnose errors writing to any output
+    Warn,
+    /// diagnose errors writing to any output not a pipe
+    WarnNopipe,
+    /// exit on error writing to any output
+    Exit,
+    /// exit on error writing to any output not a pipe
+    ExitNopipe,
+}
+
+/// copy standard input to each FILE, and also to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    pub file: Vec<String>,
+
+    /// append to the given FILEs, do not overwrite
+    #[arg(short = 'a', long, default_value_t = false)]
+    pub append: bool,
+
+    /// ignore interrupt signals
+    #[arg(short = 'i', long, default_value_t = false)]
+    pub ignore_interrupts: bool,
+
+    /// operate in a more appropriate MODE with pipes, same as --output-error=warn-nopipe
+    #[arg(short = 'p', default_value_t = false)]
+    pub pipe: bool,
+
+    /// set behavior on write error
+    #[arg(long, value_enum, value_name = "MODE", num_args = 0..=1, require_equals = true, default_missing_value = "warn-nopipe")]
+    pub output_error: Option<OutputError>,
+}
+
+impl Args {
+    /// None is the default mode: dying from SIGPIPE, diagnosing all the other errors
+    pub fn output_error_mode(&self) -> Option<OutputError> {
+        match self.output_error {
+            Some(mode) => Some(mode),
+            None if self.pipe => Some(OutputError::WarnNopipe),
+            None => None,
+        }
+    }
+}
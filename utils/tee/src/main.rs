@@ -0,0 +1,71 @@
+mod args;
+mod tee;
+
+use std::fs::OpenOptions;
+use std::io;
+use std::process::ExitCode;
+
+use args::Args;
+use clap::Parser;
+use strerror::strerror;
+use tee::{quote, tee, Output};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mode = args.output_error_mode();
+    if mode.is_none() {
+        restore_sigpipe();
+    }
+    if args.ignore_interrupts {
+        // Handler, that does nothing, is as good as ignoring the signal
+        if let Err(e) = ctrlc::set_handler(|| {}) {
+            eprintln!("tee: failed to ignore interrupts: {}", e);
+        }
+    }
+
+    let mut status = ExitCode::SUCCESS;
+    let mut outputs = vec![Output {
+        name: "standard output".into(),
+        writer: Box::new(io::stdout()),
+    }];
+    for name in &args.file {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(args.append)
+            .truncate(!args.append)
+            .open(name);
+        match file {
+            Ok(file) => outputs.push(Output {
+                name: name.clone(),
+                writer: Box::new(file),
+            }),
+            Err(e) => {
+                eprintln!("tee: {}: {}", quote(name), strerror(&e));
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    match tee(io::stdin().lock(), outputs, mode) {
+        Ok(true) => status,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("tee: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Rust ignores SIGPIPE, but by default tee should just die on a closed pipe, as everything else
+#[cfg(target_family = "unix")]
+fn restore_sigpipe() {
+    use nix::sys::signal::{signal, SigHandler, Signal};
+    // Safe: nothing else is around to be affected by the handler change this early
+    unsafe {
+        let _ = signal(Signal::SIGPIPE, SigHandler::SigDfl);
+    }
+}
+
+#[cfg(not(target_family = "unix"))]
+fn restore_sigpipe() {}
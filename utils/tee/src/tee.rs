@@ -0,0 +1,176 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, ErrorKind, Read, Write};
+use strerror::strerror;
+
+use crate::args::OutputError;
+
+const CHUNK_SIZE: usize = 8192;
+
+pub struct Output {
+    pub name: String,
+    pub writer: Box<dyn Write>,
+}
+
+#[derive(Debug)]
+pub enum TeeError {
+    Read(io::Error),
+    Write(String, io::Error),
+}
+
+impl fmt::Display for TeeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TeeError::Read(err) => write!(f, "read error: {}", strerror(err)),
+            TeeError::Write(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+        }
+    }
+}
+
+impl Error for TeeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TeeError::Read(err) | TeeError::Write(_, err) => Some(err),
+        }
+    }
+}
+
+/** Copies the input to every output, until there's nothing to read or nowhere to write.
+ *
+ * An output, that failed, is dropped, and the rest keep getting the data. Whether the
+ * failure is reported, or fatal, depends on the mode. Returns false, if there were
+ * any failures, that should affect the exit status.
+ */
+pub fn tee<R: Read>(
+    mut input: R,
+    mut outputs: Vec<Output>,
+    mode: Option<OutputError>,
+) -> Result<bool, TeeError> {
+    let mut ok = true;
+    let mut buffer = [0_u8; CHUNK_SIZE];
+    while !outputs.is_empty() {
+        let bytes_read = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(TeeError::Read(e)),
+        };
+        let mut failed = Vec::new();
+        for (i, output) in outputs.iter_mut().enumerate() {
+            let result = output
+                .writer
+                .write_all(&buffer[..bytes_read])
+                .and_then(|_| output.writer.flush());
+            if let Err(e) = result {
+                let is_pipe = e.kind() == ErrorKind::BrokenPipe;
+                let error = TeeError::Write(output.name.clone(), e);
+                match mode {
+                    Some(OutputError::WarnNopipe | OutputError::ExitNopipe) if is_pipe => {}
+                    Some(OutputError::Exit | OutputError::ExitNopipe) => return Err(error),
+                    _ => {
+                        eprintln!("tee: {}", error);
+                        ok = false;
+                    }
+                }
+                failed.push(i);
+            }
+        }
+        for i in failed.into_iter().rev() {
+            outputs.remove(i);
+        }
+    }
+    Ok(ok)
+}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+pub fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+    use std::io::Cursor;
+    use std::rc::Rc;
+
+    use super::*;
+
+    /// Writer, that keeps the data where the test can see it
+    #[derive(Clone, Default)]
+    struct Shared(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    struct Failing(ErrorKind);
+
+    impl Write for Failing {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(self.0))
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn output(name: &str, writer: impl Write + 'static) -> Output {
+        Output {
+            name: name.into(),
+            writer: Box::new(writer),
+        }
+    }
+
+    #[test]
+    fn copies_input_to_every_output() {
+        let (a, b) = (Shared::default(), Shared::default());
+        let data = b"x".repeat(CHUNK_SIZE * 2 + 5);
+        let outputs = vec![output("a", a.clone()), output("b", b.clone())];
+        assert!(tee(Cursor::new(data.clone()), outputs, None).unwrap());
+        assert_eq!(*a.0.borrow(), data);
+        assert_eq!(*b.0.borrow(), data);
+    }
+
+    #[test]
+    fn keeps_writing_past_failed_outputs() {
+        let a = Shared::default();
+        let outputs = vec![
+            output("bad", Failing(ErrorKind::Other)),
+            output("a", a.clone()),
+        ];
+        let ok = tee(Cursor::new(b"data"), outputs, Some(OutputError::Warn)).unwrap();
+        assert!(!ok);
+        assert_eq!(*a.0.borrow(), b"data");
+    }
+
+    #[test]
+    fn ignores_broken_pipes_in_nopipe_modes() {
+        let outputs = vec![output("p", Failing(ErrorKind::BrokenPipe))];
+        let mode = Some(OutputError::ExitNopipe);
+        assert!(tee(Cursor::new(b"data"), outputs, mode).unwrap());
+    }
+
+    #[test]
+    fn exits_on_failure_in_exit_modes() {
+        let a = Shared::default();
+        let outputs = vec![output("bad", Failing(ErrorKind::Other)), output("a", a)];
+        let err = tee(Cursor::new(b"data"), outputs, Some(OutputError::Exit)).unwrap_err();
+        assert!(matches!(err, TeeError::Write(name, _) if name == "bad"));
+    }
+
+    #[test]
+    fn quotes_unusual_names() {
+        assert_eq!(quote("/dev/full"), "/dev/full");
+        assert_eq!(quote("standard output"), "'standard output'");
+    }
+}
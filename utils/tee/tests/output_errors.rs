@@ -0,0 +1,139 @@
+// Integration tests for the write error policies: they need real pipes and devices,
+// so the binary is run the same way a shell would run it.
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+const TEE: &str = env!("CARGO_BIN_EXE_tee");
+
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("tee-test-{}-{}", std::process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn input() -> Vec<u8> {
+    (0..100000)
+        .map(|i| format!("{}\n", i))
+        .collect::<String>()
+        .into_bytes()
+}
+
+/// Runs tee with its stdout already closed on the reading side, a broken pipe
+fn run_with_broken_pipe(args: &[&str]) -> Output {
+    let mut child = Command::new(TEE)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    drop(child.stdout.take());
+    // tee may be gone before everything is written, that's fine
+    let _ = child.stdin.take().unwrap().write_all(&input());
+    child.wait_with_output().unwrap()
+}
+
+fn run_with_input(args: &[&str]) -> Output {
+    let mut child = Command::new(TEE)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"hello\n").unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn dies_from_sigpipe_by_default() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let output = run_with_broken_pipe(&[]);
+    assert_eq!(output.status.signal(), Some(13)); // SIGPIPE
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn keeps_writing_files_past_broken_pipe_with_p() {
+    let file = temp_file("nopipe");
+    let output = run_with_broken_pipe(&["-p", file.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    assert_eq!(fs::read(&file).unwrap(), input());
+    fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn reports_broken_pipe_with_warn() {
+    let file = temp_file("warn");
+    let output = run_with_broken_pipe(&["--output-error=warn", file.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "tee: 'standard output': Broken pipe\n"
+    );
+    assert_eq!(fs::read(&file).unwrap(), input());
+    fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn stops_on_broken_pipe_with_exit() {
+    let file = temp_file("exit");
+    let output = run_with_broken_pipe(&["--output-error=exit", file.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "tee: 'standard output': Broken pipe\n"
+    );
+    assert!(fs::read(&file).unwrap().len() < input().len());
+    fs::remove_file(file).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn continues_past_full_disk() {
+    let file = temp_file("full");
+    let output = run_with_input(&["/dev/full", file.to_str().unwrap()]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"hello\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "tee: /dev/full: No space left on device\n"
+    );
+    assert_eq!(fs::read(&file).unwrap(), b"hello\n");
+    fs::remove_file(file).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn stops_on_full_disk_with_exit_nopipe() {
+    let file = temp_file("full-exit");
+    let output = run_with_input(&[
+        "--output-error=exit-nopipe",
+        "/dev/full",
+        file.to_str().unwrap(),
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"hello\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "tee: /dev/full: No space left on device\n"
+    );
+    assert_eq!(fs::read(&file).unwrap(), b"");
+    fs::remove_file(file).unwrap();
+}
+
+#[test]
+fn appends_with_a() {
+    let file = temp_file("append");
+    fs::write(&file, b"before\n").unwrap();
+    let output = run_with_input(&["-a", file.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert_eq!(fs::read(&file).unwrap(), b"before\nhello\n");
+    fs::remove_file(file).unwrap();
+}
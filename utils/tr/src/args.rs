@@ -0,0 +1,83 @@
+use clap::Parser;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Translate, squeeze, and/or delete characters from standard input,
+/// writing to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// SETs are specified as strings of characters, see tr(1)
+    #[arg(value_name = "SET")]
+    pub sets: Vec<String>,
+
+    /// use the complement of SET1
+    #[arg(
+        short = 'c',
+        short_alias = 'C',
+        long = "complement",
+        default_value_t = false
+    )]
+    pub complement: bool,
+
+    /// delete characters in SET1, do not translate
+    #[arg(short = 'd', long, default_value_t = false)]
+    pub delete: bool,
+
+    /// replace each sequence of a repeated character that is listed
+    /// in the last specified SET, with a single occurrence of that character
+    #[arg(short = 's', long, default_value_t = false)]
+    pub squeeze_repeats: bool,
+
+    /// first truncate SET1 to length of SET2
+    #[arg(short = 't', long, default_value_t = false)]
+    pub truncate_set1: bool,
+
+    /// the characters are UTF-8, not bytes, and the classes are the ones of Unicode
+    #[arg(long, default_value_t = false)]
+    pub utf8: bool,
+}
+
+impl Args {
+    /// The number of the SETs depends on the options, like GNU tr tells
+    pub fn parse() -> Args {
+        let args = <Self as Parser>::parse();
+        let squeeze_deleted = args.delete == args.squeeze_repeats;
+        let min = 1 + squeeze_deleted as usize;
+        let max = 1 + (args.delete <= args.squeeze_repeats) as usize;
+        if args.sets.is_empty() {
+            usage_error("missing operand", None);
+        }
+        if args.sets.len() < min {
+            let hint = match args.squeeze_repeats {
+                true => "Two strings must be given when both deleting and squeezing repeats.",
+                false => "Two strings must be given when translating.",
+            };
+            let message = format!("missing operand after {}", quote(&args.sets[0]));
+            usage_error(&message, Some(hint));
+        }
+        if args.sets.len() > max {
+            let hint = (args.sets.len() == 2)
+                .then_some("Only one string may be given when deleting without squeezing repeats.");
+            let message = format!("extra operand {}", quote(&args.sets[max]));
+            usage_error(&message, hint);
+        }
+        args
+    }
+}
+
+fn usage_error(message: &str, hint: Option<&str>) -> ! {
+    eprintln!("tr: {}", message);
+    if let Some(hint) = hint {
+        eprintln!("{}", hint);
+    }
+    eprintln!("Try 'tr --help' for more information.");
+    std::process::exit(1);
+}
+
+fn quote(operand: &str) -> String {
+    match operand.contains('\'') {
+        true => format!("\"{}\"", operand),
+        false => format!("'{}'", operand),
+    }
+}
@@ -0,0 +1,19 @@
+mod args;
+mod tr;
+
+use std::io::{self, BufWriter};
+use std::process::ExitCode;
+
+use args::Args;
+use tr::Tr;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let result = Tr::new(&args)
+        .and_then(|tr| tr.run(io::stdin().lock(), BufWriter::new(io::stdout().lock())));
+    if let Err(e) = result {
+        eprintln!("tr: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,495 @@
+pub mod set;
+pub mod tr_error;
+
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, Read, Write};
+
+use crate::args::Args;
+use set::{set_error, Char, Class, Element, Set};
+use tr_error::TrError;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Code points, but the surrogates, for the complement in the UTF-8 mode
+const UNICODE_SIZE: usize = 0x110000 - 0x800;
+
+/// The characters of a SET, maybe complemented, the bytes are looked up in the table
+struct CharSet {
+    bytes: [bool; 256],
+    elements: Vec<Element>,
+    complement: bool,
+}
+
+impl CharSet {
+    fn new(set: &Set, complement: bool) -> Self {
+        let mut bytes = [complement; 256];
+        for (c, member) in bytes.iter_mut().enumerate() {
+            *member ^= set.contains(c as Char);
+        }
+        CharSet {
+            bytes,
+            elements: set.elements.clone(),
+            complement,
+        }
+    }
+
+    fn contains(&self, c: Char) -> bool {
+        match self.bytes.get(c as usize) {
+            Some(&member) => member,
+            None => self.elements.iter().any(|element| element.contains(c)) != self.complement,
+        }
+    }
+}
+
+enum Mapping {
+    /// The characters of SET1 to the ones of SET2 at the same places
+    Pairs(HashMap<Char, Char>),
+    /// The characters, that aren't in SET1, in order, to the ones of SET2
+    Complement {
+        set1: Vec<Char>,
+        set2: Vec<Char>,
+        truncate: bool,
+    },
+}
+
+/// What the characters are translated to, the bytes are looked up in the table
+struct Translation {
+    bytes: [Char; 256],
+    mapping: Mapping,
+}
+
+impl Translation {
+    fn new(mapping: Mapping) -> Self {
+        let mut translation = Translation {
+            bytes: [0; 256],
+            mapping,
+        };
+        for c in 0..256 {
+            translation.bytes[c] = translation.map(c as Char);
+        }
+        translation
+    }
+
+    fn translate(&self, c: Char) -> Char {
+        match self.bytes.get(c as usize) {
+            Some(&translated) => translated,
+            None => self.map(c),
+        }
+    }
+
+    fn map(&self, c: Char) -> Char {
+        match &self.mapping {
+            Mapping::Pairs(pairs) => pairs.get(&c).copied().unwrap_or(c),
+            Mapping::Complement {
+                set1,
+                set2,
+                truncate,
+            } => {
+                if set1.binary_search(&c).is_ok() {
+                    return c;
+                }
+                let surrogates = if c > 0xdfff { 0x800 } else { 0 };
+                let index = c as usize - set1.partition_point(|&member| member < c) - surrogates;
+                match set2.get(index) {
+                    Some(&translated) => translated,
+                    None if *truncate => c,
+                    None => set2.last().copied().unwrap_or(c),
+                }
+            }
+        }
+    }
+}
+
+/** Translates, squeezes, and deletes the characters.
+ *
+ * The characters are bytes, or code points in the UTF-8 mode.
+ */
+pub struct Tr {
+    delete: Option<CharSet>,
+    squeeze: Option<CharSet>,
+    translation: Option<Translation>,
+    utf8: bool,
+}
+
+impl Tr {
+    /// The SETs are checked the GNU way, before anything is read
+    pub fn new(args: &Args) -> Result<Tr, TrError> {
+        let set1 = Set::parse(&args.sets[0], args.utf8)?;
+        let set2 = match args.sets.get(1) {
+            Some(spec) => Some(Set::parse(spec, args.utf8)?),
+            None => None,
+        };
+        if set1.has_fill() {
+            return Err(set_error(
+                "the [c*] repeat construct may not appear in string1",
+            ));
+        }
+        let translation = match &set2 {
+            Some(set2) if !args.delete => Some(translation(args, &set1, set2)?),
+            Some(set2) if set2.has_fill() => {
+                return Err(set_error(
+                    "the [c*] construct may appear in string2 only when translating",
+                ))
+            }
+            _ => None,
+        };
+        let squeeze = match (&set2, args.squeeze_repeats) {
+            (_, false) => None,
+            (Some(set2), true) => Some(CharSet::new(set2, false)),
+            (None, true) => Some(CharSet::new(&set1, args.complement)),
+        };
+        Ok(Tr {
+            delete: args.delete.then(|| CharSet::new(&set1, args.complement)),
+            squeeze,
+            translation,
+            utf8: args.utf8,
+        })
+    }
+
+    pub fn run<R: Read, W: Write>(&self, input: R, output: W) -> Result<(), TrError> {
+        match self.utf8 {
+            true => self.run_chars(input, output),
+            false => self.run_bytes(input, output),
+        }
+    }
+
+    /// The tables of 256 entries go over the buffers, the translation alone goes in place
+    fn run_bytes<R: Read, W: Write>(&self, mut input: R, mut output: W) -> Result<(), TrError> {
+        let table = |set: &Option<CharSet>| set.as_ref().map_or([false; 256], |set| set.bytes);
+        let (delete, squeeze) = (table(&self.delete), table(&self.squeeze));
+        let mut translate = [0u8; 256];
+        for (c, translated) in translate.iter_mut().enumerate() {
+            *translated = match &self.translation {
+                Some(translation) => translation.bytes[c] as u8,
+                None => c as u8,
+            };
+        }
+        let in_place = self.delete.is_none() && self.squeeze.is_none();
+        let mut buffer = vec![0; BUFFER_SIZE];
+        let mut filtered = Vec::with_capacity(BUFFER_SIZE);
+        let mut last = None;
+        loop {
+            let n = match input.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(TrError::Read(e)),
+            };
+            if in_place {
+                buffer[..n]
+                    .iter_mut()
+                    .for_each(|byte| *byte = translate[*byte as usize]);
+                output.write_all(&buffer[..n]).map_err(TrError::Write)?;
+                continue;
+            }
+            filtered.clear();
+            for &byte in &buffer[..n] {
+                if delete[byte as usize] {
+                    continue;
+                }
+                let byte = translate[byte as usize];
+                if squeeze[byte as usize] && last == Some(byte) {
+                    continue;
+                }
+                last = Some(byte);
+                filtered.push(byte);
+            }
+            output.write_all(&filtered).map_err(TrError::Write)?;
+        }
+        output.flush().map_err(TrError::Write)
+    }
+
+    /** The same for the characters of UTF-8.
+     *
+     * The bytes, which aren't UTF-8, go as they are, and never match.
+     */
+    fn run_chars<R: Read, W: Write>(&self, mut input: R, mut output: W) -> Result<(), TrError> {
+        let mut buffer = vec![0; BUFFER_SIZE];
+        // The start of a character, which is cut by the end of the buffer
+        let mut pending = Vec::new();
+        let mut filtered = Vec::with_capacity(BUFFER_SIZE);
+        let mut last = None;
+        loop {
+            let n = match input.read(&mut buffer) {
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(TrError::Read(e)),
+            };
+            pending.extend_from_slice(&buffer[..n]);
+            filtered.clear();
+            let mut rest = &pending[..];
+            while !rest.is_empty() {
+                let (valid, invalid) = match std::str::from_utf8(rest) {
+                    Ok(valid) => (valid, rest.len()),
+                    Err(e) => {
+                        let valid = std::str::from_utf8(&rest[..e.valid_up_to()]).unwrap_or("");
+                        match e.error_len() {
+                            Some(len) => (valid, e.valid_up_to() + len),
+                            None if n == 0 => (valid, rest.len()),
+                            None => (valid, e.valid_up_to()),
+                        }
+                    }
+                };
+                for c in valid.chars() {
+                    if let Some(c) = self.filter(c as Char, &mut last).and_then(char::from_u32) {
+                        let mut encoded = [0; 4];
+                        filtered.extend_from_slice(c.encode_utf8(&mut encoded).as_bytes());
+                    }
+                }
+                let invalid_bytes = &rest[valid.len()..invalid];
+                if !invalid_bytes.is_empty() {
+                    filtered.extend_from_slice(invalid_bytes);
+                    last = None;
+                }
+                rest = &rest[invalid..];
+                if invalid == valid.len() {
+                    break;
+                }
+            }
+            output.write_all(&filtered).map_err(TrError::Write)?;
+            let consumed = pending.len() - rest.len();
+            pending.drain(..consumed);
+            if n == 0 {
+                break;
+            }
+        }
+        output.flush().map_err(TrError::Write)
+    }
+
+    /// The character to write, if it's neither deleted nor squeezed
+    fn filter(&self, c: Char, last: &mut Option<Char>) -> Option<Char> {
+        if self.delete.as_ref().is_some_and(|set| set.contains(c)) {
+            return None;
+        }
+        let c = self.translation.as_ref().map_or(c, |t| t.translate(c));
+        if self.squeeze.as_ref().is_some_and(|set| set.contains(c)) && *last == Some(c) {
+            return None;
+        }
+        *last = Some(c);
+        Some(c)
+    }
+}
+
+/** The translation of SET1 to SET2.
+ *
+ * SET2 is extended with its last character, unless SET1 is truncated,
+ * and only [:upper:] and [:lower:] may be there, in place of the same
+ * classes of SET1.
+ */
+fn translation(args: &Args, set1: &Set, set2: &Set) -> Result<Translation, TrError> {
+    let has = |check: fn(&Element) -> bool| set2.elements.iter().any(check);
+    if has(|element| matches!(element, Element::Equivalence(_))) {
+        return Err(set_error(
+            "[=c=] expressions may not appear in string2 when translating",
+        ));
+    }
+    if has(|element| matches!(element, Element::Class(class) if !class.is_case())) {
+        return Err(set_error(
+            "when translating, the only character classes that may appear in\n\
+             string2 are 'upper' and 'lower'",
+        ));
+    }
+    // The characters of the complement in order, only the number of them for now
+    let members: Vec<Char> = match args.complement {
+        true => set1.iter(0).collect::<BTreeSet<_>>().into_iter().collect(),
+        false => Vec::new(),
+    };
+    let len1 = match args.complement {
+        true if args.utf8 => UNICODE_SIZE - members.len(),
+        true => 256 - members.len(),
+        false => set1.len(),
+    };
+    let case_mapped;
+    let set2 = match args.complement {
+        true => set2,
+        false => {
+            let fill = len1.saturating_sub(set2.len());
+            case_mapped = case_mapped_classes(set1, set2, fill)
+                .ok_or_else(|| set_error("misaligned [:upper:] and/or [:lower:] construct"))?;
+            &case_mapped
+        }
+    };
+    let fill = len1.saturating_sub(set2.len());
+    let len2 = match set2.has_fill() {
+        true => set2.len() + fill,
+        false => set2.len(),
+    };
+    if len1 > len2 && !args.truncate_set1 {
+        if len2 == 0 {
+            return Err(set_error(
+                "when not truncating set1, string2 must be non-empty",
+            ));
+        }
+        if matches!(
+            set2.elements.last(),
+            Some(Element::Class(_) | Element::Case(..))
+        ) {
+            return Err(set_error(
+                "when translating with string1 longer than string2,\n\
+                 the latter string must not end with a character class",
+            ));
+        }
+    }
+    if args.complement && set1.has_class() {
+        let same_length = len2 == len1 || (len2 < len1 && !args.truncate_set1);
+        if !same_length || !set2.is_homogeneous() {
+            return Err(set_error(
+                "when translating with complemented character classes,\n\
+                 string2 must map all characters in the domain to one",
+            ));
+        }
+    }
+    let truncate = args.truncate_set1;
+    let mapping = match args.complement {
+        true => Mapping::Complement {
+            set1: members,
+            set2: set2.iter(fill).take(len1).collect(),
+            truncate,
+        },
+        false => {
+            let last = set2.last().unwrap_or_default();
+            let extended = set2.iter(fill).chain(std::iter::repeat(last));
+            let set2: Box<dyn Iterator<Item = Char>> = match truncate {
+                true => Box::new(set2.iter(fill)),
+                false => Box::new(extended),
+            };
+            // The later pairs win over the earlier ones of the same character
+            Mapping::Pairs(set1.iter(0).zip(set2).collect())
+        }
+    };
+    Ok(Translation::new(mapping))
+}
+
+/** SET2 with its [:upper:] and [:lower:] in place of the same classes of SET1, as their case
+ * mappings, so the classes of Unicode translate each other too. Nothing, if they aren't
+ * in the same places.
+ */
+fn case_mapped_classes(set1: &Set, set2: &Set, fill: usize) -> Option<Set> {
+    let case_classes1: Vec<(usize, Class)> = set1
+        .elements
+        .iter()
+        .zip(set1.offsets(0))
+        .filter_map(|(element, offset)| match element {
+            Element::Class(class) if class.is_case() => Some((offset, *class)),
+            _ => None,
+        })
+        .collect();
+    let mut offset: usize = 0;
+    let mut elements = Vec::with_capacity(set2.elements.len());
+    for &element in &set2.elements {
+        let element = match element {
+            Element::Class(class) if class.is_case() => {
+                let &(_, source) = case_classes1.iter().find(|(start, _)| *start == offset)?;
+                Element::Case(source, class)
+            }
+            element => element,
+        };
+        offset = offset.saturating_add(element.len(fill));
+        elements.push(element);
+    }
+    Some(Set { elements })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tr(options: &str, sets: &[&str], input: &[u8]) -> Result<Vec<u8>, String> {
+        let args = Args {
+            sets: sets.iter().map(|set| set.to_string()).collect(),
+            complement: options.contains('c'),
+            delete: options.contains('d'),
+            squeeze_repeats: options.contains('s'),
+            truncate_set1: options.contains('t'),
+            utf8: options.contains('u'),
+        };
+        let tr = Tr::new(&args).map_err(|e| e.to_string())?;
+        let mut output = Vec::new();
+        tr.run(input, &mut output).unwrap();
+        Ok(output)
+    }
+
+    const INPUT: &[u8] = b"Hello, World! abc\n";
+
+    #[test]
+    fn translates() {
+        let translated = |options, sets: &[&str]| tr(options, sets, INPUT).unwrap();
+        assert_eq!(
+            translated("", &["[:lower:]", "[:upper:]"]),
+            b"HELLO, WORLD! ABC\n"
+        );
+        assert_eq!(translated("", &["a-c", "x"]), b"Hello, World! xxx\n");
+        assert_eq!(translated("t", &["a-c", "x"]), b"Hello, World! xbc\n");
+        assert_eq!(translated("", &["abc", "[x*]y"]), b"Hello, World! xxy\n");
+        assert_eq!(translated("", &["ll", "xy"]), b"Heyyo, Woryd! abc\n");
+        assert_eq!(translated("c", &["a", "[:upper:]a"]), b"aaaaaaaaaaaaaaaaaK");
+        assert_eq!(translated("tc", &["a", "[:upper:]"]), b"Hello, World! abcK");
+    }
+
+    #[test]
+    fn deletes_and_squeezes() {
+        let filtered = |options, sets: &[&str]| tr(options, sets, INPUT).unwrap();
+        assert_eq!(filtered("d", &["[:lower:]"]), b"H, W! \n");
+        assert_eq!(filtered("s", &["l"]), b"Helo, World! abc\n");
+        assert_eq!(
+            filtered("cs", &["[:alpha:]", "\\n"]),
+            b"Hello\nWorld\nabc\n"
+        );
+        assert_eq!(filtered("ds", &["l", "o"]), b"Heo, Word! abc\n");
+        assert_eq!(filtered("cd", &["[:alpha:]\\n"]), b"HelloWorldabc\n");
+    }
+
+    #[test]
+    fn checks_the_sets() {
+        let error = |options, sets: &[&str]| tr(options, sets, INPUT).unwrap_err();
+        assert_eq!(
+            error("", &["a-z", "[:upper:]"]),
+            "misaligned [:upper:] and/or [:lower:] construct"
+        );
+        assert_eq!(
+            error("c", &["[:lower:]", "[:upper:]"]),
+            "when translating with string1 longer than string2,\n\
+             the latter string must not end with a character class"
+        );
+        assert_eq!(
+            error("c", &["[:lower:]", "xy"]),
+            "when translating with complemented character classes,\n\
+             string2 must map all characters in the domain to one"
+        );
+        assert_eq!(
+            error("ds", &["a", "[b*]"]),
+            "the [c*] construct may appear in string2 only when translating"
+        );
+        assert!(tr("c", &["[:lower:]", "[x*]"], INPUT).is_ok());
+    }
+
+    #[test]
+    fn translates_utf8() {
+        let input = "añb€\u{1F600}\n\u{ff}".as_bytes();
+        let output = tr("u", &["ñ€", "n["], input).unwrap();
+        assert_eq!(output, "anb[\u{1F600}\n\u{ff}".as_bytes());
+        let output = tr("ud", &["[=€=]a-c"], b"a\xe2\x82b\xff\xe2\x82\xac").unwrap();
+        assert_eq!(output, b"\xe2\x82\xff");
+        let output = tr("uc", &["a", "x"], input).unwrap();
+        assert_eq!(output, b"axxxxxx");
+        let output = tr("us", &["€"], "€€a€€".as_bytes()).unwrap();
+        assert_eq!(output, "€a€".as_bytes());
+    }
+
+    #[test]
+    fn uses_the_unicode_classes_in_utf8() {
+        let translated = |options, sets: &[&str], input: &str| {
+            String::from_utf8(tr(options, sets, input.as_bytes()).unwrap()).unwrap()
+        };
+        let sets = ["[:lower:]x", "[:upper:]y"];
+        assert_eq!(translated("u", &sets, "éα ßſ xk"), "ÉΑ ßS yK");
+        assert_eq!(translated("", &sets, "éα xk"), "éα yK");
+        let sets = ["[:upper:]", "[:lower:]"];
+        assert_eq!(translated("u", &sets, "ÉΑ \u{212a}"), "éα k");
+        assert_eq!(
+            translated("ud", &["[:alpha:][:space:]"], "aé 1\u{3000}α!"),
+            "1!"
+        );
+        assert_eq!(translated("uc", &["[:punct:]", "_"], "a!«»"), "_!«»");
+    }
+}
@@ -0,0 +1,528 @@
+use std::iter;
+
+use super::tr_error::TrError;
+
+/// A character of a SET, a byte, or a code point in the UTF-8 mode
+pub type Char = u32;
+
+/// The names of the character classes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClassName {
+    Alnum,
+    Alpha,
+    Blank,
+    Cntrl,
+    Digit,
+    Graph,
+    Lower,
+    Print,
+    Punct,
+    Space,
+    Upper,
+    Xdigit,
+}
+
+/// A character class, of ASCII as in the C locale, or of Unicode in the UTF-8 mode
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Class {
+    pub name: ClassName,
+    pub utf8: bool,
+}
+
+impl Class {
+    fn parse(name: &str, utf8: bool) -> Option<Class> {
+        let name = match name {
+            "alnum" => ClassName::Alnum,
+            "alpha" => ClassName::Alpha,
+            "blank" => ClassName::Blank,
+            "cntrl" => ClassName::Cntrl,
+            "digit" => ClassName::Digit,
+            "graph" => ClassName::Graph,
+            "lower" => ClassName::Lower,
+            "print" => ClassName::Print,
+            "punct" => ClassName::Punct,
+            "space" => ClassName::Space,
+            "upper" => ClassName::Upper,
+            "xdigit" => ClassName::Xdigit,
+            _ => return None,
+        };
+        Some(Class { name, utf8 })
+    }
+
+    pub fn contains(&self, c: Char) -> bool {
+        match self.utf8 {
+            true => char::from_u32(c).is_some_and(|c| self.contains_unicode(c)),
+            false => u8::try_from(c).is_ok_and(|c| self.contains_ascii(c)),
+        }
+    }
+
+    fn contains_ascii(&self, c: u8) -> bool {
+        match self.name {
+            ClassName::Alnum => c.is_ascii_alphanumeric(),
+            ClassName::Alpha => c.is_ascii_alphabetic(),
+            ClassName::Blank => c == b' ' || c == b'\t',
+            ClassName::Cntrl => c.is_ascii_control(),
+            ClassName::Digit => c.is_ascii_digit(),
+            ClassName::Graph => c.is_ascii_graphic(),
+            ClassName::Lower => c.is_ascii_lowercase(),
+            ClassName::Print => c.is_ascii_graphic() || c == b' ',
+            ClassName::Punct => c.is_ascii_punctuation(),
+            ClassName::Space => c.is_ascii_whitespace() || c == 0x0b,
+            ClassName::Upper => c.is_ascii_uppercase(),
+            ClassName::Xdigit => c.is_ascii_hexdigit(),
+        }
+    }
+
+    /// The digits stay ASCII, as in the UTF-8 locales of glibc
+    fn contains_unicode(&self, c: char) -> bool {
+        let graph = !c.is_control() && !c.is_whitespace();
+        match self.name {
+            ClassName::Alnum => c.is_alphanumeric(),
+            ClassName::Alpha => c.is_alphabetic(),
+            ClassName::Blank => c == '\t' || (c.is_whitespace() && !c.is_control()),
+            ClassName::Cntrl => c.is_control(),
+            ClassName::Digit => c.is_ascii_digit(),
+            ClassName::Graph => graph,
+            ClassName::Lower => c.is_lowercase(),
+            ClassName::Print => graph || c == ' ',
+            ClassName::Punct => graph && !c.is_alphanumeric(),
+            ClassName::Space => c.is_whitespace(),
+            ClassName::Upper => c.is_uppercase(),
+            ClassName::Xdigit => c.is_ascii_hexdigit(),
+        }
+    }
+
+    /// [:upper:] and [:lower:] translate each other, in the case of the one of SET2
+    pub fn is_case(&self) -> bool {
+        matches!(self.name, ClassName::Lower | ClassName::Upper)
+    }
+
+    /// The character in the case of [:upper:] or [:lower:], if it's a single character
+    fn convert(&self, c: Char) -> Char {
+        let Some(ch) = char::from_u32(c) else {
+            return c;
+        };
+        let converted: Vec<char> = match self.name {
+            ClassName::Upper => ch.to_uppercase().collect(),
+            ClassName::Lower => ch.to_lowercase().collect(),
+            _ => return c,
+        };
+        match converted[..] {
+            [converted] => converted as Char,
+            _ => c,
+        }
+    }
+
+    fn members(self) -> impl Iterator<Item = Char> {
+        let end = if self.utf8 { 0x110000 } else { 128 };
+        (0..end).filter(move |&c| self.contains(c))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Element {
+    Char(Char),
+    Range(Char, Char),
+    Class(Class),
+    Equivalence(Char),
+    /// [c*n], without the count it's as many, as SET1 needs
+    Repeat(Char, Option<usize>),
+    /// [:upper:] or [:lower:] of SET2 in place of the case class of SET1, in its case
+    Case(Class, Class),
+}
+
+impl Element {
+    pub fn contains(&self, c: Char) -> bool {
+        match *self {
+            Element::Char(member) | Element::Equivalence(member) | Element::Repeat(member, _) => {
+                member == c
+            }
+            Element::Range(first, last) => (first..=last).contains(&c),
+            Element::Class(class) | Element::Case(_, class) => class.contains(c),
+        }
+    }
+
+    /// How many characters it stands for, the repeat without a count is `fill` of them
+    pub fn len(&self, fill: usize) -> usize {
+        match *self {
+            Element::Char(_) | Element::Equivalence(_) => 1,
+            Element::Range(first, last) => (first..=last).filter(|&c| is_char(c)).count(),
+            Element::Class(class) | Element::Case(class, _) => class.members().count(),
+            Element::Repeat(_, count) => count.unwrap_or(fill),
+        }
+    }
+
+    fn iter(self, fill: usize) -> Box<dyn Iterator<Item = Char>> {
+        match self {
+            Element::Char(c) | Element::Equivalence(c) => Box::new(iter::once(c)),
+            Element::Range(first, last) => Box::new((first..=last).filter(|&c| is_char(c))),
+            Element::Class(class) => Box::new(class.members()),
+            Element::Repeat(c, count) => Box::new(iter::repeat_n(c, count.unwrap_or(fill))),
+            Element::Case(source, target) => {
+                Box::new(source.members().map(move |c| target.convert(c)))
+            }
+        }
+    }
+}
+
+/// The surrogates are left out of the ranges of code points
+fn is_char(c: Char) -> bool {
+    char::from_u32(c).is_some()
+}
+
+/** An array of characters, SET1 or SET2.
+ *
+ * Most of the characters represent themselves, and there are backslash
+ * escapes, ranges, classes, equivalence classes and repeats.
+ */
+#[derive(Debug, Default, PartialEq)]
+pub struct Set {
+    pub elements: Vec<Element>,
+}
+
+impl Set {
+    /// The SET of bytes, or of code points in the UTF-8 mode, the warnings are written right away
+    pub fn parse(spec: &str, utf8: bool) -> Result<Set, TrError> {
+        let chars = unescape(spec, utf8);
+        let mut elements = Vec::new();
+        let mut i = 0;
+        while i < chars.len() {
+            if chars[i] == ('[' as Char, false) {
+                if let Some((element, next)) = parse_bracket(&chars, i, utf8)? {
+                    elements.push(element);
+                    i = next;
+                    continue;
+                }
+            }
+            match chars.get(i + 1..i + 3) {
+                Some(&[(dash, false), (last, _)]) if dash == '-' as Char => {
+                    let first = chars[i].0;
+                    if first > last {
+                        return Err(TrError::Set(format!(
+                            "range-endpoints of '{}-{}' are in reverse collating sequence order",
+                            printable(first),
+                            printable(last)
+                        )));
+                    }
+                    elements.push(Element::Range(first, last));
+                    i += 3;
+                }
+                _ => {
+                    elements.push(Element::Char(chars[i].0));
+                    i += 1;
+                }
+            }
+        }
+        Ok(Set { elements })
+    }
+
+    /// The characters in order, the repeat without a count is `fill` of them
+    pub fn iter(&self, fill: usize) -> impl Iterator<Item = Char> + '_ {
+        self.elements
+            .iter()
+            .flat_map(move |element| element.iter(fill))
+    }
+
+    /// How many characters there are, without the repeat without a count
+    pub fn len(&self) -> usize {
+        self.elements
+            .iter()
+            .fold(0, |len: usize, element| len.saturating_add(element.len(0)))
+    }
+
+    /// Where the elements start, when they go in order
+    pub fn offsets(&self, fill: usize) -> Vec<usize> {
+        let mut offset: usize = 0;
+        self.elements
+            .iter()
+            .map(|element| {
+                let start = offset;
+                offset = offset.saturating_add(element.len(fill));
+                start
+            })
+            .collect()
+    }
+
+    pub fn last(&self) -> Option<Char> {
+        self.elements.last().and_then(|element| match *element {
+            Element::Char(c) | Element::Equivalence(c) | Element::Repeat(c, _) => Some(c),
+            Element::Range(_, last) => Some(last),
+            Element::Class(class) => class.members().last(),
+            Element::Case(source, target) => source.members().last().map(|c| target.convert(c)),
+        })
+    }
+
+    pub fn contains(&self, c: Char) -> bool {
+        self.elements.iter().any(|element| element.contains(c))
+    }
+
+    pub fn has_fill(&self) -> bool {
+        self.elements
+            .iter()
+            .any(|element| matches!(element, Element::Repeat(_, None)))
+    }
+
+    pub fn has_class(&self) -> bool {
+        self.elements
+            .iter()
+            .any(|element| matches!(element, Element::Class(_) | Element::Case(..)))
+    }
+
+    /// Whether all the characters are the same one
+    pub fn is_homogeneous(&self) -> bool {
+        let mut chars = self.elements.iter().map(|element| match *element {
+            Element::Char(c) | Element::Equivalence(c) | Element::Repeat(c, _) => Some(c),
+            Element::Range(first, last) if first == last => Some(first),
+            Element::Range(..) | Element::Class(_) | Element::Case(..) => None,
+        });
+        let first = chars.next().flatten();
+        first.is_some() && chars.all(|c| c == first)
+    }
+}
+
+/// The characters of the SET, and whether they are escaped, so they aren't special
+fn unescape(spec: &str, utf8: bool) -> Vec<(Char, bool)> {
+    let units: Vec<Char> = match utf8 {
+        true => spec.chars().map(Char::from).collect(),
+        false => spec.bytes().map(Char::from).collect(),
+    };
+    let octal = |i: usize| {
+        units
+            .get(i)
+            .and_then(|&c| char::from_u32(c))
+            .and_then(|c| c.to_digit(8))
+    };
+    let mut chars = Vec::with_capacity(units.len());
+    let mut i = 0;
+    while i < units.len() {
+        if units[i] != '\\' as Char {
+            chars.push((units[i], false));
+            i += 1;
+            continue;
+        }
+        let Some(&escaped) = units.get(i + 1) else {
+            eprintln!("tr: warning: an unescaped backslash at end of string is not portable");
+            chars.push(('\\' as Char, false));
+            break;
+        };
+        i += 2;
+        let c = match char::from_u32(escaped).unwrap_or_default() {
+            'a' => 7,
+            'b' => 8,
+            'f' => 12,
+            'n' => b'\n' as Char,
+            'r' => b'\r' as Char,
+            't' => b'\t' as Char,
+            'v' => 11,
+            '0'..='7' => {
+                let mut c = escaped - '0' as Char;
+                // Up to 3 digits, as long as it's a byte
+                for _ in 0..2 {
+                    match octal(i) {
+                        Some(digit) if c * 8 + digit < 256 => {
+                            c = c * 8 + digit;
+                            i += 1;
+                        }
+                        Some(digit) => {
+                            eprintln!(
+                                "tr: warning: the ambiguous octal escape \\{:o}{} is being\n\t\
+                                 interpreted as the 2-byte sequence \\0{:o}, {}",
+                                c, digit, c, digit
+                            );
+                            break;
+                        }
+                        None => break,
+                    }
+                }
+                c
+            }
+            _ => escaped,
+        };
+        chars.push((c, true));
+    }
+    chars
+}
+
+/// "[:class:]", "[=c=]" or "[c*n]" at `i`, and where it ends, or nothing, if it's just "["
+fn parse_bracket(
+    chars: &[(Char, bool)],
+    i: usize,
+    utf8: bool,
+) -> Result<Option<(Element, usize)>, TrError> {
+    let unescaped = |j: usize, c: char| chars.get(j) == Some(&(c as Char, false));
+    for delimiter in [':', '='] {
+        if !unescaped(i + 1, delimiter) {
+            continue;
+        }
+        let close = (i + 2..chars.len().saturating_sub(1))
+            .find(|&j| unescaped(j, delimiter) && unescaped(j + 1, ']'));
+        let Some(close) = close else {
+            continue;
+        };
+        let operand = &chars[i + 2..close];
+        let text = text(operand, utf8);
+        let element = match (delimiter, operand) {
+            (':', []) => return Err(set_error("missing character class name '[::]'")),
+            (':', _) => match Class::parse(&text, utf8) {
+                Some(class) => Element::Class(class),
+                None => {
+                    let message = format!("invalid character class '{}'", text);
+                    return Err(TrError::Set(message));
+                }
+            },
+            (_, []) => return Err(set_error("missing equivalence class character '[==]'")),
+            (_, [(c, _)]) => Element::Equivalence(*c),
+            _ => {
+                let message = format!(
+                    "{}: equivalence class operand must be a single character",
+                    text
+                );
+                return Err(TrError::Set(message));
+            }
+        };
+        return Ok(Some((element, close + 2)));
+    }
+    if i + 1 >= chars.len() || !unescaped(i + 2, '*') {
+        return Ok(None);
+    }
+    for j in i + 3..chars.len() {
+        match chars[j] {
+            (_, true) => break,
+            (c, false) if c == ']' as Char => {
+                let digits = text(&chars[i + 3..j], utf8);
+                let count = parse_count(&digits).ok_or_else(|| {
+                    let digits: String =
+                        chars[i + 3..j].iter().map(|&(c, _)| printable(c)).collect();
+                    TrError::Set(format!(
+                        "invalid repeat count '{}' in [c*n] construct",
+                        digits
+                    ))
+                })?;
+                let element = Element::Repeat(chars[i + 1].0, count.filter(|&count| count != 0));
+                return Ok(Some((element, j + 1)));
+            }
+            _ => {}
+        }
+    }
+    Ok(None)
+}
+
+/// The count of a repeat, octal when it starts with 0, no count is Some(None)
+fn parse_count(digits: &str) -> Option<Option<usize>> {
+    if digits.is_empty() {
+        return Some(None);
+    }
+    let radix = if digits.starts_with('0') { 8 } else { 10 };
+    let digits = digits.trim_start();
+    if digits.starts_with('-') {
+        return None;
+    }
+    usize::from_str_radix(digits, radix).ok().map(Some)
+}
+
+fn text(chars: &[(Char, bool)], utf8: bool) -> String {
+    match utf8 {
+        true => chars
+            .iter()
+            .filter_map(|&(c, _)| char::from_u32(c))
+            .collect(),
+        false => {
+            let bytes: Vec<u8> = chars.iter().map(|&(c, _)| c as u8).collect();
+            String::from_utf8_lossy(&bytes).into_owned()
+        }
+    }
+}
+
+/// The character for the messages, with the unprintable ones in octal
+fn printable(c: Char) -> String {
+    match char::from_u32(c) {
+        Some(c) if c == ' ' || c.is_ascii_graphic() => c.to_string(),
+        _ if c < 256 => format!("\\{:03o}", c),
+        Some(c) => c.to_string(),
+        None => format!("\\{:o}", c),
+    }
+}
+
+pub fn set_error(message: &str) -> TrError {
+    TrError::Set(message.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn elements(spec: &str) -> Vec<Element> {
+        Set::parse(spec, false).unwrap().elements
+    }
+
+    fn error(spec: &str) -> String {
+        Set::parse(spec, false).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn parses_the_constructs() {
+        use Element::*;
+        assert_eq!(
+            elements("a-c[:digit:][=x=][y*3][z*]"),
+            [
+                Range(97, 99),
+                Class(super::Class {
+                    name: ClassName::Digit,
+                    utf8: false
+                }),
+                Equivalence(120),
+                Repeat(121, Some(3)),
+                Repeat(122, None)
+            ]
+        );
+        assert_eq!(elements("[a*010]"), [Repeat(97, Some(8))]);
+        assert_eq!(elements("a-"), [Char(97), Char(45)]);
+        assert_eq!(elements("a\\-z"), [Char(97), Char(45), Char(122)]);
+        assert_eq!(elements("[:a"), [Char(91), Char(58), Char(97)]);
+        assert_eq!(
+            elements("[b*\\n]"),
+            [Char(91), Char(98), Char(42), Char(10), Char(93)]
+        );
+        assert_eq!(
+            elements("\\n\\101\\q\\"),
+            [Char(10), Char(65), Char(113), Char(92)]
+        );
+        assert_eq!(elements("\\400"), [Char(32), Char(48)]);
+        assert_eq!(
+            Set::parse("é-ë", true).unwrap().elements,
+            [Range(0xe9, 0xeb)]
+        );
+    }
+
+    #[test]
+    fn reports_the_errors() {
+        assert_eq!(
+            error("z-a"),
+            "range-endpoints of 'z-a' are in reverse collating sequence order"
+        );
+        assert_eq!(
+            error("\n-\\001"),
+            "range-endpoints of '\\012-\\001' are in reverse collating sequence order"
+        );
+        assert_eq!(error("[:foo:]"), "invalid character class 'foo'");
+        assert_eq!(error("[::]"), "missing character class name '[::]'");
+        assert_eq!(
+            error("[=ab=]"),
+            "ab: equivalence class operand must be a single character"
+        );
+        assert_eq!(
+            error("[a*08]"),
+            "invalid repeat count '08' in [c*n] construct"
+        );
+    }
+
+    #[test]
+    fn expands_in_order() {
+        let set = Set::parse("[:upper:]x-z[a*]", false).unwrap();
+        let chars: String = set.iter(2).filter_map(char::from_u32).collect();
+        assert_eq!(chars, "ABCDEFGHIJKLMNOPQRSTUVWXYZxyzaa");
+        assert_eq!(set.len(), 29);
+        assert_eq!(set.offsets(2), [0, 26, 29]);
+        assert_eq!(set.last(), Some(97));
+    }
+}
@@ -0,0 +1,31 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum TrError {
+    Read(io::Error),
+    Write(io::Error),
+    /// Wrong SETs, with the GNU message
+    Set(String),
+}
+
+impl fmt::Display for TrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrError::Read(err) => write!(f, "read error: {}", strerror(err)),
+            TrError::Write(err) => write!(f, "write error: {}", strerror(err)),
+            TrError::Set(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for TrError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TrError::Read(err) | TrError::Write(err) => Some(err),
+            TrError::Set(_) => None,
+        }
+    }
+}
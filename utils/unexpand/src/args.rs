@@ -0,0 +1,85 @@
+use std::ffi::OsString;
+
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser};
+use tab_stops::TabStops;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// Convert blanks in each FILE to tabs, writing to standard output
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no FILE, or when FILE is -, read standard input
+    #[arg(default_value = "-")]
+    pub file: Vec<String>,
+
+    /// convert all blanks, instead of just initial blanks
+    #[arg(short = 'a', long, default_value_t = false)]
+    pub all: bool,
+
+    /// convert only leading sequences of blanks (overrides -a)
+    #[arg(long, default_value_t = false)]
+    pub first_only: bool,
+
+    /// have tabs N characters apart, not 8, or at the comma separated LIST
+    /// of positions; the last one can be prefixed with '/' for the tabs every
+    /// N columns after it, or with '+' for the ones every N columns from it (enables -a)
+    #[arg(short = 't', long, value_name = "LIST")]
+    pub tabs: Vec<String>,
+
+    /// The obsolete -N, which doesn't enable -a
+    #[arg(long, hide = true)]
+    pub obsolete_tabs: Vec<String>,
+
+    /// All the lists of -t and -N in the order of the options
+    #[arg(skip)]
+    pub tab_stops: TabStops,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let arguments = obsolete_tabs(std::env::args_os());
+        let matches = <Self as CommandFactory>::command().get_matches_from(arguments);
+        let mut args =
+            <Self as FromArgMatches>::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+        args.tab_stops = TabStops::parse(&ordered_lists(&matches)).unwrap_or_else(|err| {
+            eprintln!("unexpand: {}", err);
+            std::process::exit(1);
+        });
+        args.all = (args.all || !args.tabs.is_empty()) && !args.first_only;
+        args
+    }
+}
+
+/// The obsolete -N and -N,M,.. go as --obsolete-tabs, clap doesn't take the digits for options
+fn obsolete_tabs(arguments: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    let mut options = true;
+    arguments
+        .map(|argument| match argument.to_str() {
+            Some("--") => {
+                options = false;
+                argument
+            }
+            Some(list)
+                if options
+                    && list.starts_with('-')
+                    && list[1..].starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                format!("--obsolete-tabs={}", &list[1..]).into()
+            }
+            _ => argument,
+        })
+        .collect()
+}
+
+/// The lists of both -t and -N, the stops of every one go after the previous ones
+fn ordered_lists(matches: &ArgMatches) -> Vec<String> {
+    let mut lists: Vec<(usize, String)> = ["tabs", "obsolete_tabs"]
+        .into_iter()
+        .filter_map(|id| Some(matches.indices_of(id)?.zip(matches.get_many::<String>(id)?)))
+        .flatten()
+        .map(|(index, list)| (index, list.clone()))
+        .collect();
+    lists.sort();
+    lists.into_iter().map(|(_, list)| list).collect()
+}
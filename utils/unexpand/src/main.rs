@@ -0,0 +1,39 @@
+mod args;
+mod unexpand;
+
+use std::io::{self, BufWriter, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use unexpand::unexpand_error::UnexpandError;
+use unexpand::Unexpander;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let mut unexpander = Unexpander::new(&args.tab_stops, args.all);
+    let mut status = ExitCode::SUCCESS;
+    for file in &args.file {
+        match unexpander.unexpand_file(file, &mut output) {
+            Ok(()) => {}
+            Err(e @ UnexpandError::Write(_)) => {
+                eprintln!("unexpand: {}", e);
+                return ExitCode::FAILURE;
+            }
+            Err(e) => {
+                // Flushing whatever was written before the error, so it goes before the message
+                let _ = output.flush();
+                eprintln!("unexpand: {}", e);
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    if let Err(e) = unexpander
+        .finish(&mut output)
+        .and_then(|_| output.flush().map_err(UnexpandError::Write))
+    {
+        eprintln!("unexpand: {}", e);
+        return ExitCode::FAILURE;
+    }
+    status
+}
@@ -0,0 +1,180 @@
+pub mod unexpand_error;
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+
+use chunk_reader::ChunkReader;
+use tab_stops::{width, Chars, Columns, TabStops};
+
+use unexpand_error::UnexpandError;
+
+/** Replaces the blanks before the tab stops with the tabs, the way GNU unexpand does.
+ *
+ * The FILEs go as one input, so a line can go on in the next FILE.
+ */
+pub struct Unexpander<'a> {
+    tab_stops: &'a TabStops,
+    /// The blanks after the non-blank characters as well
+    all: bool,
+    columns: Columns,
+    convert: bool,
+    next_tab: usize,
+    /// The blanks, which aren't known yet to be replaced
+    pending: Vec<u8>,
+    /// The first pending blank comes just before a tab stop
+    one_blank_before_tab_stop: bool,
+    prev_blank: bool,
+}
+
+impl<'a> Unexpander<'a> {
+    pub fn new(tab_stops: &'a TabStops, all: bool) -> Self {
+        Unexpander {
+            tab_stops,
+            all,
+            columns: Columns::default(),
+            convert: true,
+            next_tab: 0,
+            pending: Vec::new(),
+            one_blank_before_tab_stop: false,
+            prev_blank: true,
+        }
+    }
+
+    pub fn unexpand_file<W: Write>(
+        &mut self,
+        name: &str,
+        output: &mut W,
+    ) -> Result<(), UnexpandError> {
+        let read_error = |e| UnexpandError::Read(name.to_string(), e);
+        let input: Box<dyn Read> = match name {
+            "-" => Box::new(io::stdin()),
+            _ => Box::new(File::open(name).map_err(read_error)?),
+        };
+        let mut unexpanded = Vec::new();
+        for line in BufReader::new(input).chunks(b'\n', 0) {
+            let line = line.map_err(read_error)?;
+            unexpanded.clear();
+            self.unexpand(&line, &mut unexpanded);
+            output
+                .write_all(&unexpanded)
+                .map_err(UnexpandError::Write)?;
+        }
+        Ok(())
+    }
+
+    /// The blanks pending at the end of the input
+    pub fn finish<W: Write>(&mut self, output: &mut W) -> Result<(), UnexpandError> {
+        let mut unexpanded = Vec::new();
+        self.flush_pending(&mut unexpanded);
+        output.write_all(&unexpanded).map_err(UnexpandError::Write)
+    }
+
+    /** A single space stays, unless it's followed by another blank.
+     *
+     * Past the last tab stop, nothing is replaced till the end of the line.
+     */
+    fn unexpand(&mut self, line: &[u8], unexpanded: &mut Vec<u8>) {
+        for (bytes, c) in Chars::new(line) {
+            let mut bytes = bytes;
+            if self.convert {
+                let blank = c == Some(' ') || c == Some('\t');
+                if blank {
+                    match self.columns.next_tab(self.tab_stops) {
+                        None => self.convert = false,
+                        Some(next_tab) => {
+                            self.next_tab = next_tab;
+                            if c == Some('\t') {
+                                self.columns.column = next_tab;
+                            } else {
+                                self.columns.advance(1);
+                                let at_tab = self.columns.column == next_tab;
+                                if !(self.prev_blank && at_tab) {
+                                    // It isn't known yet, whether the blanks will be replaced
+                                    self.one_blank_before_tab_stop |= at_tab;
+                                    self.pending.push(b' ');
+                                    self.prev_blank = true;
+                                    continue;
+                                }
+                                bytes = b"\t";
+                            }
+                            // The pending blanks are gone, but a single one before the previous stop
+                            self.pending
+                                .truncate(self.one_blank_before_tab_stop as usize);
+                            if let Some(first) = self.pending.first_mut() {
+                                *first = b'\t';
+                            }
+                        }
+                    }
+                } else if c == Some('\x08') {
+                    self.columns.backspace();
+                    self.next_tab = self.columns.column;
+                } else {
+                    self.columns.advance(c.map_or(1, width));
+                }
+                self.flush_pending(unexpanded);
+                self.prev_blank = blank;
+                self.convert &= self.all || blank;
+            }
+            unexpanded.extend_from_slice(bytes);
+            if c == Some('\n') {
+                self.columns.reset();
+                self.convert = true;
+                self.next_tab = 0;
+                self.prev_blank = true;
+            }
+        }
+    }
+
+    /// The single blank before a tab stop is a tab, if more blanks follow it
+    fn flush_pending(&mut self, unexpanded: &mut Vec<u8>) {
+        if self.pending.len() > 1 && self.one_blank_before_tab_stop {
+            self.pending[0] = b'\t';
+        }
+        unexpanded.append(&mut self.pending);
+        self.one_blank_before_tab_stop = false;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unexpand(lists: &[&str], all: bool, input: &str) -> String {
+        let tab_stops = TabStops::parse(lists).unwrap();
+        let mut unexpander = Unexpander::new(&tab_stops, all);
+        let mut unexpanded = Vec::new();
+        for line in input.split_inclusive('\n') {
+            unexpander.unexpand(line.as_bytes(), &mut unexpanded);
+        }
+        unexpander.finish(&mut unexpanded).unwrap();
+        String::from_utf8(unexpanded).unwrap()
+    }
+
+    #[test]
+    fn replaces_the_blanks() {
+        let input = "        a       b  c        d\n   x    y\n \t  z\n";
+        assert_eq!(
+            unexpand(&[], false, input),
+            "\ta       b  c        d\n   x    y\n\t  z\n"
+        );
+        assert_eq!(
+            unexpand(&[], true, input),
+            "\ta\tb  c\t    d\n   x\ty\n\t  z\n"
+        );
+        assert_eq!(
+            unexpand(&["3,+4"], true, input),
+            "\t\t a\t\t b\tc\t\t d\n\tx\t y\n\t  z\n"
+        );
+        assert_eq!(unexpand(&["2"], true, "a   "), "a\t\t");
+    }
+
+    #[test]
+    fn counts_the_display_width() {
+        assert_eq!(unexpand(&["4"], true, "日本    x\n"), "日本\tx\n");
+        assert_eq!(unexpand(&["4"], true, "日 x    y\n"), "日 x\ty\n");
+        assert_eq!(
+            unexpand(&["4"], true, "e\u{301}      x\n"),
+            "e\u{301}\t   x\n"
+        );
+    }
+}
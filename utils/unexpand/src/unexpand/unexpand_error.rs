@@ -0,0 +1,38 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum UnexpandError {
+    /// Opening or reading a FILE, GNU reports both the same way
+    Read(String, io::Error),
+    Write(io::Error),
+}
+
+impl fmt::Display for UnexpandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnexpandError::Read(name, err) => write!(f, "{}: {}", quote(name), strerror(err)),
+            UnexpandError::Write(err) => write!(f, "write error: {}", strerror(err)),
+        }
+    }
+}
+
+impl Error for UnexpandError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UnexpandError::Read(_, err) | UnexpandError::Write(err) => Some(err),
+        }
+    }
+}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
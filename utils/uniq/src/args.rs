@@ -0,0 +1,155 @@
+use std::error::Error;
+
+use clap::{Parser, ValueEnum};
+
+use crate::uniq::uniq_error::ArgError;
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+/// How the groups of --all-repeated are delimited
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum AllRepeated {
+    /// no delimiters
+    #[default]
+    None,
+    /// an empty line before each group
+    Prepend,
+    /// an empty line between the groups
+    Separate,
+}
+
+/// Where the empty lines of --group go
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Group {
+    /// between the groups
+    Separate,
+    /// before each group
+    Prepend,
+    /// after each group
+    Append,
+    /// before and after each group
+    Both,
+}
+
+/// Filter adjacent matching lines from INPUT (or standard input),
+/// writing to OUTPUT (or standard output)
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    /// With no INPUT, or when INPUT is -, read standard input
+    #[arg(default_value = "-")]
+    pub input: String,
+
+    /// Standard output without OUTPUT
+    pub output: Option<String>,
+
+    #[arg(hide = true)]
+    pub extra: Vec<String>,
+
+    /// prefix lines by the number of occurrences
+    #[arg(short = 'c', long, default_value_t = false)]
+    pub count: bool,
+
+    /// only print duplicate lines, one for each group
+    #[arg(short = 'd', long, default_value_t = false)]
+    pub repeated: bool,
+
+    /// print all duplicate lines
+    #[arg(short = 'D', default_value_t = false)]
+    pub all_repeated_none: bool,
+
+    /// like -D, but allow separating groups with an empty line
+    #[arg(long, value_enum, value_name = "METHOD", num_args = 0..=1, require_equals = true, default_missing_value = "none")]
+    pub all_repeated: Option<AllRepeated>,
+
+    /// avoid comparing the first N fields
+    #[arg(short = 'f', long, value_name = "N", value_parser = parse_skip_fields, allow_hyphen_values = true, default_value_t = 0)]
+    pub skip_fields: usize,
+
+    /// show all items, separating groups with an empty line
+    #[arg(long, value_enum, value_name = "METHOD", num_args = 0..=1, require_equals = true, default_missing_value = "separate")]
+    pub group: Option<Group>,
+
+    /// ignore differences in case when comparing
+    #[arg(short = 'i', long, default_value_t = false)]
+    pub ignore_case: bool,
+
+    /// avoid comparing the first N characters
+    #[arg(short = 's', long, value_name = "N", value_parser = parse_skip_chars, allow_hyphen_values = true, default_value_t = 0)]
+    pub skip_chars: usize,
+
+    /// only print unique lines
+    #[arg(short = 'u', long, default_value_t = false)]
+    pub unique: bool,
+
+    /// line delimiter is NUL, not newline
+    #[arg(short = 'z', long, default_value_t = false)]
+    pub zero_terminated: bool,
+
+    /// compare no more than N characters in lines
+    #[arg(short = 'w', long, value_name = "N", value_parser = parse_check_chars, allow_hyphen_values = true)]
+    pub check_chars: Option<usize>,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::try_parse().unwrap_or_else(|err| {
+            if let Some(invalid) = err.source().and_then(|e| e.downcast_ref::<ArgError>()) {
+                eprintln!("uniq: {}", invalid);
+                std::process::exit(1);
+            }
+            err.exit()
+        });
+        if let Some(extra) = args.extra.first() {
+            usage_error(&format!("extra operand '{}'", extra));
+        }
+        if args.all_repeated_none && args.all_repeated.is_none() {
+            args.all_repeated = Some(AllRepeated::None);
+        }
+        let output_options = args.count || args.repeated || args.unique;
+        if args.group.is_some() && (output_options || args.all_repeated.is_some()) {
+            usage_error("--group is mutually exclusive with -c/-d/-D/-u");
+        }
+        if args.count && args.all_repeated.is_some() {
+            usage_error("printing all duplicated lines and repeat counts is meaningless");
+        }
+        args
+    }
+
+    pub fn terminator(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+}
+
+/// GNU usage errors are followed by the hint to see --help
+fn usage_error(message: &str) -> ! {
+    eprintln!("uniq: {}", message);
+    eprintln!("Try 'uniq --help' for more information.");
+    std::process::exit(1);
+}
+
+/// A count, the way strtoumax() reads it, too big ones are as good as the biggest one
+fn parse_count(count: &str, what: &str) -> Result<usize, ArgError> {
+    let trimmed = count.trim_start();
+    let digits = trimmed.strip_prefix('+').unwrap_or(trimmed);
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(ArgError(format!("{}: invalid number of {}", count, what)));
+    }
+    Ok(digits.parse().unwrap_or(usize::MAX))
+}
+
+fn parse_skip_fields(count: &str) -> Result<usize, ArgError> {
+    parse_count(count, "fields to skip")
+}
+
+fn parse_skip_chars(count: &str) -> Result<usize, ArgError> {
+    parse_count(count, "bytes to skip")
+}
+
+fn parse_check_chars(count: &str) -> Result<usize, ArgError> {
+    parse_count(count, "bytes to compare")
+}
@@ -0,0 +1,38 @@
+mod args;
+mod uniq;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+use args::Args;
+use uniq::uniq;
+use uniq::uniq_error::UniqError;
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        eprintln!("uniq: {}", e);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// The INPUT is opened first, so OUTPUT isn't created, when there's nothing to read
+fn run(args: &Args) -> Result<(), UniqError> {
+    let input: Box<dyn Read> = match args.input.as_str() {
+        "-" => Box::new(io::stdin()),
+        name => Box::new(File::open(name).map_err(|e| UniqError::Open(name.to_string(), e))?),
+    };
+    let output: Box<dyn Write> = match args.output.as_deref() {
+        None | Some("-") => Box::new(io::stdout()),
+        Some(name) => {
+            Box::new(File::create(name).map_err(|e| UniqError::Create(name.to_string(), e))?)
+        }
+    };
+    let mut output = BufWriter::new(output);
+    let result = uniq(args, &args.input, input, &mut output);
+    // Flushing whatever was written before the error, so it goes before the message
+    let flushed = output.flush().map_err(UniqError::Write);
+    result.and(flushed)
+}
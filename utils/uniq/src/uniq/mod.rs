@@ -0,0 +1,260 @@
+pub mod uniq_error;
+
+use std::io::{BufReader, Read, Write};
+
+use chunk_reader::ChunkReader;
+
+use crate::args::{AllRepeated, Args, Group};
+use uniq_error::UniqError;
+
+/** Writes the INPUT lines into the OUTPUT, the adjacent matching ones as asked.
+ *
+ * The lines are read one by one, only the previous one is kept. The last line
+ * gets the terminator, if it hasn't one.
+ */
+pub fn uniq<W: Write>(
+    args: &Args,
+    name: &str,
+    input: Box<dyn Read>,
+    output: &mut W,
+) -> Result<(), UniqError> {
+    let terminator = args.terminator();
+    let mut lines = BufReader::new(input).chunks(terminator, 0);
+    let mut next_line = || match lines.next() {
+        Some(Ok(mut line)) => {
+            if line.last() == Some(&terminator) {
+                line.pop();
+            }
+            Ok(Some(line))
+        }
+        Some(Err(e)) => Err(UniqError::Read(name.to_string(), e)),
+        None => Ok(None),
+    };
+    let mut uniq = Uniq { args, output };
+    match args.group {
+        Some(group) => uniq.grouped(group, &mut next_line),
+        None => uniq.filtered(&mut next_line),
+    }
+}
+
+struct Uniq<'a, W> {
+    args: &'a Args,
+    output: &'a mut W,
+}
+
+impl<W: Write> Uniq<'_, W> {
+    /** All the lines with the groups delimited, for --group.
+     *
+     * The delimiters are empty lines, the groups are never empty themselves.
+     */
+    fn grouped(
+        &mut self,
+        group: Group,
+        next_line: &mut impl FnMut() -> Result<Option<Vec<u8>>, UniqError>,
+    ) -> Result<(), UniqError> {
+        let mut previous: Option<Vec<u8>> = None;
+        while let Some(line) = next_line()? {
+            let new_group = match &previous {
+                Some(previous) => !self.matches(previous, &line),
+                None => true,
+            };
+            let delimited = match group {
+                Group::Prepend | Group::Both => true,
+                Group::Append | Group::Separate => previous.is_some(),
+            };
+            if new_group && delimited {
+                self.write_delimiter()?;
+            }
+            self.write(&line, None)?;
+            previous = Some(line);
+        }
+        if previous.is_some() && matches!(group, Group::Append | Group::Both) {
+            self.write_delimiter()?;
+        }
+        Ok(())
+    }
+
+    /** The lines chosen by -d, -D and -u, the first ones of the groups by default.
+     *
+     * The previous line is the first one of its group, but with -D all of the
+     * repeated lines go one by one, so it's the last one, which was seen.
+     */
+    fn filtered(
+        &mut self,
+        next_line: &mut impl FnMut() -> Result<Option<Vec<u8>>, UniqError>,
+    ) -> Result<(), UniqError> {
+        let Some(mut previous) = next_line()? else {
+            return Ok(());
+        };
+        let all_repeated = self.args.all_repeated;
+        // Lines after the first one of the current group
+        let mut repeats = 0;
+        let mut first_group = true;
+        while let Some(line) = next_line()? {
+            let same = self.matches(&previous, &line);
+            if same {
+                repeats += 1;
+            }
+            match all_repeated {
+                Some(_) if !same && repeats > 0 => first_group = false,
+                Some(AllRepeated::Prepend) if same && repeats == 1 => self.write_delimiter()?,
+                Some(AllRepeated::Separate) if same && repeats == 1 && !first_group => {
+                    self.write_delimiter()?
+                }
+                _ => {}
+            }
+            if !same || all_repeated.is_some() {
+                self.write_filtered(&previous, same, repeats)?;
+                previous = line;
+                if !same {
+                    repeats = 0;
+                }
+            }
+        }
+        self.write_filtered(&previous, false, repeats)
+    }
+
+    /// Whether the parts of the lines, that are compared, are the same
+    fn matches(&self, a: &[u8], b: &[u8]) -> bool {
+        let (a, b) = (self.compared(a), self.compared(b));
+        match self.args.ignore_case {
+            true => a.eq_ignore_ascii_case(b),
+            false => a == b,
+        }
+    }
+
+    /// The line after -f fields and -s characters, no longer than -w characters
+    fn compared<'l>(&self, line: &'l [u8]) -> &'l [u8] {
+        // Newlines separate the fields too, there may be some with -z
+        let blank = |c: &u8| matches!(c, b' ' | b'\t' | b'\n');
+        let mut start = 0;
+        for _ in 0..self.args.skip_fields {
+            if start >= line.len() {
+                break;
+            }
+            start += line[start..].iter().take_while(|c| blank(c)).count();
+            start += line[start..].iter().take_while(|c| !blank(c)).count();
+        }
+        let start = start.saturating_add(self.args.skip_chars).min(line.len());
+        let end = match self.args.check_chars {
+            Some(chars) => start.saturating_add(chars).min(line.len()),
+            None => line.len(),
+        };
+        &line[start..end]
+    }
+
+    /** Writes the line, if it's chosen.
+     *
+     * It's unique without the repeats, the first one of its group, when it
+     * doesn't match the next line, one of the later ones otherwise.
+     */
+    fn write_filtered(&mut self, line: &[u8], same: bool, repeats: usize) -> Result<(), UniqError> {
+        let args = self.args;
+        let chosen = match repeats {
+            0 => !args.repeated && args.all_repeated.is_none(),
+            _ if !same => !args.unique,
+            _ => args.all_repeated.is_some(),
+        };
+        if !chosen {
+            return Ok(());
+        }
+        self.write(line, args.count.then_some(repeats + 1))
+    }
+
+    fn write(&mut self, line: &[u8], count: Option<usize>) -> Result<(), UniqError> {
+        if let Some(count) = count {
+            write!(self.output, "{:7} ", count).map_err(UniqError::Write)?;
+        }
+        self.output
+            .write_all(line)
+            .and_then(|_| self.output.write_all(&[self.args.terminator()]))
+            .map_err(UniqError::Write)
+    }
+
+    fn write_delimiter(&mut self) -> Result<(), UniqError> {
+        self.output
+            .write_all(&[self.args.terminator()])
+            .map_err(UniqError::Write)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn uniq_of(args: &Args, input: &'static str) -> String {
+        let mut output = Vec::new();
+        uniq(args, "-", Box::new(Cursor::new(input)), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    const INPUT: &str = "a\na\nb\nc\nc\nc\nd";
+
+    #[test]
+    fn filters_the_lines() {
+        let args = |count, repeated, unique| Args {
+            count,
+            repeated,
+            unique,
+            ..Default::default()
+        };
+        assert_eq!(uniq_of(&args(false, false, false), INPUT), "a\nb\nc\nd\n");
+        assert_eq!(
+            uniq_of(&args(true, false, false), INPUT),
+            "      2 a\n      1 b\n      3 c\n      1 d\n"
+        );
+        assert_eq!(uniq_of(&args(false, true, false), INPUT), "a\nc\n");
+        assert_eq!(uniq_of(&args(false, false, true), INPUT), "b\nd\n");
+        assert_eq!(uniq_of(&args(false, true, true), INPUT), "");
+    }
+
+    #[test]
+    fn delimits_the_groups() {
+        let all_repeated = |method| Args {
+            all_repeated: Some(method),
+            ..Default::default()
+        };
+        assert_eq!(
+            uniq_of(&all_repeated(AllRepeated::None), INPUT),
+            "a\na\nc\nc\nc\n"
+        );
+        assert_eq!(
+            uniq_of(&all_repeated(AllRepeated::Prepend), INPUT),
+            "\na\na\n\nc\nc\nc\n"
+        );
+        assert_eq!(
+            uniq_of(&all_repeated(AllRepeated::Separate), INPUT),
+            "a\na\n\nc\nc\nc\n"
+        );
+        let group = |method| Args {
+            group: Some(method),
+            ..Default::default()
+        };
+        assert_eq!(uniq_of(&group(Group::Separate), "a\na\nb"), "a\na\n\nb\n");
+        assert_eq!(uniq_of(&group(Group::Both), "a\na\nb"), "\na\na\n\nb\n\n");
+        assert_eq!(uniq_of(&group(Group::Append), ""), "");
+    }
+
+    #[test]
+    fn compares_parts_of_the_lines() {
+        let args = Args {
+            skip_fields: 1,
+            skip_chars: 2,
+            check_chars: Some(2),
+            ignore_case: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            uniq_of(&args, "1 xab1\n22\tyAB2\n 3 zab\n4 zac\n"),
+            "1 xab1\n4 zac\n"
+        );
+        let args = Args {
+            zero_terminated: true,
+            skip_fields: 1,
+            ..Default::default()
+        };
+        assert_eq!(uniq_of(&args, "a\nx\0b\nx\0c"), "a\nx\0c\0");
+    }
+}
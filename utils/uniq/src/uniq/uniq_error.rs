@@ -0,0 +1,58 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+#[derive(Debug)]
+pub enum UniqError {
+    Open(String, io::Error),
+    Read(String, io::Error),
+    Create(String, io::Error),
+    Write(io::Error),
+}
+
+impl fmt::Display for UniqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UniqError::Open(name, err) | UniqError::Create(name, err) => {
+                write!(f, "{}: {}", quote(name), strerror(err))
+            }
+            // GNU doesn't tell the reason here
+            UniqError::Read(name, _) => write!(f, "error reading '{}'", name),
+            UniqError::Write(err) => write!(f, "write error: {}", strerror(err)),
+        }
+    }
+}
+
+impl Error for UniqError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            UniqError::Open(_, err)
+            | UniqError::Read(_, err)
+            | UniqError::Create(_, err)
+            | UniqError::Write(err) => Some(err),
+        }
+    }
+}
+
+/// Wrong arguments, reported in GNU format instead of the clap one
+#[derive(Debug, PartialEq)]
+pub struct ArgError(pub String);
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Error for ArgError {}
+
+/// Names with anything unusual get quoted, the way GNU does in diagnostics
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "._-+/,:@%^=".contains(c);
+    if !name.is_empty() && name.chars().all(plain) {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
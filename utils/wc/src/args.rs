@@ -0,0 +1,85 @@
+use clap::{Parser, ValueEnum};
+
+// @see https://docs.rs/clap/latest/clap/_derive/_tutorial/index.html
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum Total {
+    /// print the total line if more than one file is given
+    #[default]
+    Auto,
+    Always,
+    /// print only the total counts, without the label
+    Only,
+    Never,
+}
+
+/// print newline, word, and byte counts for each file
+#[derive(Parser, Debug, Default)]
+#[command(author, version, about, long_about)]
+pub struct Args {
+    pub file: Vec<String>,
+
+    /// print the byte counts
+    #[arg(short = 'c', long, default_value_t = false)]
+    pub bytes: bool,
+
+    /// print the character counts
+    #[arg(short = 'm', long, default_value_t = false)]
+    pub chars: bool,
+
+    /// print the newline counts
+    #[arg(short = 'l', long, default_value_t = false)]
+    pub lines: bool,
+
+    /// read input from the files specified by NUL-terminated names in file F; if F is - then read names from standard input
+    #[arg(long, value_name = "F")]
+    pub files0_from: Option<String>,
+
+    /// print the maximum display width
+    #[arg(short = 'L', long, default_value_t = false)]
+    pub max_line_length: bool,
+
+    /// when to print a line with total counts
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = Total::Auto)]
+    pub total: Total,
+
+    /// print the word counts
+    #[arg(short = 'w', long, default_value_t = false)]
+    pub words: bool,
+}
+
+impl Args {
+    pub fn parse() -> Args {
+        let mut args = <Self as Parser>::parse();
+        if let (Some(_), Some(file)) = (&args.files0_from, args.file.first()) {
+            eprintln!("wc: extra operand '{}'", file);
+            eprintln!("file operands cannot be combined with --files0-from");
+            std::process::exit(1);
+        }
+        if !(args.bytes || args.chars || args.lines || args.max_line_length || args.words) {
+            args.lines = true;
+            args.words = true;
+            args.bytes = true;
+        }
+        args
+    }
+
+    /// Number of columns in the output
+    pub fn counts_number(&self) -> usize {
+        [
+            self.lines,
+            self.words,
+            self.chars,
+            self.bytes,
+            self.max_line_length,
+        ]
+        .iter()
+        .filter(|c| **c)
+        .count()
+    }
+
+    /// Everything, but lines and bytes, needs the input to be decoded
+    pub fn needs_decoding(&self) -> bool {
+        self.words || self.chars || self.max_line_length
+    }
+}
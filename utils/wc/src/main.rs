@@ -0,0 +1,128 @@
+mod args;
+mod wc;
+
+use std::fs;
+use std::io::{self, BufWriter, Read, Write};
+use std::process::ExitCode;
+
+use args::{Args, Total};
+use strerror::strerror;
+use wc::counter::Counts;
+use wc::wc_error::WcError;
+use wc::{format_counts, metadata, number_width, wc};
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let mut output = BufWriter::new(io::stdout());
+    let mut status = ExitCode::SUCCESS;
+
+    // The stdin read implicitly goes without a name
+    let (names, show_names) = match &args.files0_from {
+        Some(list) => match read_files0(list) {
+            Ok(names) => (names, true),
+            Err(e) => {
+                eprintln!("wc: cannot open '{}' for reading: {}", list, strerror(&e));
+                return ExitCode::FAILURE;
+            }
+        },
+        None if args.file.is_empty() => (vec!["-".to_string()], false),
+        None => (args.file.clone(), true),
+    };
+
+    // GNU streams --files0-from names, so it can't know the sizes beforehand, unless it's a file
+    let sizes_known = match &args.files0_from {
+        Some(list) => metadata(list).is_some_and(|m| m.is_file()),
+        None => true,
+    };
+    let width = if sizes_known && !(names.len() == 1 && args.counts_number() == 1) {
+        number_width(&names.iter().map(|n| metadata(n)).collect::<Vec<_>>())
+    } else {
+        1
+    };
+
+    let mut total = Counts::default();
+    for (i, name) in names.iter().enumerate() {
+        if let Some(list) = &args.files0_from {
+            if let Some(message) = invalid_list_entry(list, name, i + 1) {
+                eprintln!("wc: {}", message);
+                status = ExitCode::FAILURE;
+                continue;
+            }
+        }
+        let counts = match wc(&args, name) {
+            Ok(counts) => counts,
+            Err(e) => {
+                let _ = output.flush();
+                eprintln!("wc: {}", e);
+                status = ExitCode::FAILURE;
+                match e {
+                    // Whatever was counted before the failure is still printed
+                    WcError::Read(_, _, counts) => counts,
+                    _ => continue,
+                }
+            }
+        };
+        if args.total != Total::Only {
+            print(
+                &mut output,
+                &args,
+                &counts,
+                width,
+                show_names.then_some(name),
+            );
+        }
+        total += counts;
+    }
+
+    match args.total {
+        Total::Always => print(&mut output, &args, &total, width, Some("total")),
+        Total::Auto if names.len() > 1 => print(&mut output, &args, &total, width, Some("total")),
+        Total::Only => print(&mut output, &args, &total, width, None),
+        _ => {}
+    }
+    if let Err(e) = output.flush() {
+        eprintln!("wc: write error: {}", strerror(&e));
+        status = ExitCode::FAILURE;
+    }
+    status
+}
+
+fn print<W: Write>(output: &mut W, args: &Args, counts: &Counts, width: usize, name: Option<&str>) {
+    let line = format_counts(args, counts, width);
+    // Write errors are reported once, when flushing in the end
+    let _ = match name {
+        Some(name) => writeln!(output, "{} {}", line, name),
+        None => writeln!(output, "{}", line),
+    };
+}
+
+/** Reads NUL-terminated file names of --files0-from */
+fn read_files0(list: &str) -> io::Result<Vec<String>> {
+    let mut data = Vec::new();
+    match list {
+        "-" => io::stdin().read_to_end(&mut data)?,
+        _ => fs::File::open(list)?.read_to_end(&mut data)?,
+    };
+    if data.last() == Some(&0) {
+        data.pop();
+    }
+    if data.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(data
+        .split(|c| *c == 0)
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect())
+}
+
+fn invalid_list_entry(list: &str, name: &str, number: usize) -> Option<String> {
+    if name.is_empty() {
+        return Some(WcError::EmptyName(list.to_string(), number).to_string());
+    }
+    if list == "-" && name == "-" {
+        return Some(
+            "when reading file names from standard input, no file name of '-' allowed".into(),
+        );
+    }
+    None
+}
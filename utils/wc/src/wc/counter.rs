@@ -0,0 +1,254 @@
+// The counters go over the buffers of BufRead, not through lib/chunk_reader: that one
+// issues a read() per byte and copies every line into a Vec of its own, which costs
+// more than the counting itself. The lines are found with memchr in whole buffers.
+
+use std::io::{self, BufRead};
+use std::ops::AddAssign;
+use std::str;
+
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counts {
+    pub lines: u64,
+    pub words: u64,
+    pub chars: u64,
+    pub bytes: u64,
+    pub max_line_length: u64,
+}
+
+impl AddAssign for Counts {
+    fn add_assign(&mut self, other: Counts) {
+        self.lines += other.lines;
+        self.words += other.words;
+        self.chars += other.chars;
+        self.bytes += other.bytes;
+        self.max_line_length = self.max_line_length.max(other.max_line_length);
+    }
+}
+
+/** Counting bytes and lines only, which is all most of the callers want.
+ *
+ * There is no need to decode anything, so whole buffers are scanned with memchr.
+ */
+pub fn count_lines<R: BufRead>(mut input: R, counts: &mut Counts) -> io::Result<()> {
+    loop {
+        let buffer = match input.fill_buf() {
+            Ok([]) => return Ok(()),
+            Ok(buffer) => buffer,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        counts.lines += memchr::memchr_iter(b'\n', buffer).count() as u64;
+        counts.bytes += buffer.len() as u64;
+        let len = buffer.len();
+        input.consume(len);
+    }
+}
+
+/** Counting everything, decoding the input as UTF-8.
+ *
+ * Bytes, that aren't valid UTF-8, aren't counted as characters, and they neither start
+ * nor end a word, the same as non-printable characters.
+ */
+pub fn count_all<R: BufRead>(mut input: R, counts: &mut Counts) -> io::Result<()> {
+    let mut counter = Counter::new(counts);
+    loop {
+        let buffer = match input.fill_buf() {
+            Ok([]) => break,
+            Ok(buffer) => buffer,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => {
+                counter.finish();
+                return Err(e);
+            }
+        };
+        counter.feed(buffer);
+        let len = buffer.len();
+        input.consume(len);
+    }
+    counter.finish();
+    Ok(())
+}
+
+struct Counter<'a> {
+    counts: &'a mut Counts,
+    in_word: bool,
+    line_length: u64,
+    /// Beginning of a multibyte character, that is split between the buffers
+    pending: Vec<u8>,
+}
+
+impl<'a> Counter<'a> {
+    fn new(counts: &'a mut Counts) -> Self {
+        Self {
+            counts,
+            in_word: false,
+            line_length: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        self.counts.lines += memchr::memchr_iter(b'\n', data).count() as u64;
+        self.counts.bytes += data.len() as u64;
+        if !self.pending.is_empty() {
+            let needed = utf8_len(self.pending[0]) - self.pending.len();
+            let taken = needed.min(data.len());
+            self.pending.extend_from_slice(&data[..taken]);
+            data = &data[taken..];
+            if taken < needed {
+                return;
+            }
+            let pending = std::mem::take(&mut self.pending);
+            self.decode(&pending);
+        }
+        self.decode(data);
+    }
+
+    fn decode(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            match str::from_utf8(data) {
+                Ok(valid) => {
+                    self.chars(valid);
+                    return;
+                }
+                Err(e) => {
+                    let (valid, rest) = data.split_at(e.valid_up_to());
+                    self.chars(str::from_utf8(valid).expect("checked by from_utf8 above"));
+                    match e.error_len() {
+                        Some(invalid_len) => data = &rest[invalid_len..],
+                        None => {
+                            self.pending.extend_from_slice(rest);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn chars(&mut self, valid: &str) {
+        for c in valid.chars() {
+            self.counts.chars += 1;
+            match c {
+                '\n' | '\r' | '\x0c' => {
+                    self.end_line();
+                    self.in_word = false;
+                }
+                '\t' => {
+                    self.line_length += 8 - self.line_length % 8;
+                    self.in_word = false;
+                }
+                _ if is_word_separator(c) => {
+                    self.line_length += c.width().unwrap_or(0) as u64;
+                    self.in_word = false;
+                }
+                _ => {
+                    if let Some(width) = c.width() {
+                        self.line_length += width as u64;
+                        if !self.in_word {
+                            self.counts.words += 1;
+                            self.in_word = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn end_line(&mut self) {
+        self.counts.max_line_length = self.counts.max_line_length.max(self.line_length);
+        self.line_length = 0;
+    }
+
+    /// Incomplete character at the end of the input is just a bunch of invalid bytes
+    fn finish(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        if let Some((_, rest)) = pending.split_first() {
+            // Whatever was collected after the invalid byte, still may be valid
+            self.decode(rest);
+            self.pending.clear();
+        }
+        self.end_line();
+    }
+}
+
+/// Whitespace, except for the no-break spaces, which are rather a part of the word
+fn is_word_separator(c: char) -> bool {
+    c.is_whitespace() && !matches!(c, '\u{a0}' | '\u{2007}' | '\u{202f}')
+}
+
+/// Length of UTF-8 sequence by its first byte
+fn utf8_len(first: u8) -> usize {
+    match first {
+        0xf0.. => 4,
+        0xe0.. => 3,
+        0xc0.. => 2,
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::{BufReader, Cursor};
+
+    use super::*;
+
+    fn count(data: &[u8]) -> Counts {
+        let mut counts = Counts::default();
+        count_all(Cursor::new(data), &mut counts).unwrap();
+        counts
+    }
+
+    #[test]
+    fn counts_lines_and_bytes() {
+        let mut counts = Counts::default();
+        count_lines(Cursor::new(b"hello world\nfoo\tbar baz\nx"), &mut counts).unwrap();
+        assert_eq!(counts.lines, 2);
+        assert_eq!(counts.bytes, 25);
+    }
+
+    #[test]
+    fn counts_everything() {
+        let counts = count(b"hello world\nfoo\tbar baz\n");
+        assert_eq!(
+            counts,
+            Counts {
+                lines: 2,
+                words: 5,
+                chars: 24,
+                bytes: 24,
+                max_line_length: 15,
+            }
+        );
+    }
+
+    #[test]
+    fn skips_invalid_and_control_characters() {
+        let counts = count(b"ab\ncd\xc3\xa9\x01 x\n\tz\xff");
+        assert_eq!(counts.chars, 12);
+        assert_eq!(counts.words, 4);
+        assert_eq!(counts.max_line_length, 9);
+        assert_eq!(count(b"a\x01b \x01 c").words, 2);
+    }
+
+    #[test]
+    fn measures_display_width() {
+        assert_eq!(count("日本語\n".as_bytes()).max_line_length, 6);
+        assert_eq!(count(b"ab\rc\n").max_line_length, 2);
+        assert_eq!(count("\u{a0}x \u{3000} y".as_bytes()).words, 2);
+    }
+
+    #[test]
+    fn handles_characters_split_between_buffers() {
+        let data = "é".repeat(10);
+        let mut counts = Counts::default();
+        // Odd capacity, so every other character is split
+        count_all(BufReader::with_capacity(3, data.as_bytes()), &mut counts).unwrap();
+        assert_eq!(counts.chars, 10);
+        assert_eq!(counts.bytes, 20);
+        assert_eq!(counts.words, 1);
+        assert_eq!(counts.max_line_length, 10);
+    }
+}
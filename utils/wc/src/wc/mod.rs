@@ -0,0 +1,147 @@
+pub mod counter;
+pub mod wc_error;
+
+use std::fs::{self, File, Metadata};
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+
+use crate::args::Args;
+use counter::{count_all, count_lines, Counts};
+use wc_error::WcError;
+
+pub fn wc(args: &Args, name: &str) -> Result<Counts, WcError> {
+    let mut counts = Counts::default();
+    let result = match open(name).map_err(|e| WcError::Open(name.to_string(), e))? {
+        Some(mut file) => match bytes_to_end(args, &mut file) {
+            Some(bytes) => {
+                counts.bytes = bytes;
+                Ok(())
+            }
+            None => count(args, file, &mut counts),
+        },
+        None => count(args, io::stdin().lock(), &mut counts),
+    };
+    result.map_err(|e| WcError::Read(name.to_string(), e, counts))?;
+    Ok(counts)
+}
+
+/** Input metadata, for the stdin too where possible */
+pub fn metadata(name: &str) -> Option<Metadata> {
+    match name {
+        "-" => stdin_file()?.metadata().ok(),
+        _ => fs::metadata(name).ok(),
+    }
+}
+
+/// None means the stdin, that can't be used as a file on this platform
+fn open(name: &str) -> io::Result<Option<File>> {
+    match name {
+        "-" => Ok(stdin_file()),
+        _ => File::open(name).map(Some),
+    }
+}
+
+#[cfg(target_family = "unix")]
+fn stdin_file() -> Option<File> {
+    use std::os::fd::AsFd;
+    let fd = io::stdin().as_fd().try_clone_to_owned().ok()?;
+    Some(File::from(fd))
+}
+
+#[cfg(not(target_family = "unix"))]
+fn stdin_file() -> Option<File> {
+    None
+}
+
+fn count<R: Read>(args: &Args, input: R, counts: &mut Counts) -> io::Result<()> {
+    let reader = BufReader::new(input);
+    if args.needs_decoding() {
+        count_all(reader, counts)
+    } else {
+        count_lines(reader, counts)
+    }
+}
+
+/** Byte count of a regular file without reading it, when it's the only thing asked.
+ *
+ * Files like the ones in /proc report zero size, those have to be read anyway.
+ */
+fn bytes_to_end(args: &Args, file: &mut File) -> Option<u64> {
+    if !args.bytes || args.lines || args.needs_decoding() {
+        return None;
+    }
+    let metadata = file.metadata().ok()?;
+    if !metadata.is_file() || metadata.len() == 0 {
+        return None;
+    }
+    let position = file.stream_position().ok()?;
+    let bytes = metadata.len().saturating_sub(position);
+    // Leaving the shared stdin offset where `wc` would leave it after reading
+    file.seek(SeekFrom::End(0)).ok()?;
+    Some(bytes)
+}
+
+/** Column width, that fits any of the counts.
+ *
+ * The counts can't be more than the total size of the files, so it's enough to
+ * fit that. Anything that isn't a regular file might be of any size, so there's
+ * a reasonable minimum for those, the same one GNU uses.
+ */
+pub fn number_width(inputs: &[Option<Metadata>]) -> usize {
+    let mut minimum_width = 1;
+    let mut regular_total = 0_u64;
+    for metadata in inputs.iter().flatten() {
+        if metadata.is_file() {
+            regular_total = regular_total.saturating_add(metadata.len());
+        } else {
+            minimum_width = 7;
+        }
+    }
+    regular_total.to_string().len().max(minimum_width)
+}
+
+pub fn format_counts(args: &Args, counts: &Counts, width: usize) -> String {
+    [
+        (args.lines, counts.lines),
+        (args.words, counts.words),
+        (args.chars, counts.chars),
+        (args.bytes, counts.bytes),
+        (args.max_line_length, counts.max_line_length),
+    ]
+    .iter()
+    .filter(|(enabled, _)| *enabled)
+    .map(|(_, count)| format!("{:>width$}", count, width = width))
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn formats_enabled_counts_only() {
+        let args = Args {
+            lines: true,
+            bytes: true,
+            ..Default::default()
+        };
+        let counts = Counts {
+            lines: 2,
+            words: 5,
+            bytes: 24,
+            ..Default::default()
+        };
+        assert_eq!(format_counts(&args, &counts, 3), "  2  24");
+        assert_eq!(format_counts(&args, &counts, 1), "2 24");
+    }
+
+    #[test]
+    fn width_fits_total_size_of_regular_files() {
+        let file = fs::metadata("Cargo.toml").ok();
+        let width = file.as_ref().unwrap().len().to_string().len();
+        assert_eq!(number_width(std::slice::from_ref(&file)), width);
+        assert_eq!(number_width(&[None]), 1);
+        let dir = fs::metadata("src").ok();
+        assert_eq!(number_width(&[file, dir]), 7);
+    }
+}
@@ -0,0 +1,37 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use strerror::strerror;
+
+use super::counter::Counts;
+
+#[derive(Debug)]
+pub enum WcError {
+    Open(String, io::Error),
+    /// Read failed midway, whatever was counted before still gets printed
+    Read(String, io::Error, Counts),
+    /// Zero-length name in --files0-from list: list name and the name number
+    EmptyName(String, usize),
+}
+
+impl fmt::Display for WcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WcError::Open(name, err) | WcError::Read(name, err, _) => {
+                write!(f, "{}: {}", name, strerror(err))
+            }
+            WcError::EmptyName(list, n) => {
+                write!(f, "{}:{}: invalid zero-length file name", list, n)
+            }
+        }
+    }
+}
+
+impl Error for WcError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WcError::Open(_, err) | WcError::Read(_, err, _) => Some(err),
+            WcError::EmptyName(_, _) => None,
+        }
+    }
+}